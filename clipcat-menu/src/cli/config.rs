@@ -1,6 +1,8 @@
 use std::path::PathBuf;
 
 use clap::Args;
+use clipcat_base::{ClipboardKind, ListOrder, ListQuery};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
 #[derive(Args)]
 pub struct RofiConfig {
@@ -74,3 +76,89 @@ pub struct CustomFinderConfig {
     )]
     pub arguments: Option<String>,
 }
+
+#[derive(Args)]
+pub struct ListConfig {
+    #[arg(
+        long = "order",
+        default_value = "newest",
+        help = "Specify sort order of clips (\"newest\", \"oldest\", \"most-used\", \
+                \"frecency\"), it is ignored when searching"
+    )]
+    pub order: ListOrder,
+
+    #[arg(
+        long = "kinds",
+        short = 'k',
+        help = "Only show clips in specified clipboard (\"clipboard\", \"primary\", \"secondary\")"
+    )]
+    pub kinds: Vec<ClipboardKind>,
+
+    #[arg(
+        long = "mime",
+        help = "Only show clips with specified MIME type, such as \"text/plain\" or \"image/*\""
+    )]
+    pub mimes: Vec<mime::Mime>,
+
+    #[arg(
+        long = "tag",
+        short = 't',
+        help = "Only show clips with any of specified tags, such as \"deploy\""
+    )]
+    pub tags: Vec<String>,
+
+    #[arg(long = "pinned", help = "Only show pinned clips if true, unpinned clips if false")]
+    pub pinned: Option<bool>,
+
+    #[arg(long = "snippet", help = "Only show snippets if true, other clips if false")]
+    pub snippet: Option<bool>,
+
+    #[arg(
+        long = "since",
+        value_parser = parse_timestamp,
+        help = "Only show clips copied at or after the RFC 3339 timestamp"
+    )]
+    pub since: Option<OffsetDateTime>,
+
+    #[arg(
+        long = "until",
+        value_parser = parse_timestamp,
+        help = "Only show clips copied before the RFC 3339 timestamp"
+    )]
+    pub until: Option<OffsetDateTime>,
+
+    #[arg(long = "offset", default_value = "0", help = "Number of clips to skip")]
+    pub offset: usize,
+
+    #[arg(
+        long = "limit",
+        short = 'l',
+        default_value = "0",
+        help = "Maximum number of clips to show, 0 means no limit"
+    )]
+    pub limit: usize,
+}
+
+impl ListConfig {
+    pub fn into_query(self, preview_length: usize) -> ListQuery {
+        let Self { order, kinds, mimes, tags, pinned, snippet, since, until, offset, limit } = self;
+        ListQuery {
+            preview_length,
+            offset,
+            limit,
+            order,
+            kinds,
+            mimes,
+            tags,
+            pinned,
+            snippet,
+            since,
+            until,
+        }
+    }
+}
+
+#[inline]
+fn parse_timestamp(src: &str) -> Result<OffsetDateTime, time::error::Parse> {
+    OffsetDateTime::parse(src, &Rfc3339)
+}
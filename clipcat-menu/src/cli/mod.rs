@@ -1,9 +1,11 @@
 mod config;
 
-use std::{io::Write, path::PathBuf};
+use std::{collections::HashMap, io::Write, path::PathBuf};
 
 use clap::{CommandFactory, Parser, Subcommand};
-use clipcat_base::{ClipEntryMetadata, ClipboardKind};
+use clipcat_base::{
+    ClipEntryMetadata, ClipboardKind, ListQuery, SearchMode, SearchQuery, SnippetTemplate,
+};
 use clipcat_client::{Client, Manager, System};
 use clipcat_external_editor::ExternalEditor;
 use snafu::ResultExt;
@@ -43,6 +45,19 @@ pub struct Cli {
     #[arg(long, short = 'f', env = "CLIPCAT_MENU_FINDER", help = "Specify a finder")]
     finder: Option<FinderType>,
 
+    #[arg(long = "search", short = 's', help = "Only show clips matching the pattern")]
+    search: Option<String>,
+
+    #[arg(
+        long = "search-mode",
+        default_value = "exact",
+        help = "Specify search mode (\"exact\", \"case-insensitive\", \"regex\", \"fuzzy\")"
+    )]
+    search_mode: SearchMode,
+
+    #[command(flatten)]
+    list_config: config::ListConfig,
+
     #[command(flatten)]
     rofi_config: config::RofiConfig,
 
@@ -106,38 +121,16 @@ impl Cli {
             log_level,
             config_file,
             finder,
+            search,
+            search_mode,
+            list_config,
             rofi_config,
             dmenu_config,
             custom_finder_config,
         } = self;
 
-        match commands {
-            Some(Commands::Version { client }) if client => {
-                print_only_client_version();
-                return Ok(());
-            }
-            Some(Commands::Completions { shell }) => {
-                let mut app = Self::command();
-                let bin_name = app.get_name().to_string();
-                clap_complete::generate(shell, &mut app, bin_name, &mut std::io::stdout());
-
-                return Ok(());
-            }
-            Some(Commands::DefaultConfig) => {
-                let config_text =
-                    toml::to_string_pretty(&Config::default()).expect("Config is serializable");
-                std::io::stdout()
-                    .write_all(config_text.as_bytes())
-                    .expect("Failed to write to stdout");
-                return Ok(());
-            }
-            Some(Commands::ListFinder) => {
-                for ty in FinderType::available_types() {
-                    println!("{ty}");
-                }
-                return Ok(());
-            }
-            _ => {}
+        if run_offline_command(commands.as_ref()) {
+            return Ok(());
         }
 
         let mut config =
@@ -155,60 +148,166 @@ impl Cli {
                 let access_token = config.access_token();
                 Client::new(config.server_endpoint, access_token).await?
             };
-            let clips = client.list(config.preview_length).await?;
-
-            match commands {
-                Some(Commands::Version { .. }) => print_version(&client).await,
-                Some(Commands::Insert { mut kinds }) => {
-                    if kinds.is_empty() {
-                        kinds.push(ClipboardKind::Clipboard);
-                    } else {
-                        kinds.sort_unstable();
-                        kinds.dedup();
-                    }
+            let query = list_config.into_query(config.preview_length);
+            let clips = list_clips(&client, search, search_mode, query).await?;
 
-                    insert_clip(&clips, &finder, &client, &kinds).await?;
-                }
-                None => insert_clip(&clips, &finder, &client, &[ClipboardKind::Clipboard]).await?,
-                Some(Commands::Remove) => {
-                    let selections = finder.multiple_select(&clips).await?;
-                    let ids: Vec<_> = selections.into_iter().map(|(_, clip)| clip.id).collect();
-                    let removed_ids = client.batch_remove(&ids).await?;
-                    for id in removed_ids {
-                        tracing::info!("Removing clip (id: {id:016x})");
-                    }
-                }
-                Some(Commands::Edit { editor }) => {
-                    let selection = finder.single_select(&clips).await?;
-                    if let Some((_index, metadata)) = selection {
-                        let clip = client.get(metadata.id).await?;
-                        if clip.is_utf8_string() {
-                            let editor = ExternalEditor::new(editor);
-                            let new_data = editor
-                                .execute(&clip.as_utf8_string())
-                                .await
-                                .context(error::CallEditorSnafu)?;
-                            let (ok, new_id) =
-                                client.update(clip.id(), new_data.as_bytes(), clip.mime()).await?;
-                            if ok {
-                                tracing::info!("Editing clip (id: {:016x})", new_id);
-                            }
-                            let _ok = client.mark(new_id, ClipboardKind::Clipboard).await?;
-                            drop(client);
-                        }
-                    } else {
-                        tracing::info!("Nothing is selected");
-                        return Ok(());
+            run_command(commands, client, &clips, &finder).await
+        };
+
+        Runtime::new().context(error::InitializeTokioRuntimeSnafu)?.block_on(fut)
+    }
+}
+
+// runs the commands which do not connect to the server, `true` is returned if
+// `commands` is one of them
+fn run_offline_command(commands: Option<&Commands>) -> bool {
+    match commands {
+        Some(Commands::Version { client }) if *client => {
+            print_only_client_version();
+            true
+        }
+        Some(Commands::Completions { shell }) => {
+            let mut app = Cli::command();
+            let bin_name = app.get_name().to_string();
+            clap_complete::generate(*shell, &mut app, bin_name, &mut std::io::stdout());
+
+            true
+        }
+        Some(Commands::DefaultConfig) => {
+            let config_text =
+                toml::to_string_pretty(&Config::default()).expect("Config is serializable");
+            std::io::stdout().write_all(config_text.as_bytes()).expect("Failed to write to stdout");
+            true
+        }
+        Some(Commands::ListFinder) => {
+            for ty in FinderType::available_types() {
+                println!("{ty}");
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+async fn run_command(
+    commands: Option<Commands>,
+    client: Client,
+    clips: &[ClipEntryMetadata],
+    finder: &FinderRunner,
+) -> Result<(), Error> {
+    match commands {
+        Some(Commands::Version { .. }) => print_version(&client).await,
+        Some(Commands::Insert { mut kinds }) => {
+            if kinds.is_empty() {
+                kinds.push(ClipboardKind::Clipboard);
+            } else {
+                kinds.sort_unstable();
+                kinds.dedup();
+            }
+
+            insert_clip(clips, finder, &client, &kinds).await?;
+        }
+        None => insert_clip(clips, finder, &client, &[ClipboardKind::Clipboard]).await?,
+        Some(Commands::Remove) => {
+            let selections = finder.multiple_select(clips).await?;
+            let ids: Vec<_> = selections.into_iter().map(|(_, clip)| clip.id).collect();
+            let removed_ids = client.batch_remove(&ids).await?;
+            for id in removed_ids {
+                tracing::info!("Removing clip (id: {id:016x})");
+            }
+        }
+        Some(Commands::Edit { editor }) => {
+            let selection = finder.single_select(clips).await?;
+            if let Some((_index, metadata)) = selection {
+                let clip = client.get(metadata.id).await?;
+                if clip.is_utf8_string() {
+                    let editor = ExternalEditor::new(editor);
+                    let new_data = editor
+                        .execute(&clip.as_utf8_string())
+                        .await
+                        .context(error::CallEditorSnafu)?;
+                    let (ok, new_id) =
+                        client.update(clip.id(), new_data.as_bytes(), clip.mime()).await?;
+                    if ok {
+                        tracing::info!("Editing clip (id: {:016x})", new_id);
                     }
+                    let _ok = client.mark(new_id, ClipboardKind::Clipboard).await?;
+                    drop(client);
                 }
-                _ => unreachable!(),
+            } else {
+                tracing::info!("Nothing is selected");
+                return Ok(());
             }
+        }
+        _ => unreachable!(),
+    }
 
-            Ok(())
-        };
+    Ok(())
+}
 
-        Runtime::new().context(error::InitializeTokioRuntimeSnafu)?.block_on(fut)
+// filters of `query` are applied to search as well
+async fn list_clips(
+    client: &Client,
+    search: Option<String>,
+    search_mode: SearchMode,
+    query: ListQuery,
+) -> Result<Vec<ClipEntryMetadata>, Error> {
+    let mut clips = if let Some(pattern) = search {
+        search_clips(client, pattern, search_mode, query).await?
+    } else {
+        client.list(query).await?
+    };
+    // snippets are shown by name, such as `[snippet] signature`
+    for clip in &mut clips {
+        if let Some(name) = &clip.snippet_name {
+            clip.preview = format!("[snippet] {name}");
+        }
     }
+    Ok(clips)
+}
+
+// search results are ordered by relevance, the order of `query` is ignored
+async fn search_clips(
+    client: &Client,
+    pattern: String,
+    mode: SearchMode,
+    query: ListQuery,
+) -> Result<Vec<ClipEntryMetadata>, Error> {
+    let ListQuery {
+        preview_length,
+        offset,
+        limit,
+        kinds,
+        mimes,
+        tags,
+        pinned,
+        snippet,
+        since,
+        until,
+        ..
+    } = query;
+    // search does not skip results, the first `offset` results are dropped here
+    let limit = if limit == 0 { 0 } else { offset.saturating_add(limit) };
+    let query = SearchQuery {
+        pattern,
+        mode,
+        kinds,
+        mimes,
+        tags,
+        pinned,
+        snippet,
+        since,
+        until,
+        preview_length,
+        limit,
+    };
+    Ok(client
+        .search(query)
+        .await?
+        .into_iter()
+        .map(|search_match| search_match.metadata)
+        .skip(offset)
+        .collect())
 }
 
 async fn insert_clip(
@@ -219,9 +318,18 @@ async fn insert_clip(
 ) -> Result<(), Error> {
     let selection = finder.single_select(clips).await?;
     if let Some((index, clip)) = selection {
+        let prompts = if clip.template {
+            let Some(prompts) = read_prompts(finder, client, clip.id).await? else {
+                tracing::info!("Prompt is cancelled");
+                return Ok(());
+            };
+            prompts
+        } else {
+            HashMap::new()
+        };
         tracing::info!("Inserting clip (index: {index}, id: {:016x})", clip.id);
         for &clipboard_kind in clipboard_kinds {
-            let _ok = client.mark(clip.id, clipboard_kind).await?;
+            let _ok = client.mark_with_prompts(clip.id, clipboard_kind, prompts.clone()).await?;
         }
     } else {
         tracing::info!("Nothing is selected");
@@ -230,6 +338,25 @@ async fn insert_clip(
     Ok(())
 }
 
+// reads values of `{{prompt:<label>}}` placeholders of snippet template `id`
+// through the finder, returns `None` if any of them is cancelled
+async fn read_prompts(
+    finder: &FinderRunner,
+    client: &Client,
+    id: u64,
+) -> Result<Option<HashMap<String, String>>, Error> {
+    let clip = client.get(id).await?;
+    let template = SnippetTemplate::parse(&clip.as_utf8_string());
+    let mut prompts = HashMap::new();
+    for label in template.prompts() {
+        let Some(value) = finder.prompt(label).await? else {
+            return Ok(None);
+        };
+        let _unused = prompts.insert(label.to_string(), value);
+    }
+    Ok(Some(prompts))
+}
+
 fn print_only_client_version() {
     let client_version = Cli::command().get_version().unwrap_or_default().to_string();
     std::io::stdout()
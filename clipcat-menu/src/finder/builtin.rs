@@ -47,6 +47,23 @@ impl BuiltinFinder {
 
         Ok(self.parse_output(output.as_bytes()))
     }
+
+    pub async fn prompt(&self, label: &str) -> Result<Option<String>, FinderError> {
+        let prompt = format!("{label}: ");
+        tokio::task::spawn_blocking(move || {
+            let options = SkimOptionsBuilder::default()
+                .height(Some("100%"))
+                .prompt(Some(&prompt))
+                .build()
+                .unwrap();
+
+            // show no items, the typed query is the value
+            let items = SkimItemReader::default().of_bufread(Cursor::new(String::new()));
+            Skim::run_with(&options, Some(items)).filter(|out| !out.is_abort).map(|out| out.query)
+        })
+        .await
+        .context(error::JoinTaskSnafu)
+    }
 }
 
 impl FinderStream for BuiltinFinder {}
@@ -41,6 +41,14 @@ impl ExternalProgram for Choose {
             .chain(self.extra_arguments.clone())
             .collect()
     }
+
+    fn prompt_args(&self, label: &str) -> Vec<String> {
+        // `-m` returns the query if it does not match any item
+        ["-m".to_string(), "-p".to_string(), label.to_string()]
+            .into_iter()
+            .chain(self.extra_arguments.clone())
+            .collect()
+    }
 }
 
 impl FinderStream for Choose {
@@ -27,6 +27,13 @@ impl ExternalProgram for Dmenu {
             .chain(self.extra_arguments.clone())
             .collect()
     }
+
+    fn prompt_args(&self, label: &str) -> Vec<String> {
+        ["-p".to_owned(), label.to_owned()]
+            .into_iter()
+            .chain(self.extra_arguments.clone())
+            .collect()
+    }
 }
 
 impl FinderStream for Dmenu {
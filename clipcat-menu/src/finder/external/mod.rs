@@ -28,6 +28,18 @@ pub trait ExternalProgram: FinderStream + Send + Sync {
             .spawn()
     }
 
+    /// Arguments to read a line of text typed by user, `label` is shown as
+    /// prompt.
+    fn prompt_args(&self, _label: &str) -> Vec<String> { self.args(SelectionMode::Single) }
+
+    fn spawn_prompt_child(&self, label: &str) -> Result<tokio::process::Child, std::io::Error> {
+        Command::new(self.program())
+            .args(self.prompt_args(label))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+    }
+
     fn set_program_path(&mut self, _program_path: PathBuf) {}
 
     fn set_arguments(&mut self, _arguments: &[String]) {}
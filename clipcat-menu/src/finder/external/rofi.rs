@@ -46,6 +46,13 @@ impl ExternalProgram for Rofi {
         .chain(self.extra_arguments.clone())
         .collect()
     }
+
+    fn prompt_args(&self, label: &str) -> Vec<String> {
+        ["-dmenu".to_owned(), "-p".to_owned(), label.to_owned()]
+            .into_iter()
+            .chain(self.extra_arguments.clone())
+            .collect()
+    }
 }
 
 impl FinderStream for Rofi {
@@ -17,6 +17,11 @@ impl ExternalProgram for Skim {
             SelectionMode::Multiple => vec!["--multi".to_owned()],
         }
     }
+
+    fn prompt_args(&self, label: &str) -> Vec<String> {
+        // the query is printed even if nothing matches
+        vec!["--print-query".to_owned(), "--prompt".to_owned(), format!("{label}: ")]
+    }
 }
 
 impl FinderStream for Skim {}
@@ -186,6 +186,25 @@ impl FinderRunner {
         }
     }
 
+    /// Reads a line of text typed by user, `label` is shown as prompt. Returns
+    /// `None` if user cancels it.
+    pub async fn prompt(&self, label: &str) -> Result<Option<String>, FinderError> {
+        let Some(external) = &self.external else {
+            return BuiltinFinder::new().prompt(label).await;
+        };
+
+        let child = external
+            .spawn_prompt_child(label)
+            .context(error::SpawnExternalProgramSnafu { program: external.program() })?;
+        // stdin is closed before waiting, no item is shown
+        let output = child.wait_with_output().await.context(error::ReadStdoutSnafu)?;
+        if output.stdout.is_empty() {
+            return Ok(None);
+        }
+        let output = String::from_utf8_lossy(&output.stdout);
+        Ok(Some(output.lines().next().unwrap_or_default().to_string()))
+    }
+
     #[inline]
     pub fn set_line_length(&mut self, line_length: usize) {
         if let Some(external) = self.external.as_mut() {
@@ -7,10 +7,11 @@ mod shadow {
     pub use self::build::*;
 }
 
-use std::{io::Write, sync::Arc};
+use std::io::Write;
 
 use clap::{CommandFactory, Parser, Subcommand};
-use clipcat_base::{ClipFilter, ClipboardKind};
+use clipcat_base::{ClipboardKind, SharedClipFilter};
+use clipcat_server::backend::ClipboardChange;
 use serde::Serialize;
 use snafu::ResultExt;
 use time::OffsetDateTime;
@@ -93,14 +94,16 @@ impl Cli {
                     async move {
                         let backend = clipcat_server::backend::new(
                             clipboard_kinds,
-                            &Arc::new(ClipFilter::default()),
+                            &SharedClipFilter::default(),
                             &[],
                         )
                         .context(error::InitializeClipboardBackendSnafu)?;
                         let mut subscriber =
                             backend.subscribe().context(error::SubscribeClipboardSnafu)?;
 
-                        while let Some((kind, mime)) = subscriber.next().await {
+                        while let Some(ClipboardChange { kind, mime, source_application }) =
+                            subscriber.next().await
+                        {
                             match kind {
                                 ClipboardKind::Clipboard if enable_clipboard => {}
                                 ClipboardKind::Primary if enable_primary => {}
@@ -111,7 +114,7 @@ impl Cli {
                             let info = {
                                 let now = OffsetDateTime::now_local()
                                     .unwrap_or_else(|_| OffsetDateTime::now_utc());
-                                ClipInfo { kind, mime, timestamp: now }
+                                ClipInfo { kind, mime, timestamp: now, source_application }
                             };
                             serde_json::to_writer_pretty(std::io::stdout(), &info)
                                 .expect("`ClipInfo` is serializable");
@@ -140,6 +143,9 @@ struct ClipInfo {
 
     #[serde(with = "time::serde::rfc3339")]
     timestamp: OffsetDateTime,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_application: Option<String>,
 }
 
 fn main() {
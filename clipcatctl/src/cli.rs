@@ -1,10 +1,16 @@
-use std::{io::Write, num::ParseIntError, path::PathBuf};
+use std::{collections::HashMap, io::Write, num::ParseIntError, path::PathBuf};
 
 use clap::{CommandFactory, Parser, Subcommand};
-use clipcat_base::{ClipEntryMetadata, ClipboardKind, ClipboardWatcherState};
-use clipcat_client::{Client, Manager as _, System, Watcher as _};
+use clipcat_base::{
+    archive::{self, ImportMode},
+    ClipEntry, ClipEntryMetadata, ClipboardEvent, ClipboardKind, ClipboardWatcherState, ListOrder,
+    ListQuery, SearchMode, SearchQuery,
+};
+use clipcat_client::{Client, History as _, Manager as _, System, Watcher as _};
 use clipcat_external_editor::ExternalEditor;
+use futures::StreamExt;
 use snafu::ResultExt;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     runtime::Runtime,
@@ -111,10 +117,17 @@ pub enum Commands {
         file_path: Option<PathBuf>,
     },
 
-    #[clap(about = "Print clip with <id>")]
+    #[clap(about = "Print clip with <id>, or the <index>-th most recent clip with `@<index>`")]
     Get {
-        #[clap(value_parser = parse_hex)]
-        id: Option<u64>,
+        #[clap(value_parser = parse_clip_address)]
+        clip: Option<ClipAddress>,
+
+        #[clap(
+            long = "mime",
+            short = 'm',
+            help = "Print the representation of clip in specified MIME type, e.g. \"text/html\""
+        )]
+        mime: Option<mime::Mime>,
     },
 
     #[clap(
@@ -122,10 +135,107 @@ pub enum Commands {
         about = "Print history of clipboard"
     )]
     List {
+        #[clap(long = "offset", default_value = "0", help = "Number of clips to skip")]
+        offset: usize,
+
+        #[clap(
+            long = "limit",
+            short = 'l',
+            default_value = "0",
+            help = "Maximum number of clips, 0 means no limit"
+        )]
+        limit: usize,
+
+        #[clap(
+            long = "order",
+            default_value = "newest",
+            help = "Specify sort order (\"newest\", \"oldest\", \"most-used\", \"frecency\")"
+        )]
+        order: ListOrder,
+
+        #[clap(
+            long = "kinds",
+            short = 'k',
+            help = "Only list clips in specified clipboard (\"clipboard\", \"primary\", \
+                    \"secondary\")"
+        )]
+        kinds: Vec<ClipboardKind>,
+
+        #[clap(
+            long = "mime",
+            help = "Only list clips with specified MIME type, such as \"text/plain\" or \
+                    \"image/*\""
+        )]
+        mimes: Vec<mime::Mime>,
+
+        #[clap(long = "tag", short = 't', help = "Only list clips with any of specified tags")]
+        tags: Vec<String>,
+
+        #[clap(long = "pinned", help = "Only list pinned clips if true, unpinned clips if false")]
+        pinned: Option<bool>,
+
+        #[clap(long = "snippet", help = "Only list snippets if true, other clips if false")]
+        snippet: Option<bool>,
+
+        #[clap(
+            long = "since",
+            value_parser = parse_timestamp,
+            help = "Only list clips copied at or after the RFC 3339 timestamp"
+        )]
+        since: Option<OffsetDateTime>,
+
+        #[clap(
+            long = "until",
+            value_parser = parse_timestamp,
+            help = "Only list clips copied before the RFC 3339 timestamp"
+        )]
+        until: Option<OffsetDateTime>,
+
         #[clap(long)]
         no_id: bool,
     },
 
+    #[clap(aliases = &["find"], about = "Search clips matching <pattern>")]
+    Search {
+        #[clap(
+            long = "mode",
+            default_value = "exact",
+            help = "Specify search mode (\"exact\", \"case-insensitive\", \"regex\", \"fuzzy\")"
+        )]
+        mode: SearchMode,
+
+        #[clap(
+            long = "kinds",
+            short = 'k',
+            help = "Only search clips in specified clipboard (\"clipboard\", \"primary\", \
+                    \"secondary\")"
+        )]
+        kinds: Vec<ClipboardKind>,
+
+        #[clap(
+            long = "mime",
+            help = "Only search clips with specified MIME type, such as \"text/plain\" or \
+                    \"image/*\""
+        )]
+        mimes: Vec<mime::Mime>,
+
+        #[clap(long = "tag", short = 't', help = "Only search clips with any of specified tags")]
+        tags: Vec<String>,
+
+        #[clap(
+            long = "limit",
+            short = 'l',
+            default_value = "0",
+            help = "Maximum number of results"
+        )]
+        limit: usize,
+
+        #[clap(long)]
+        no_id: bool,
+
+        pattern: String,
+    },
+
     #[clap(about = "Update clip with <id>")]
     Update {
         #[clap(value_parser = parse_hex)]
@@ -148,7 +258,11 @@ pub enum Commands {
     )]
     Remove { ids: Vec<String> },
 
-    #[clap(name = "promote", about = "Replace content of clipboard with clip with <id>")]
+    #[clap(
+        name = "promote",
+        about = "Replace content of clipboard with clip with <id>, or the <index>-th most recent \
+                 clip with `@<index>`"
+    )]
     Mark {
         #[clap(
             long = "kinds",
@@ -158,10 +272,57 @@ pub enum Commands {
         )]
         kinds: Vec<ClipboardKind>,
 
-        #[clap(value_parser = parse_hex )]
+        #[clap(
+            long = "prompt",
+            short = 'p',
+            value_parser = parse_prompt,
+            help = "Value of a prompt of snippet template in form of `<label>=<value>`"
+        )]
+        prompts: Vec<(String, String)>,
+
+        #[clap(value_parser = parse_clip_address)]
+        clip: ClipAddress,
+    },
+
+    #[clap(about = "Print clip with <id>, or the <index>-th most recent clip with `@<index>`, \
+                    placeholders of snippet template are expanded")]
+    Expand {
+        #[clap(
+            long = "prompt",
+            short = 'p',
+            value_parser = parse_prompt,
+            help = "Value of a prompt of snippet template in form of `<label>=<value>`"
+        )]
+        prompts: Vec<(String, String)>,
+
+        #[clap(value_parser = parse_clip_address)]
+        clip: ClipAddress,
+    },
+
+    #[clap(about = "Pin clip with <id>, pinned clips are kept when history is full or cleared")]
+    Pin {
+        #[clap(value_parser = parse_hex)]
         id: u64,
     },
 
+    #[clap(about = "Unpin clip with <id>")]
+    Unpin {
+        #[clap(value_parser = parse_hex)]
+        id: u64,
+    },
+
+    #[clap(about = "Manage tags of clips")]
+    Tag {
+        #[clap(subcommand)]
+        commands: TagCommands,
+    },
+
+    #[clap(about = "Manage snippets")]
+    Snippet {
+        #[clap(subcommand)]
+        commands: SnippetCommands,
+    },
+
     #[clap(
         aliases = &["remove-all"],
         about = "Remove all clips in clipboard"
@@ -185,6 +346,150 @@ pub enum Commands {
 
     #[clap(aliases = &["watcher-state"], about = "Get clipboard watcher state")]
     GetWatcherState,
+
+    #[clap(aliases = &["events"], about = "Print clipboard events as they happen")]
+    Subscribe,
+
+    #[clap(
+        aliases = &["reload-config"],
+        about = "Reload configuration of server without restarting it"
+    )]
+    Reload,
+
+    #[clap(about = "Export clip history into a portable archive directory <path>")]
+    Export { path: PathBuf },
+
+    #[clap(about = "Import clips from the archive directory <path> into clip history")]
+    Import {
+        #[clap(
+            long = "mode",
+            default_value = "merge",
+            help = "Specify how clips are imported (\"merge\", \"replace\"), clips with the same \
+                    content are merged in both modes, \"replace\" removes existing clips first"
+        )]
+        mode: ImportMode,
+
+        path: PathBuf,
+    },
+
+    #[clap(about = "Manage clip history stored by server")]
+    History {
+        #[clap(subcommand)]
+        commands: HistoryCommands,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum HistoryCommands {
+    #[clap(about = "Re-encrypt history with a new key, the key is prompted if no source is given")]
+    Rekey {
+        #[clap(
+            long = "current-key-file",
+            help = "Read the current key from file, the current key is prompted if no source is \
+                    given"
+        )]
+        current_key_file: Option<PathBuf>,
+
+        #[clap(
+            long = "current-key-env",
+            conflicts_with = "current_key_file",
+            help = "Read the current key from environment variable"
+        )]
+        current_key_env: Option<String>,
+
+        #[clap(
+            long = "not-encrypted",
+            conflicts_with_all = ["current_key_file", "current_key_env"],
+            help = "History is not encrypted currently"
+        )]
+        not_encrypted: bool,
+
+        #[clap(long = "key-file", help = "Read the new key from file")]
+        key_file: Option<PathBuf>,
+
+        #[clap(long = "key-env", help = "Read the new key from environment variable")]
+        key_env: Option<String>,
+
+        #[clap(
+            long = "no-key",
+            conflicts_with_all = ["key_file", "key_env"],
+            help = "Remove encryption of history"
+        )]
+        no_key: bool,
+    },
+
+    #[clap(about = "Print number of clips and bytes taken in memory and on disk")]
+    Usage,
+}
+
+#[derive(Clone, Subcommand)]
+pub enum TagCommands {
+    #[clap(about = "Tag clip with <id> with [tags]")]
+    Add {
+        #[clap(value_parser = parse_hex)]
+        id: u64,
+
+        #[clap(required = true)]
+        tags: Vec<String>,
+    },
+
+    #[clap(aliases = &["rm", "delete", "del"], about = "Remove [tags] from clip with <id>")]
+    Remove {
+        #[clap(value_parser = parse_hex)]
+        id: u64,
+
+        #[clap(required = true)]
+        tags: Vec<String>,
+    },
+
+    #[clap(aliases = &["ls"], about = "Print all tags with number of clips tagged with them")]
+    List,
+}
+
+#[derive(Clone, Subcommand)]
+pub enum SnippetCommands {
+    #[clap(aliases = &["ls"], about = "Print names of snippets")]
+    List,
+
+    #[clap(about = "Print content of snippet with <name>, the template is printed as is unless \
+                    `--expand` is given")]
+    Get {
+        name: String,
+
+        #[clap(long = "expand", short = 'x', help = "Expand placeholders of snippet template")]
+        expand: bool,
+
+        #[clap(
+            long = "prompt",
+            short = 'p',
+            value_parser = parse_prompt,
+            requires = "expand",
+            help = "Value of a prompt of snippet template in form of `<label>=<value>`"
+        )]
+        prompts: Vec<(String, String)>,
+    },
+
+    #[clap(about = "Save [data], the clip given with `--clip` or the current clip as snippet \
+                    <name> in the snippet directory")]
+    Add {
+        name: String,
+
+        data: Option<String>,
+
+        #[clap(long = "clip", short = 'c', value_parser = parse_clip_address)]
+        clip: Option<ClipAddress>,
+    },
+
+    #[clap(aliases = &["rm", "delete", "del"], about = "Remove snippet with <name>")]
+    Remove { name: String },
+
+    #[clap(about = "Edit snippet with <name>")]
+    Edit {
+        #[clap(env = "EDITOR", long = "editor", short = 'e')]
+        editor: String,
+
+        name: String,
+    },
 }
 
 impl Default for Cli {
@@ -266,20 +571,87 @@ impl Cli {
                     return Ok(0);
                 }
                 None => {
-                    print_list(&client, config.preview_length, false).await?;
+                    let query =
+                        ListQuery { preview_length: config.preview_length, ..ListQuery::default() };
+                    print_metadata_list(client.list(query).await?, false).await?;
                 }
-                Some(Commands::List { no_id }) => {
-                    print_list(&client, config.preview_length, no_id).await?;
+                Some(Commands::List {
+                    offset,
+                    limit,
+                    order,
+                    kinds,
+                    mimes,
+                    tags,
+                    pinned,
+                    snippet,
+                    since,
+                    until,
+                    no_id,
+                }) => {
+                    let query = ListQuery {
+                        preview_length: config.preview_length,
+                        offset,
+                        limit,
+                        order,
+                        kinds,
+                        mimes,
+                        tags,
+                        pinned,
+                        snippet,
+                        since,
+                        until,
+                    };
+                    print_metadata_list(client.list(query).await?, no_id).await?;
                 }
-                Some(Commands::Get { id }) => {
-                    let data = if let Some(id) = id {
-                        client.get(id).await?.preview_information(None)
+                Some(Commands::Search { mode, kinds, mimes, tags, limit, no_id, pattern }) => {
+                    let query = SearchQuery {
+                        pattern,
+                        mode,
+                        kinds,
+                        mimes,
+                        tags,
+                        preview_length: config.preview_length,
+                        limit,
+                        ..SearchQuery::default()
+                    };
+                    let metadata_list =
+                        client.search(query).await?.into_iter().map(|m| m.metadata).collect();
+                    print_metadata_list(metadata_list, no_id).await?;
+                }
+                Some(Commands::Get { clip, mime: Some(mime) }) => {
+                    let clip = if let Some(clip) = clip {
+                        get_clip(&client, clip).await?
                     } else {
+                        client.get_current_clip(ClipboardKind::Clipboard).await?
+                    };
+                    let data = clip.representation(&mime).ok_or_else(|| {
+                        Error::RepresentationUnavailable {
+                            mime: mime.to_string(),
+                            available: clip
+                                .mimes()
+                                .iter()
+                                .map(ToString::to_string)
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        }
+                    })?;
+                    save_file_or_write_stdout(None, data).await?;
+                }
+                Some(Commands::Get { clip, mime: None }) => {
+                    let data = if let Some(clip) = clip {
+                        get_clip(&client, clip).await?.preview_information(None)
+                    } else {
+                        let query = ListQuery {
+                            preview_length: config.preview_length,
+                            limit: 1,
+                            kinds: vec![ClipboardKind::Clipboard],
+                            ..ListQuery::default()
+                        };
                         client
-                            .list(config.preview_length)
+                            .list(query)
                             .await?
                             .into_iter()
-                            .find(|metadata| metadata.kind == ClipboardKind::Clipboard)
+                            .next()
                             .map(|metadata| metadata.preview)
                             .unwrap_or_default()
                     };
@@ -354,19 +726,128 @@ impl Cli {
                         println!("{new_id:016x}");
                     }
                 }
-                Some(Commands::Mark { id, mut kinds }) => {
+                Some(Commands::Mark { mut clip, mut kinds, prompts }) => {
                     if kinds.is_empty() {
                         kinds.push(ClipboardKind::Clipboard);
                     } else {
                         kinds.sort_unstable();
                         kinds.dedup();
                     }
+                    let prompts = prompts.into_iter().collect::<HashMap<_, _>>();
                     for kind in kinds {
-                        if client.mark(id, kind).await? {
+                        let marked_id = match clip {
+                            ClipAddress::Id(id) => client
+                                .mark_with_prompts(id, kind, prompts.clone())
+                                .await?
+                                .then_some(id),
+                            ClipAddress::Index(index) => {
+                                client.mark_by_index(index, kind, prompts.clone()).await?
+                            }
+                        };
+                        if let Some(id) = marked_id {
+                            // the marked clip becomes the most recent one, address it by ID
+                            clip = ClipAddress::Id(id);
                             println!("Ok ({kind})");
                         }
                     }
                 }
+                Some(Commands::Expand { clip, prompts }) => {
+                    let id = match clip {
+                        ClipAddress::Id(id) => id,
+                        ClipAddress::Index(_) => get_clip(&client, clip).await?.id(),
+                    };
+                    let clip = client.expand(id, prompts.into_iter().collect()).await?;
+                    save_file_or_write_stdout(None, clip.as_bytes()).await?;
+                }
+                Some(Commands::Pin { id }) => {
+                    if client.pin(id).await? {
+                        println!("Ok");
+                    }
+                }
+                Some(Commands::Unpin { id }) => {
+                    if client.unpin(id).await? {
+                        println!("Ok");
+                    }
+                }
+                Some(Commands::Tag { commands: TagCommands::Add { id, tags } }) => {
+                    for tag in tags {
+                        if client.tag(id, &tag).await? {
+                            println!("Ok ({tag})");
+                        }
+                    }
+                }
+                Some(Commands::Tag { commands: TagCommands::Remove { id, tags } }) => {
+                    for tag in tags {
+                        if client.untag(id, &tag).await? {
+                            println!("Ok ({tag})");
+                        }
+                    }
+                }
+                Some(Commands::Tag { commands: TagCommands::List }) => {
+                    for (tag, count) in client.list_tags().await? {
+                        println!("{tag}: {count}");
+                    }
+                }
+                Some(Commands::Snippet { commands: SnippetCommands::List }) => {
+                    for ClipEntryMetadata { id, snippet_name, preview, .. } in
+                        client.list_snippets(config.preview_length).await?
+                    {
+                        let name = snippet_name.unwrap_or_default();
+                        println!("{id:016x} {name}: {preview}");
+                    }
+                }
+                Some(Commands::Snippet {
+                    commands: SnippetCommands::Get { name, expand, prompts },
+                }) => {
+                    let id = client
+                        .list_snippets(config.preview_length)
+                        .await?
+                        .into_iter()
+                        .find(|metadata| metadata.snippet_name.as_deref() == Some(name.as_str()))
+                        .map(|metadata| metadata.id)
+                        .ok_or(Error::SnippetNotFound { name })?;
+                    let clip = if expand {
+                        client.expand(id, prompts.into_iter().collect()).await?
+                    } else {
+                        client.get(id).await?
+                    };
+                    save_file_or_write_stdout(None, clip.as_bytes()).await?;
+                }
+                Some(Commands::Snippet { commands: SnippetCommands::Add { name, data, clip } }) => {
+                    let content = match (data, clip) {
+                        (Some(data), _) => data,
+                        (None, Some(clip)) => get_clip(&client, clip).await?.as_utf8_string(),
+                        (None, None) => client
+                            .get_current_clip(ClipboardKind::Clipboard)
+                            .await?
+                            .as_utf8_string(),
+                    };
+                    let id = client.add_snippet(&name, &content).await?;
+                    println!("{id:016x}");
+                }
+                Some(Commands::Snippet { commands: SnippetCommands::Remove { name } }) => {
+                    if client.remove_snippet(&name).await? {
+                        println!("Ok");
+                    } else {
+                        return Err(Error::SnippetNotFound { name });
+                    }
+                }
+                Some(Commands::Snippet { commands: SnippetCommands::Edit { editor, name } }) => {
+                    let id = client
+                        .list_snippets(config.preview_length)
+                        .await?
+                        .into_iter()
+                        .find(|metadata| metadata.snippet_name.as_deref() == Some(name.as_str()))
+                        .map(|metadata| metadata.id)
+                        .ok_or_else(|| Error::SnippetNotFound { name: name.clone() })?;
+                    let editor = ExternalEditor::new(editor);
+                    let content = editor
+                        .execute(&client.get(id).await?.as_utf8_string())
+                        .await
+                        .context(error::CallEditorSnafu)?;
+                    let new_id = client.update_snippet(&name, &content).await?;
+                    println!("{new_id:016x}");
+                }
                 Some(Commands::EnableWatcher) => {
                     print_watcher_state(client.enable_watcher().await?);
                 }
@@ -379,6 +860,70 @@ impl Cli {
                 Some(Commands::GetWatcherState) => {
                     print_watcher_state(client.get_watcher_state().await?);
                 }
+                Some(Commands::Reload) => {
+                    let restart_required = client.reload_config().await?;
+                    if restart_required.is_empty() {
+                        println!("Ok");
+                    } else {
+                        println!(
+                            "Ok, changes of {} take effect after restarting server",
+                            restart_required.join(", ")
+                        );
+                    }
+                }
+                Some(Commands::Subscribe) => {
+                    let mut events = client.subscribe(config.preview_length).await?;
+                    while let Some(event) = events.next().await {
+                        print_event(&event?).await?;
+                    }
+                }
+                Some(Commands::Export { path }) => {
+                    let clips = client.export_history().await?;
+                    let count = archive::write(&path, &clips).context(error::WriteArchiveSnafu)?;
+                    println!("Exported {count} clip(s) into {}", path.display());
+                }
+                Some(Commands::Import { mode, path }) => {
+                    let clips = archive::read(&path).context(error::ReadArchiveSnafu)?;
+                    let (inserted, merged) = client.import_history(clips, mode).await?;
+                    println!("Imported {inserted} clip(s), merged {merged} clip(s)");
+                }
+                Some(Commands::History {
+                    commands:
+                        HistoryCommands::Rekey {
+                            current_key_file,
+                            current_key_env,
+                            not_encrypted,
+                            key_file,
+                            key_env,
+                            no_key,
+                        },
+                }) => {
+                    let current_key = if not_encrypted {
+                        None
+                    } else {
+                        Some(read_current_key(current_key_file, current_key_env).await?)
+                    };
+                    let key =
+                        if no_key { None } else { Some(read_new_key(key_file, key_env).await?) };
+                    if client.rekey_history(current_key.as_deref(), key.as_deref()).await? {
+                        println!(
+                            "History is encrypted with the new key, remember to update \
+                             `history_encryption` in the configuration of {}",
+                            clipcat_base::DAEMON_PROGRAM_NAME
+                        );
+                    } else {
+                        println!(
+                            "History is decrypted, remember to disable `history_encryption` in \
+                             the configuration of {}",
+                            clipcat_base::DAEMON_PROGRAM_NAME
+                        );
+                    }
+                }
+                Some(Commands::History { commands: HistoryCommands::Usage }) => {
+                    println!("clips: {len}", len = client.length().await?);
+                    println!("memory: {size} bytes", size = client.size_in_bytes().await?);
+                    println!("disk: {size} bytes", size = client.history_size_in_bytes().await?);
+                }
                 _ => unreachable!(),
             }
 
@@ -445,6 +990,64 @@ where
 }
 
 #[inline]
+// reads a key from `key_file` or the environment variable `key_env`, `None` is
+// returned if neither of them is given
+async fn read_key_from(
+    key_file: Option<PathBuf>,
+    key_env: Option<String>,
+) -> Result<Option<String>, Error> {
+    if let Some(key_file) = key_file {
+        let key = tokio::fs::read_to_string(&key_file)
+            .await
+            .context(error::ReadFileSnafu { filename: key_file })?;
+        Ok(Some(key.trim_end().to_string()))
+    } else if let Some(name) = key_env {
+        std::env::var(&name).context(error::ReadEnvironmentVariableSnafu { name }).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+async fn read_current_key(
+    key_file: Option<PathBuf>,
+    key_env: Option<String>,
+) -> Result<Vec<u8>, Error> {
+    let key = if let Some(key) = read_key_from(key_file, key_env).await? {
+        key
+    } else {
+        rpassword::prompt_password("Current history encryption key: ")
+            .context(error::PromptKeySnafu)?
+    };
+
+    if key.is_empty() {
+        return Err(Error::EmptyKey);
+    }
+    Ok(key.into_bytes())
+}
+
+async fn read_new_key(
+    key_file: Option<PathBuf>,
+    key_env: Option<String>,
+) -> Result<Vec<u8>, Error> {
+    let key = if let Some(key) = read_key_from(key_file, key_env).await? {
+        key
+    } else {
+        let key = rpassword::prompt_password("New history encryption key: ")
+            .context(error::PromptKeySnafu)?;
+        let confirmation =
+            rpassword::prompt_password("Confirm new key: ").context(error::PromptKeySnafu)?;
+        if key != confirmation {
+            return Err(Error::KeyMismatch);
+        }
+        key
+    };
+
+    if key.is_empty() {
+        return Err(Error::EmptyKey);
+    }
+    Ok(key.into_bytes())
+}
+
 fn print_watcher_state(state: ClipboardWatcherState) {
     let project_name = clipcat_base::PROJECT_NAME_WITH_INITIAL_CAPITAL;
     let msg = match state {
@@ -454,8 +1057,35 @@ fn print_watcher_state(state: ClipboardWatcherState) {
     println!("{msg}");
 }
 
-async fn print_list(client: &Client, preview_length: usize, no_id: bool) -> Result<(), Error> {
-    let metadata_list = client.list(preview_length).await?;
+async fn print_event(event: &ClipboardEvent) -> Result<(), Error> {
+    let name = event.as_str();
+    let output = match event {
+        ClipboardEvent::Inserted(ClipEntryMetadata { id, preview, .. }) => {
+            format!("{name} {id:016x}: {preview}\n")
+        }
+        ClipboardEvent::Removed(id) => format!("{name} {id:016x}\n"),
+        ClipboardEvent::Updated { old_id, metadata: ClipEntryMetadata { id, preview, .. } } => {
+            format!("{name} {old_id:016x} -> {id:016x}: {preview}\n")
+        }
+        ClipboardEvent::Marked(ClipEntryMetadata { id, kind, preview, .. }) => {
+            format!("{name} {id:016x} ({kind}): {preview}\n")
+        }
+        ClipboardEvent::Cleared => format!("{name}\n"),
+        ClipboardEvent::WatcherToggled(state) => {
+            let state = match state {
+                ClipboardWatcherState::Enabled => "enabled",
+                ClipboardWatcherState::Disabled => "disabled",
+            };
+            format!("{name} {state}\n")
+        }
+    };
+    tokio::io::stdout().write_all(output.as_bytes()).await.context(error::WriteStdoutSnafu)
+}
+
+async fn print_metadata_list(
+    metadata_list: Vec<ClipEntryMetadata>,
+    no_id: bool,
+) -> Result<(), Error> {
     for metadata in metadata_list {
         let ClipEntryMetadata { id, preview, .. } = metadata;
         let output = if no_id { format!("{preview}\n") } else { format!("{id:016x}: {preview}\n") };
@@ -466,3 +1096,37 @@ async fn print_list(client: &Client, preview_length: usize, no_id: bool) -> Resu
 
 #[inline]
 fn parse_hex(src: &str) -> Result<u64, ParseIntError> { u64::from_str_radix(src, 16) }
+
+#[derive(Clone, Copy, Debug)]
+pub enum ClipAddress {
+    Id(u64),
+
+    // the most recent clip is at index 0
+    Index(usize),
+}
+
+#[inline]
+fn parse_clip_address(src: &str) -> Result<ClipAddress, ParseIntError> {
+    src.strip_prefix('@').map_or_else(
+        || parse_hex(src).map(ClipAddress::Id),
+        |index| index.parse().map(ClipAddress::Index),
+    )
+}
+
+async fn get_clip(client: &Client, clip: ClipAddress) -> Result<ClipEntry, Error> {
+    match clip {
+        ClipAddress::Id(id) => Ok(client.get(id).await?),
+        ClipAddress::Index(index) => Ok(client.get_by_index(index).await?),
+    }
+}
+
+fn parse_prompt(src: &str) -> Result<(String, String), String> {
+    src.split_once('=')
+        .map(|(label, value)| (label.trim().to_string(), value.to_string()))
+        .ok_or_else(|| format!("invalid prompt `{src}`, `<label>=<value>` is expected"))
+}
+
+#[inline]
+fn parse_timestamp(src: &str) -> Result<OffsetDateTime, time::error::Parse> {
+    OffsetDateTime::parse(src, &Rfc3339)
+}
@@ -32,6 +32,30 @@ pub enum Error {
 
     #[snafu(display("{source}"))]
     CheckUtf8String { source: Utf8Error },
+
+    #[snafu(display("Could not read environment variable `{name}`, error: {source}"))]
+    ReadEnvironmentVariable { name: String, source: std::env::VarError },
+
+    #[snafu(display("Could not read key from terminal, error: {source}"))]
+    PromptKey { source: std::io::Error },
+
+    #[snafu(display("Keys do not match"))]
+    KeyMismatch,
+
+    #[snafu(display("Key must not be empty"))]
+    EmptyKey,
+
+    #[snafu(display("Clip is not available as `{mime}`, available types: {available}"))]
+    RepresentationUnavailable { mime: String, available: String },
+
+    #[snafu(display("Snippet `{name}` is not found"))]
+    SnippetNotFound { name: String },
+
+    #[snafu(display("Could not write archive, error: {source}"))]
+    WriteArchive { source: clipcat_base::archive::Error },
+
+    #[snafu(display("Could not read archive, error: {source}"))]
+    ReadArchive { source: clipcat_base::archive::Error },
 }
 
 impl From<clipcat_external_editor::Error> for Error {
@@ -54,6 +78,12 @@ impl From<clipcat_client::error::GetClipError> for Error {
     }
 }
 
+impl From<clipcat_client::error::GetClipByIndexError> for Error {
+    fn from(err: clipcat_client::error::GetClipByIndexError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
 impl From<clipcat_client::error::GetCurrentClipError> for Error {
     fn from(err: clipcat_client::error::GetCurrentClipError) -> Self {
         Self::Operation { error: err.to_string() }
@@ -90,6 +120,78 @@ impl From<clipcat_client::error::MarkClipError> for Error {
     }
 }
 
+impl From<clipcat_client::error::MarkClipByIndexError> for Error {
+    fn from(err: clipcat_client::error::MarkClipByIndexError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::ExpandClipError> for Error {
+    fn from(err: clipcat_client::error::ExpandClipError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::PinClipError> for Error {
+    fn from(err: clipcat_client::error::PinClipError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::UnpinClipError> for Error {
+    fn from(err: clipcat_client::error::UnpinClipError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::TagClipError> for Error {
+    fn from(err: clipcat_client::error::TagClipError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::UntagClipError> for Error {
+    fn from(err: clipcat_client::error::UntagClipError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::ListTagsError> for Error {
+    fn from(err: clipcat_client::error::ListTagsError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::ListSnippetsError> for Error {
+    fn from(err: clipcat_client::error::ListSnippetsError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::AddSnippetError> for Error {
+    fn from(err: clipcat_client::error::AddSnippetError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::UpdateSnippetError> for Error {
+    fn from(err: clipcat_client::error::UpdateSnippetError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::RemoveSnippetError> for Error {
+    fn from(err: clipcat_client::error::RemoveSnippetError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::ReloadConfigError> for Error {
+    fn from(err: clipcat_client::error::ReloadConfigError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
 impl From<clipcat_client::error::UpdateClipError> for Error {
     fn from(err: clipcat_client::error::UpdateClipError) -> Self {
         Self::Operation { error: err.to_string() }
@@ -102,6 +204,18 @@ impl From<clipcat_client::error::ListClipError> for Error {
     }
 }
 
+impl From<clipcat_client::error::SearchClipError> for Error {
+    fn from(err: clipcat_client::error::SearchClipError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::SubscribeError> for Error {
+    fn from(err: clipcat_client::error::SubscribeError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
 impl From<clipcat_client::error::EnableWatcherError> for Error {
     fn from(err: clipcat_client::error::EnableWatcherError) -> Self {
         Self::Operation { error: err.to_string() }
@@ -129,3 +243,27 @@ impl From<clipcat_client::error::GetWatcherStateError> for Error {
 impl From<clipcat_base::ClipEntryError> for Error {
     fn from(error: clipcat_base::ClipEntryError) -> Self { Self::EncodeData { error } }
 }
+
+impl From<clipcat_client::error::GetHistorySizeError> for Error {
+    fn from(err: clipcat_client::error::GetHistorySizeError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::RekeyHistoryError> for Error {
+    fn from(err: clipcat_client::error::RekeyHistoryError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::ExportHistoryError> for Error {
+    fn from(err: clipcat_client::error::ExportHistoryError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
+
+impl From<clipcat_client::error::ImportHistoryError> for Error {
+    fn from(err: clipcat_client::error::ImportHistoryError) -> Self {
+        Self::Operation { error: err.to_string() }
+    }
+}
@@ -69,6 +69,21 @@ pub enum Commands {
 
     #[clap(about = "Output default configuration")]
     DefaultConfig,
+
+    #[clap(about = "Check or repair clip history")]
+    History {
+        #[clap(subcommand)]
+        commands: HistoryCommands,
+    },
+}
+
+#[derive(Clone, Copy, Subcommand)]
+pub enum HistoryCommands {
+    #[clap(about = "Check clip history for corrupted records, missing images and orphaned images")]
+    Check,
+
+    #[clap(about = "Repair clip history, the original history is backed up beside it")]
+    Repair,
 }
 
 impl Default for Cli {
@@ -99,9 +114,13 @@ impl Cli {
                     .expect("failed to write to stdout");
                 Ok(())
             }
+            Some(Commands::History { commands }) => {
+                let config = self.load_config()?;
+                run_history_command(&config, commands)
+            }
             None => {
                 let config = self.load_config()?;
-                run_clipcatd(config, self.replace)
+                run_clipcatd(config, self)
             }
         }
     }
@@ -110,7 +129,9 @@ impl Cli {
         let config_file = &self.config_file.clone().unwrap_or_else(Config::search_config_file_path);
         let mut config = Config::load(config_file)?;
 
-        config.daemonize = !self.no_daemon;
+        // systemd tracks the process it starts, forking would detach the daemon from
+        // the notification socket and the sockets passed to it
+        config.daemonize = !self.no_daemon && !is_started_by_systemd();
 
         if let Some(history_file_path) = &self.history_file_path {
             config.history_file_path.clone_from(history_file_path);
@@ -144,13 +165,13 @@ impl Cli {
 }
 
 #[allow(clippy::cognitive_complexity)]
-fn run_clipcatd(config: Config, replace: bool) -> Result<(), Error> {
+fn run_clipcatd(config: Config, cli: Cli) -> Result<(), Error> {
     config.log.registry();
 
     let pid_file = PidFile::from(config.pid_file.clone());
     if pid_file.exists() {
         let pid = pid_file.try_load()?;
-        if replace {
+        if cli.replace {
             if let Err(err) = kill_other(pid) {
                 tracing::warn!(
                     "Error occurs while trying to terminate another instance, error: {err}"
@@ -176,13 +197,25 @@ fn run_clipcatd(config: Config, replace: bool) -> Result<(), Error> {
         }
     }
 
+    // load the key before daemonizing, terminal is not available after that
+    let history_encryption_key = config.history_encryption.load_key()?;
+
     if config.daemonize {
         daemonize::Daemonize::new().pid_file(pid_file.path()).start()?;
     } else {
         pid_file.create()?;
     }
 
-    let config = clipcat_server::Config::from(config);
+    let config_loader: clipcat_server::config::ConfigLoader = Box::new(move |running| {
+        // the key could not be prompted again after daemonizing, the running one is
+        // kept, it is updated once history is rekeyed
+        cli.load_config().map_err(|err| err.to_string()).map(|config| clipcat_server::Config {
+            history_encryption_key: running.history_encryption_key.clone(),
+            ..clipcat_server::Config::from(config)
+        })
+    });
+    let config =
+        clipcat_server::Config { history_encryption_key, ..clipcat_server::Config::from(config) };
 
     tracing::info!(
         "{} is initializing, pid: {}",
@@ -193,9 +226,9 @@ fn run_clipcatd(config: Config, replace: bool) -> Result<(), Error> {
     tracing::info!("Initializing Tokio runtime");
 
     let exit_status = match Runtime::new().context(error::InitializeTokioRuntimeSnafu) {
-        Ok(runtime) => {
-            runtime.block_on(clipcat_server::serve_with_shutdown(config)).map_err(Error::from)
-        }
+        Ok(runtime) => runtime
+            .block_on(clipcat_server::serve_with_shutdown(config, config_loader))
+            .map_err(Error::from),
         Err(err) => Err(err),
     };
 
@@ -207,6 +240,60 @@ fn run_clipcatd(config: Config, replace: bool) -> Result<(), Error> {
     exit_status
 }
 
+fn run_history_command(config: &Config, command: HistoryCommands) -> Result<(), Error> {
+    // the daemon appends to history while running, a repaired history would be
+    // overwritten, a PID file left by a daemon which is gone is ignored
+    let pid_file = PidFile::from(config.pid_file.clone());
+    if matches!(command, HistoryCommands::Repair) && pid_file.exists() {
+        let pid = pid_file.try_load()?;
+        if is_process_alive(pid) {
+            return Err(Error::DaemonRunning { pid });
+        }
+    }
+
+    let key = config.history_encryption.load_key()?;
+    let file_path = &config.history_file_path;
+    let driver = config.history_driver.into();
+
+    let runtime = Runtime::new().context(error::InitializeTokioRuntimeSnafu)?;
+    let report = runtime.block_on(async {
+        match command {
+            HistoryCommands::Check => {
+                clipcat_server::check_history(file_path, driver, key.as_ref()).await
+            }
+            HistoryCommands::Repair => {
+                clipcat_server::repair_history(file_path, driver, key.as_ref()).await
+            }
+        }
+    })?;
+
+    std::io::stdout().write_all(report.to_string().as_bytes()).expect("failed to write to stdout");
+    if matches!(command, HistoryCommands::Check) && !report.is_healthy() {
+        return Err(Error::UnhealthyHistory);
+    }
+    Ok(())
+}
+
+fn is_started_by_systemd() -> bool {
+    // the variables are inherited by processes spawned from a service, they are
+    // only honoured if systemd passes them to this process
+    let is_current_process = |key: &str| {
+        std::env::var(key)
+            .is_ok_and(|pid| pid.parse::<u32>().is_ok_and(|pid| pid == std::process::id()))
+    };
+    is_current_process("LISTEN_PID")
+        || (std::env::var_os("NOTIFY_SOCKET").is_some()
+            && (is_current_process("MAINPID") || is_current_process("SYSTEMD_EXEC_PID")))
+}
+
+#[allow(unsafe_code)]
+#[inline]
+fn is_process_alive(pid: libc::pid_t) -> bool {
+    // no signal is sent, only the existence of the process is checked
+    let ret = unsafe { libc::kill(pid, 0) };
+    ret == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
 #[allow(unsafe_code)]
 #[inline]
 fn kill_other(pid: libc::pid_t) -> Result<(), Error> {
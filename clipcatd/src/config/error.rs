@@ -13,4 +13,21 @@ pub enum Error {
 
     #[snafu(display("Could not resolve file path {}, error: {source}", file_path.display()))]
     ResolveFilePath { file_path: PathBuf, source: std::io::Error },
+
+    #[snafu(display(
+        "Could not read history encryption key from {}, error: {source}",
+        file_path.display()
+    ))]
+    ReadEncryptionKeyFile { file_path: PathBuf, source: std::io::Error },
+
+    #[snafu(display(
+        "Could not read history encryption key from environment variable `{name}`, error: {source}"
+    ))]
+    ReadEncryptionKeyEnvironmentVariable { name: String, source: std::env::VarError },
+
+    #[snafu(display("Could not read history encryption key from terminal, error: {source}"))]
+    PromptEncryptionKey { source: std::io::Error },
+
+    #[snafu(display("History encryption key must not be empty"))]
+    EmptyEncryptionKey,
 }
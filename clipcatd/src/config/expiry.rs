@@ -0,0 +1,60 @@
+use std::{collections::HashSet, str::FromStr, time::Duration};
+
+use clipcat_base::ClipboardKind;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExpiryConfig {
+    #[serde(default = "ExpiryConfig::default_check_interval_secs")]
+    pub check_interval_secs: u64,
+
+    #[serde(default)]
+    pub rules: Vec<ExpiryRuleConfig>,
+}
+
+impl ExpiryConfig {
+    pub const fn default_check_interval_secs() -> u64 { 60 }
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        Self { check_interval_secs: Self::default_check_interval_secs(), rules: Vec::new() }
+    }
+}
+
+impl From<ExpiryConfig> for clipcat_server::config::ExpiryConfig {
+    fn from(ExpiryConfig { check_interval_secs, rules }: ExpiryConfig) -> Self {
+        Self {
+            check_interval: Duration::from_secs(check_interval_secs),
+            rules: rules.into_iter().map(clipcat_server::config::ExpiryRule::from).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExpiryRuleConfig {
+    // names of clipboard kinds, the rule applies to all kinds if it is empty
+    #[serde(default)]
+    pub kinds: HashSet<String>,
+
+    // either a MIME type like `image/png` or a top-level type like `image`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime: Option<String>,
+
+    pub max_age_secs: u64,
+}
+
+impl From<ExpiryRuleConfig> for clipcat_server::config::ExpiryRule {
+    fn from(ExpiryRuleConfig { kinds, mime, max_age_secs }: ExpiryRuleConfig) -> Self {
+        let kinds = kinds
+            .into_iter()
+            .filter_map(|kind| {
+                ClipboardKind::from_str(&kind)
+                    .map_err(|err| tracing::warn!("Ignore clipboard kind `{kind}`, error: {err}"))
+                    .ok()
+            })
+            .collect();
+        let max_age = time::Duration::seconds(i64::try_from(max_age_secs).unwrap_or(i64::MAX));
+        Self { kinds, mime, max_age }
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use clipcat_server::config::{HistoryEncryptionKey, HistoryEncryptionKeySource};
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::config::{error, Error};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryDriver {
+    #[default]
+    #[serde(alias = "fs")]
+    FileSystem,
+
+    Sqlite,
+}
+
+impl From<HistoryDriver> for clipcat_server::config::HistoryDriver {
+    fn from(driver: HistoryDriver) -> Self {
+        match driver {
+            HistoryDriver::FileSystem => Self::FileSystem,
+            HistoryDriver::Sqlite => Self::Sqlite,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HistoryEncryptionConfig {
+    pub enable: bool,
+
+    // the key is read from `key_file`, then from the environment variable `key_env`, it is
+    // prompted on terminal if neither of them is provided
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_file: Option<PathBuf>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_env: Option<String>,
+}
+
+impl HistoryEncryptionConfig {
+    pub fn key_source(&self) -> Option<HistoryEncryptionKeySource> {
+        if !self.enable {
+            return None;
+        }
+
+        let source = match (&self.key_file, &self.key_env) {
+            (Some(file_path), _) => HistoryEncryptionKeySource::File(file_path.clone()),
+            (None, Some(name)) => HistoryEncryptionKeySource::EnvironmentVariable(name.clone()),
+            (None, None) => HistoryEncryptionKeySource::Prompt,
+        };
+        Some(source)
+    }
+
+    pub fn load_key(&self) -> Result<Option<HistoryEncryptionKey>, Error> {
+        if !self.enable {
+            return Ok(None);
+        }
+
+        let key = if let Some(file_path) = &self.key_file {
+            std::fs::read_to_string(file_path)
+                .context(error::ReadEncryptionKeyFileSnafu { file_path: file_path.clone() })?
+                .trim_end()
+                .to_string()
+        } else if let Some(name) = &self.key_env {
+            std::env::var(name)
+                .context(error::ReadEncryptionKeyEnvironmentVariableSnafu { name: name.clone() })?
+        } else {
+            rpassword::prompt_password("History encryption key: ")
+                .context(error::PromptEncryptionKeySnafu)?
+        };
+
+        if key.is_empty() {
+            return Err(Error::EmptyEncryptionKey);
+        }
+        Ok(Some(HistoryEncryptionKey::from(key.into_bytes())))
+    }
+}
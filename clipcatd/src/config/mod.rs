@@ -1,10 +1,14 @@
 mod dbus;
 mod desktop_notification;
 mod error;
+mod expiry;
 mod grpc;
+mod history;
 mod metrics;
+mod rest;
 mod snippet;
 mod watcher;
+mod web;
 
 use std::path::{Path, PathBuf};
 
@@ -15,8 +19,16 @@ use snafu::ResultExt;
 
 pub use self::error::Error;
 use self::{
-    dbus::DBusConfig, desktop_notification::DesktopNotificationConfig, grpc::GrpcConfig,
-    metrics::MetricsConfig, snippet::SnippetConfig, watcher::WatcherConfig,
+    dbus::DBusConfig,
+    desktop_notification::DesktopNotificationConfig,
+    expiry::ExpiryConfig,
+    grpc::GrpcConfig,
+    history::{HistoryDriver, HistoryEncryptionConfig},
+    metrics::MetricsConfig,
+    rest::RestConfig,
+    snippet::SnippetConfig,
+    watcher::WatcherConfig,
+    web::WebConfig,
 };
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -32,12 +44,29 @@ pub struct Config {
     #[serde(default = "Config::default_max_history")]
     pub max_history: usize,
 
+    // 0 means unlimited
+    #[serde(default)]
+    pub max_history_bytes: usize,
+
+    // 0 means unlimited
+    #[serde(default)]
+    pub max_history_disk_bytes: u64,
+
     #[serde(default = "Config::default_synchronize_selection_with_clipboard")]
     pub synchronize_selection_with_clipboard: bool,
 
     #[serde(default = "Config::default_history_file_path")]
     pub history_file_path: PathBuf,
 
+    #[serde(default)]
+    pub history_driver: HistoryDriver,
+
+    #[serde(default)]
+    pub history_encryption: HistoryEncryptionConfig,
+
+    #[serde(default)]
+    pub expiry: ExpiryConfig,
+
     #[serde(default)]
     pub log: clipcat_cli::config::LogConfig,
 
@@ -53,11 +82,26 @@ pub struct Config {
     #[serde(default)]
     pub metrics: MetricsConfig,
 
+    #[serde(default)]
+    pub web: WebConfig,
+
+    #[serde(default)]
+    pub rest: RestConfig,
+
     #[serde(default)]
     pub desktop_notification: DesktopNotificationConfig,
 
     #[serde(default)]
     pub snippets: Vec<SnippetConfig>,
+
+    // snippets added with `clipcatctl snippet add` are stored in it
+    #[serde(default = "Config::default_snippet_directory")]
+    pub snippet_directory: PathBuf,
+
+    // `{{env:<name>}}` of snippets expands to an empty string unless `<name>`
+    // is listed
+    #[serde(default)]
+    pub snippet_environment_variables: Vec<String>,
 }
 
 impl Default for Config {
@@ -67,7 +111,12 @@ impl Default for Config {
             pid_file: Self::default_pid_file_path(),
             primary_threshold_ms: Self::default_primary_threshold_ms(),
             max_history: Self::default_max_history(),
+            max_history_bytes: 0,
+            max_history_disk_bytes: 0,
             history_file_path: Self::default_history_file_path(),
+            history_driver: HistoryDriver::default(),
+            history_encryption: HistoryEncryptionConfig::default(),
+            expiry: ExpiryConfig::default(),
             synchronize_selection_with_clipboard:
                 Self::default_synchronize_selection_with_clipboard(),
             log: clipcat_cli::config::LogConfig::default(),
@@ -76,7 +125,11 @@ impl Default for Config {
             desktop_notification: DesktopNotificationConfig::default(),
             dbus: DBusConfig::default(),
             metrics: MetricsConfig::default(),
+            web: WebConfig::default(),
+            rest: RestConfig::default(),
             snippets: Vec::new(),
+            snippet_directory: Self::default_snippet_directory(),
+            snippet_environment_variables: Vec::new(),
         }
     }
 }
@@ -125,6 +178,18 @@ impl Config {
         .collect()
     }
 
+    #[inline]
+    pub fn default_snippet_directory() -> PathBuf {
+        let base_dirs = BaseDirs::new().expect("`BaseDirs::new` always success");
+        [
+            PathBuf::from(base_dirs.data_dir()),
+            PathBuf::from(clipcat_base::PROJECT_NAME),
+            PathBuf::from("snippets"),
+        ]
+        .into_iter()
+        .collect()
+    }
+
     #[inline]
     pub const fn default_synchronize_selection_with_clipboard() -> bool { true }
 
@@ -185,6 +250,15 @@ impl Config {
 
         config.history_file_path = resolve_path(&config.history_file_path)?;
 
+        config.snippet_directory = resolve_path(&config.snippet_directory)?;
+
+        config.history_encryption.key_file =
+            match config.history_encryption.key_file.map(resolve_path) {
+                Some(Ok(path)) => Some(path),
+                Some(Err(err)) => return Err(err),
+                None => None,
+            };
+
         Ok(config)
     }
 }
@@ -195,13 +269,23 @@ impl From<Config> for clipcat_server::Config {
             grpc,
             primary_threshold_ms,
             max_history,
+            max_history_bytes,
+            max_history_disk_bytes,
             synchronize_selection_with_clipboard,
             history_file_path,
+            history_driver,
+            history_encryption,
+            expiry,
             watcher,
             desktop_notification,
             dbus,
             metrics,
+            web,
+            rest,
             snippets,
+            snippet_directory,
+            snippet_environment_variables,
+            log,
             ..
         }: Config,
     ) -> Self {
@@ -222,6 +306,8 @@ impl From<Config> for clipcat_server::Config {
             clipcat_server::config::DesktopNotificationConfig::from(desktop_notification);
         let dbus = clipcat_server::config::DBusConfig::from(dbus);
         let metrics = clipcat_server::config::MetricsConfig::from(metrics);
+        let web = clipcat_server::config::WebConfig::from(web);
+        let rest = clipcat_server::config::RestConfig::from(rest);
         let snippets =
             snippets.into_iter().map(clipcat_server::config::SnippetConfig::from).collect();
 
@@ -231,13 +317,31 @@ impl From<Config> for clipcat_server::Config {
             grpc_access_token,
             primary_threshold,
             max_history,
+            max_history_bytes,
+            max_history_disk_bytes,
             synchronize_selection_with_clipboard,
             history_file_path,
+            history_driver: history_driver.into(),
+            // the key is loaded separately, it might be prompted on terminal
+            history_encryption_key: None,
+            history_encryption: history_encryption.key_source(),
+            log: clipcat_server::config::LogConfig {
+                file_path: log.file_path,
+                emit_journald: log.emit_journald,
+                emit_stdout: log.emit_stdout,
+                emit_stderr: log.emit_stderr,
+                level: log.level,
+            },
+            expiry: expiry.into(),
             watcher,
             dbus,
             desktop_notification,
             metrics,
+            web,
+            rest,
             snippets,
+            snippet_directory: Some(snippet_directory),
+            snippet_environment_variables,
         }
     }
 }
@@ -0,0 +1,56 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RestConfig {
+    #[serde(default)]
+    pub enable_http: bool,
+
+    #[serde(default)]
+    pub enable_local_socket: bool,
+
+    #[serde(default = "RestConfig::default_host")]
+    pub host: IpAddr,
+
+    #[serde(default = "RestConfig::default_port")]
+    pub port: u16,
+
+    #[serde(default = "clipcat_base::config::default_rest_unix_domain_socket")]
+    pub local_socket: PathBuf,
+}
+
+impl RestConfig {
+    #[inline]
+    pub const fn socket_address(&self) -> SocketAddr { SocketAddr::new(self.host, self.port) }
+
+    #[inline]
+    pub const fn default_host() -> IpAddr { clipcat_base::DEFAULT_REST_HOST }
+
+    #[inline]
+    pub const fn default_port() -> u16 { clipcat_base::DEFAULT_REST_PORT }
+}
+
+impl Default for RestConfig {
+    fn default() -> Self {
+        Self {
+            enable_http: false,
+            enable_local_socket: false,
+            host: Self::default_host(),
+            port: Self::default_port(),
+            local_socket: clipcat_base::config::default_rest_unix_domain_socket(),
+        }
+    }
+}
+
+impl From<RestConfig> for clipcat_server::config::RestConfig {
+    fn from(config: RestConfig) -> Self {
+        Self {
+            listen_address: config.enable_http.then_some(config.socket_address()),
+            local_socket: config.enable_local_socket.then_some(config.local_socket),
+        }
+    }
+}
@@ -1,5 +1,9 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
+use clipcat_base::{ClipboardKind, SecretAction, SecretKind};
 use serde::{Deserialize, Serialize};
 
 // SAFETY: user may use bool to enable/disable the functions
@@ -32,6 +36,41 @@ pub struct WatcherConfig {
 
     #[serde(default = "WatcherConfig::default_filter_image_max_size")]
     pub filter_image_max_size: usize,
+
+    #[serde(default)]
+    pub source_application_rules: Vec<SourceApplicationRuleConfig>,
+
+    #[serde(default)]
+    pub secret_detection: SecretDetectionConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SourceApplicationRuleConfig {
+    // names of clipboard kinds, the rule applies to all kinds if it is empty
+    #[serde(default)]
+    pub kinds: HashSet<String>,
+
+    #[serde(default)]
+    pub allow: HashSet<String>,
+
+    #[serde(default)]
+    pub deny: HashSet<String>,
+}
+
+impl From<SourceApplicationRuleConfig> for clipcat_base::SourceApplicationRule {
+    fn from(
+        SourceApplicationRuleConfig { kinds, allow, deny }: SourceApplicationRuleConfig,
+    ) -> Self {
+        let kinds = kinds
+            .into_iter()
+            .filter_map(|kind| {
+                ClipboardKind::from_str(&kind)
+                    .map_err(|err| tracing::warn!("Ignore clipboard kind `{kind}`, error: {err}"))
+                    .ok()
+            })
+            .collect();
+        Self { kinds, allow, deny }
+    }
 }
 
 impl Default for WatcherConfig {
@@ -46,6 +85,8 @@ impl Default for WatcherConfig {
             denied_text_regex_patterns: HashSet::new(),
             filter_image_max_size: Self::default_filter_image_max_size(),
             sensitive_x11_atoms: Self::default_sensitive_x11_atoms(),
+            source_application_rules: Vec::new(),
+            secret_detection: SecretDetectionConfig::default(),
         }
     }
 }
@@ -62,6 +103,8 @@ impl From<WatcherConfig> for clipcat_server::ClipboardWatcherOptions {
             denied_text_regex_patterns,
             filter_image_max_size,
             sensitive_x11_atoms,
+            source_application_rules,
+            secret_detection,
         }: WatcherConfig,
     ) -> Self {
         Self {
@@ -74,6 +117,106 @@ impl From<WatcherConfig> for clipcat_server::ClipboardWatcherOptions {
             filter_image_max_size,
             denied_text_regex_patterns,
             sensitive_x11_atoms,
+            source_application_rules: source_application_rules
+                .into_iter()
+                .map(clipcat_base::SourceApplicationRule::from)
+                .collect(),
+            secret_detection_rules: secret_detection.into_rules(),
+        }
+    }
+}
+
+// rules for common token formats keep the clip in memory only by default, rules
+// which are prone to false positives are disabled by default
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct SecretDetectionConfig {
+    pub aws_access_key: SecretRuleConfig,
+
+    pub github_token: SecretRuleConfig,
+
+    pub gitlab_token: SecretRuleConfig,
+
+    pub json_web_token: SecretRuleConfig,
+
+    pub pem_private_key: SecretRuleConfig,
+
+    pub credit_card_number: SecretRuleConfig,
+
+    pub high_entropy: SecretRuleConfig,
+}
+
+impl Default for SecretDetectionConfig {
+    fn default() -> Self {
+        Self {
+            aws_access_key: SecretRuleConfig::enabled(),
+            github_token: SecretRuleConfig::enabled(),
+            gitlab_token: SecretRuleConfig::enabled(),
+            json_web_token: SecretRuleConfig::enabled(),
+            pem_private_key: SecretRuleConfig::enabled(),
+            credit_card_number: SecretRuleConfig::default(),
+            high_entropy: SecretRuleConfig::default(),
+        }
+    }
+}
+
+impl SecretDetectionConfig {
+    // returns the enabled rules
+    fn into_rules(self) -> HashMap<SecretKind, SecretAction> {
+        let Self {
+            aws_access_key,
+            github_token,
+            gitlab_token,
+            json_web_token,
+            pem_private_key,
+            credit_card_number,
+            high_entropy,
+        } = self;
+        [
+            (SecretKind::AwsAccessKey, aws_access_key),
+            (SecretKind::GitHubToken, github_token),
+            (SecretKind::GitLabToken, gitlab_token),
+            (SecretKind::JsonWebToken, json_web_token),
+            (SecretKind::PemPrivateKey, pem_private_key),
+            (SecretKind::CreditCardNumber, credit_card_number),
+            (SecretKind::HighEntropy, high_entropy),
+        ]
+        .into_iter()
+        .filter(|(_, rule)| rule.enable)
+        .map(|(kind, rule)| (kind, rule.action.into()))
+        .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SecretRuleConfig {
+    #[serde(default)]
+    pub enable: bool,
+
+    #[serde(default)]
+    pub action: SecretActionConfig,
+}
+
+impl SecretRuleConfig {
+    const fn enabled() -> Self { Self { enable: true, action: SecretActionConfig::MemoryOnly } }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretActionConfig {
+    // the clip is not captured
+    Drop,
+
+    // the clip is captured but never written to history
+    #[default]
+    MemoryOnly,
+}
+
+impl From<SecretActionConfig> for SecretAction {
+    fn from(action: SecretActionConfig) -> Self {
+        match action {
+            SecretActionConfig::Drop => Self::Drop,
+            SecretActionConfig::MemoryOnly => Self::KeepInMemory,
         }
     }
 }
@@ -0,0 +1,38 @@
+use std::net::{IpAddr, SocketAddr};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct WebConfig {
+    #[serde(default)]
+    pub enable: bool,
+
+    #[serde(default = "WebConfig::default_host")]
+    pub host: IpAddr,
+
+    #[serde(default = "WebConfig::default_port")]
+    pub port: u16,
+}
+
+impl WebConfig {
+    #[inline]
+    pub const fn socket_address(&self) -> SocketAddr { SocketAddr::new(self.host, self.port) }
+
+    #[inline]
+    pub const fn default_host() -> IpAddr { clipcat_base::DEFAULT_WEBUI_HOST }
+
+    #[inline]
+    pub const fn default_port() -> u16 { clipcat_base::DEFAULT_WEBUI_PORT }
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self { enable: false, host: Self::default_host(), port: Self::default_port() }
+    }
+}
+
+impl From<WebConfig> for clipcat_server::config::WebConfig {
+    fn from(config: WebConfig) -> Self {
+        Self { enable: config.enable, listen_address: config.socket_address() }
+    }
+}
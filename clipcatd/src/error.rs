@@ -22,6 +22,12 @@ pub enum Error {
 
     #[snafu(display("Failed to send `SIGTERM` to PID `{pid}`"))]
     SendSignalTermination { pid: libc::pid_t },
+
+    #[snafu(display("Another instance (PID: {pid}) is running, please terminate `{pid}` first"))]
+    DaemonRunning { pid: libc::pid_t },
+
+    #[snafu(display("History has problems, run `clipcatd history repair` to repair it"))]
+    UnhealthyHistory,
 }
 
 impl From<daemonize::Error> for Error {
@@ -53,6 +59,8 @@ impl CommandError for Error {
             | Self::Daemonize { .. }
             | Self::SendSignalTermination { .. }
             | Self::PidFile { .. } => exitcode::IOERR,
+            Self::DaemonRunning { .. } => exitcode::TEMPFAIL,
+            Self::UnhealthyHistory => exitcode::DATAERR,
         }
     }
 }
@@ -0,0 +1,430 @@
+//! Portable archive of clips.
+//!
+//! An archive is a directory with the following layout:
+//!
+//! ```text
+//! <archive>/
+//! ├── manifest.json    format name, version, export time and number of clips
+//! ├── clips.jsonl      one JSON object per clip, from the newest to the oldest
+//! └── data/            images and binary representations, named by SHA-256 digest
+//! ```
+//!
+//! Each line of `clips.jsonl` looks like:
+//!
+//! ```json
+//! {"digest":"<sha256 of content>","kind":"Clipboard","mime":"text/plain; charset=utf-8",
+//!  "timestamp":"2024-01-01T00:00:00Z","text":"hello","pinned":false,"tags":["work"],
+//!  "source_application":"firefox","use_count":2,"last_used":"2024-01-02T00:00:00Z",
+//!  "template":false,"representations":[{"mime":"text/html","text":"<b>hello</b>"}]}
+//! ```
+//!
+//! Textual content is stored inline as `text`, other content is stored in
+//! `data/` and referenced as `file` relative to the archive directory.
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use time::OffsetDateTime;
+
+use crate::{ClipEntry, ClipRepresentation, ClipboardKind};
+
+pub const FORMAT_NAME: &str = "clipcat-archive";
+pub const FORMAT_VERSION: u64 = 1;
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+pub const CLIPS_FILE_NAME: &str = "clips.jsonl";
+pub const DATA_DIRECTORY_NAME: &str = "data";
+
+/// How clips of an archive are imported.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ImportMode {
+    /// Clips are added to the history, clips with the same content are
+    /// merged.
+    #[default]
+    Merge,
+
+    /// The history is replaced by clips of the archive, snippets are kept.
+    Replace,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Manifest {
+    format: String,
+
+    version: u64,
+
+    #[serde(with = "time::serde::rfc3339")]
+    exported_at: OffsetDateTime,
+
+    clips: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Record {
+    digest: String,
+
+    #[serde(with = "crate::serde::clipboard_kind")]
+    kind: ClipboardKind,
+
+    #[serde(with = "crate::serde::mime")]
+    mime: mime::Mime,
+
+    #[serde(with = "time::serde::rfc3339")]
+    timestamp: OffsetDateTime,
+
+    #[serde(flatten)]
+    content: Content,
+
+    #[serde(default)]
+    pinned: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_application: Option<String>,
+
+    #[serde(default)]
+    use_count: u64,
+
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    last_used: Option<OffsetDateTime>,
+
+    #[serde(default)]
+    template: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    representations: Vec<RepresentationRecord>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RepresentationRecord {
+    #[serde(with = "crate::serde::mime")]
+    mime: mime::Mime,
+
+    #[serde(flatten)]
+    content: Content,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum Content {
+    Text { text: String },
+    File { file: PathBuf },
+}
+
+/// Writes `clips` into a new archive at `dir_path`, sensitive clips are
+/// skipped. Returns the number of written clips.
+///
+/// # Errors
+///
+/// This function will return an error if `dir_path` is a non-empty directory
+/// or the archive could not be written.
+pub fn write<'a, I>(dir_path: &Path, clips: I) -> Result<usize, Error>
+where
+    I: IntoIterator<Item = &'a ClipEntry>,
+{
+    if fs::read_dir(dir_path).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(Error::ArchiveExists { dir_path: dir_path.to_path_buf() });
+    }
+    let data_dir_path = dir_path.join(DATA_DIRECTORY_NAME);
+    fs::create_dir_all(&data_dir_path).context(CreateDirectorySnafu { dir_path: data_dir_path })?;
+
+    let clips_file_path = dir_path.join(CLIPS_FILE_NAME);
+    let mut clips_file = fs::File::create(&clips_file_path)
+        .map(BufWriter::new)
+        .context(WriteFileSnafu { file_path: clips_file_path.clone() })?;
+    let mut count = 0;
+    for clip in clips.into_iter().filter(|clip| !clip.is_sensitive()) {
+        let record = Record {
+            digest: hex::encode(clip.sha256_digest()),
+            kind: clip.kind(),
+            mime: clip.mime(),
+            timestamp: clip.timestamp(),
+            content: write_content(dir_path, &clip.mime(), clip.as_bytes())?,
+            pinned: clip.is_pinned(),
+            tags: clip.tags().to_vec(),
+            source_application: clip.source_application().map(ToString::to_string),
+            use_count: clip.use_count(),
+            last_used: clip.last_used(),
+            template: clip.is_template(),
+            representations: clip
+                .representations()
+                .iter()
+                .map(|representation| {
+                    Ok(RepresentationRecord {
+                        mime: representation.mime.clone(),
+                        content: write_content(
+                            dir_path,
+                            &representation.mime,
+                            &representation.data,
+                        )?,
+                    })
+                })
+                .collect::<Result<_, Error>>()?,
+        };
+        serde_json::to_writer(&mut clips_file, &record).context(SerializeSnafu)?;
+        clips_file
+            .write_all(b"\n")
+            .context(WriteFileSnafu { file_path: clips_file_path.clone() })?;
+        count += 1;
+    }
+    clips_file.flush().context(WriteFileSnafu { file_path: clips_file_path })?;
+
+    // the manifest is written last, an archive without it is incomplete
+    let manifest = Manifest {
+        format: FORMAT_NAME.to_string(),
+        version: FORMAT_VERSION,
+        exported_at: OffsetDateTime::now_utc(),
+        clips: count,
+    };
+    let manifest_file_path = dir_path.join(MANIFEST_FILE_NAME);
+    let manifest = serde_json::to_vec_pretty(&manifest).context(SerializeSnafu)?;
+    fs::write(&manifest_file_path, manifest)
+        .context(WriteFileSnafu { file_path: manifest_file_path })?;
+    Ok(count)
+}
+
+/// Reads clips from the archive at `dir_path`, clips are returned in the order
+/// of the archive.
+///
+/// # Errors
+///
+/// This function will return an error if the archive is incomplete, written by
+/// a newer version, or any clip does not match its digest.
+pub fn read(dir_path: &Path) -> Result<Vec<ClipEntry>, Error> {
+    let manifest_file_path = dir_path.join(MANIFEST_FILE_NAME);
+    let manifest = fs::read(&manifest_file_path)
+        .context(ReadFileSnafu { file_path: manifest_file_path.clone() })?;
+    let manifest = serde_json::from_slice::<Manifest>(&manifest)
+        .context(DeserializeSnafu { file_path: manifest_file_path, line: 1_usize })?;
+    if manifest.format != FORMAT_NAME {
+        return Err(Error::UnknownFormat { format: manifest.format });
+    }
+    if manifest.version > FORMAT_VERSION {
+        return Err(Error::NewerVersion { current: FORMAT_VERSION, new: manifest.version });
+    }
+
+    let clips_file_path = dir_path.join(CLIPS_FILE_NAME);
+    let clips_file = fs::File::open(&clips_file_path)
+        .context(ReadFileSnafu { file_path: clips_file_path.clone() })?;
+    let mut clips = Vec::with_capacity(manifest.clips);
+    for (index, line) in BufReader::new(clips_file).lines().enumerate() {
+        let line = line.context(ReadFileSnafu { file_path: clips_file_path.clone() })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str::<Record>(&line)
+            .context(DeserializeSnafu { file_path: clips_file_path.clone(), line: index + 1 })?;
+        clips.push(read_record(dir_path, record, index + 1)?);
+    }
+    Ok(clips)
+}
+
+fn read_record(dir_path: &Path, record: Record, line: usize) -> Result<ClipEntry, Error> {
+    let Record {
+        digest,
+        kind,
+        mime,
+        timestamp,
+        content,
+        pinned,
+        tags,
+        source_application,
+        use_count,
+        last_used,
+        template,
+        representations,
+    } = record;
+
+    let data = read_content(dir_path, content)?;
+    let mut clip =
+        ClipEntry::new(&data, &mime, kind, Some(timestamp)).context(InvalidClipSnafu { line })?;
+    if hex::encode(clip.sha256_digest()) != digest.to_lowercase() {
+        return Err(Error::DigestMismatch { line });
+    }
+    clip.set_pinned(pinned);
+    clip.set_tags(tags);
+    clip.set_source_application(source_application);
+    clip.set_usage(use_count, last_used);
+    clip.set_template(template);
+    let representations = representations
+        .into_iter()
+        .map(|RepresentationRecord { mime, content }| {
+            read_content(dir_path, content).map(|data| ClipRepresentation::new(mime, data))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    clip.set_representations(representations);
+    Ok(clip)
+}
+
+// textual content is stored inline, other content is stored in the data
+// directory and named by its digest so that the same content is stored once
+fn write_content(dir_path: &Path, mime: &mime::Mime, data: &[u8]) -> Result<Content, Error> {
+    if mime.type_() == mime::TEXT {
+        if let Ok(text) = std::str::from_utf8(data) {
+            return Ok(Content::Text { text: text.to_string() });
+        }
+    }
+
+    let digest = hex::encode(<sha2::Sha256 as sha2::Digest>::digest(data));
+    let file_name = if mime.type_() == mime::IMAGE {
+        format!("{digest}.{}", crate::utils::image::file_extension(mime))
+    } else {
+        digest
+    };
+    let file = [Path::new(DATA_DIRECTORY_NAME), Path::new(&file_name)].iter().collect::<PathBuf>();
+    let file_path = dir_path.join(&file);
+    if !file_path.exists() {
+        fs::write(&file_path, data).context(WriteFileSnafu { file_path })?;
+    }
+    Ok(Content::File { file })
+}
+
+fn read_content(dir_path: &Path, content: Content) -> Result<Vec<u8>, Error> {
+    match content {
+        Content::Text { text } => Ok(text.into_bytes()),
+        Content::File { file } => {
+            // referenced files must stay inside of the archive
+            if !file.components().all(|component| matches!(component, Component::Normal(_))) {
+                return Err(Error::InvalidFilePath { file_path: file });
+            }
+            let file_path = dir_path.join(file);
+            fs::read(&file_path).context(ReadFileSnafu { file_path })
+        }
+    }
+}
+
+impl FromStr for ImportMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "merge" => Ok(Self::Merge),
+            "replace" => Ok(Self::Replace),
+            _ => Err(Error::ParseImportMode { value: s.to_string() }),
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Error {
+    #[snafu(display("Archive `{}` already exists and is not empty", dir_path.display()))]
+    ArchiveExists { dir_path: PathBuf },
+
+    #[snafu(display("Failed to create directory `{}`, error: {source}", dir_path.display()))]
+    CreateDirectory { dir_path: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Failed to write file `{}`, error: {source}", file_path.display()))]
+    WriteFile { file_path: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Failed to read file `{}`, error: {source}", file_path.display()))]
+    ReadFile { file_path: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Failed to serialize archive, error: {source}"))]
+    Serialize { source: serde_json::Error },
+
+    #[snafu(display("Failed to parse line {line} of `{}`, error: {source}", file_path.display()))]
+    Deserialize { file_path: PathBuf, line: usize, source: serde_json::Error },
+
+    #[snafu(display("`{format}` is not a clipcat archive"))]
+    UnknownFormat { format: String },
+
+    #[snafu(display(
+        "Archive is written in a newer version: `{new}`, current version: `{current}`"
+    ))]
+    NewerVersion { current: u64, new: u64 },
+
+    #[snafu(display("Clip at line {line} is invalid, error: {source}"))]
+    InvalidClip { line: usize, source: crate::ClipEntryError },
+
+    #[snafu(display("Content of clip at line {line} does not match its digest"))]
+    DigestMismatch { line: usize },
+
+    #[snafu(display("File `{}` is outside of the archive", file_path.display()))]
+    InvalidFilePath { file_path: PathBuf },
+
+    #[snafu(display("Could not parse import mode `{value}`, expected `merge` or `replace`"))]
+    ParseImportMode { value: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use time::OffsetDateTime;
+
+    use crate::{archive, ClipEntry, ClipRepresentation, ClipboardKind};
+
+    fn temp_archive_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "clipcat-archive-{name}-{pid}-{nanos}",
+            pid = std::process::id(),
+            nanos = OffsetDateTime::now_utc().unix_timestamp_nanos()
+        ))
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let dir_path = temp_archive_path("roundtrip");
+
+        let mut text = ClipEntry::from_string("hello", ClipboardKind::Primary);
+        text.set_pinned(true);
+        text.set_tags(["work", "notes"]);
+        text.set_source_application(Some("firefox".to_string()));
+        text.set_usage(3, Some(OffsetDateTime::now_utc()));
+        text.set_representations([ClipRepresentation::new(
+            "text/html".parse().unwrap(),
+            &b"<b>hello</b>"[..],
+        )]);
+        let png = crate::utils::image::encode_rgba_as_png(1, 1, &[0, 0, 0, 255]).unwrap();
+        let image = ClipEntry::new(&png, &mime::IMAGE_PNG, ClipboardKind::Clipboard, None).unwrap();
+        let mut sensitive = ClipEntry::from_string("secret", ClipboardKind::Clipboard);
+        sensitive.set_sensitive(true);
+
+        let count = archive::write(&dir_path, [&text, &image, &sensitive]).unwrap();
+        assert_eq!(count, 2);
+        assert!(archive::write(&dir_path, [&text]).is_err());
+
+        let clips = archive::read(&dir_path).unwrap();
+        assert_eq!(clips, vec![text.clone(), image]);
+        assert_eq!(clips[0].kind(), ClipboardKind::Primary);
+        assert!(clips[0].is_pinned());
+        assert_eq!(clips[0].tags(), text.tags());
+        assert_eq!(clips[0].source_application(), Some("firefox"));
+        assert_eq!(clips[0].use_count(), 3);
+        assert_eq!(clips[0].representations(), text.representations());
+        assert_eq!(clips[0].timestamp(), text.timestamp());
+        assert_eq!(clips[1].mime(), mime::IMAGE_PNG);
+
+        std::fs::remove_dir_all(dir_path).unwrap();
+    }
+
+    #[test]
+    fn test_read_rejects_tampered_archive() {
+        let dir_path = temp_archive_path("tampered");
+        let clip = ClipEntry::from_string("hello", ClipboardKind::Clipboard);
+        let _count = archive::write(&dir_path, [&clip]).unwrap();
+
+        let clips_file_path = dir_path.join(archive::CLIPS_FILE_NAME);
+        let clips = std::fs::read_to_string(&clips_file_path).unwrap();
+        std::fs::write(&clips_file_path, clips.replace("hello", "world")).unwrap();
+        assert!(matches!(
+            archive::read(&dir_path),
+            Err(archive::Error::DigestMismatch { line: 1 })
+        ));
+
+        std::fs::write(&clips_file_path, clips.replace("\"text\":\"hello\"", "\"file\":\"../x\""))
+            .unwrap();
+        assert!(matches!(archive::read(&dir_path), Err(archive::Error::InvalidFilePath { .. })));
+
+        std::fs::remove_dir_all(dir_path).unwrap();
+    }
+}
@@ -16,6 +16,20 @@ pub fn default_unix_domain_socket() -> PathBuf {
     .collect()
 }
 
+/// # Panics
+/// This function should never panic
+#[inline]
+pub fn default_rest_unix_domain_socket() -> PathBuf {
+    let base_dirs = BaseDirs::new().expect("`BaseDirs::new` always success");
+    [
+        base_dirs.runtime_dir().map_or_else(std::env::temp_dir, Path::to_path_buf),
+        PathBuf::from(crate::PROJECT_NAME),
+        PathBuf::from("rest.sock"),
+    ]
+    .into_iter()
+    .collect()
+}
+
 /// # Panics
 /// This function should never panic
 #[inline]
@@ -2,14 +2,14 @@ use std::{
     cmp::Ordering,
     fmt,
     hash::{Hash, Hasher},
+    path::{Path, PathBuf},
 };
 
-use image::ImageEncoder as _;
 use sha2::{Digest, Sha256};
-use snafu::{ResultExt, Snafu};
+use snafu::Snafu;
 use time::{format_description::well_known::Rfc3339, OffsetDateTime, UtcOffset};
 
-use crate::{ClipboardContent, ClipboardKind};
+use crate::{utils, ClipRepresentation, ClipboardContent, ClipboardKind};
 
 #[derive(Clone, Debug, Eq)]
 pub struct Entry {
@@ -20,6 +20,36 @@ pub struct Entry {
     timestamp: OffsetDateTime,
 
     sha256_digest: Vec<u8>,
+
+    pinned: bool,
+
+    // alternative formats of `content`, such as `text/html` or `text/uri-list`
+    representations: Vec<ClipRepresentation>,
+
+    // the application which owned the selection while the clip was captured
+    source_application: Option<String>,
+
+    // sensitive clips are kept in memory only and never written to history
+    sensitive: bool,
+
+    // number of times the clip is marked
+    use_count: u64,
+
+    // the time when the clip is marked last time
+    last_used: Option<OffsetDateTime>,
+
+    // user-defined tags, sorted and deduplicated
+    tags: Vec<String>,
+
+    // the content is a snippet template, it is expanded when the clip is
+    // marked
+    template: bool,
+
+    // name of the snippet which the clip is loaded from
+    snippet_name: Option<String>,
+
+    // the file which the snippet is loaded from, `None` for inline snippets
+    snippet_path: Option<PathBuf>,
 }
 
 impl Entry {
@@ -34,29 +64,12 @@ impl Entry {
         let content = if mime.type_() == mime::TEXT {
             ClipboardContent::Plaintext(String::from_utf8_lossy(data).to_string())
         } else if mime.type_() == mime::IMAGE {
-            let image_format = match mime.subtype() {
-                mime::PNG => image::ImageFormat::Png,
-                mime::JPEG => image::ImageFormat::Jpeg,
-                mime::GIF => image::ImageFormat::Gif,
-                mime::BMP => image::ImageFormat::Bmp,
-                _ => return Err(Error::FormatNotAvailable),
-            };
-
-            let cursor = std::io::Cursor::new(&data);
-            let mut reader = image::ImageReader::new(cursor);
-            reader.set_format(image_format);
-            reader
-                .decode()
-                .map(|img| {
-                    let image = img.into_rgba8();
-                    let (w, h) = image.dimensions();
-                    ClipboardContent::Image {
-                        width: w as usize,
-                        height: h as usize,
-                        bytes: bytes::Bytes::from(image.into_raw()),
-                    }
-                })
-                .context(ConvertImageSnafu {})?
+            let mime = mime.essence_str().parse::<mime::Mime>().unwrap_or_else(|_| mime.clone());
+            if !utils::image::is_supported(&mime) {
+                return Err(Error::FormatNotAvailable);
+            }
+            utils::image::validate(&mime, data)?;
+            ClipboardContent::Image { mime, bytes: bytes::Bytes::copy_from_slice(data) }
         } else {
             return Err(Error::FormatNotAvailable);
         };
@@ -64,7 +77,22 @@ impl Entry {
         let sha256_digest = compute_sha256_digest(&content);
         let timestamp = timestamp.unwrap_or_else(OffsetDateTime::now_utc);
 
-        Ok(Self { content, clipboard_kind, timestamp, sha256_digest })
+        Ok(Self {
+            content,
+            clipboard_kind,
+            timestamp,
+            sha256_digest,
+            pinned: false,
+            representations: Vec::new(),
+            source_application: None,
+            sensitive: false,
+            use_count: 0,
+            last_used: None,
+            tags: Vec::new(),
+            template: false,
+            snippet_name: None,
+            snippet_path: None,
+        })
     }
 
     #[inline]
@@ -85,6 +113,16 @@ impl Entry {
             clipboard_kind,
             timestamp: timestamp.unwrap_or_else(OffsetDateTime::now_utc),
             sha256_digest,
+            pinned: false,
+            representations: Vec::new(),
+            source_application: None,
+            sensitive: false,
+            use_count: 0,
+            last_used: None,
+            tags: Vec::new(),
+            template: false,
+            snippet_name: None,
+            snippet_path: None,
         }
     }
 
@@ -103,6 +141,220 @@ impl Entry {
     #[inline]
     pub fn set_timestamp(&mut self, timestamp: OffsetDateTime) { self.timestamp = timestamp; }
 
+    #[inline]
+    #[must_use]
+    pub const fn is_pinned(&self) -> bool { self.pinned }
+
+    #[inline]
+    pub fn set_pinned(&mut self, pinned: bool) { self.pinned = pinned; }
+
+    /// Returns the application which the clip was copied from, if it is known.
+    #[inline]
+    #[must_use]
+    pub fn source_application(&self) -> Option<&str> { self.source_application.as_deref() }
+
+    #[inline]
+    pub fn set_source_application(&mut self, source_application: Option<String>) {
+        self.source_application = source_application.filter(|name| !name.is_empty());
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn is_sensitive(&self) -> bool { self.sensitive }
+
+    #[inline]
+    pub fn set_sensitive(&mut self, sensitive: bool) { self.sensitive = sensitive; }
+
+    /// Returns `true` if the content is a snippet template with placeholders,
+    /// see [`crate::SnippetTemplate`].
+    #[inline]
+    #[must_use]
+    pub const fn is_template(&self) -> bool { self.template }
+
+    #[inline]
+    pub fn set_template(&mut self, template: bool) { self.template = template; }
+
+    /// Returns the name of the snippet which the clip is loaded from.
+    #[inline]
+    #[must_use]
+    pub fn snippet_name(&self) -> Option<&str> { self.snippet_name.as_deref() }
+
+    /// Returns the file which the snippet is loaded from.
+    #[inline]
+    #[must_use]
+    pub fn snippet_path(&self) -> Option<&Path> { self.snippet_path.as_deref() }
+
+    #[inline]
+    pub fn set_snippet(&mut self, name: Option<String>, path: Option<PathBuf>) {
+        self.snippet_name = name.filter(|name| !name.is_empty());
+        self.snippet_path = path.filter(|path| !path.as_os_str().is_empty());
+    }
+
+    /// Returns the number of times the clip is marked.
+    #[inline]
+    #[must_use]
+    pub const fn use_count(&self) -> u64 { self.use_count }
+
+    /// Returns the time when the clip is marked last time.
+    #[inline]
+    #[must_use]
+    pub const fn last_used(&self) -> Option<OffsetDateTime> { self.last_used }
+
+    #[inline]
+    pub fn set_usage(&mut self, use_count: u64, last_used: Option<OffsetDateTime>) {
+        self.use_count = use_count;
+        self.last_used = last_used;
+    }
+
+    /// Returns the tags of the clip in lexicographic order.
+    #[inline]
+    #[must_use]
+    pub fn tags(&self) -> &[String] { &self.tags }
+
+    #[inline]
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.binary_search_by(|t| t.as_str().cmp(tag)).is_ok()
+    }
+
+    /// Sets the tags of the clip, tags are trimmed and empty tags are dropped.
+    pub fn set_tags<I, S>(&mut self, tags: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.tags = tags
+            .into_iter()
+            .map(|tag| tag.as_ref().trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        self.tags.sort_unstable();
+        self.tags.dedup();
+    }
+
+    /// Adds `tag` to the clip, returns `false` if `tag` is empty or the clip
+    /// is already tagged with it.
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return false;
+        }
+        match self.tags.binary_search_by(|t| t.as_str().cmp(tag)) {
+            Ok(_) => false,
+            Err(index) => {
+                self.tags.insert(index, tag.to_string());
+                true
+            }
+        }
+    }
+
+    /// Removes `tag` from the clip, returns `false` if the clip is not tagged
+    /// with it.
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        match self.tags.binary_search_by(|t| t.as_str().cmp(tag.trim())) {
+            Ok(index) => {
+                let _tag = self.tags.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Merges the state of `other`, a clip with the same content, into the
+    /// clip. The clip is pinned if either is pinned, tags are united, and the
+    /// later timestamp and usage are kept.
+    pub fn merge(&mut self, other: &Self) {
+        self.pinned |= other.pinned;
+        for tag in &other.tags {
+            let _unused = self.add_tag(tag);
+        }
+        self.timestamp = self.timestamp.max(other.timestamp);
+        self.use_count = self.use_count.max(other.use_count);
+        self.last_used = self.last_used.max(other.last_used);
+        if self.source_application.is_none() {
+            self.source_application.clone_from(&other.source_application);
+        }
+        if self.representations.is_empty() {
+            self.representations.clone_from(&other.representations);
+        }
+    }
+
+    /// Records a use of the clip at `now`.
+    #[inline]
+    pub fn record_use(&mut self, now: OffsetDateTime) {
+        self.use_count = self.use_count.saturating_add(1);
+        self.last_used = Some(now);
+    }
+
+    /// Returns the frecency score of the clip at `now`, a clip scores higher
+    /// if it is used more often and more recently.
+    #[must_use]
+    pub fn frecency(&self, now: OffsetDateTime) -> u64 {
+        let age = now - self.last_used.unwrap_or(self.timestamp);
+        let weight = if age < time::Duration::HOUR {
+            100
+        } else if age < time::Duration::DAY {
+            80
+        } else if age < time::Duration::WEEK {
+            60
+        } else if age < time::Duration::days(30) {
+            40
+        } else {
+            20
+        };
+        self.use_count.saturating_add(1).saturating_mul(weight)
+    }
+
+    /// Returns the alternative formats of the clip, the primary content is not
+    /// included.
+    #[inline]
+    #[must_use]
+    pub fn representations(&self) -> &[ClipRepresentation] { &self.representations }
+
+    /// Sets the alternative formats of the clip, representations which are not
+    /// captured, empty or in the same format as the primary content are
+    /// dropped.
+    pub fn set_representations<I>(&mut self, representations: I)
+    where
+        I: IntoIterator<Item = ClipRepresentation>,
+    {
+        let primary_mime = self.mime();
+        self.representations.clear();
+        for representation in representations {
+            if representation.is_empty()
+                || !ClipRepresentation::is_captured(&representation.mime)
+                || representation.mime.essence_str() == primary_mime.essence_str()
+                || self.representations.iter().any(|existing| {
+                    existing.mime.essence_str() == representation.mime.essence_str()
+                })
+            {
+                continue;
+            }
+            self.representations.push(representation);
+        }
+    }
+
+    /// Returns MIME types of all formats of the clip, the primary one comes
+    /// first.
+    #[must_use]
+    pub fn mimes(&self) -> Vec<mime::Mime> {
+        std::iter::once(self.mime())
+            .chain(self.representations.iter().map(|representation| representation.mime.clone()))
+            .collect()
+    }
+
+    /// Returns the data of the clip in format `mime`.
+    #[must_use]
+    pub fn representation(&self, mime: &mime::Mime) -> Option<Vec<u8>> {
+        if mime.essence_str() == self.mime().essence_str() {
+            return self.encoded().ok();
+        }
+        self.representations
+            .iter()
+            .find(|representation| representation.mime.essence_str() == mime.essence_str())
+            .map(|representation| representation.data.to_vec())
+    }
+
     #[inline]
     #[must_use]
     pub const fn is_utf8_string(&self) -> bool { self.content.is_plaintext() }
@@ -126,7 +378,11 @@ impl Entry {
             .unwrap_or_default();
         let size = humansize::format_size(self.content.len(), humansize::BINARY);
         let content_type = self.content.mime();
-        format!("[{content_type} {size} {timestamp}]")
+        if let Some((width, height)) = self.content.image_dimensions() {
+            format!("[{content_type} {width}x{height} {size} {timestamp}]")
+        } else {
+            format!("[{content_type} {size} {timestamp}]")
+        }
     }
 
     #[must_use]
@@ -180,6 +436,18 @@ impl Entry {
     #[must_use]
     pub fn len(&self) -> usize { self.content.len() }
 
+    /// Returns the number of bytes of the clip data, alternative
+    /// representations included.
+    #[must_use]
+    pub fn size_in_bytes(&self) -> usize {
+        self.as_bytes().len()
+            + self
+                .representations
+                .iter()
+                .map(|representation| representation.data.len())
+                .sum::<usize>()
+    }
+
     #[inline]
     #[must_use]
     pub fn as_bytes(&self) -> &[u8] {
@@ -194,15 +462,13 @@ impl Entry {
     pub fn encoded(&self) -> Result<Vec<u8>, Error> {
         match &self.content {
             ClipboardContent::Plaintext(text) => Ok(text.as_bytes().to_vec()),
-            ClipboardContent::Image { width, height, bytes } => {
-                encode_as_png(*width, *height, bytes)
-            }
+            ClipboardContent::Image { bytes, .. } => Ok(bytes.to_vec()),
         }
     }
 
     #[inline]
     #[must_use]
-    pub const fn mime(&self) -> mime::Mime { self.content.mime() }
+    pub fn mime(&self) -> mime::Mime { self.content.mime() }
 
     #[inline]
     pub fn metadata(&self, preview_length: Option<usize>) -> Metadata {
@@ -212,6 +478,13 @@ impl Entry {
             timestamp: self.timestamp,
             mime: self.mime(),
             preview: self.preview_information(preview_length),
+            pinned: self.pinned,
+            source_application: self.source_application.clone(),
+            sensitive: self.sensitive,
+            tags: self.tags.clone(),
+            template: self.template,
+            snippet_name: self.snippet_name.clone(),
+            snippet_path: self.snippet_path.clone(),
         }
     }
 
@@ -227,6 +500,16 @@ impl Default for Entry {
             clipboard_kind: ClipboardKind::Clipboard,
             timestamp: OffsetDateTime::now_utc(),
             sha256_digest,
+            pinned: false,
+            representations: Vec::new(),
+            source_application: None,
+            sensitive: false,
+            use_count: 0,
+            last_used: None,
+            tags: Vec::new(),
+            template: false,
+            snippet_name: None,
+            snippet_path: None,
         }
     }
 }
@@ -267,6 +550,20 @@ pub struct Metadata {
     pub mime: mime::Mime,
 
     pub preview: String,
+
+    pub pinned: bool,
+
+    pub source_application: Option<String>,
+
+    pub sensitive: bool,
+
+    pub tags: Vec<String>,
+
+    pub template: bool,
+
+    pub snippet_name: Option<String>,
+
+    pub snippet_path: Option<PathBuf>,
 }
 
 impl PartialOrd for Metadata {
@@ -282,28 +579,11 @@ impl Ord for Metadata {
     }
 }
 
-fn encode_as_png(width: usize, height: usize, bytes: &[u8]) -> Result<Vec<u8>, Error> {
-    let (width, height) =
-        (u32::try_from(width).unwrap_or_default(), u32::try_from(height).unwrap_or_default());
-    if bytes.is_empty() || width == 0 || height == 0 {
-        return Err(Error::EmptyImage);
-    }
-
-    let mut png_bytes = Vec::new();
-    image::codecs::png::PngEncoder::new(&mut png_bytes)
-        .write_image(bytes, width, height, image::ExtendedColorType::Rgba8)
-        .context(ConvertImageSnafu {})?;
-
-    Ok(png_bytes)
-}
-
 fn compute_sha256_digest(content: &ClipboardContent) -> Vec<u8> {
     let mut hasher = Sha256::new();
     match content {
         ClipboardContent::Plaintext(text) => hasher.update(text.as_bytes()),
-        ClipboardContent::Image { width, height, bytes } => {
-            hasher.update(encode_as_png(*width, *height, bytes).unwrap_or_default());
-        }
+        ClipboardContent::Image { bytes, .. } => hasher.update(bytes),
     }
     hasher.finalize().to_vec()
 }
@@ -0,0 +1,31 @@
+use crate::{ClipEntryMetadata, ClipboardWatcherState};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Event {
+    Inserted(ClipEntryMetadata),
+
+    Removed(u64),
+
+    Updated { old_id: u64, metadata: ClipEntryMetadata },
+
+    Marked(ClipEntryMetadata),
+
+    Cleared,
+
+    WatcherToggled(ClipboardWatcherState),
+}
+
+impl Event {
+    #[inline]
+    #[must_use]
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::Inserted(_) => "inserted",
+            Self::Removed(_) => "removed",
+            Self::Updated { .. } => "updated",
+            Self::Marked(_) => "marked",
+            Self::Cleared => "cleared",
+            Self::WatcherToggled(_) => "watcher-toggled",
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use std::collections::HashSet;
+use std::{
+    collections::HashSet,
+    sync::{Arc, PoisonError, RwLock},
+};
 
-use crate::ClipboardContent;
+use crate::{ClipboardContent, ClipboardKind, SecretDetection, SecretDetector};
 
 #[derive(Clone, Debug)]
 pub struct Filter {
@@ -10,6 +13,39 @@ pub struct Filter {
     filter_text_min_length: usize,
     filter_text_max_length: usize,
     filter_image_max_size: usize,
+    source_application_rules: Vec<SourceApplicationRule>,
+    secret_detector: SecretDetector,
+}
+
+/// Decides whether clips copied from an application are captured, names of
+/// applications are compared case-insensitively.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SourceApplicationRule {
+    /// Clipboard kinds which the rule applies to, the rule applies to all kinds
+    /// if it is empty.
+    pub kinds: HashSet<ClipboardKind>,
+
+    /// Only clips copied from these applications are captured if it is not
+    /// empty.
+    pub allow: HashSet<String>,
+
+    /// Clips copied from these applications are never captured.
+    pub deny: HashSet<String>,
+}
+
+impl SourceApplicationRule {
+    fn applies_to(&self, kind: ClipboardKind) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(&kind)
+    }
+
+    // returns `true` if the clip should be ignored
+    fn filter(&self, source_application: Option<&str>) -> bool {
+        let contains = |names: &HashSet<String>| {
+            source_application
+                .is_some_and(|app| names.iter().any(|name| name.eq_ignore_ascii_case(app)))
+        };
+        contains(&self.deny) || (!self.allow.is_empty() && !contains(&self.allow))
+    }
 }
 
 impl Filter {
@@ -29,9 +65,20 @@ impl Filter {
 
             // 5 MiB
             filter_image_max_size: 5 * (1 << 20),
+
+            source_application_rules: Vec::new(),
+
+            secret_detector: SecretDetector::new(),
         }
     }
 
+    pub fn add_source_application_rules<I>(&mut self, rules: I)
+    where
+        I: IntoIterator<Item = SourceApplicationRule>,
+    {
+        self.source_application_rules.extend(rules);
+    }
+
     pub fn add_sensitive_atoms<I>(&mut self, sensitive_atoms: I)
     where
         I: IntoIterator<Item = String>,
@@ -39,6 +86,10 @@ impl Filter {
         self.sensitive_atoms.extend(sensitive_atoms);
     }
 
+    pub fn set_secret_detector(&mut self, secret_detector: SecretDetector) {
+        self.secret_detector = secret_detector;
+    }
+
     pub fn set_regex_patterns(&mut self, regex_patterns: regex::RegexSet) {
         self.regex_set = regex_patterns;
     }
@@ -65,6 +116,20 @@ impl Filter {
         }
     }
 
+    /// Returns the secret found in text clips, images are never inspected.
+    #[must_use]
+    pub fn detect_secret<C>(&self, content: C) -> Option<SecretDetection>
+    where
+        C: AsRef<ClipboardContent>,
+    {
+        match content.as_ref() {
+            ClipboardContent::Plaintext(text) if !self.secret_detector.is_empty() => {
+                self.secret_detector.detect(text)
+            }
+            _ => None,
+        }
+    }
+
     #[inline]
     #[must_use]
     pub fn filter_sensitive_atoms<'a, I>(&self, mut atoms: I) -> bool
@@ -74,6 +139,21 @@ impl Filter {
         atoms.any(|atom| self.sensitive_atoms.contains(atom))
     }
 
+    /// Returns `true` if clips of `kind` copied from `source_application`
+    /// should be ignored, an unknown application only passes rules without
+    /// allow list.
+    #[must_use]
+    pub fn filter_source_application(
+        &self,
+        kind: ClipboardKind,
+        source_application: Option<&str>,
+    ) -> bool {
+        self.source_application_rules
+            .iter()
+            .filter(|rule| rule.applies_to(kind))
+            .any(|rule| rule.filter(source_application))
+    }
+
     #[inline]
     #[must_use]
     pub fn filter_by_mime_type(&self, mime: &mime::Mime) -> bool {
@@ -126,6 +206,81 @@ impl Default for Filter {
             filter_text_max_length: 5 * (1 << 20),
             // 5 MiB
             filter_image_max_size: 5 * (1 << 20),
+
+            source_application_rules: Vec::new(),
+
+            secret_detector: SecretDetector::new(),
         }
     }
 }
+
+/// A [`Filter`] shared by clipboard listeners and watchers, it could be
+/// replaced at runtime when the configuration is reloaded.
+#[derive(Clone, Debug, Default)]
+pub struct Shared(Arc<RwLock<Arc<Filter>>>);
+
+impl Shared {
+    #[must_use]
+    pub fn new(filter: Filter) -> Self { Self(Arc::new(RwLock::new(Arc::new(filter)))) }
+
+    /// Returns the current filter, later replacements do not affect it.
+    #[must_use]
+    pub fn load(&self) -> Arc<Filter> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    pub fn replace(&self, filter: Filter) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(filter);
+    }
+}
+
+impl From<Filter> for Shared {
+    fn from(filter: Filter) -> Self { Self::new(filter) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{ClipFilter, ClipboardKind, SharedClipFilter, SourceApplicationRule};
+
+    #[test]
+    fn test_filter_source_application() {
+        let mut filter = ClipFilter::new();
+        filter.add_source_application_rules([
+            SourceApplicationRule {
+                deny: HashSet::from(["KeePassXC".to_string()]),
+                ..SourceApplicationRule::default()
+            },
+            SourceApplicationRule {
+                kinds: HashSet::from([ClipboardKind::Primary]),
+                allow: HashSet::from(["Alacritty".to_string()]),
+                ..SourceApplicationRule::default()
+            },
+        ]);
+
+        assert!(filter.filter_source_application(ClipboardKind::Clipboard, Some("keepassxc")));
+        assert!(!filter.filter_source_application(ClipboardKind::Clipboard, Some("firefox")));
+        assert!(!filter.filter_source_application(ClipboardKind::Clipboard, None));
+
+        assert!(filter.filter_source_application(ClipboardKind::Primary, Some("KeePassXC")));
+        assert!(filter.filter_source_application(ClipboardKind::Primary, Some("firefox")));
+        assert!(filter.filter_source_application(ClipboardKind::Primary, None));
+        assert!(!filter.filter_source_application(ClipboardKind::Primary, Some("alacritty")));
+    }
+
+    #[test]
+    fn test_replace_shared_filter() {
+        let shared = SharedClipFilter::new(ClipFilter::new());
+        let previous = shared.load();
+        assert!(!previous.filter_by_text_size("hello"));
+
+        let mut filter = ClipFilter::new();
+        filter.set_text_min_length(10);
+        let cloned = shared.clone();
+        cloned.replace(filter);
+        assert!(shared.load().filter_by_text_size("hello"));
+        assert!(cloned.load().filter_by_text_size("hello"));
+        assert!(!previous.filter_by_text_size("hello"));
+    }
+}
@@ -1,8 +1,15 @@
+pub mod archive;
 pub mod config;
 mod entry;
+mod event;
 mod filter;
 mod kind;
+mod list;
+mod representation;
+mod search;
+mod secret;
 pub mod serde;
+mod template;
 pub mod utils;
 mod watcher_state;
 
@@ -19,8 +26,23 @@ use once_cell::sync::Lazy;
 
 pub use self::{
     entry::{Entry as ClipEntry, Error as ClipEntryError, Metadata as ClipEntryMetadata},
-    filter::Filter as ClipFilter,
+    event::Event as ClipboardEvent,
+    filter::{Filter as ClipFilter, Shared as SharedClipFilter, SourceApplicationRule},
     kind::Kind as ClipboardKind,
+    list::{Error as ListError, Order as ListOrder, Query as ListQuery},
+    representation::Representation as ClipRepresentation,
+    search::{
+        Error as SearchError, Match as SearchMatch, Matcher as SearchMatcher, Mode as SearchMode,
+        Query as SearchQuery,
+    },
+    secret::{
+        Action as SecretAction, Detection as SecretDetection, Detector as SecretDetector,
+        Kind as SecretKind,
+    },
+    template::{
+        Context as TemplateContext, Error as TemplateError, Placeholder as TemplatePlaceholder,
+        Template as SnippetTemplate,
+    },
     watcher_state::WatcherState as ClipboardWatcherState,
 };
 
@@ -64,6 +86,9 @@ pub const DEFAULT_GRPC_HOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 pub const DEFAULT_WEBUI_PORT: u16 = 45046;
 pub const DEFAULT_WEBUI_HOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 
+pub const DEFAULT_REST_PORT: u16 = 45048;
+pub const DEFAULT_REST_HOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
 pub const DEFAULT_METRICS_PORT: u16 = 45047;
 pub const DEFAULT_METRICS_HOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 
@@ -90,7 +115,8 @@ pub fn fallback_project_config_directories() -> Vec<PathBuf> {
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum ClipboardContent {
     Plaintext(String),
-    Image { width: usize, height: usize, bytes: Bytes },
+    // `bytes` are kept in the encoding of `mime`
+    Image { mime: mime::Mime, bytes: Bytes },
 }
 
 impl Default for ClipboardContent {
@@ -120,11 +146,59 @@ impl ClipboardContent {
     #[inline]
     pub const fn is_image(&self) -> bool { matches!(&self, Self::Image { .. }) }
 
+    /// Converts RGBA pixels into an image encoded as PNG.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the image is empty or could not be
+    /// encoded.
+    pub fn from_rgba(width: usize, height: usize, bytes: &[u8]) -> Result<Self, ClipEntryError> {
+        let bytes = utils::image::encode_rgba_as_png(width, height, bytes)?;
+        Ok(Self::Image { mime: mime::IMAGE_PNG, bytes: Bytes::from(bytes) })
+    }
+
     #[inline]
-    pub const fn mime(&self) -> mime::Mime {
+    pub fn mime(&self) -> mime::Mime {
         match self {
             Self::Plaintext(_) => mime::TEXT_PLAIN_UTF_8,
-            Self::Image { .. } => mime::IMAGE_PNG,
+            Self::Image { mime, .. } => mime.clone(),
+        }
+    }
+
+    /// Returns width and height of the image, the image header is parsed on
+    /// every call.
+    #[inline]
+    pub fn image_dimensions(&self) -> Option<(u32, u32)> {
+        match self {
+            Self::Plaintext(_) => None,
+            Self::Image { mime, bytes } => utils::image::dimensions(mime, bytes),
+        }
+    }
+
+    /// Decodes the image into RGBA pixels, returns width, height and pixels.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the content is not an image or the
+    /// image could not be decoded.
+    pub fn to_rgba(&self) -> Result<(usize, usize, Vec<u8>), ClipEntryError> {
+        match self {
+            Self::Plaintext(_) => Err(ClipEntryError::FormatNotAvailable),
+            Self::Image { mime, bytes } => utils::image::decode_rgba(mime, bytes),
+        }
+    }
+
+    /// Scales the image down to fit in `max_size` x `max_size` and encodes it
+    /// as PNG.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the content is not a raster image
+    /// or the image could not be converted.
+    pub fn thumbnail(&self, max_size: u32) -> Result<Vec<u8>, ClipEntryError> {
+        match self {
+            Self::Plaintext(_) => Err(ClipEntryError::FormatNotAvailable),
+            Self::Image { mime, bytes } => utils::image::thumbnail(mime, bytes, max_size),
         }
     }
 
@@ -0,0 +1,197 @@
+use std::{fmt, str::FromStr};
+
+use snafu::Snafu;
+use time::OffsetDateTime;
+
+use crate::{search::match_mime, ClipEntry, ClipboardKind};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Order {
+    #[default]
+    Newest,
+    Oldest,
+    MostUsed,
+
+    // clips which are used more often and more recently come first
+    Frecency,
+}
+
+impl Order {
+    #[inline]
+    #[must_use]
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::Newest => "newest",
+            Self::Oldest => "oldest",
+            Self::MostUsed => "most-used",
+            Self::Frecency => "frecency",
+        }
+    }
+}
+
+impl FromStr for Order {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "newest" => Ok(Self::Newest),
+            "oldest" => Ok(Self::Oldest),
+            "most-used" | "most_used" => Ok(Self::MostUsed),
+            "frecency" => Ok(Self::Frecency),
+            _ => Err(Error::ParseOrder { value: s.to_string() }),
+        }
+    }
+}
+
+impl From<Order> for i32 {
+    fn from(order: Order) -> Self {
+        match order {
+            Order::Newest => 0,
+            Order::Oldest => 1,
+            Order::MostUsed => 2,
+            Order::Frecency => 3,
+        }
+    }
+}
+
+impl From<i32> for Order {
+    fn from(v: i32) -> Self {
+        match v {
+            1 => Self::Oldest,
+            2 => Self::MostUsed,
+            3 => Self::Frecency,
+            _ => Self::Newest,
+        }
+    }
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Query {
+    pub preview_length: usize,
+
+    // number of clips to skip after filtering and sorting
+    pub offset: usize,
+
+    // no limit if it is zero
+    pub limit: usize,
+
+    pub order: Order,
+
+    // match all kinds if it is empty
+    pub kinds: Vec<ClipboardKind>,
+
+    // match all MIME types if it is empty, `image/*` matches all images
+    pub mimes: Vec<mime::Mime>,
+
+    // match clips with any of the tags, match all clips if it is empty
+    pub tags: Vec<String>,
+
+    // match both pinned and unpinned clips if it is `None`
+    pub pinned: Option<bool>,
+
+    // match both snippets and other clips if it is `None`
+    pub snippet: Option<bool>,
+
+    // inclusive
+    pub since: Option<OffsetDateTime>,
+
+    // exclusive
+    pub until: Option<OffsetDateTime>,
+}
+
+impl Query {
+    /// Returns `true` if `clip` passes all filters of this query.
+    #[must_use]
+    pub fn matches(&self, clip: &ClipEntry, is_snippet: bool) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&clip.kind()) {
+            return false;
+        }
+
+        let clip_mime = clip.mime();
+        if !self.mimes.is_empty() && !self.mimes.iter().any(|mime| match_mime(mime, &clip_mime)) {
+            return false;
+        }
+
+        if !self.tags.is_empty() && !self.tags.iter().any(|tag| clip.has_tag(tag)) {
+            return false;
+        }
+
+        if self.pinned.is_some_and(|pinned| pinned != clip.is_pinned()) {
+            return false;
+        }
+
+        if self.snippet.is_some_and(|snippet| snippet != is_snippet) {
+            return false;
+        }
+
+        let timestamp = clip.timestamp();
+        self.since.map_or(true, |since| timestamp >= since)
+            && self.until.map_or(true, |until| timestamp < until)
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    #[snafu(display("Could not parse list order, value: {value}"))]
+    ParseOrder { value: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use crate::{ClipEntry, ClipboardKind, ListOrder, ListQuery};
+
+    #[test]
+    fn test_parse_order() {
+        assert_eq!("newest".parse::<ListOrder>().unwrap(), ListOrder::Newest);
+        assert_eq!("Oldest".parse::<ListOrder>().unwrap(), ListOrder::Oldest);
+        assert_eq!("most-used".parse::<ListOrder>().unwrap(), ListOrder::MostUsed);
+        assert_eq!("frecency".parse::<ListOrder>().unwrap(), ListOrder::Frecency);
+        assert!("random".parse::<ListOrder>().is_err());
+        for order in
+            [ListOrder::Newest, ListOrder::Oldest, ListOrder::MostUsed, ListOrder::Frecency]
+        {
+            assert_eq!(ListOrder::from(i32::from(order)), order);
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let mut clip = ClipEntry::from_string("abc", ClipboardKind::Primary);
+        clip.set_pinned(true);
+        clip.set_tags(["deploy", "work"]);
+
+        assert!(ListQuery::default().matches(&clip, false));
+        assert!(ListQuery { kinds: vec![ClipboardKind::Primary], ..ListQuery::default() }
+            .matches(&clip, false));
+        assert!(!ListQuery { kinds: vec![ClipboardKind::Clipboard], ..ListQuery::default() }
+            .matches(&clip, false));
+        assert!(ListQuery { mimes: vec![mime::TEXT_STAR], ..ListQuery::default() }
+            .matches(&clip, false));
+        assert!(!ListQuery { mimes: vec![mime::IMAGE_STAR], ..ListQuery::default() }
+            .matches(&clip, false));
+        assert!(ListQuery {
+            tags: vec!["misc".to_string(), "work".to_string()],
+            ..ListQuery::default()
+        }
+        .matches(&clip, false));
+        assert!(!ListQuery { tags: vec!["misc".to_string()], ..ListQuery::default() }
+            .matches(&clip, false));
+        assert!(ListQuery { pinned: Some(true), ..ListQuery::default() }.matches(&clip, false));
+        assert!(!ListQuery { pinned: Some(false), ..ListQuery::default() }.matches(&clip, false));
+        assert!(ListQuery { snippet: Some(true), ..ListQuery::default() }.matches(&clip, true));
+        assert!(!ListQuery { snippet: Some(true), ..ListQuery::default() }.matches(&clip, false));
+
+        let timestamp = clip.timestamp();
+        assert!(ListQuery { since: Some(timestamp), ..ListQuery::default() }.matches(&clip, false));
+        assert!(!ListQuery { until: Some(timestamp), ..ListQuery::default() }.matches(&clip, false));
+        assert!(!ListQuery { since: Some(OffsetDateTime::now_utc()), ..ListQuery::default() }
+            .matches(&clip, false));
+    }
+}
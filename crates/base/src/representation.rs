@@ -0,0 +1,43 @@
+use bytes::Bytes;
+
+// alternative formats of a clip which are captured besides plaintext and image
+const CAPTURED_MIMES: &[&str] =
+    &["text/html", "text/uri-list", "text/rtf", "text/richtext", "application/rtf"];
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Representation {
+    pub mime: mime::Mime,
+
+    pub data: Bytes,
+}
+
+impl Representation {
+    #[inline]
+    pub fn new<D>(mime: mime::Mime, data: D) -> Self
+    where
+        D: Into<Bytes>,
+    {
+        Self { mime, data: data.into() }
+    }
+
+    /// Returns whether representations in format `mime` are captured along with
+    /// a clip.
+    #[inline]
+    #[must_use]
+    pub fn is_captured(mime: &mime::Mime) -> bool { CAPTURED_MIMES.contains(&mime.essence_str()) }
+
+    /// Returns MIME types of representations which are captured along with a
+    /// clip.
+    #[inline]
+    pub fn captured_mimes() -> impl Iterator<Item = mime::Mime> {
+        CAPTURED_MIMES.iter().filter_map(|mime| mime.parse().ok())
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool { self.data.is_empty() }
+
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> usize { self.data.len() }
+}
@@ -0,0 +1,373 @@
+use std::{cmp::Ordering, fmt, ops::Range, str::FromStr};
+
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use snafu::{ResultExt, Snafu};
+use time::OffsetDateTime;
+
+use crate::{ClipEntry, ClipEntryMetadata, ClipboardContent, ClipboardKind};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Mode {
+    #[default]
+    Exact,
+    CaseInsensitive,
+    Regex,
+    Fuzzy,
+}
+
+impl Mode {
+    #[inline]
+    #[must_use]
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::Exact => "exact",
+            Self::CaseInsensitive => "case-insensitive",
+            Self::Regex => "regex",
+            Self::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+impl FromStr for Mode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "exact" => Ok(Self::Exact),
+            "case-insensitive" | "ignore-case" | "icase" => Ok(Self::CaseInsensitive),
+            "regex" | "regexp" => Ok(Self::Regex),
+            "fuzzy" => Ok(Self::Fuzzy),
+            _ => Err(Error::ParseMode { value: s.to_string() }),
+        }
+    }
+}
+
+impl From<Mode> for i32 {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Exact => 0,
+            Mode::CaseInsensitive => 1,
+            Mode::Regex => 2,
+            Mode::Fuzzy => 3,
+        }
+    }
+}
+
+impl From<i32> for Mode {
+    fn from(v: i32) -> Self {
+        match v {
+            1 => Self::CaseInsensitive,
+            2 => Self::Regex,
+            3 => Self::Fuzzy,
+            _ => Self::Exact,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Query {
+    pub pattern: String,
+
+    pub mode: Mode,
+
+    // match all kinds if it is empty
+    pub kinds: Vec<ClipboardKind>,
+
+    // match all MIME types if it is empty, `image/*` matches all images
+    pub mimes: Vec<mime::Mime>,
+
+    // match clips with any of the tags, match all clips if it is empty
+    pub tags: Vec<String>,
+
+    // match both pinned and unpinned clips if it is `None`
+    pub pinned: Option<bool>,
+
+    // match both snippets and other clips if it is `None`
+    pub snippet: Option<bool>,
+
+    // inclusive
+    pub since: Option<OffsetDateTime>,
+
+    // exclusive
+    pub until: Option<OffsetDateTime>,
+
+    pub preview_length: usize,
+
+    // no limit if it is zero
+    pub limit: usize,
+}
+
+impl Query {
+    /// # Errors
+    ///
+    /// This function will return an error if the pattern is not a valid
+    /// regular expression in [`Mode::Regex`].
+    pub fn build_matcher(&self) -> Result<Matcher, Error> {
+        let regex = match self.mode {
+            Mode::Exact | Mode::Fuzzy => None,
+            Mode::CaseInsensitive => Some(
+                regex::RegexBuilder::new(&regex::escape(&self.pattern))
+                    .case_insensitive(true)
+                    .build()
+                    .context(BuildRegexSnafu)?,
+            ),
+            Mode::Regex => Some(regex::Regex::new(&self.pattern).context(BuildRegexSnafu)?),
+        };
+        Ok(Matcher { query: self.clone(), regex, fuzzy: SkimMatcherV2::default() })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Match {
+    pub metadata: ClipEntryMetadata,
+
+    pub score: i64,
+
+    // byte ranges of matched text in the full content of clip
+    pub ranges: Vec<Range<usize>>,
+}
+
+impl PartialOrd for Match {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for Match {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match other.score.cmp(&self.score) {
+            Ordering::Equal => self.metadata.cmp(&other.metadata),
+            ord => ord,
+        }
+    }
+}
+
+pub struct Matcher {
+    query: Query,
+    regex: Option<regex::Regex>,
+    fuzzy: SkimMatcherV2,
+}
+
+impl Matcher {
+    #[inline]
+    #[must_use]
+    pub const fn query(&self) -> &Query { &self.query }
+
+    /// Searches `clips`, `is_snippet` tells whether a clip is a snippet.
+    #[must_use]
+    pub fn search<'a, I, F>(&self, clips: I, is_snippet: F) -> Vec<Match>
+    where
+        I: IntoIterator<Item = &'a ClipEntry>,
+        F: Fn(&ClipEntry) -> bool,
+    {
+        let mut matches: Vec<_> =
+            clips.into_iter().filter_map(|clip| self.matches(clip, is_snippet(clip))).collect();
+        matches.sort_unstable();
+        if self.query.limit > 0 {
+            matches.truncate(self.query.limit);
+        }
+        matches
+    }
+
+    #[must_use]
+    pub fn matches(&self, clip: &ClipEntry, is_snippet: bool) -> Option<Match> {
+        if !self.query.kinds.is_empty() && !self.query.kinds.contains(&clip.kind()) {
+            return None;
+        }
+
+        let clip_mime = clip.mime();
+        if !self.query.mimes.is_empty()
+            && !self.query.mimes.iter().any(|mime| match_mime(mime, &clip_mime))
+        {
+            return None;
+        }
+
+        if !self.query.tags.is_empty() && !self.query.tags.iter().any(|tag| clip.has_tag(tag)) {
+            return None;
+        }
+
+        if self.query.pinned.is_some_and(|pinned| pinned != clip.is_pinned()) {
+            return None;
+        }
+
+        if self.query.snippet.is_some_and(|snippet| snippet != is_snippet) {
+            return None;
+        }
+
+        let timestamp = clip.timestamp();
+        if self.query.since.is_some_and(|since| timestamp < since)
+            || self.query.until.is_some_and(|until| timestamp >= until)
+        {
+            return None;
+        }
+
+        let (score, ranges) = if self.query.pattern.is_empty() {
+            (0, Vec::new())
+        } else {
+            let ClipboardContent::Plaintext(text) = clip.as_ref() else {
+                return None;
+            };
+            self.match_text(text)?
+        };
+
+        Some(Match { metadata: clip.metadata(Some(self.query.preview_length)), score, ranges })
+    }
+
+    fn match_text(&self, text: &str) -> Option<(i64, Vec<Range<usize>>)> {
+        let ranges: Vec<_> = match (self.query.mode, &self.regex) {
+            (Mode::Fuzzy, _) => {
+                let (score, indices) = self.fuzzy.fuzzy_indices(text, &self.query.pattern)?;
+                return Some((score, char_indices_to_byte_ranges(text, &indices)));
+            }
+            (_, Some(regex)) => regex
+                .find_iter(text)
+                .filter(|m| !m.is_empty())
+                .map(|m| m.start()..m.end())
+                .collect(),
+            (_, None) => text
+                .match_indices(self.query.pattern.as_str())
+                .map(|(start, m)| start..start + m.len())
+                .collect(),
+        };
+
+        if ranges.is_empty() {
+            None
+        } else {
+            Some((i64::try_from(ranges.len()).unwrap_or(i64::MAX), ranges))
+        }
+    }
+}
+
+pub fn match_mime(expected: &mime::Mime, mime: &mime::Mime) -> bool {
+    expected.type_() == mime.type_()
+        && (expected.subtype() == mime::STAR || expected.subtype() == mime.subtype())
+}
+
+fn char_indices_to_byte_ranges(text: &str, indices: &[usize]) -> Vec<Range<usize>> {
+    let mut indices = indices.iter().peekable();
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (char_index, (byte_index, ch)) in text.char_indices().enumerate() {
+        if indices.peek().is_none() {
+            break;
+        }
+        if indices.next_if_eq(&&char_index).is_some() {
+            let end = byte_index + ch.len_utf8();
+            match ranges.last_mut() {
+                Some(last) if last.end == byte_index => last.end = end,
+                _ => ranges.push(byte_index..end),
+            }
+        }
+    }
+    ranges
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    #[snafu(display("Could not parse search mode, value: {value}"))]
+    ParseMode { value: String },
+
+    #[snafu(display("Invalid search pattern, error: {source}"))]
+    BuildRegex { source: regex::Error },
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use crate::{ClipEntry, ClipboardKind, SearchMode, SearchQuery};
+
+    fn search(pattern: &str, mode: SearchMode, clips: &[ClipEntry]) -> Vec<String> {
+        let query = SearchQuery { pattern: pattern.to_string(), mode, ..SearchQuery::default() };
+        query
+            .build_matcher()
+            .unwrap()
+            .search(clips, |_| false)
+            .into_iter()
+            .map(|m| m.metadata.preview)
+            .collect()
+    }
+
+    #[test]
+    fn test_modes() {
+        let clips = vec![
+            ClipEntry::from_string("Hello World", ClipboardKind::Clipboard),
+            ClipEntry::from_string("hello hello", ClipboardKind::Primary),
+            ClipEntry::from_string("Goodbye", ClipboardKind::Clipboard),
+        ];
+
+        assert_eq!(search("Hello", SearchMode::Exact, &clips), vec!["Hello World"]);
+        assert_eq!(
+            search("hello", SearchMode::CaseInsensitive, &clips),
+            vec!["hello hello", "Hello World"]
+        );
+        assert_eq!(search("^Good", SearchMode::Regex, &clips), vec!["Goodbye"]);
+        assert_eq!(search("gdby", SearchMode::Fuzzy, &clips), vec!["Goodbye"]);
+        assert_eq!(search("", SearchMode::Exact, &clips).len(), clips.len());
+
+        let query = SearchQuery {
+            pattern: "(".to_string(),
+            mode: SearchMode::Regex,
+            ..SearchQuery::default()
+        };
+        assert!(query.build_matcher().is_err());
+    }
+
+    #[test]
+    fn test_filters_and_ranges() {
+        let clips = vec![
+            ClipEntry::from_string("абв abc", ClipboardKind::Clipboard),
+            ClipEntry::from_string("abc", ClipboardKind::Primary),
+        ];
+        let query = SearchQuery {
+            pattern: "abc".to_string(),
+            kinds: vec![ClipboardKind::Clipboard],
+            mimes: vec![mime::TEXT_STAR],
+            ..SearchQuery::default()
+        };
+        let matches = query.build_matcher().unwrap().search(&clips, |_| false);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].ranges, vec![7..10]);
+
+        let query = SearchQuery { mimes: vec![mime::IMAGE_STAR], ..SearchQuery::default() };
+        assert!(query.build_matcher().unwrap().search(&clips, |_| false).is_empty());
+
+        let mut tagged = clips.clone();
+        tagged[1].set_tags(["work"]);
+        let query = SearchQuery { tags: vec!["work".to_string()], ..SearchQuery::default() };
+        let matches = query.build_matcher().unwrap().search(&tagged, |_| false);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].metadata.tags, vec!["work"]);
+
+        let query = SearchQuery {
+            pattern: "бв".to_string(),
+            mode: SearchMode::Fuzzy,
+            ..SearchQuery::default()
+        };
+        let matches = query.build_matcher().unwrap().search(&clips, |_| false);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].ranges, vec![2..6]);
+
+        let mut pinned = clips.clone();
+        pinned[0].set_pinned(true);
+        let query = SearchQuery { pinned: Some(true), ..SearchQuery::default() };
+        let matches = query.build_matcher().unwrap().search(&pinned, |_| false);
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].metadata.pinned);
+
+        // snippets are told by the caller, whether or not they have a name
+        let query = SearchQuery { snippet: Some(true), ..SearchQuery::default() };
+        let matches =
+            query.build_matcher().unwrap().search(&clips, |clip| clip.id() == clips[1].id());
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].metadata.id, clips[1].id());
+
+        let timestamp = OffsetDateTime::now_utc() + time::Duration::days(1);
+        let query = SearchQuery { since: Some(timestamp), ..SearchQuery::default() };
+        assert!(query.build_matcher().unwrap().search(&clips, |_| false).is_empty());
+        let query = SearchQuery { until: Some(timestamp), ..SearchQuery::default() };
+        assert_eq!(query.build_matcher().unwrap().search(&clips, |_| false).len(), 2);
+    }
+}
@@ -0,0 +1,256 @@
+use std::{collections::HashMap, fmt};
+
+use snafu::{ResultExt, Snafu};
+use time::{format_description, OffsetDateTime};
+
+const OPEN_DELIMITER: &str = "{{";
+const CLOSE_DELIMITER: &str = "}}";
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Placeholder {
+    // `{{date}}` or `{{date:<strftime format>}}`
+    Date(String),
+
+    // `{{env:<name>}}`
+    Env(String),
+
+    // `{{clipboard}}`, the current clip
+    Clipboard,
+
+    // `{{prompt:<label>}}`, the value is provided by user
+    Prompt(String),
+}
+
+impl Placeholder {
+    fn parse(expression: &str) -> Option<Self> {
+        let (name, argument) = match expression.split_once(':') {
+            Some((name, argument)) => (name.trim(), Some(argument.trim())),
+            None => (expression.trim(), None),
+        };
+        match (name, argument) {
+            ("date", None) => Some(Self::Date(DEFAULT_DATE_FORMAT.to_string())),
+            ("date", Some(format)) => Some(Self::Date(format.to_string())),
+            ("env", Some(name)) if !name.is_empty() => Some(Self::Env(name.to_string())),
+            ("clipboard", None) => Some(Self::Clipboard),
+            ("prompt", Some(label)) if !label.is_empty() => Some(Self::Prompt(label.to_string())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Placeholder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Date(format) => write!(f, "{OPEN_DELIMITER}date:{format}{CLOSE_DELIMITER}"),
+            Self::Env(name) => write!(f, "{OPEN_DELIMITER}env:{name}{CLOSE_DELIMITER}"),
+            Self::Clipboard => write!(f, "{OPEN_DELIMITER}clipboard{CLOSE_DELIMITER}"),
+            Self::Prompt(label) => write!(f, "{OPEN_DELIMITER}prompt:{label}{CLOSE_DELIMITER}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Segment {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+/// A snippet with placeholders, such as `{{date:%Y-%m-%d}}`, `{{env:USER}}`,
+/// `{{clipboard}}` and `{{prompt:Ticket id}}`. Text enclosed in braces which
+/// is not a known placeholder is kept as-is.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    #[must_use]
+    pub fn parse(text: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find(OPEN_DELIMITER) {
+            let after_open = &rest[start + OPEN_DELIMITER.len()..];
+            let Some(end) = after_open.find(CLOSE_DELIMITER) else { break };
+            literal.push_str(&rest[..start]);
+            if let Some(placeholder) = Placeholder::parse(&after_open[..end]) {
+                if !literal.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut literal)));
+                }
+                segments.push(Segment::Placeholder(placeholder));
+            } else {
+                literal.push_str(&rest[start..start + OPEN_DELIMITER.len() + end]);
+                literal.push_str(CLOSE_DELIMITER);
+            }
+            rest = &after_open[end + CLOSE_DELIMITER.len()..];
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Text(literal));
+        }
+        Self { segments }
+    }
+
+    /// Returns `true` if `text` contains any placeholder.
+    #[inline]
+    #[must_use]
+    pub fn has_placeholders(text: &str) -> bool {
+        Self::parse(text).placeholders().next().is_some()
+    }
+
+    pub fn placeholders(&self) -> impl Iterator<Item = &Placeholder> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Placeholder(placeholder) => Some(placeholder),
+            Segment::Text(_) => None,
+        })
+    }
+
+    /// Returns labels of `{{prompt:<label>}}` placeholders in order of their
+    /// first appearance.
+    #[must_use]
+    pub fn prompts(&self) -> Vec<&str> {
+        let mut labels = Vec::new();
+        for placeholder in self.placeholders() {
+            if let Placeholder::Prompt(label) = placeholder {
+                if !labels.contains(&label.as_str()) {
+                    labels.push(label.as_str());
+                }
+            }
+        }
+        labels
+    }
+
+    /// Expands all placeholders with `context`. Environment variables which
+    /// are not in `context` expand to an empty string.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a prompt value is missing or a
+    /// date format is invalid.
+    pub fn expand(&self, context: &Context) -> Result<String, Error> {
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => output.push_str(text),
+                Segment::Placeholder(Placeholder::Date(format)) => {
+                    let items = format_description::parse_strftime_borrowed(format)
+                        .context(ParseDateFormatSnafu { format: format.clone() })?;
+                    let date = context
+                        .now
+                        .format(&items)
+                        .context(FormatDateSnafu { format: format.clone() })?;
+                    output.push_str(&date);
+                }
+                Segment::Placeholder(Placeholder::Env(name)) => {
+                    output.push_str(context.environment.get(name).map_or("", String::as_str));
+                }
+                Segment::Placeholder(Placeholder::Clipboard) => {
+                    output.push_str(context.clipboard.as_deref().unwrap_or_default());
+                }
+                Segment::Placeholder(Placeholder::Prompt(label)) => {
+                    let value = context
+                        .prompts
+                        .get(label)
+                        .ok_or_else(|| Error::MissingPrompt { label: label.clone() })?;
+                    output.push_str(value);
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Context {
+    // the time to expand `{{date}}` with
+    pub now: OffsetDateTime,
+
+    // text of the current clip, `{{clipboard}}` expands to an empty string if
+    // it is `None`
+    pub clipboard: Option<String>,
+
+    // values of prompts keyed by label
+    pub prompts: HashMap<String, String>,
+
+    // environment variables which `{{env:<name>}}` is allowed to read
+    pub environment: HashMap<String, String>,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self {
+            now: OffsetDateTime::now_utc(),
+            clipboard: None,
+            prompts: HashMap::new(),
+            environment: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    #[snafu(display("Value of prompt `{label}` is not provided"))]
+    MissingPrompt { label: String },
+
+    #[snafu(display("Invalid date format `{format}`, error: {source}"))]
+    ParseDateFormat { format: String, source: time::error::InvalidFormatDescription },
+
+    #[snafu(display("Could not format date with `{format}`, error: {source}"))]
+    FormatDate { format: String, source: time::error::Format },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use time::macros::datetime;
+
+    use crate::{SnippetTemplate, TemplateContext, TemplateError, TemplatePlaceholder};
+
+    #[test]
+    fn test_parse() {
+        let template = SnippetTemplate::parse("{{ prompt:Ticket id }} {{unknown}} {{env:USER}}{{");
+        assert_eq!(
+            template.placeholders().collect::<Vec<_>>(),
+            [
+                &TemplatePlaceholder::Prompt("Ticket id".to_string()),
+                &TemplatePlaceholder::Env("USER".to_string())
+            ]
+        );
+        assert_eq!(template.prompts(), ["Ticket id"]);
+
+        assert!(SnippetTemplate::has_placeholders("{{clipboard}}"));
+        assert!(!SnippetTemplate::has_placeholders("{{ name }} and {{"));
+    }
+
+    #[test]
+    fn test_expand() {
+        let context = TemplateContext {
+            now: datetime!(2024-03-01 12:30 UTC),
+            clipboard: Some("main".to_string()),
+            prompts: HashMap::from([("Ticket id".to_string(), "CC-42".to_string())]),
+            environment: HashMap::from([("USER".to_string(), "alice".to_string())]),
+        };
+
+        let template = SnippetTemplate::parse(
+            "{{date}} {{date:%H:%M}} git checkout -b {{prompt:Ticket id}} {{clipboard}} {{x}}",
+        );
+        assert_eq!(
+            template.expand(&context).unwrap(),
+            "2024-03-01 12:30 git checkout -b CC-42 main {{x}}"
+        );
+
+        // variables which are not allowed are never read from environment
+        let template = SnippetTemplate::parse("{{env:USER}}:{{env:PATH}}");
+        assert_eq!(template.expand(&context).unwrap(), "alice:");
+
+        let template = SnippetTemplate::parse("{{prompt:Reviewer}}");
+        assert!(matches!(
+            template.expand(&context),
+            Err(TemplateError::MissingPrompt { label }) if label == "Reviewer"
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+use image::ImageEncoder as _;
+use snafu::ResultExt;
+
+use crate::entry::{ConvertImageSnafu, Error};
+
+// image formats which are kept in their original encoding
+const SUPPORTED_MIMES: &[&str] =
+    &["image/png", "image/jpeg", "image/gif", "image/bmp", "image/webp", "image/svg+xml"];
+
+/// Returns whether images in format `mime` are accepted.
+#[inline]
+#[must_use]
+pub fn is_supported(mime: &mime::Mime) -> bool { SUPPORTED_MIMES.contains(&mime.essence_str()) }
+
+/// Returns the file extension of images in format `mime`.
+#[must_use]
+pub fn file_extension(mime: &mime::Mime) -> &'static str {
+    match mime.essence_str() {
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/bmp" => "bmp",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        _ => "png",
+    }
+}
+
+#[inline]
+#[must_use]
+pub fn is_vector(mime: &mime::Mime) -> bool { mime.essence_str() == "image/svg+xml" }
+
+fn raster_format(mime: &mime::Mime) -> Option<image::ImageFormat> {
+    match mime.essence_str() {
+        "image/png" => Some(image::ImageFormat::Png),
+        "image/jpeg" => Some(image::ImageFormat::Jpeg),
+        "image/gif" => Some(image::ImageFormat::Gif),
+        "image/bmp" => Some(image::ImageFormat::Bmp),
+        "image/webp" => Some(image::ImageFormat::WebP),
+        _ => None,
+    }
+}
+
+fn reader<'a>(
+    mime: &mime::Mime,
+    data: &'a [u8],
+) -> Result<image::ImageReader<std::io::Cursor<&'a [u8]>>, Error> {
+    let format = raster_format(mime).ok_or(Error::FormatNotAvailable)?;
+    Ok(image::ImageReader::with_format(std::io::Cursor::new(data), format))
+}
+
+/// Checks whether `data` is an image in format `mime` without decoding the
+/// pixels.
+///
+/// # Errors
+///
+/// This function will return an error if the format is not supported or
+/// `data` is not a valid image.
+pub fn validate(mime: &mime::Mime, data: &[u8]) -> Result<(), Error> {
+    if data.is_empty() {
+        return Err(Error::EmptyImage);
+    }
+    if is_vector(mime) {
+        return if std::str::from_utf8(data).is_ok_and(|svg| svg.contains("<svg")) {
+            Ok(())
+        } else {
+            Err(Error::FormatNotAvailable)
+        };
+    }
+    let _dimensions = reader(mime, data)?.into_dimensions().context(ConvertImageSnafu)?;
+    Ok(())
+}
+
+/// Reads width and height of an image from its header, `None` is returned for
+/// vector images and invalid images.
+#[must_use]
+pub fn dimensions(mime: &mime::Mime, data: &[u8]) -> Option<(u32, u32)> {
+    reader(mime, data).ok()?.into_dimensions().ok()
+}
+
+/// Decodes an image into RGBA pixels, returns width, height and pixels.
+///
+/// # Errors
+///
+/// This function will return an error if the image is a vector image or it
+/// could not be decoded.
+pub fn decode_rgba(mime: &mime::Mime, data: &[u8]) -> Result<(usize, usize, Vec<u8>), Error> {
+    let image = reader(mime, data)?.decode().context(ConvertImageSnafu)?.into_rgba8();
+    let (width, height) = image.dimensions();
+    let (width, height) =
+        (usize::try_from(width).unwrap_or_default(), usize::try_from(height).unwrap_or_default());
+    Ok((width, height, image.into_raw()))
+}
+
+/// Scales an image down to fit in `max_size` x `max_size` and encodes it as
+/// PNG, the aspect ratio is kept and smaller images are not enlarged.
+///
+/// # Errors
+///
+/// This function will return an error if the image is a vector image or it
+/// could not be decoded or encoded.
+pub fn thumbnail(mime: &mime::Mime, data: &[u8], max_size: u32) -> Result<Vec<u8>, Error> {
+    let image = reader(mime, data)?.decode().context(ConvertImageSnafu)?;
+    let image = if image.width() > max_size || image.height() > max_size {
+        image.thumbnail(max_size, max_size)
+    } else {
+        image
+    };
+    let image = image.into_rgba8();
+    let (width, height) = image.dimensions();
+    encode_rgba_as_png(
+        usize::try_from(width).unwrap_or_default(),
+        usize::try_from(height).unwrap_or_default(),
+        image.as_raw(),
+    )
+}
+
+/// Encodes RGBA pixels as PNG.
+///
+/// # Errors
+///
+/// This function will return an error if the image is empty or could not be
+/// encoded.
+pub fn encode_rgba_as_png(width: usize, height: usize, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let (width, height) =
+        (u32::try_from(width).unwrap_or_default(), u32::try_from(height).unwrap_or_default());
+    if bytes.is_empty() || width == 0 || height == 0 {
+        return Err(Error::EmptyImage);
+    }
+
+    let mut png_bytes = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut png_bytes)
+        .write_image(bytes, width, height, image::ExtendedColorType::Rgba8)
+        .context(ConvertImageSnafu)?;
+
+    Ok(png_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{entry::Error, utils::image};
+
+    // a 2x1 image with a red and a transparent pixel
+    const PIXELS: [u8; 8] = [255, 0, 0, 255, 0, 0, 0, 0];
+
+    #[test]
+    fn test_supported_formats() {
+        assert!(image::is_supported(&mime::IMAGE_PNG));
+        assert!(image::is_supported(&"image/webp".parse().unwrap()));
+        assert!(image::is_supported(&mime::IMAGE_SVG));
+        assert!(!image::is_supported(&"image/tiff".parse().unwrap()));
+        assert!(!image::is_supported(&mime::TEXT_PLAIN_UTF_8));
+
+        assert_eq!(image::file_extension(&mime::IMAGE_JPEG), "jpg");
+        assert_eq!(image::file_extension(&mime::IMAGE_SVG), "svg");
+        assert_eq!(image::file_extension(&mime::IMAGE_PNG), "png");
+
+        assert!(image::is_vector(&mime::IMAGE_SVG));
+        assert!(!image::is_vector(&mime::IMAGE_PNG));
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let png = image::encode_rgba_as_png(2, 1, &PIXELS).unwrap();
+        assert!(image::validate(&mime::IMAGE_PNG, &png).is_ok());
+        assert_eq!(image::dimensions(&mime::IMAGE_PNG, &png), Some((2, 1)));
+        assert_eq!(image::decode_rgba(&mime::IMAGE_PNG, &png).unwrap(), (2, 1, PIXELS.to_vec()));
+
+        // the data is decoded in the format it is declared in
+        assert!(image::validate(&mime::IMAGE_JPEG, &png).is_err());
+        assert_eq!(image::dimensions(&mime::IMAGE_JPEG, &png), None);
+    }
+
+    #[test]
+    fn test_thumbnail() {
+        let pixels = [255; 4 * 40 * 20];
+        let png = image::encode_rgba_as_png(40, 20, &pixels).unwrap();
+
+        let thumbnail = image::thumbnail(&mime::IMAGE_PNG, &png, 10).unwrap();
+        assert_eq!(image::dimensions(&mime::IMAGE_PNG, &thumbnail), Some((10, 5)));
+
+        // small images are not enlarged
+        let thumbnail = image::thumbnail(&mime::IMAGE_PNG, &png, 100).unwrap();
+        assert_eq!(image::dimensions(&mime::IMAGE_PNG, &thumbnail), Some((40, 20)));
+
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1"/>"#;
+        assert!(matches!(
+            image::thumbnail(&mime::IMAGE_SVG, svg, 10),
+            Err(Error::FormatNotAvailable)
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(matches!(image::validate(&mime::IMAGE_PNG, b""), Err(Error::EmptyImage)));
+        assert!(image::validate(&mime::IMAGE_PNG, b"not an image").is_err());
+        assert!(matches!(
+            image::validate(&"image/tiff".parse().unwrap(), b"II*\0"),
+            Err(Error::FormatNotAvailable)
+        ));
+
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1"/>"#;
+        assert!(image::validate(&mime::IMAGE_SVG, svg).is_ok());
+        assert!(image::validate(&mime::IMAGE_SVG, b"<html></html>").is_err());
+        assert_eq!(image::dimensions(&mime::IMAGE_SVG, svg), None);
+        assert!(image::decode_rgba(&mime::IMAGE_SVG, svg).is_err());
+    }
+
+    #[test]
+    fn test_encode_empty_image() {
+        assert!(matches!(image::encode_rgba_as_png(0, 1, &PIXELS), Err(Error::EmptyImage)));
+        assert!(matches!(image::encode_rgba_as_png(2, 1, &[]), Err(Error::EmptyImage)));
+    }
+}
@@ -1,4 +1,5 @@
 pub mod fs;
+pub mod image;
 mod retry_interval;
 
 pub use self::retry_interval::RetryInterval;
@@ -88,6 +88,34 @@ impl fmt::Display for UpdateClipError {
     }
 }
 
+#[derive(Debug)]
+pub enum GetClipByIndexError {
+    Status { source: tonic::Status, index: usize },
+    Empty { index: usize },
+}
+
+impl fmt::Display for GetClipByIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source, .. } => source.fmt(f),
+            Self::Empty { index } => write!(f, "There is no clip at index {index}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MarkClipByIndexError {
+    Status { source: tonic::Status, index: usize, kind: ClipboardKind },
+}
+
+impl fmt::Display for MarkClipByIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source, .. } => source.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum MarkClipError {
     Status { source: tonic::Status, id: u64, kind: ClipboardKind },
@@ -101,6 +129,138 @@ impl fmt::Display for MarkClipError {
     }
 }
 
+#[derive(Debug)]
+pub enum ExpandClipError {
+    Status { source: tonic::Status, id: u64 },
+    Empty,
+}
+
+impl fmt::Display for ExpandClipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source, .. } => source.fmt(f),
+            Self::Empty => f.write_str("Clip is not found"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PinClipError {
+    Status { source: tonic::Status, id: u64 },
+}
+
+impl fmt::Display for PinClipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source, .. } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum UnpinClipError {
+    Status { source: tonic::Status, id: u64 },
+}
+
+impl fmt::Display for UnpinClipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source, .. } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TagClipError {
+    Status { source: tonic::Status, id: u64, tag: String },
+}
+
+impl fmt::Display for TagClipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source, .. } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum UntagClipError {
+    Status { source: tonic::Status, id: u64, tag: String },
+}
+
+impl fmt::Display for UntagClipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source, .. } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ListTagsError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for ListTagsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ListSnippetsError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for ListSnippetsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AddSnippetError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for AddSnippetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum UpdateSnippetError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for UpdateSnippetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RemoveSnippetError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for RemoveSnippetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum RemoveClipError {
     Status { source: tonic::Status },
@@ -166,6 +326,32 @@ impl fmt::Display for ListClipError {
     }
 }
 
+#[derive(Debug)]
+pub enum SearchClipError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for SearchClipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SubscribeError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for SubscribeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum EnableWatcherError {
     Status { source: tonic::Status },
@@ -230,3 +416,68 @@ impl fmt::Display for GetSystemVersionError {
         }
     }
 }
+
+#[derive(Debug)]
+pub enum ReloadConfigError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for ReloadConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RekeyHistoryError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for RekeyHistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GetHistorySizeError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for GetHistorySizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportHistoryError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for ExportHistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ImportHistoryError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for ImportHistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
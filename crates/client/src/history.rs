@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use clipcat_base::{archive::ImportMode, ClipEntry};
+use clipcat_proto as proto;
+use futures::TryStreamExt;
+use tonic::Request;
+
+use crate::{
+    error::{ExportHistoryError, GetHistorySizeError, ImportHistoryError, RekeyHistoryError},
+    Client,
+};
+
+#[async_trait]
+pub trait History {
+    /// Re-encrypts the history with `new_key`, the history is decrypted if
+    /// `new_key` is `None`. `current_key` must be the key which the history is
+    /// encrypted with, `None` if it is not encrypted. Returns whether the
+    /// history is encrypted.
+    async fn rekey_history(
+        &self,
+        current_key: Option<&[u8]>,
+        new_key: Option<&[u8]>,
+    ) -> Result<bool, RekeyHistoryError>;
+
+    /// Returns the number of bytes taken by the history on disk.
+    async fn history_size_in_bytes(&self) -> Result<u64, GetHistorySizeError>;
+
+    /// Returns all clips except sensitive clips and snippets, from the newest
+    /// to the oldest.
+    async fn export_history(&self) -> Result<Vec<ClipEntry>, ExportHistoryError>;
+
+    /// Imports `clips` into the history, returns the numbers of inserted clips
+    /// and clips merged into clips with the same content.
+    async fn import_history(
+        &self,
+        clips: Vec<ClipEntry>,
+        mode: ImportMode,
+    ) -> Result<(u64, u64), ImportHistoryError>;
+}
+
+#[async_trait]
+impl History for Client {
+    async fn rekey_history(
+        &self,
+        current_key: Option<&[u8]>,
+        new_key: Option<&[u8]>,
+    ) -> Result<bool, RekeyHistoryError> {
+        let key = new_key.map(<[u8]>::to_vec).unwrap_or_default();
+        let current_key = current_key.map(<[u8]>::to_vec).unwrap_or_default();
+        let proto::RekeyResponse { encrypted } =
+            proto::HistoryClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .rekey(Request::new(proto::RekeyRequest { key, current_key }))
+                .await
+                .map_err(|source| RekeyHistoryError::Status { source })?
+                .into_inner();
+        Ok(encrypted)
+    }
+
+    async fn history_size_in_bytes(&self) -> Result<u64, GetHistorySizeError> {
+        let proto::SizeResponse { size_in_bytes } =
+            proto::HistoryClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .size(Request::new(()))
+                .await
+                .map_err(|source| GetHistorySizeError::Status { source })?
+                .into_inner();
+        Ok(size_in_bytes)
+    }
+
+    async fn export_history(&self) -> Result<Vec<ClipEntry>, ExportHistoryError> {
+        let clips =
+            proto::HistoryClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .export(Request::new(()))
+                .await
+                .map_err(|source| ExportHistoryError::Status { source })?
+                .into_inner()
+                .map_ok(ClipEntry::from)
+                .try_collect()
+                .await
+                .map_err(|source| ExportHistoryError::Status { source })?;
+        Ok(clips)
+    }
+
+    async fn import_history(
+        &self,
+        clips: Vec<ClipEntry>,
+        mode: ImportMode,
+    ) -> Result<(u64, u64), ImportHistoryError> {
+        let mode = i32::from(proto::ImportMode::from(mode));
+        // at least one message is sent so that the mode reaches the server even if
+        // there is no clip
+        let requests = if clips.is_empty() {
+            vec![proto::ImportRequest { mode, clip: None }]
+        } else {
+            clips
+                .into_iter()
+                .map(|clip| proto::ImportRequest {
+                    mode,
+                    clip: Some(proto::ArchivedClip::from(clip)),
+                })
+                .collect()
+        };
+        let proto::ImportResponse { inserted, merged } =
+            proto::HistoryClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .import(Request::new(futures::stream::iter(requests)))
+                .await
+                .map_err(|source| ImportHistoryError::Status { source })?
+                .into_inner();
+        Ok((inserted, merged))
+    }
+}
@@ -1,4 +1,5 @@
 pub mod error;
+mod history;
 mod interceptor;
 mod manager;
 mod system;
@@ -12,7 +13,8 @@ use tokio::net::UnixStream;
 use self::interceptor::Interceptor;
 pub use self::{
     error::{Error, Result},
-    manager::Manager,
+    history::History,
+    manager::{ClipboardEventStream, Manager},
     system::System,
     watcher::Watcher,
 };
@@ -1,20 +1,35 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
-use clipcat_base::{ClipEntry, ClipEntryMetadata, ClipboardKind};
+use clipcat_base::{
+    ClipEntry, ClipEntryMetadata, ClipboardEvent, ClipboardKind, ListQuery, SearchMatch,
+    SearchQuery,
+};
 use clipcat_proto as proto;
+use futures::{future, stream::BoxStream, StreamExt};
 use tonic::Request;
 
 use crate::{
     error::{
-        BatchRemoveClipError, ClearClipError, GetClipError, GetCurrentClipError, GetLengthError,
-        InsertClipError, ListClipError, MarkClipError, RemoveClipError, UpdateClipError,
+        AddSnippetError, BatchRemoveClipError, ClearClipError, ExpandClipError,
+        GetClipByIndexError, GetClipError, GetCurrentClipError, GetLengthError, InsertClipError,
+        ListClipError, ListSnippetsError, ListTagsError, MarkClipByIndexError, MarkClipError,
+        PinClipError, RemoveClipError, RemoveSnippetError, SearchClipError, SubscribeError,
+        TagClipError, UnpinClipError, UntagClipError, UpdateClipError, UpdateSnippetError,
     },
     Client,
 };
 
+pub type ClipboardEventStream = BoxStream<'static, Result<ClipboardEvent, SubscribeError>>;
+
 #[async_trait]
 pub trait Manager {
     async fn get(&self, id: u64) -> Result<ClipEntry, GetClipError>;
 
+    /// Returns the clip at `index` of the history, the most recent clip is at
+    /// index 0.
+    async fn get_by_index(&self, index: usize) -> Result<ClipEntry, GetClipByIndexError>;
+
     async fn get_current_clip(&self, kind: ClipboardKind)
         -> Result<ClipEntry, GetCurrentClipError>;
 
@@ -27,6 +42,66 @@ pub trait Manager {
 
     async fn mark(&self, id: u64, kind: ClipboardKind) -> Result<bool, MarkClipError>;
 
+    /// Same as [`Self::mark`], placeholders of a snippet template are expanded
+    /// with `prompts` keyed by prompt label.
+    async fn mark_with_prompts(
+        &self,
+        id: u64,
+        kind: ClipboardKind,
+        prompts: HashMap<String, String>,
+    ) -> Result<bool, MarkClipError>;
+
+    /// Returns clip `id` with placeholders of a snippet template expanded,
+    /// other clips are returned as-is.
+    async fn expand(
+        &self,
+        id: u64,
+        prompts: HashMap<String, String>,
+    ) -> Result<ClipEntry, ExpandClipError>;
+
+    /// Marks the clip at `index` of the history, returns its ID if it is
+    /// marked. Placeholders of a snippet template are expanded with `prompts`
+    /// keyed by prompt label.
+    async fn mark_by_index(
+        &self,
+        index: usize,
+        kind: ClipboardKind,
+        prompts: HashMap<String, String>,
+    ) -> Result<Option<u64>, MarkClipByIndexError>;
+
+    async fn pin(&self, id: u64) -> Result<bool, PinClipError>;
+
+    async fn unpin(&self, id: u64) -> Result<bool, UnpinClipError>;
+
+    /// Tags clip `id` with `tag`, returns `false` if the clip is not found or
+    /// `tag` is empty.
+    async fn tag(&self, id: u64, tag: &str) -> Result<bool, TagClipError>;
+
+    /// Removes `tag` from clip `id`, returns `false` if the clip is not tagged
+    /// with `tag`.
+    async fn untag(&self, id: u64, tag: &str) -> Result<bool, UntagClipError>;
+
+    /// Returns all tags in use with the number of clips tagged with each of
+    /// them.
+    async fn list_tags(&self) -> Result<Vec<(String, usize)>, ListTagsError>;
+
+    /// Returns metadata of snippets sorted by name.
+    async fn list_snippets(
+        &self,
+        preview_length: usize,
+    ) -> Result<Vec<ClipEntryMetadata>, ListSnippetsError>;
+
+    /// Creates a snippet file `name` in the snippet directory of the server
+    /// and returns the `id` of the new snippet clip.
+    async fn add_snippet(&self, name: &str, content: &str) -> Result<u64, AddSnippetError>;
+
+    /// Replaces the content of snippet `name` and returns the `id` of the
+    /// updated snippet clip.
+    async fn update_snippet(&self, name: &str, content: &str) -> Result<u64, UpdateSnippetError>;
+
+    /// Removes snippet `name`, returns `false` if there is no such snippet.
+    async fn remove_snippet(&self, name: &str) -> Result<bool, RemoveSnippetError>;
+
     async fn insert(
         &self,
         data: &[u8],
@@ -48,13 +123,25 @@ pub trait Manager {
 
     async fn length(&self) -> Result<usize, GetLengthError>;
 
-    async fn list(&self, preview_length: usize) -> Result<Vec<ClipEntryMetadata>, ListClipError>;
+    /// Returns the number of bytes of clips kept in memory.
+    async fn size_in_bytes(&self) -> Result<usize, GetLengthError>;
+
+    /// Returns metadata of clips matching `query`, in the order of
+    /// `query.order`.
+    async fn list(&self, query: ListQuery) -> Result<Vec<ClipEntryMetadata>, ListClipError>;
+
+    async fn search(&self, query: SearchQuery) -> Result<Vec<SearchMatch>, SearchClipError>;
 
     async fn remove(&self, id: u64) -> Result<bool, RemoveClipError>;
 
     async fn batch_remove(&self, ids: &[u64]) -> Result<Vec<u64>, BatchRemoveClipError>;
 
     async fn clear(&self) -> Result<(), ClearClipError>;
+
+    async fn subscribe(
+        &self,
+        preview_length: usize,
+    ) -> Result<ClipboardEventStream, SubscribeError>;
 }
 
 #[async_trait]
@@ -69,6 +156,18 @@ impl Manager for Client {
             .map_or_else(|| Err(GetClipError::Empty), |data| Ok(data.into()))
     }
 
+    async fn get_by_index(&self, index: usize) -> Result<ClipEntry, GetClipByIndexError> {
+        proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+            .get_by_index(Request::new(proto::GetByIndexRequest {
+                index: u64::try_from(index).unwrap_or(u64::MAX),
+            }))
+            .await
+            .map_err(|source| GetClipByIndexError::Status { source, index })?
+            .into_inner()
+            .data
+            .map_or_else(|| Err(GetClipByIndexError::Empty { index }), |data| Ok(data.into()))
+    }
+
     async fn get_current_clip(
         &self,
         kind: ClipboardKind,
@@ -102,15 +201,161 @@ impl Manager for Client {
     }
 
     async fn mark(&self, id: u64, kind: ClipboardKind) -> Result<bool, MarkClipError> {
+        self.mark_with_prompts(id, kind, HashMap::new()).await
+    }
+
+    async fn mark_with_prompts(
+        &self,
+        id: u64,
+        kind: ClipboardKind,
+        prompts: HashMap<String, String>,
+    ) -> Result<bool, MarkClipError> {
         let proto::MarkResponse { ok } =
             proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
-                .mark(Request::new(proto::MarkRequest { id, kind: kind.into() }))
+                .mark(Request::new(proto::MarkRequest { id, kind: kind.into(), prompts }))
                 .await
                 .map_err(|source| MarkClipError::Status { source, id, kind })?
                 .into_inner();
         Ok(ok)
     }
 
+    async fn expand(
+        &self,
+        id: u64,
+        prompts: HashMap<String, String>,
+    ) -> Result<ClipEntry, ExpandClipError> {
+        proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+            .expand(Request::new(proto::ExpandRequest { id, prompts }))
+            .await
+            .map_err(|source| ExpandClipError::Status { source, id })?
+            .into_inner()
+            .data
+            .map_or_else(|| Err(ExpandClipError::Empty), |data| Ok(data.into()))
+    }
+
+    async fn mark_by_index(
+        &self,
+        index: usize,
+        kind: ClipboardKind,
+        prompts: HashMap<String, String>,
+    ) -> Result<Option<u64>, MarkClipByIndexError> {
+        let proto::MarkByIndexResponse { ok, id } =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .mark_by_index(Request::new(proto::MarkByIndexRequest {
+                    index: u64::try_from(index).unwrap_or(u64::MAX),
+                    kind: kind.into(),
+                    prompts,
+                }))
+                .await
+                .map_err(|source| MarkClipByIndexError::Status { source, index, kind })?
+                .into_inner();
+        Ok(ok.then_some(id))
+    }
+
+    async fn pin(&self, id: u64) -> Result<bool, PinClipError> {
+        let proto::PinResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .pin(Request::new(proto::PinRequest { id }))
+                .await
+                .map_err(|source| PinClipError::Status { source, id })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn unpin(&self, id: u64) -> Result<bool, UnpinClipError> {
+        let proto::UnpinResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .unpin(Request::new(proto::UnpinRequest { id }))
+                .await
+                .map_err(|source| UnpinClipError::Status { source, id })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn tag(&self, id: u64, tag: &str) -> Result<bool, TagClipError> {
+        let proto::TagResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .tag(Request::new(proto::TagRequest { id, tag: tag.to_string() }))
+                .await
+                .map_err(|source| TagClipError::Status { source, id, tag: tag.to_string() })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn untag(&self, id: u64, tag: &str) -> Result<bool, UntagClipError> {
+        let proto::UntagResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .untag(Request::new(proto::UntagRequest { id, tag: tag.to_string() }))
+                .await
+                .map_err(|source| UntagClipError::Status { source, id, tag: tag.to_string() })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn list_tags(&self) -> Result<Vec<(String, usize)>, ListTagsError> {
+        let proto::ListTagsResponse { tags } =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .list_tags(Request::new(()))
+                .await
+                .map_err(|source| ListTagsError::Status { source })?
+                .into_inner();
+        Ok(tags
+            .into_iter()
+            .map(|proto::TagInfo { name, count }| (name, usize::try_from(count).unwrap_or(0)))
+            .collect())
+    }
+
+    async fn list_snippets(
+        &self,
+        preview_length: usize,
+    ) -> Result<Vec<ClipEntryMetadata>, ListSnippetsError> {
+        let proto::ListSnippetsResponse { snippets } =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .list_snippets(Request::new(proto::ListSnippetsRequest {
+                    preview_length: u64::try_from(preview_length).unwrap_or(u64::MAX),
+                }))
+                .await
+                .map_err(|source| ListSnippetsError::Status { source })?
+                .into_inner();
+        Ok(snippets.into_iter().map(ClipEntryMetadata::from).collect())
+    }
+
+    async fn add_snippet(&self, name: &str, content: &str) -> Result<u64, AddSnippetError> {
+        let proto::AddSnippetResponse { id } =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .add_snippet(Request::new(proto::AddSnippetRequest {
+                    name: name.to_owned(),
+                    content: content.to_owned(),
+                }))
+                .await
+                .map_err(|source| AddSnippetError::Status { source })?
+                .into_inner();
+        Ok(id)
+    }
+
+    async fn update_snippet(&self, name: &str, content: &str) -> Result<u64, UpdateSnippetError> {
+        let proto::UpdateSnippetResponse { id } =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .update_snippet(Request::new(proto::UpdateSnippetRequest {
+                    name: name.to_owned(),
+                    content: content.to_owned(),
+                }))
+                .await
+                .map_err(|source| UpdateSnippetError::Status { source })?
+                .into_inner();
+        Ok(id)
+    }
+
+    async fn remove_snippet(&self, name: &str) -> Result<bool, RemoveSnippetError> {
+        let proto::RemoveSnippetResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .remove_snippet(Request::new(proto::RemoveSnippetRequest { name: name.to_owned() }))
+                .await
+                .map_err(|source| RemoveSnippetError::Status { source })?
+                .into_inner();
+        Ok(ok)
+    }
+
     async fn insert(
         &self,
         data: &[u8],
@@ -131,7 +376,7 @@ impl Manager for Client {
     }
 
     async fn length(&self) -> Result<usize, GetLengthError> {
-        let proto::LengthResponse { length } =
+        let proto::LengthResponse { length, .. } =
             proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
                 .length(Request::new(()))
                 .await
@@ -140,12 +385,20 @@ impl Manager for Client {
         Ok(usize::try_from(length).unwrap_or(0))
     }
 
-    async fn list(&self, preview_length: usize) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
-        let mut list: Vec<_> =
+    async fn size_in_bytes(&self) -> Result<usize, GetLengthError> {
+        let proto::LengthResponse { size_in_bytes, .. } =
             proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
-                .list(Request::new(proto::ListRequest {
-                    preview_length: u64::try_from(preview_length).unwrap_or(30),
-                }))
+                .length(Request::new(()))
+                .await
+                .map_err(|source| GetLengthError::Status { source })?
+                .into_inner();
+        Ok(usize::try_from(size_in_bytes).unwrap_or(0))
+    }
+
+    async fn list(&self, query: ListQuery) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+        let list =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .list(Request::new(proto::ListRequest::from(query)))
                 .await
                 .map_err(|source| ListClipError::Status { source })?
                 .into_inner()
@@ -153,10 +406,44 @@ impl Manager for Client {
                 .into_iter()
                 .map(ClipEntryMetadata::from)
                 .collect();
-        list.sort_unstable();
         Ok(list)
     }
 
+    async fn subscribe(
+        &self,
+        preview_length: usize,
+    ) -> Result<ClipboardEventStream, SubscribeError> {
+        let stream =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .subscribe(Request::new(proto::SubscribeRequest {
+                    preview_length: u64::try_from(preview_length).unwrap_or(u64::MAX),
+                }))
+                .await
+                .map_err(|source| SubscribeError::Status { source })?
+                .into_inner()
+                .filter_map(|event| {
+                    future::ready(match event {
+                        Ok(event) => Option::<ClipboardEvent>::from(event).map(Ok),
+                        Err(source) => Some(Err(SubscribeError::Status { source })),
+                    })
+                });
+        Ok(stream.boxed())
+    }
+
+    async fn search(&self, query: SearchQuery) -> Result<Vec<SearchMatch>, SearchClipError> {
+        let results =
+            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .search(Request::new(proto::SearchRequest::from(query)))
+                .await
+                .map_err(|source| SearchClipError::Status { source })?
+                .into_inner()
+                .results
+                .into_iter()
+                .map(SearchMatch::from)
+                .collect();
+        Ok(results)
+    }
+
     async fn remove(&self, id: u64) -> Result<bool, RemoveClipError> {
         let proto::RemoveResponse { ok } =
             proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
@@ -1,6 +1,4 @@
-use std::sync::Arc;
-
-use clipcat_base::ClipboardContent;
+use clipcat_base::{ClipboardContent, SharedClipFilter};
 use clipcat_clipboard::{Clipboard, ClipboardKind, ClipboardLoadWait, Error};
 use snafu::ErrorCompat;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -8,7 +6,8 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 fn main() -> Result<(), Error> {
     init_tracing();
 
-    let clipboard = Clipboard::new(ClipboardKind::Clipboard, Arc::default(), Vec::new())?;
+    let clipboard =
+        Clipboard::new(ClipboardKind::Clipboard, SharedClipFilter::default(), Vec::new())?;
     println!("Waiting for new clipboard event...");
     println!("You can to copy some text from other window...");
     for _ in 0..10 {
@@ -6,7 +6,7 @@ use std::{
     time::{Duration, Instant},
 };
 
-use clipcat_base::ClipboardContent;
+use clipcat_base::{ClipboardContent, SharedClipFilter};
 use clipcat_clipboard::{Clipboard, ClipboardKind, ClipboardStore};
 use sigfinn::{ExitStatus, LifecycleManager};
 use snafu::ErrorCompat;
@@ -15,7 +15,8 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     init_tracing();
-    let clipboard = Clipboard::new(ClipboardKind::Clipboard, Arc::default(), Vec::new())?;
+    let clipboard =
+        Clipboard::new(ClipboardKind::Clipboard, SharedClipFilter::default(), Vec::new())?;
     let data = format!("{:?}", Instant::now());
 
     let lifecycle_manager = LifecycleManager::<clipcat_clipboard::Error>::new();
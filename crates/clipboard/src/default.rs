@@ -17,7 +17,8 @@ use std::{
 ))]
 use arboard::{ClearExtLinux, GetExtLinux, SetExtLinux};
 use bytes::Bytes;
-use clipcat_base::{ClipFilter, ClipboardContent};
+use clipcat_base::{utils, ClipRepresentation, ClipboardContent, SharedClipFilter};
+use snafu::ResultExt;
 
 #[cfg(target_os = "macos")]
 use crate::listener::MacOsListener;
@@ -30,10 +31,10 @@ use crate::listener::MacOsListener;
         target_os = "emscripten"
     ))
 ))]
-use crate::listener::{WaylandListener, X11Listener};
+use crate::listener::{WaylandListener, X11Listener, X11RepresentationReader};
 use crate::{
-    traits::EventObserver, ClipboardKind, ClipboardLoad, ClipboardStore, ClipboardSubscribe, Error,
-    Subscriber,
+    error, traits::EventObserver, ClipboardKind, ClipboardLoad, ClipboardStore, ClipboardSubscribe,
+    Error, Subscriber,
 };
 
 #[derive(Clone)]
@@ -52,13 +53,40 @@ pub struct Clipboard {
         ))
     ))]
     clipboard_kind: arboard::LinuxClipboardKind,
+
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "android",
+            target_os = "emscripten"
+        ))
+    ))]
+    representation_source: RepresentationSource,
+}
+
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "android",
+        target_os = "emscripten"
+    ))
+))]
+#[allow(variant_size_differences)]
+#[derive(Clone, Debug)]
+enum RepresentationSource {
+    X11(Arc<X11RepresentationReader>),
+    Wayland(ClipboardKind),
 }
 
 impl Clipboard {
     /// # Errors
     pub fn new(
         clipboard_kind: ClipboardKind,
-        clip_filter: Arc<ClipFilter>,
+        clip_filter: SharedClipFilter,
         event_observers: Vec<Arc<dyn EventObserver>>,
     ) -> Result<Self, Error> {
         #[cfg(all(
@@ -95,36 +123,40 @@ impl Clipboard {
     /// # Errors
     fn new_on_linux(
         clipboard_kind: ClipboardKind,
-        clip_filter: Arc<ClipFilter>,
+        clip_filter: SharedClipFilter,
         event_observers: Vec<Arc<dyn EventObserver>>,
     ) -> Result<Self, Error> {
-        let listener: Arc<dyn ClipboardSubscribe<Subscriber = Subscriber>> =
-            if let Ok(display_name) = std::env::var("WAYLAND_DISPLAY") {
+        let (listener, representation_source): (
+            Arc<dyn ClipboardSubscribe<Subscriber = Subscriber>>,
+            _,
+        ) = if let Ok(display_name) = std::env::var("WAYLAND_DISPLAY") {
+            tracing::info!(
+                "Build Wayland listener ({clipboard_kind}) with display `{display_name}`"
+            );
+            (
+                Arc::new(WaylandListener::new(clipboard_kind)?),
+                RepresentationSource::Wayland(clipboard_kind),
+            )
+        } else {
+            let display_name = std::env::var("DISPLAY").ok();
+            if let Some(display_name) = &display_name {
                 tracing::info!(
-                    "Build Wayland listener ({clipboard_kind}) with display `{display_name}`"
+                    "Build X11 listener ({clipboard_kind}) with display `{display_name}`"
                 );
-                Arc::new(WaylandListener::new(clipboard_kind)?)
-            } else {
-                match std::env::var("DISPLAY") {
-                    Ok(display_name) => {
-                        tracing::info!(
-                            "Build X11 listener ({clipboard_kind}) with display `{display_name}`"
-                        );
-                        Arc::new(X11Listener::new(
-                            Some(display_name),
-                            clipboard_kind,
-                            clip_filter,
-                            event_observers,
-                        )?)
-                    }
-                    Err(_) => Arc::new(X11Listener::new(
-                        None,
-                        clipboard_kind,
-                        clip_filter,
-                        event_observers,
-                    )?),
-                }
-            };
+            }
+            (
+                Arc::new(X11Listener::new(
+                    display_name.clone(),
+                    clipboard_kind,
+                    clip_filter,
+                    event_observers,
+                )?),
+                RepresentationSource::X11(Arc::new(X11RepresentationReader::new(
+                    display_name,
+                    clipboard_kind,
+                ))),
+            )
+        };
 
         let clear_on_drop = Arc::new(AtomicBool::from(false));
 
@@ -133,7 +165,7 @@ impl Clipboard {
             ClipboardKind::Primary => arboard::LinuxClipboardKind::Primary,
             ClipboardKind::Secondary => arboard::LinuxClipboardKind::Secondary,
         };
-        Ok(Self { listener, clear_on_drop, clipboard_kind })
+        Ok(Self { listener, clear_on_drop, clipboard_kind, representation_source })
     }
 
     /// # Errors
@@ -187,6 +219,10 @@ impl ClipboardLoad for Clipboard {
                         }
                     }
                 } else if mime.type_() == mime::IMAGE {
+                    if let Some(content) = self.load_encoded_image(&mime) {
+                        return Ok(content);
+                    }
+
                     #[cfg(all(
                         unix,
                         not(any(
@@ -203,11 +239,8 @@ impl ClipboardLoad for Clipboard {
 
                     match maybe_image {
                         Ok(arboard::ImageData { width, height, bytes }) => {
-                            Ok(ClipboardContent::Image {
-                                width,
-                                height,
-                                bytes: Bytes::from(bytes.into_owned()),
-                            })
+                            ClipboardContent::from_rgba(width, height, &bytes)
+                                .context(error::ConvertImageSnafu)
                         }
                         Err(arboard::Error::ClipboardNotSupported) => unreachable!(),
                         Err(err) => {
@@ -221,11 +254,119 @@ impl ClipboardLoad for Clipboard {
             }
         }
     }
+
+    fn load_representations(&self) -> Result<Vec<ClipRepresentation>, Error> {
+        #[cfg(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "android",
+                target_os = "emscripten"
+            ))
+        ))]
+        {
+            match &self.representation_source {
+                RepresentationSource::X11(reader) => reader.load().map_err(Error::from),
+                RepresentationSource::Wayland(kind) => {
+                    crate::listener::wayland::load_representations(*kind).map_err(Error::from)
+                }
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            Ok(Vec::new())
+        }
+    }
 }
 
-impl ClipboardStore for Clipboard {
-    #[inline]
-    fn store(&self, content: ClipboardContent) -> Result<(), Error> {
+// contents which are ready to be set with `arboard`
+enum ArboardContent {
+    // `html` is offered along with plaintext if it is present
+    Text { text: String, html: Option<String> },
+    Image { width: usize, height: usize, bytes: Vec<u8> },
+}
+
+impl Clipboard {
+    // loads the image in its original encoding, `None` is returned if it is not
+    // available
+    fn load_encoded_image(&self, mime: &mime::Mime) -> Option<ClipboardContent> {
+        if !utils::image::is_supported(mime) {
+            return None;
+        }
+
+        #[cfg(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "android",
+                target_os = "emscripten"
+            ))
+        ))]
+        let maybe_data = match &self.representation_source {
+            RepresentationSource::X11(reader) => {
+                reader.load_target(mime.essence_str()).map_err(Error::from)
+            }
+            RepresentationSource::Wayland(kind) => {
+                crate::listener::wayland::load_contents(*kind, mime.essence_str())
+                    .map_err(Error::from)
+            }
+        };
+
+        #[cfg(target_os = "macos")]
+        let maybe_data: Result<Option<Vec<u8>>, Error> = Ok(None);
+
+        match maybe_data {
+            Ok(Some(data)) if utils::image::validate(mime, &data).is_ok() => {
+                Some(ClipboardContent::Image { mime: mime.clone(), bytes: Bytes::from(data) })
+            }
+            Ok(_) => None,
+            Err(err) => {
+                tracing::debug!("Could not load image in format `{mime}`, error: {err}");
+                None
+            }
+        }
+    }
+
+    fn store_image(&self, mime: &mime::Mime, bytes: &[u8]) -> Result<(), Error> {
+        #[cfg(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "android",
+                target_os = "emscripten"
+            ))
+        ))]
+        if let RepresentationSource::Wayland(kind) = &self.representation_source {
+            // the original format is offered along with PNG which is understood by most
+            // applications
+            let mut formats = vec![(mime.clone(), bytes.to_vec())];
+            if *mime != mime::IMAGE_PNG && !utils::image::is_vector(mime) {
+                let (width, height, pixels) =
+                    utils::image::decode_rgba(mime, bytes).context(error::ConvertImageSnafu)?;
+                let png = utils::image::encode_rgba_as_png(width, height, &pixels)
+                    .context(error::ConvertImageSnafu)?;
+                formats.push((mime::IMAGE_PNG, png));
+            }
+            return crate::listener::wayland::store_image(*kind, formats).map_err(Error::from);
+        }
+
+        if utils::image::is_vector(mime) {
+            // `arboard` is not able to offer vector images, offer the markup instead
+            let text = String::from_utf8_lossy(bytes).into_owned();
+            return self.set_contents(ArboardContent::Text { text, html: None });
+        }
+
+        let (width, height, bytes) =
+            utils::image::decode_rgba(mime, bytes).context(error::ConvertImageSnafu)?;
+        self.set_contents(ArboardContent::Image { width, height, bytes })
+    }
+
+    // sets the clipboard in a background thread
+    fn set_contents(&self, content: ArboardContent) -> Result<(), Error> {
         let mut arboard = arboard::Clipboard::new()?;
         #[cfg(all(
             unix,
@@ -257,28 +398,85 @@ impl ClipboardStore for Clipboard {
                     ))
                 ))]
                 let _result = match content {
-                    ClipboardContent::Plaintext(text) => {
+                    ArboardContent::Text { text, html: Some(html) } => {
+                        arboard.set().clipboard(clipboard_kind).wait().html(html, Some(text))
+                    }
+                    ArboardContent::Text { text, html: None } => {
                         arboard.set().clipboard(clipboard_kind).wait().text(text)
                     }
-                    ClipboardContent::Image { width, height, bytes } => arboard
+                    ArboardContent::Image { width, height, bytes } => arboard
                         .set()
                         .clipboard(clipboard_kind)
                         .wait()
-                        .image(arboard::ImageData { width, height, bytes: bytes.to_vec().into() }),
+                        .image(arboard::ImageData { width, height, bytes: bytes.into() }),
                 };
 
                 #[cfg(target_os = "macos")]
                 let _result = match content {
-                    ClipboardContent::Plaintext(text) => arboard.set().text(text),
-                    ClipboardContent::Image { width, height, bytes } => arboard
+                    ArboardContent::Text { text, html: Some(html) } => {
+                        arboard.set().html(html, Some(text))
+                    }
+                    ArboardContent::Text { text, html: None } => arboard.set().text(text),
+                    ArboardContent::Image { width, height, bytes } => arboard
                         .set()
-                        .image(arboard::ImageData { width, height, bytes: bytes.to_vec().into() }),
+                        .image(arboard::ImageData { width, height, bytes: bytes.into() }),
                 };
 
                 clear_on_drop.store(false, Ordering::Relaxed);
             });
         Ok(())
     }
+}
+
+impl ClipboardStore for Clipboard {
+    #[inline]
+    fn store(&self, content: ClipboardContent) -> Result<(), Error> {
+        match content {
+            ClipboardContent::Plaintext(text) => {
+                self.set_contents(ArboardContent::Text { text, html: None })
+            }
+            ClipboardContent::Image { mime, bytes } => self.store_image(&mime, &bytes),
+        }
+    }
+
+    fn store_with_representations(
+        &self,
+        content: ClipboardContent,
+        representations: Vec<ClipRepresentation>,
+    ) -> Result<(), Error> {
+        let ClipboardContent::Plaintext(text) = content else {
+            return self.store(content);
+        };
+        if representations.is_empty() {
+            return self.store(ClipboardContent::Plaintext(text));
+        }
+
+        #[cfg(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "android",
+                target_os = "emscripten"
+            ))
+        ))]
+        if let RepresentationSource::Wayland(kind) = &self.representation_source {
+            // all formats are offered on Wayland
+            return crate::listener::wayland::store_with_representations(
+                *kind,
+                text,
+                representations,
+            )
+            .map_err(Error::from);
+        }
+
+        // only `text/html` is supported by `arboard`
+        let html = representations
+            .into_iter()
+            .find(|representation| representation.mime.essence_str() == "text/html")
+            .map(|representation| String::from_utf8_lossy(&representation.data).to_string());
+        self.set_contents(ArboardContent::Text { text, html })
+    }
 
     #[inline]
     fn clear(&self) -> Result<(), Error> {
@@ -37,6 +37,9 @@ pub enum Error {
     #[snafu(display("Clipboard is empty"))]
     Empty,
 
+    #[snafu(display("Could not convert image, error: {source}"))]
+    ConvertImage { source: clipcat_base::ClipEntryError },
+
     #[snafu(display("Primitive was poisoned"))]
     PrimitivePoisoned,
 
@@ -21,7 +21,7 @@ pub use self::{
     default::Clipboard,
     error::Error,
     local::Clipboard as LocalClipboard,
-    pubsub::Subscriber,
+    pubsub::{Change as ClipboardChange, Subscriber},
     traits::{
         EventObserver, Load as ClipboardLoad, LoadExt as ClipboardLoadExt,
         LoadWait as ClipboardLoadWait, Store as ClipboardStore, StoreExt as ClipboardStoreExt,
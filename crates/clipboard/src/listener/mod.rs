@@ -34,5 +34,8 @@ pub use self::macos::Listener as MacOsListener;
 ))]
 pub use self::{
     wayland::{Error as WaylandListenerError, Listener as WaylandListener},
-    x11::{Error as X11ListenerError, Listener as X11Listener},
+    x11::{
+        Error as X11ListenerError, Listener as X11Listener,
+        RepresentationReader as X11RepresentationReader,
+    },
 };
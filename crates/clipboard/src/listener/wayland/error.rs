@@ -6,4 +6,13 @@ use snafu::Snafu;
 pub enum Error {
     #[snafu(display("Clipboard kind `{kind}` is not supported"))]
     ClipboardKindNotSupported { kind: ClipboardKind },
+
+    #[snafu(display("Could not paste contents from Wayland clipboard, error: {source}"))]
+    PasteContents { source: wl_clipboard_rs::paste::Error },
+
+    #[snafu(display("Could not read contents from Wayland clipboard, error: {source}"))]
+    ReadContents { source: std::io::Error },
+
+    #[snafu(display("Could not copy contents into Wayland clipboard, error: {source}"))]
+    CopyContents { source: wl_clipboard_rs::copy::Error },
 }
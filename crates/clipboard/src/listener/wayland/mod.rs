@@ -1,6 +1,7 @@
 mod error;
 
 use std::{
+    io::Read,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -9,8 +10,11 @@ use std::{
     time::Duration,
 };
 
+use clipcat_base::ClipRepresentation;
+use snafu::ResultExt;
 use wl_clipboard_rs::paste::{
-    get_mime_types as wl_clipboard_get_mime_types, Error as WaylandError, Seat,
+    get_contents as wl_clipboard_get_contents, get_mime_types as wl_clipboard_get_mime_types,
+    Error as WaylandError, MimeType, Seat,
 };
 
 pub use self::error::Error;
@@ -33,10 +37,7 @@ impl Listener {
         let (notifier, subscriber) = pubsub::new(clipboard_kind);
         let is_running = Arc::new(AtomicBool::new(true));
 
-        let clipboard_type = match clipboard_kind {
-            ClipboardKind::Clipboard => wl_clipboard_rs::paste::ClipboardType::Regular,
-            _ => wl_clipboard_rs::paste::ClipboardType::Primary,
-        };
+        let clipboard_type = clipboard_type(clipboard_kind);
 
         if clipboard_type == wl_clipboard_rs::paste::ClipboardType::Primary {
             if let Ok(supported) = wl_clipboard_rs::utils::is_primary_selection_supported() {
@@ -53,6 +54,120 @@ impl Listener {
     }
 }
 
+/// Loads alternative formats of current content from Wayland clipboard.
+pub fn load_representations(
+    clipboard_kind: ClipboardKind,
+) -> Result<Vec<ClipRepresentation>, Error> {
+    let clipboard_type = clipboard_type(clipboard_kind);
+    let mime_types = match wl_clipboard_get_mime_types(clipboard_type, Seat::Unspecified) {
+        Ok(mime_types) => mime_types,
+        Err(WaylandError::NoSeats | WaylandError::ClipboardEmpty | WaylandError::NoMimeType) => {
+            return Ok(Vec::new());
+        }
+        Err(source) => return Err(Error::PasteContents { source }),
+    };
+
+    let mut representations = Vec::new();
+    for mime_type in mime_types {
+        let Ok(mime) = mime_type.parse::<mime::Mime>() else { continue };
+        if !ClipRepresentation::is_captured(&mime) {
+            continue;
+        }
+
+        let (mut reader, _) = wl_clipboard_get_contents(
+            clipboard_type,
+            Seat::Unspecified,
+            MimeType::Specific(&mime_type),
+        )
+        .context(error::PasteContentsSnafu)?;
+        let mut data = Vec::new();
+        let _ = reader.read_to_end(&mut data).context(error::ReadContentsSnafu)?;
+        representations.push(ClipRepresentation::new(mime, data));
+    }
+    Ok(representations)
+}
+
+/// Loads current content of Wayland clipboard in format `mime`, `None` is
+/// returned if the content is not available in the format.
+pub fn load_contents(clipboard_kind: ClipboardKind, mime: &str) -> Result<Option<Vec<u8>>, Error> {
+    let clipboard_type = clipboard_type(clipboard_kind);
+    let mut reader = match wl_clipboard_get_contents(
+        clipboard_type,
+        Seat::Unspecified,
+        MimeType::Specific(mime),
+    ) {
+        Ok((reader, _)) => reader,
+        Err(WaylandError::NoSeats | WaylandError::ClipboardEmpty | WaylandError::NoMimeType) => {
+            return Ok(None);
+        }
+        Err(source) => return Err(Error::PasteContents { source }),
+    };
+    let mut data = Vec::new();
+    let _ = reader.read_to_end(&mut data).context(error::ReadContentsSnafu)?;
+    Ok(Some(data))
+}
+
+/// Offers `text` and its alternative formats on Wayland clipboard, requests are
+/// served in a background thread until another client takes over the clipboard.
+pub fn store_with_representations(
+    clipboard_kind: ClipboardKind,
+    text: String,
+    representations: Vec<ClipRepresentation>,
+) -> Result<(), Error> {
+    use wl_clipboard_rs::copy::{MimeSource, MimeType, Source};
+
+    let sources = std::iter::once(MimeSource {
+        source: Source::Bytes(text.into_bytes().into_boxed_slice()),
+        mime_type: MimeType::Text,
+    })
+    .chain(representations.into_iter().map(|ClipRepresentation { mime, data }| MimeSource {
+        source: Source::Bytes(data.to_vec().into_boxed_slice()),
+        mime_type: MimeType::Specific(mime.essence_str().to_string()),
+    }))
+    .collect();
+    copy_sources(clipboard_kind, sources)
+}
+
+/// Offers an image in each of `formats` on Wayland clipboard, a format is a
+/// pair of MIME and encoded image.
+pub fn store_image(
+    clipboard_kind: ClipboardKind,
+    formats: Vec<(mime::Mime, Vec<u8>)>,
+) -> Result<(), Error> {
+    use wl_clipboard_rs::copy::{MimeSource, MimeType, Source};
+
+    let sources = formats
+        .into_iter()
+        .map(|(mime, data)| MimeSource {
+            source: Source::Bytes(data.into_boxed_slice()),
+            mime_type: MimeType::Specific(mime.essence_str().to_string()),
+        })
+        .collect();
+    copy_sources(clipboard_kind, sources)
+}
+
+fn copy_sources(
+    clipboard_kind: ClipboardKind,
+    sources: Vec<wl_clipboard_rs::copy::MimeSource>,
+) -> Result<(), Error> {
+    use wl_clipboard_rs::copy::{ClipboardType, Options};
+
+    let mut options = Options::new();
+    let _ = options.clipboard(match clipboard_kind {
+        ClipboardKind::Clipboard => ClipboardType::Regular,
+        _ => ClipboardType::Primary,
+    });
+    options.copy_multi(sources).context(error::CopyContentsSnafu)
+}
+
+#[inline]
+const fn clipboard_type(clipboard_kind: ClipboardKind) -> wl_clipboard_rs::paste::ClipboardType {
+    match clipboard_kind {
+        ClipboardKind::Clipboard => wl_clipboard_rs::paste::ClipboardType::Regular,
+        _ => wl_clipboard_rs::paste::ClipboardType::Primary,
+    }
+}
+
 impl ClipboardSubscribe for Listener {
     type Subscriber = Subscriber;
 
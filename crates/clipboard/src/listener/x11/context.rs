@@ -1,7 +1,6 @@
 use std::{
     mem::size_of,
     os::{fd::AsRawFd, unix::prelude::RawFd},
-    thread,
     time::{Duration, Instant},
 };
 
@@ -23,6 +22,7 @@ use crate::{
 };
 
 const LONG_TIMEOUT_DUR: Duration = Duration::from_millis(1000);
+const EVENT_TOKEN: mio::Token = mio::Token(0);
 
 #[derive(Debug)]
 pub struct Context {
@@ -42,6 +42,17 @@ impl Context {
         Ok(ctx)
     }
 
+    /// Creates a context which only reads the selection, it neither listens to
+    /// selection events nor claims the clipboard manager.
+    pub fn new_reader(
+        display_name: Option<String>,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<Self, Error> {
+        let (connection, window) = new_connection(display_name.as_deref())?;
+        let atom_cache = AtomCache::new(&connection)?;
+        Ok(Self { display_name, connection, window, atom_cache, clipboard_kind })
+    }
+
     pub fn reconnect(&mut self) -> Result<(), Error> {
         let (connection, window) = new_connection(self.display_name.as_deref())?;
         self.atom_cache = AtomCache::new(&connection)?;
@@ -130,14 +141,9 @@ impl Context {
         );
         self.connection.sync().context(error::SynchroniseWithX11Snafu)?;
 
-        let timeout_end = Instant::now() + LONG_TIMEOUT_DUR;
-        while Instant::now() < timeout_end {
-            let maybe_event = self.connection.poll_for_event().context(error::PollForEventSnafu)?;
-            let Some(event) = maybe_event else {
-                thread::sleep(Duration::from_millis(1));
-                continue;
-            };
-
+        let mut waiter = EventWaiter::new(self)?;
+        let deadline = Instant::now() + LONG_TIMEOUT_DUR;
+        while let Some(event) = self.wait_for_event(&mut waiter, deadline)? {
             match event {
                 // The first response after requesting a selection.
                 x11rb::protocol::Event::SelectionNotify(event) => {
@@ -176,6 +182,193 @@ impl Context {
         Ok(Vec::new())
     }
 
+    /// Returns the content of the selection in format `target`, `None` is
+    /// returned if the selection owner refuses to convert or does not respond
+    /// in time. Large contents are transferred incrementally with `INCR`.
+    pub fn get_contents(&self, target: &str) -> Result<Option<Vec<u8>>, Error> {
+        let target_atom = get_intern_atom(&self.connection, target.as_bytes())?;
+        drop(
+            self.connection
+                .delete_property(self.window, self.atom_cache.clipcat_clipboard)
+                .context(error::DeletePropertySnafu)?,
+        );
+        drop(
+            self.connection
+                .convert_selection(
+                    self.window,
+                    self.clipboard_kind_atom(),
+                    target_atom,
+                    self.atom_cache.clipcat_clipboard,
+                    xproto::Time::CURRENT_TIME,
+                )
+                .context(error::ConvertSelectionSnafu)?,
+        );
+        self.connection.sync().context(error::SynchroniseWithX11Snafu)?;
+
+        let mut waiter = EventWaiter::new(self)?;
+        let deadline = Instant::now() + LONG_TIMEOUT_DUR;
+        while let Some(event) = self.wait_for_event(&mut waiter, deadline)? {
+            if let x11rb::protocol::Event::SelectionNotify(event) = event {
+                if event.property == u32::from(xproto::AtomEnum::NONE) {
+                    return Ok(None);
+                }
+
+                // deleting the property also asks the owner to start an
+                // incremental transfer
+                let reply = self.take_property(event.property)?;
+                if reply.type_ == self.atom_cache.incr {
+                    let size_hint = reply.value32().and_then(|mut values| values.next());
+                    return self.read_incremental(&mut waiter, event.property, size_hint, target);
+                }
+                return Ok(Some(reply.value));
+            }
+        }
+        Ok(None)
+    }
+
+    // Reads the chunks of an `INCR` transfer, each chunk is announced by a new
+    // value of `property` and an empty chunk ends the transfer.
+    fn read_incremental(
+        &self,
+        waiter: &mut EventWaiter,
+        property: xproto::Atom,
+        size_hint: Option<u32>,
+        target: &str,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let mut data =
+            Vec::with_capacity(size_hint.and_then(|size| usize::try_from(size).ok()).unwrap_or(0));
+        let mut deadline = Instant::now() + LONG_TIMEOUT_DUR;
+        while let Some(event) = self.wait_for_event(waiter, deadline)? {
+            let x11rb::protocol::Event::PropertyNotify(event) = event else { continue };
+            if event.atom != property || event.state != xproto::Property::NEW_VALUE {
+                continue;
+            }
+
+            let chunk = self.take_property(property)?.value;
+            if chunk.is_empty() {
+                return Ok(Some(data));
+            }
+            data.extend_from_slice(&chunk);
+            deadline = Instant::now() + LONG_TIMEOUT_DUR;
+        }
+
+        tracing::warn!(
+            "Incremental transfer of `{target}` timed out after {len} bytes, ignore it",
+            len = data.len()
+        );
+        Ok(None)
+    }
+
+    // Reads and deletes `property` of the window.
+    fn take_property(&self, property: xproto::Atom) -> Result<xproto::GetPropertyReply, Error> {
+        self.connection
+            .get_property(true, self.window, property, xproto::AtomEnum::ANY, 0, u32::MAX / 4)
+            .context(error::GetPropertySnafu)?
+            .reply()
+            .context(error::GetPropertyReplySnafu)
+    }
+
+    // Returns the next event, waits until `deadline` if there is none queued.
+    // `None` is returned once `deadline` has passed.
+    fn wait_for_event(
+        &self,
+        waiter: &mut EventWaiter,
+        deadline: Instant,
+    ) -> Result<Option<x11rb::protocol::Event>, Error> {
+        loop {
+            // events may have been read from the socket along with replies,
+            // check the queue before waiting for the socket
+            if let Some(event) =
+                self.connection.poll_for_event().context(error::PollForEventSnafu)?
+            {
+                return Ok(Some(event));
+            }
+
+            let Some(timeout) = deadline
+                .checked_duration_since(Instant::now())
+                .filter(|timeout| !timeout.is_zero())
+            else {
+                return Ok(None);
+            };
+            if let Err(err) = waiter.poll.poll(&mut waiter.events, Some(timeout)) {
+                if err.kind() != std::io::ErrorKind::Interrupted {
+                    return Err(err).context(error::WaitForEventSnafu);
+                }
+            }
+        }
+    }
+
+    /// Resolves the application which owns the selection through the
+    /// `WM_CLASS` or `_NET_WM_PID` property of `owner` and its ancestors.
+    ///
+    /// The owner window is usually an unmapped helper window of the
+    /// application, so the search walks up the window tree for a few levels.
+    pub fn owner_application(&self, owner: xproto::Window) -> Option<String> {
+        const MAX_DEPTH: usize = 4;
+
+        let mut window = owner;
+        for _ in 0..MAX_DEPTH {
+            if window == x11rb::NONE {
+                break;
+            }
+            if let Some(name) = self.window_class(window).or_else(|| self.window_process(window)) {
+                return Some(name);
+            }
+
+            let tree = self.connection.query_tree(window).ok()?.reply().ok()?;
+            if tree.parent == tree.root {
+                break;
+            }
+            window = tree.parent;
+        }
+        None
+    }
+
+    fn window_class(&self, window: xproto::Window) -> Option<String> {
+        let reply = self
+            .connection
+            .get_property(
+                false,
+                window,
+                xproto::AtomEnum::WM_CLASS,
+                xproto::AtomEnum::STRING,
+                0,
+                1024,
+            )
+            .ok()?
+            .reply()
+            .ok()?;
+
+        // `WM_CLASS` consists of the instance name and the class name, both are
+        // terminated by NUL
+        let mut names = reply.value.split(|&c| c == 0).filter(|name| !name.is_empty());
+        let instance = names.next();
+        names.next().or(instance).map(|name| String::from_utf8_lossy(name).to_string())
+    }
+
+    fn window_process(&self, window: xproto::Window) -> Option<String> {
+        let pid = self
+            .connection
+            .get_property(
+                false,
+                window,
+                self.atom_cache.net_wm_pid,
+                xproto::AtomEnum::CARDINAL,
+                0,
+                1,
+            )
+            .ok()?
+            .reply()
+            .ok()?
+            .value32()?
+            .next()?;
+
+        std::fs::read_to_string(format!("/proc/{pid}/comm"))
+            .ok()
+            .map(|comm| comm.trim().to_string())
+            .filter(|comm| !comm.is_empty())
+    }
+
     pub fn display_name(&self) -> String {
         let display_name = self.display_name.as_deref().unwrap_or(":0");
         format!("display: {display_name}")
@@ -186,6 +379,28 @@ impl AsRawFd for Context {
     fn as_raw_fd(&self) -> RawFd { self.connection.stream().as_raw_fd() }
 }
 
+// Waits for the connection to become readable, so that events are awaited
+// instead of being polled repeatedly.
+#[derive(Debug)]
+struct EventWaiter {
+    poll: mio::Poll,
+    events: mio::Events,
+}
+
+impl EventWaiter {
+    fn new(context: &Context) -> Result<Self, Error> {
+        let poll = mio::Poll::new().context(error::InitializeMioPollSnafu)?;
+        poll.registry()
+            .register(
+                &mut mio::unix::SourceFd(&context.as_raw_fd()),
+                EVENT_TOKEN,
+                mio::Interest::READABLE,
+            )
+            .context(error::RegisterIoResourceSnafu)?;
+        Ok(Self { poll, events: mio::Events::with_capacity(1) })
+    }
+}
+
 #[derive(Debug)]
 struct AtomCache {
     clipcat_clipboard: xproto::Atom,
@@ -194,6 +409,8 @@ struct AtomCache {
     primary_selection: xproto::Atom,
     secondary_selection: xproto::Atom,
     targets: xproto::Atom,
+    incr: xproto::Atom,
+    net_wm_pid: xproto::Atom,
 }
 
 impl AtomCache {
@@ -205,6 +422,8 @@ impl AtomCache {
             primary_selection: xproto::AtomEnum::PRIMARY.into(),
             secondary_selection: xproto::AtomEnum::SECONDARY.into(),
             targets: get_intern_atom(conn, b"TARGETS")?,
+            incr: get_intern_atom(conn, b"INCR")?,
+            net_wm_pid: get_intern_atom(conn, b"_NET_WM_PID")?,
         })
     }
 }
@@ -28,6 +28,11 @@ pub enum Error {
         source: std::io::Error,
     },
 
+    #[snafu(display("Error occurred while waiting for event, error: {source}"))]
+    WaitForEvent {
+        source: std::io::Error,
+    },
+
     #[snafu(display("Xfixes is not present"))]
     XfixesNotPresent,
 
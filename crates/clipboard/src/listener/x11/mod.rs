@@ -11,7 +11,8 @@ use std::{
     time::Duration,
 };
 
-use clipcat_base::{utils::RetryInterval, ClipFilter};
+use clipcat_base::{utils::RetryInterval, ClipRepresentation, SharedClipFilter};
+use parking_lot::Mutex;
 use snafu::ResultExt;
 use x11rb::protocol::Event as X11Event;
 
@@ -38,7 +39,7 @@ impl Listener {
     pub fn new(
         display_name: Option<String>,
         clipboard_kind: ClipboardKind,
-        clip_filter: Arc<ClipFilter>,
+        clip_filter: SharedClipFilter,
         event_observers: Vec<Arc<dyn EventObserver>>,
     ) -> Result<Self, crate::Error> {
         let (notifier, subscriber) = pubsub::new(clipboard_kind);
@@ -59,6 +60,65 @@ impl Listener {
     }
 }
 
+/// Reads the selection in specific formats with a dedicated connection, so that
+/// it does not interfere with the listener.
+#[derive(Debug)]
+pub struct RepresentationReader {
+    display_name: Option<String>,
+    clipboard_kind: ClipboardKind,
+    context: Mutex<Option<Context>>,
+}
+
+impl RepresentationReader {
+    pub const fn new(display_name: Option<String>, clipboard_kind: ClipboardKind) -> Self {
+        Self { display_name, clipboard_kind, context: Mutex::new(None) }
+    }
+
+    pub fn load(&self) -> Result<Vec<ClipRepresentation>, Error> {
+        self.with_context(read_representations)
+    }
+
+    /// Reads the selection in format `target`, `None` is returned if the
+    /// selection is not available in the format.
+    pub fn load_target(&self, target: &str) -> Result<Option<Vec<u8>>, Error> {
+        self.with_context(|context| context.get_contents(target))
+    }
+
+    fn with_context<F, T>(&self, f: F) -> Result<T, Error>
+    where
+        F: Fn(&Context) -> Result<T, Error>,
+    {
+        let mut context = self.context.lock();
+        if let Some(context) = context.as_ref() {
+            match f(context) {
+                Ok(value) => return Ok(value),
+                Err(err) => tracing::debug!("{err}, try to re-connect"),
+            }
+        }
+
+        // connect lazily and re-connect once if the connection was broken
+        let new_context = Context::new_reader(self.display_name.clone(), self.clipboard_kind)?;
+        let result = f(&new_context);
+        *context = Some(new_context);
+        drop(context);
+        result
+    }
+}
+
+fn read_representations(context: &Context) -> Result<Vec<ClipRepresentation>, Error> {
+    let mut representations = Vec::new();
+    for format in context.get_available_formats()? {
+        let Ok(mime) = format.parse::<mime::Mime>() else { continue };
+        if !ClipRepresentation::is_captured(&mime) {
+            continue;
+        }
+        if let Some(data) = context.get_contents(&format)? {
+            representations.push(ClipRepresentation::new(mime, data));
+        }
+    }
+    Ok(representations)
+}
+
 impl ClipboardSubscribe for Listener {
     type Subscriber = Subscriber;
 
@@ -82,7 +142,7 @@ fn build_thread(
     is_running: Arc<AtomicBool>,
     mut context: Context,
     notifier: pubsub::Publisher,
-    clip_filter: Arc<ClipFilter>,
+    clip_filter: SharedClipFilter,
     event_observers: Vec<Arc<dyn EventObserver>>,
 ) -> thread::JoinHandle<Result<(), Error>> {
     let retry_interval = RetryInterval::new(MAX_RETRY_COUNT, Duration::from_secs(3))
@@ -119,17 +179,20 @@ fn build_thread(
                 for event in &events {
                     if event.token() == CONTEXT_TOKEN {
                         match context.poll_for_event() {
-                            Ok(X11Event::XfixesSelectionNotify(_event)) => {
+                            Ok(X11Event::XfixesSelectionNotify(event)) => {
                                 match context.get_available_formats() {
                                     Ok(mut formats) => {
                                         // filter sensitive content
-                                        if clip_filter.filter_sensitive_atoms(formats.iter()) {
+                                        if clip_filter.load().filter_sensitive_atoms(formats.iter())
+                                        {
                                             tracing::info!("Sensitive content detected, ignore it");
                                             continue;
                                         }
 
                                         if let Some(mime) = extract_mime(&mut formats) {
-                                            notifier.notify_all(mime);
+                                            let source_application =
+                                                context.owner_application(event.owner);
+                                            notifier.notify_all_from(mime, source_application);
                                         }
                                     }
                                     Err(err) => {
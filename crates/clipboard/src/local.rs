@@ -1,6 +1,6 @@
 use std::sync::{Arc, RwLock};
 
-use clipcat_base::ClipboardContent;
+use clipcat_base::{ClipRepresentation, ClipboardContent};
 
 use crate::{
     pubsub::{self, Publisher, Subscriber},
@@ -10,6 +10,7 @@ use crate::{
 #[derive(Clone, Debug)]
 pub struct Clipboard {
     data: Arc<RwLock<Option<ClipboardContent>>>,
+    representations: Arc<RwLock<Vec<ClipRepresentation>>>,
     publisher: Arc<Publisher>,
     subscriber: Subscriber,
 }
@@ -18,7 +19,8 @@ impl Default for Clipboard {
     fn default() -> Self {
         let (publisher, subscriber) = pubsub::new(ClipboardKind::Clipboard);
         let data = Arc::default();
-        Self { publisher: Arc::new(publisher), subscriber, data }
+        let representations = Arc::default();
+        Self { publisher: Arc::new(publisher), subscriber, data, representations }
     }
 }
 
@@ -32,7 +34,7 @@ impl Clipboard {
     pub fn with_content(content: ClipboardContent) -> Self {
         let data = Arc::new(RwLock::new(Some(content)));
         let (publisher, subscriber) = pubsub::new(ClipboardKind::Clipboard);
-        Self { data, publisher: Arc::new(publisher), subscriber }
+        Self { data, representations: Arc::default(), publisher: Arc::new(publisher), subscriber }
     }
 }
 
@@ -59,11 +61,31 @@ impl ClipboardLoad for Clipboard {
             Err(err) => Err(err),
         }
     }
+
+    fn load_representations(&self) -> Result<Vec<ClipRepresentation>, Error> {
+        self.representations
+            .read()
+            .map(|representations| representations.clone())
+            .map_err(|_err| Error::PrimitivePoisoned)
+    }
 }
 
 impl ClipboardStore for Clipboard {
     #[inline]
     fn store(&self, content: ClipboardContent) -> Result<(), Error> {
+        self.store_with_representations(content, Vec::new())
+    }
+
+    fn store_with_representations(
+        &self,
+        content: ClipboardContent,
+        representations: Vec<ClipRepresentation>,
+    ) -> Result<(), Error> {
+        match self.representations.write() {
+            Ok(mut data) => *data = representations,
+            Err(_err) => return Err(Error::PrimitivePoisoned),
+        }
+
         let mime = content.mime();
         match self.data.write() {
             Ok(mut data) => {
@@ -79,6 +101,7 @@ impl ClipboardStore for Clipboard {
         match self.data.write() {
             Ok(mut data) => {
                 *data = None;
+                drop(self.representations.write().map(|mut data| data.clear()));
                 Ok(())
             }
             Err(_err) => Err(Error::PrimitivePoisoned),
@@ -4,7 +4,7 @@ use parking_lot::{Condvar, Mutex};
 
 use crate::{ClipboardKind, ClipboardWait, Error};
 
-type StateData = Mutex<(State, Option<mime::Mime>)>;
+type StateData = Mutex<(State, Option<(mime::Mime, Option<String>)>)>;
 
 pub fn new(kind: ClipboardKind) -> (Publisher, Subscriber) {
     let inner = Arc::new((Mutex::new((State::Running, None)), Condvar::new()));
@@ -19,13 +19,27 @@ enum State {
     Stopped,
 }
 
+/// A change of clipboard content.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Change {
+    pub kind: ClipboardKind,
+
+    pub mime: mime::Mime,
+
+    /// The application which owns the new content, if it is known.
+    pub source_application: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct Publisher(Arc<(StateData, Condvar)>);
 
 impl Publisher {
-    pub fn notify_all(&self, mime: mime::Mime) {
+    #[inline]
+    pub fn notify_all(&self, mime: mime::Mime) { self.notify_all_from(mime, None); }
+
+    pub fn notify_all_from(&self, mime: mime::Mime, source_application: Option<String>) {
         let (lock, condvar) = &*self.0;
-        *lock.lock() = (State::Running, Some(mime));
+        *lock.lock() = (State::Running, Some((mime, source_application)));
         let _unused = condvar.notify_all();
     }
 }
@@ -47,13 +61,17 @@ pub struct Subscriber {
 // FIXME:
 #[allow(clippy::significant_drop_in_scrutinee)]
 impl ClipboardWait for Subscriber {
-    fn wait(&self) -> Result<(ClipboardKind, mime::Mime), Error> {
+    fn wait(&self) -> Result<Change, Error> {
         let (lock, condvar) = &*self.inner;
         let result = {
             let mut state = lock.lock();
             condvar.wait(&mut state);
             match *state {
-                (State::Running, Some(ref mime)) => Ok((self.kind, mime.clone())),
+                (State::Running, Some((ref mime, ref source_application))) => Ok(Change {
+                    kind: self.kind,
+                    mime: mime.clone(),
+                    source_application: source_application.clone(),
+                }),
                 (State::Running | State::Stopped, _) => Err(Error::NotifierClosed),
             }
         };
@@ -1,11 +1,16 @@
-use clipcat_base::ClipboardContent;
+use clipcat_base::{ClipRepresentation, ClipboardContent};
 
-use crate::{ClipboardKind, Error, ListenerKind};
+use crate::{ClipboardChange, ClipboardKind, Error, ListenerKind};
 
 pub trait Load {
     /// # Errors
     fn load(&self, mime: Option<mime::Mime>) -> Result<ClipboardContent, Error>;
 
+    /// Loads alternative formats of current content, such as `text/html`.
+    ///
+    /// # Errors
+    fn load_representations(&self) -> Result<Vec<ClipRepresentation>, Error> { Ok(Vec::new()) }
+
     fn is_empty(&self) -> bool { matches!(self.load(None), Err(Error::Empty)) }
 }
 
@@ -13,13 +18,26 @@ pub trait Store {
     /// # Errors
     fn store(&self, content: ClipboardContent) -> Result<(), Error>;
 
+    /// Stores `content` along with its alternative formats, alternative formats
+    /// are dropped if they are not supported.
+    ///
+    /// # Errors
+    fn store_with_representations(
+        &self,
+        content: ClipboardContent,
+        representations: Vec<ClipRepresentation>,
+    ) -> Result<(), Error> {
+        drop(representations);
+        self.store(content)
+    }
+
     /// # Errors
     fn clear(&self) -> Result<(), Error>;
 }
 
 pub trait Wait {
     /// # Errors
-    fn wait(&self) -> Result<(ClipboardKind, mime::Mime), Error>;
+    fn wait(&self) -> Result<ClipboardChange, Error>;
 }
 
 pub trait Subscribe: Send + Sync {
@@ -54,7 +72,7 @@ impl<C: Store + ?Sized> StoreExt for C {}
 pub trait LoadWait: Load + Subscribe {
     /// # Errors
     fn load_wait(&self) -> Result<ClipboardContent, Error> {
-        let (_, mime) = self.subscribe()?.wait()?;
+        let ClipboardChange { mime, .. } = self.subscribe()?.wait()?;
         self.load(Some(mime))
     }
 }
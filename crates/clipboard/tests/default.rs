@@ -1,7 +1,6 @@
 mod common;
 
-use std::sync::Arc;
-
+use clipcat_base::SharedClipFilter;
 #[cfg(all(
     unix,
     not(any(
@@ -30,7 +29,7 @@ impl ClipboardTester for Tester {
     type Clipboard = Clipboard;
 
     fn new_clipboard(&self) -> Result<Self::Clipboard, Error> {
-        let clipboard = Clipboard::new(self.kind, Arc::default(), Vec::new())?;
+        let clipboard = Clipboard::new(self.kind, SharedClipFilter::default(), Vec::new())?;
         Ok(clipboard)
     }
 }
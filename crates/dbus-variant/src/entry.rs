@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{path::PathBuf, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
@@ -17,6 +17,20 @@ pub struct Entry {
     mime: String,
 
     timestamp: i64,
+
+    pinned: bool,
+
+    source_application: String,
+
+    sensitive: bool,
+
+    tags: Vec<String>,
+
+    template: bool,
+
+    snippet_name: String,
+
+    snippet_path: String,
 }
 
 impl From<clipcat_base::ClipEntry> for Entry {
@@ -26,17 +40,60 @@ impl From<clipcat_base::ClipEntry> for Entry {
         let id = entry.id();
         let kind = entry.kind();
         let timestamp = entry.timestamp().unix_timestamp();
+        let pinned = entry.is_pinned();
+        let source_application = entry.source_application().unwrap_or_default().to_owned();
+        let sensitive = entry.is_sensitive();
+        let tags = entry.tags().to_vec();
+        let template = entry.is_template();
+        let snippet_name = entry.snippet_name().unwrap_or_default().to_owned();
+        let snippet_path =
+            entry.snippet_path().map(|path| path.display().to_string()).unwrap_or_default();
 
-        Self { id, data, clipboard_kind: kind.into(), mime, timestamp }
+        Self {
+            id,
+            data,
+            clipboard_kind: kind.into(),
+            mime,
+            timestamp,
+            pinned,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        }
     }
 }
 
 impl From<Entry> for clipcat_base::ClipEntry {
-    fn from(Entry { id: _, data, clipboard_kind, mime, timestamp }: Entry) -> Self {
+    fn from(
+        Entry {
+            id: _,
+            data,
+            clipboard_kind,
+            mime,
+            timestamp,
+            pinned,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        }: Entry,
+    ) -> Self {
         let timestamp = OffsetDateTime::from_unix_timestamp(timestamp).ok();
         let kind = clipcat_base::ClipboardKind::from(clipboard_kind);
         let mime = mime::Mime::from_str(&mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
-        Self::new(&data, &mime, kind, timestamp).unwrap_or_default()
+        let mut entry = Self::new(&data, &mime, kind, timestamp).unwrap_or_default();
+        entry.set_pinned(pinned);
+        entry.set_source_application(Some(source_application));
+        entry.set_sensitive(sensitive);
+        entry.set_tags(tags);
+        entry.set_template(template);
+        entry.set_snippet(Some(snippet_name), Some(PathBuf::from(snippet_path)));
+        entry
     }
 }
 
@@ -47,24 +104,90 @@ pub struct EntryMetadata {
     kind: ClipboardKind,
     timestamp: i64,
     preview: String,
+    pinned: bool,
+    source_application: String,
+    sensitive: bool,
+    tags: Vec<String>,
+    template: bool,
+    snippet_name: String,
+    snippet_path: String,
 }
 
 impl From<clipcat_base::ClipEntryMetadata> for EntryMetadata {
     fn from(metadata: clipcat_base::ClipEntryMetadata) -> Self {
-        let clipcat_base::ClipEntryMetadata { id, kind: clipboard_kind, timestamp, mime, preview } =
-            metadata;
+        let clipcat_base::ClipEntryMetadata {
+            id,
+            kind: clipboard_kind,
+            timestamp,
+            mime,
+            preview,
+            pinned,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        } = metadata;
         let mime = mime.essence_str().to_owned();
         let timestamp = timestamp.unix_timestamp();
-        Self { id, preview, kind: clipboard_kind.into(), mime, timestamp }
+        let source_application = source_application.unwrap_or_default();
+        let snippet_name = snippet_name.unwrap_or_default();
+        let snippet_path = snippet_path.map(|path| path.display().to_string()).unwrap_or_default();
+        Self {
+            id,
+            preview,
+            kind: clipboard_kind.into(),
+            mime,
+            timestamp,
+            pinned,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        }
     }
 }
 
 impl From<EntryMetadata> for clipcat_base::ClipEntryMetadata {
-    fn from(EntryMetadata { id, mime, kind, timestamp, preview }: EntryMetadata) -> Self {
+    fn from(
+        EntryMetadata {
+            id,
+            mime,
+            kind,
+            timestamp,
+            preview,
+            pinned,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        }: EntryMetadata,
+    ) -> Self {
         let timestamp = OffsetDateTime::from_unix_timestamp(timestamp)
             .unwrap_or_else(|_| OffsetDateTime::now_utc());
         let clipboard_kind = clipcat_base::ClipboardKind::from(kind);
         let mime = mime::Mime::from_str(&mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
-        Self { id, kind: clipboard_kind, timestamp, mime, preview }
+        let source_application = Some(source_application).filter(|name| !name.is_empty());
+        let snippet_name = Some(snippet_name).filter(|name| !name.is_empty());
+        let snippet_path = Some(snippet_path).filter(|path| !path.is_empty()).map(PathBuf::from);
+        Self {
+            id,
+            kind: clipboard_kind,
+            timestamp,
+            mime,
+            preview,
+            pinned,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        }
     }
 }
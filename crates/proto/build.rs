@@ -9,7 +9,12 @@ fn prost_config() -> Config {
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure().compile_protos_with_config(
         prost_config(),
-        &["proto/manager.proto", "proto/system.proto", "proto/watcher.proto"],
+        &[
+            "proto/history.proto",
+            "proto/manager.proto",
+            "proto/system.proto",
+            "proto/watcher.proto",
+        ],
         &["proto/"],
     )?;
     Ok(())
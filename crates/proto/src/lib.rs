@@ -23,22 +23,33 @@ mod proto {
     tonic::include_proto!("clipcat");
 }
 
-use std::str::FromStr;
+use std::{path::PathBuf, str::FromStr};
 
 use time::OffsetDateTime;
 
 pub use self::proto::{
+    clipboard_event,
+    history_client::HistoryClient,
+    history_server::{History, HistoryServer},
     manager_client::ManagerClient,
     manager_server::{Manager, ManagerServer},
     system_client::SystemClient,
     system_server::{System, SystemServer},
     watcher_client::WatcherClient,
     watcher_server::{Watcher, WatcherServer},
-    BatchRemoveRequest, BatchRemoveResponse, ClipEntry, ClipEntryMetadata, ClipboardKind,
+    AddSnippetRequest, AddSnippetResponse, ArchivedClip, BatchRemoveRequest, BatchRemoveResponse,
+    ClipEntry, ClipEntryMetadata, ClipRepresentation, ClipUpdatedEvent, ClipboardEvent,
+    ClipboardKind, ExpandRequest, ExpandResponse, GetByIndexRequest, GetByIndexResponse,
     GetCurrentClipRequest, GetCurrentClipResponse, GetRequest, GetResponse,
-    GetSystemVersionResponse, InsertRequest, InsertResponse, LengthResponse, ListRequest,
-    ListResponse, MarkRequest, MarkResponse, RemoveRequest, RemoveResponse, UpdateRequest,
-    UpdateResponse, WatcherState, WatcherStateReply,
+    GetSystemVersionResponse, ImportMode, ImportRequest, ImportResponse, InsertRequest,
+    InsertResponse, LengthResponse, ListOrder, ListRequest, ListResponse, ListSnippetsRequest,
+    ListSnippetsResponse, ListTagsResponse, MarkByIndexRequest, MarkByIndexResponse, MarkRequest,
+    MarkResponse, MatchRange, PinRequest, PinResponse, RekeyRequest, RekeyResponse,
+    ReloadConfigResponse, RemoveRequest, RemoveResponse, RemoveSnippetRequest,
+    RemoveSnippetResponse, SearchMode, SearchRequest, SearchResponse, SearchResult, SizeResponse,
+    SubscribeRequest, TagInfo, TagRequest, TagResponse, UnpinRequest, UnpinResponse, UntagRequest,
+    UntagResponse, UpdateRequest, UpdateResponse, UpdateSnippetRequest, UpdateSnippetResponse,
+    WatcherState, WatcherStateReply,
 };
 
 impl From<ClipboardKind> for clipcat_base::ClipboardKind {
@@ -68,38 +79,429 @@ impl From<clipcat_base::ClipEntry> for ClipEntry {
         let id = entry.id();
         let kind = entry.kind();
         let timestamp = utils::datetime_to_timestamp(&entry.timestamp());
+        let pinned = entry.is_pinned();
+        let representations =
+            entry.representations().iter().cloned().map(ClipRepresentation::from).collect();
+        let source_application = entry.source_application().unwrap_or_default().to_owned();
+        let sensitive = entry.is_sensitive();
+        let tags = entry.tags().to_vec();
+        let template = entry.is_template();
+        let snippet_name = entry.snippet_name().unwrap_or_default().to_owned();
+        let snippet_path =
+            entry.snippet_path().map(|path| path.display().to_string()).unwrap_or_default();
 
-        Self { id, data, kind: kind.into(), mime, timestamp: Some(timestamp) }
+        Self {
+            id,
+            data,
+            kind: kind.into(),
+            mime,
+            timestamp: Some(timestamp),
+            pinned,
+            representations,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        }
     }
 }
 
 impl From<ClipEntry> for clipcat_base::ClipEntry {
-    fn from(ClipEntry { id: _, data, mime, kind, timestamp }: ClipEntry) -> Self {
+    fn from(
+        ClipEntry {
+            id: _,
+            data,
+            mime,
+            kind,
+            timestamp,
+            pinned,
+            representations,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        }: ClipEntry,
+    ) -> Self {
         let timestamp = timestamp.and_then(|ts| utils::timestamp_to_datetime(&ts).ok());
         let kind = clipcat_base::ClipboardKind::from(kind);
         let mime = mime::Mime::from_str(&mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
-        Self::new(&data, &mime, kind, timestamp).unwrap_or_default()
+        let mut entry = Self::new(&data, &mime, kind, timestamp).unwrap_or_default();
+        entry.set_pinned(pinned);
+        entry.set_representations(representations.into_iter().filter_map(|representation| {
+            clipcat_base::ClipRepresentation::try_from(representation).ok()
+        }));
+        entry.set_source_application(Some(source_application));
+        entry.set_sensitive(sensitive);
+        entry.set_tags(tags);
+        entry.set_template(template);
+        entry.set_snippet(Some(snippet_name), Some(PathBuf::from(snippet_path)));
+        entry
+    }
+}
+
+impl From<clipcat_base::ClipEntry> for ArchivedClip {
+    fn from(entry: clipcat_base::ClipEntry) -> Self {
+        let use_count = entry.use_count();
+        let last_used = entry.last_used().as_ref().map(utils::datetime_to_timestamp);
+        Self { clip: Some(ClipEntry::from(entry)), use_count, last_used }
+    }
+}
+
+impl From<ArchivedClip> for clipcat_base::ClipEntry {
+    fn from(ArchivedClip { clip, use_count, last_used }: ArchivedClip) -> Self {
+        let mut entry = clip.map(Self::from).unwrap_or_default();
+        entry.set_usage(use_count, last_used.and_then(|ts| utils::timestamp_to_datetime(&ts).ok()));
+        entry
+    }
+}
+
+impl From<ImportMode> for clipcat_base::archive::ImportMode {
+    fn from(mode: ImportMode) -> Self {
+        match mode {
+            ImportMode::Merge => Self::Merge,
+            ImportMode::Replace => Self::Replace,
+        }
+    }
+}
+
+impl From<clipcat_base::archive::ImportMode> for ImportMode {
+    fn from(mode: clipcat_base::archive::ImportMode) -> Self {
+        match mode {
+            clipcat_base::archive::ImportMode::Merge => Self::Merge,
+            clipcat_base::archive::ImportMode::Replace => Self::Replace,
+        }
+    }
+}
+
+impl From<clipcat_base::ClipRepresentation> for ClipRepresentation {
+    fn from(
+        clipcat_base::ClipRepresentation { mime, data }: clipcat_base::ClipRepresentation,
+    ) -> Self {
+        Self { mime: mime.to_string(), data: data.to_vec() }
+    }
+}
+
+impl TryFrom<ClipRepresentation> for clipcat_base::ClipRepresentation {
+    type Error = mime::FromStrError;
+
+    fn try_from(
+        ClipRepresentation { mime, data }: ClipRepresentation,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self::new(mime::Mime::from_str(&mime)?, data))
     }
 }
 
 impl From<clipcat_base::ClipEntryMetadata> for ClipEntryMetadata {
     fn from(metadata: clipcat_base::ClipEntryMetadata) -> Self {
-        let clipcat_base::ClipEntryMetadata { id, kind: clipboard_kind, timestamp, mime, preview } =
-            metadata;
+        let clipcat_base::ClipEntryMetadata {
+            id,
+            kind: clipboard_kind,
+            timestamp,
+            mime,
+            preview,
+            pinned,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        } = metadata;
         let mime = mime.essence_str().to_owned();
         let timestamp = utils::datetime_to_timestamp(&timestamp);
-        Self { id, preview, kind: clipboard_kind.into(), mime, timestamp: Some(timestamp) }
+        Self {
+            id,
+            preview,
+            kind: clipboard_kind.into(),
+            mime,
+            timestamp: Some(timestamp),
+            pinned,
+            source_application: source_application.unwrap_or_default(),
+            sensitive,
+            tags,
+            template,
+            snippet_name: snippet_name.unwrap_or_default(),
+            snippet_path: snippet_path.map(|path| path.display().to_string()).unwrap_or_default(),
+        }
     }
 }
 
 impl From<ClipEntryMetadata> for clipcat_base::ClipEntryMetadata {
-    fn from(ClipEntryMetadata { id, mime, kind, timestamp, preview }: ClipEntryMetadata) -> Self {
+    fn from(
+        ClipEntryMetadata {
+            id,
+            mime,
+            kind,
+            timestamp,
+            preview,
+            pinned,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        }: ClipEntryMetadata,
+    ) -> Self {
         let timestamp = timestamp
             .and_then(|ts| utils::timestamp_to_datetime(&ts).ok())
             .unwrap_or_else(OffsetDateTime::now_utc);
         let clipboard_kind = clipcat_base::ClipboardKind::from(kind);
         let mime = mime::Mime::from_str(&mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
-        Self { id, kind: clipboard_kind, timestamp, mime, preview }
+        let source_application = Some(source_application).filter(|name| !name.is_empty());
+        let snippet_name = Some(snippet_name).filter(|name| !name.is_empty());
+        let snippet_path = Some(snippet_path).filter(|path| !path.is_empty()).map(PathBuf::from);
+        Self {
+            id,
+            kind: clipboard_kind,
+            timestamp,
+            mime,
+            preview,
+            pinned,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        }
+    }
+}
+
+impl From<clipcat_base::ListQuery> for ListRequest {
+    fn from(query: clipcat_base::ListQuery) -> Self {
+        let clipcat_base::ListQuery {
+            preview_length,
+            offset,
+            limit,
+            order,
+            kinds,
+            mimes,
+            tags,
+            pinned,
+            snippet,
+            since,
+            until,
+        } = query;
+        Self {
+            preview_length: u64::try_from(preview_length).unwrap_or(30),
+            offset: u64::try_from(offset).unwrap_or(0),
+            limit: u64::try_from(limit).unwrap_or(0),
+            order: order.into(),
+            kinds: kinds.into_iter().map(i32::from).collect(),
+            mimes: mimes.iter().map(|mime| mime.essence_str().to_owned()).collect(),
+            pinned,
+            snippet,
+            since: since.as_ref().map(utils::datetime_to_timestamp),
+            until: until.as_ref().map(utils::datetime_to_timestamp),
+            tags,
+        }
+    }
+}
+
+impl TryFrom<ListRequest> for clipcat_base::ListQuery {
+    type Error = tonic::Status;
+
+    fn try_from(
+        ListRequest {
+            preview_length,
+            offset,
+            limit,
+            order,
+            kinds,
+            mimes,
+            pinned,
+            snippet,
+            since,
+            until,
+            tags,
+        }: ListRequest,
+    ) -> Result<Self, Self::Error> {
+        let to_usize = |name: &str, value: u64| {
+            usize::try_from(value)
+                .map_err(|_| tonic::Status::invalid_argument(format!("`{name}` is out of range")))
+        };
+        let mimes = mimes
+            .iter()
+            .map(|mime| {
+                mime::Mime::from_str(mime).map_err(|_| {
+                    tonic::Status::invalid_argument(format!("`{mime}` is not a valid MIME type"))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            preview_length: to_usize("preview_length", preview_length)?,
+            offset: to_usize("offset", offset)?,
+            limit: to_usize("limit", limit)?,
+            order: order.into(),
+            kinds: kinds.into_iter().map(clipcat_base::ClipboardKind::from).collect(),
+            mimes,
+            tags,
+            pinned,
+            snippet,
+            since: timestamp_argument("since", since)?,
+            until: timestamp_argument("until", until)?,
+        })
+    }
+}
+
+fn timestamp_argument(
+    name: &str,
+    timestamp: Option<prost_types::Timestamp>,
+) -> Result<Option<OffsetDateTime>, tonic::Status> {
+    timestamp
+        .map(|ts| {
+            utils::timestamp_to_datetime(&ts).map_err(|_| {
+                tonic::Status::invalid_argument(format!("`{name}` is not a valid timestamp"))
+            })
+        })
+        .transpose()
+}
+
+impl From<clipcat_base::SearchQuery> for SearchRequest {
+    fn from(query: clipcat_base::SearchQuery) -> Self {
+        let clipcat_base::SearchQuery {
+            pattern,
+            mode,
+            kinds,
+            mimes,
+            tags,
+            pinned,
+            snippet,
+            since,
+            until,
+            preview_length,
+            limit,
+        } = query;
+        Self {
+            pattern,
+            mode: mode.into(),
+            kinds: kinds.into_iter().map(i32::from).collect(),
+            mimes: mimes.iter().map(|mime| mime.essence_str().to_owned()).collect(),
+            preview_length: u64::try_from(preview_length).unwrap_or(30),
+            limit: u64::try_from(limit).unwrap_or(0),
+            tags,
+            pinned,
+            snippet,
+            since: since.as_ref().map(utils::datetime_to_timestamp),
+            until: until.as_ref().map(utils::datetime_to_timestamp),
+        }
+    }
+}
+
+impl TryFrom<SearchRequest> for clipcat_base::SearchQuery {
+    type Error = tonic::Status;
+
+    fn try_from(
+        SearchRequest {
+            pattern,
+            mode,
+            kinds,
+            mimes,
+            preview_length,
+            limit,
+            tags,
+            pinned,
+            snippet,
+            since,
+            until,
+        }: SearchRequest,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            pattern,
+            mode: mode.into(),
+            kinds: kinds.into_iter().map(clipcat_base::ClipboardKind::from).collect(),
+            mimes: mimes.iter().filter_map(|mime| mime::Mime::from_str(mime).ok()).collect(),
+            tags,
+            pinned,
+            snippet,
+            since: timestamp_argument("since", since)?,
+            until: timestamp_argument("until", until)?,
+            preview_length: usize::try_from(preview_length).unwrap_or(30),
+            limit: usize::try_from(limit).unwrap_or(0),
+        })
+    }
+}
+
+impl From<clipcat_base::SearchMatch> for SearchResult {
+    fn from(
+        clipcat_base::SearchMatch { metadata, score, ranges }: clipcat_base::SearchMatch,
+    ) -> Self {
+        let ranges = ranges
+            .into_iter()
+            .map(|range| MatchRange {
+                start: u64::try_from(range.start).unwrap_or_default(),
+                end: u64::try_from(range.end).unwrap_or_default(),
+            })
+            .collect();
+        Self { metadata: Some(metadata.into()), score, ranges }
+    }
+}
+
+impl From<SearchResult> for clipcat_base::SearchMatch {
+    fn from(SearchResult { metadata, score, ranges }: SearchResult) -> Self {
+        let metadata = metadata.map_or_else(
+            || clipcat_base::ClipEntry::default().metadata(None),
+            clipcat_base::ClipEntryMetadata::from,
+        );
+        let ranges = ranges
+            .into_iter()
+            .map(|MatchRange { start, end }| {
+                usize::try_from(start).unwrap_or_default()..usize::try_from(end).unwrap_or_default()
+            })
+            .collect();
+        Self { metadata, score, ranges }
+    }
+}
+
+impl From<clipcat_base::ClipboardEvent> for ClipboardEvent {
+    fn from(event: clipcat_base::ClipboardEvent) -> Self {
+        let event = match event {
+            clipcat_base::ClipboardEvent::Inserted(metadata) => {
+                clipboard_event::Event::Inserted(metadata.into())
+            }
+            clipcat_base::ClipboardEvent::Removed(id) => clipboard_event::Event::Removed(id),
+            clipcat_base::ClipboardEvent::Updated { old_id, metadata } => {
+                clipboard_event::Event::Updated(ClipUpdatedEvent {
+                    old_id,
+                    metadata: Some(metadata.into()),
+                })
+            }
+            clipcat_base::ClipboardEvent::Marked(metadata) => {
+                clipboard_event::Event::Marked(metadata.into())
+            }
+            clipcat_base::ClipboardEvent::Cleared => clipboard_event::Event::Cleared(()),
+            clipcat_base::ClipboardEvent::WatcherToggled(state) => {
+                clipboard_event::Event::WatcherToggled(WatcherState::from(state).into())
+            }
+        };
+        Self { event: Some(event) }
+    }
+}
+
+impl From<ClipboardEvent> for Option<clipcat_base::ClipboardEvent> {
+    fn from(ClipboardEvent { event }: ClipboardEvent) -> Self {
+        let event = match event? {
+            clipboard_event::Event::Inserted(metadata) => {
+                clipcat_base::ClipboardEvent::Inserted(metadata.into())
+            }
+            clipboard_event::Event::Removed(id) => clipcat_base::ClipboardEvent::Removed(id),
+            clipboard_event::Event::Updated(ClipUpdatedEvent { old_id, metadata }) => {
+                clipcat_base::ClipboardEvent::Updated { old_id, metadata: metadata?.into() }
+            }
+            clipboard_event::Event::Marked(metadata) => {
+                clipcat_base::ClipboardEvent::Marked(metadata.into())
+            }
+            clipboard_event::Event::Cleared(()) => clipcat_base::ClipboardEvent::Cleared,
+            clipboard_event::Event::WatcherToggled(state) => {
+                clipcat_base::ClipboardEvent::WatcherToggled(state.into())
+            }
+        };
+        Some(event)
     }
 }
 
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use clipcat_base::{ClipFilter, ClipboardContent, ClipboardKind};
+use clipcat_base::{ClipRepresentation, ClipboardContent, ClipboardKind, SharedClipFilter};
 use clipcat_clipboard::{Clipboard, ClipboardLoad, ClipboardStore, ClipboardSubscribe};
 use snafu::ResultExt;
 use tokio::task;
@@ -19,7 +19,7 @@ impl Backend {
     /// # Errors
     pub fn new<I>(
         kinds: I,
-        clip_filter: &Arc<ClipFilter>,
+        clip_filter: &SharedClipFilter,
         event_observers: &[Arc<dyn clipcat_clipboard::EventObserver>],
     ) -> Result<Self>
     where
@@ -87,6 +87,15 @@ impl traits::Backend for Backend {
         .context(error::SpawnBlockingTaskSnafu)?
     }
 
+    #[inline]
+    async fn load_representations(&self, kind: ClipboardKind) -> Result<Vec<ClipRepresentation>> {
+        let clipboard = self.select_clipboard(kind)?;
+        task::spawn_blocking(move || clipboard.load_representations())
+            .await
+            .context(error::SpawnBlockingTaskSnafu)?
+            .context(error::LoadDataFromClipboardSnafu)
+    }
+
     #[inline]
     async fn store(&self, kind: ClipboardKind, data: ClipboardContent) -> Result<()> {
         if let Ok(clipboard) = self.select_clipboard(kind) {
@@ -99,6 +108,25 @@ impl traits::Backend for Backend {
         }
     }
 
+    #[inline]
+    async fn store_with_representations(
+        &self,
+        kind: ClipboardKind,
+        data: ClipboardContent,
+        representations: Vec<ClipRepresentation>,
+    ) -> Result<()> {
+        if let Ok(clipboard) = self.select_clipboard(kind) {
+            task::spawn_blocking(move || {
+                clipboard.store_with_representations(data, representations)
+            })
+            .await
+            .context(error::SpawnBlockingTaskSnafu)?
+            .context(error::StoreDataToClipboardSnafu)
+        } else {
+            Ok(())
+        }
+    }
+
     #[inline]
     async fn clear(&self, kind: ClipboardKind) -> Result<()> {
         if let Ok(clipboard) = self.select_clipboard(kind) {
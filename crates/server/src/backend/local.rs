@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use clipcat_base::ClipboardContent;
+use clipcat_base::{ClipRepresentation, ClipboardContent};
 use clipcat_clipboard::{ClipboardLoad, ClipboardStore, ClipboardSubscribe, LocalClipboard};
 use snafu::ResultExt;
 use tokio::task;
@@ -32,6 +32,16 @@ impl traits::Backend for Backend {
         .context(error::SpawnBlockingTaskSnafu)?
     }
 
+    #[inline]
+    async fn load_representations(&self, _kind: ClipboardKind) -> Result<Vec<ClipRepresentation>> {
+        let clipboard = self.0.clone();
+
+        task::spawn_blocking(move || clipboard.load_representations())
+            .await
+            .context(error::SpawnBlockingTaskSnafu)?
+            .context(error::LoadDataFromClipboardSnafu)
+    }
+
     #[inline]
     async fn store(&self, _kind: ClipboardKind, data: ClipboardContent) -> Result<()> {
         let clipboard = self.0.clone();
@@ -42,6 +52,21 @@ impl traits::Backend for Backend {
             .context(error::StoreDataToClipboardSnafu)
     }
 
+    #[inline]
+    async fn store_with_representations(
+        &self,
+        _kind: ClipboardKind,
+        data: ClipboardContent,
+        representations: Vec<ClipRepresentation>,
+    ) -> Result<()> {
+        let clipboard = self.0.clone();
+
+        task::spawn_blocking(move || clipboard.store_with_representations(data, representations))
+            .await
+            .context(error::SpawnBlockingTaskSnafu)?
+            .context(error::StoreDataToClipboardSnafu)
+    }
+
     #[inline]
     async fn clear(&self, _kind: ClipboardKind) -> Result<()> {
         let clipboard = self.0.clone();
@@ -6,7 +6,8 @@ mod traits;
 
 use std::sync::Arc;
 
-use clipcat_base::{ClipFilter, ClipboardKind};
+use clipcat_base::{ClipboardKind, SharedClipFilter};
+pub use clipcat_clipboard::ClipboardChange;
 use clipcat_clipboard::EventObserver;
 
 use self::error::Result;
@@ -19,7 +20,7 @@ pub use self::{
 /// # Errors
 pub fn new<I>(
     kinds: I,
-    clip_filter: &Arc<ClipFilter>,
+    clip_filter: &SharedClipFilter,
     event_observers: &[Arc<dyn EventObserver>],
 ) -> Result<Box<dyn traits::Backend>>
 where
@@ -39,7 +40,7 @@ where
 /// # Errors
 pub fn new_shared<I>(
     kinds: I,
-    clip_filter: &Arc<ClipFilter>,
+    clip_filter: &SharedClipFilter,
     event_observers: &[Arc<dyn EventObserver>],
 ) -> Result<Arc<dyn traits::Backend>>
 where
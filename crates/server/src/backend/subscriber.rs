@@ -1,18 +1,21 @@
 use std::iter::IntoIterator;
 
-use clipcat_base::ClipboardKind;
-use clipcat_clipboard::ClipboardWait;
+use clipcat_clipboard::{ClipboardChange, ClipboardWait};
 use tokio::{sync::mpsc, task};
 
 #[derive(Debug)]
 pub struct Subscriber {
-    receiver: mpsc::UnboundedReceiver<(ClipboardKind, mime::Mime)>,
+    receiver: mpsc::UnboundedReceiver<ClipboardChange>,
     join_handles: task::JoinSet<()>,
 }
 
 impl Subscriber {
-    pub async fn next(&mut self) -> Option<(ClipboardKind, mime::Mime)> {
-        self.receiver.recv().await
+    pub async fn next(&mut self) -> Option<ClipboardChange> { self.receiver.recv().await }
+
+    #[cfg(test)]
+    #[must_use]
+    pub fn from_receiver(receiver: mpsc::UnboundedReceiver<ClipboardChange>) -> Self {
+        Self { receiver, join_handles: task::JoinSet::new() }
     }
 }
 
@@ -35,12 +38,12 @@ where
                 let _unused = join_handles.spawn_blocking({
                     let event_sender = sender.clone();
                     move || {
-                        while let Ok(kind) = subscriber.wait() {
+                        while let Ok(change) = subscriber.wait() {
                             if event_sender.is_closed() {
                                 break;
                             }
 
-                            if let Err(_err) = event_sender.send(kind) {
+                            if let Err(_err) = event_sender.send(change) {
                                 break;
                             }
                         }
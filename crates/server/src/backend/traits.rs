@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use clipcat_base::{ClipboardContent, ClipboardKind};
+use clipcat_base::{ClipRepresentation, ClipboardContent, ClipboardKind};
 
 use crate::backend::{error::Result, Subscriber};
 
@@ -8,8 +8,17 @@ pub trait Backend: Sync + Send {
     async fn load(&self, kind: ClipboardKind, mime: Option<mime::Mime>)
         -> Result<ClipboardContent>;
 
+    async fn load_representations(&self, kind: ClipboardKind) -> Result<Vec<ClipRepresentation>>;
+
     async fn store(&self, kind: ClipboardKind, data: ClipboardContent) -> Result<()>;
 
+    async fn store_with_representations(
+        &self,
+        kind: ClipboardKind,
+        data: ClipboardContent,
+        representations: Vec<ClipRepresentation>,
+    ) -> Result<()>;
+
     async fn clear(&self, kind: ClipboardKind) -> Result<()>;
 
     /// # Errors
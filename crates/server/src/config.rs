@@ -1,4 +1,7 @@
-use std::{net::SocketAddr, path::PathBuf, time::Duration};
+use std::{collections::HashSet, fmt, net::SocketAddr, path::PathBuf, time::Duration};
+
+use clipcat_base::{ClipEntry, ClipboardKind};
+use zeroize::Zeroizing;
 
 use crate::ClipboardWatcherOptions;
 
@@ -14,10 +17,29 @@ pub struct Config {
 
     pub max_history: usize,
 
+    // 0 means unlimited
+    pub max_history_bytes: usize,
+
+    // 0 means unlimited
+    pub max_history_disk_bytes: u64,
+
     pub synchronize_selection_with_clipboard: bool,
 
     pub history_file_path: PathBuf,
 
+    pub history_driver: HistoryDriver,
+
+    pub history_encryption_key: Option<HistoryEncryptionKey>,
+
+    // where the key is loaded from, `None` means the history is not encrypted, the key
+    // could not be loaded again once the daemon is started so only the source is compared
+    pub history_encryption: Option<HistoryEncryptionKeySource>,
+
+    // the log is set up before the server is started
+    pub log: LogConfig,
+
+    pub expiry: ExpiryConfig,
+
     pub watcher: ClipboardWatcherOptions,
 
     pub dbus: DBusConfig,
@@ -26,10 +48,171 @@ pub struct Config {
 
     pub metrics: MetricsConfig,
 
+    pub web: WebConfig,
+
+    pub rest: RestConfig,
+
     pub snippets: Vec<SnippetConfig>,
+
+    // snippets added at runtime are stored in it, they can not be added if it
+    // is `None`
+    pub snippet_directory: Option<PathBuf>,
+
+    // environment variables which snippet templates are allowed to read
+    pub snippet_environment_variables: Vec<String>,
+}
+
+/// Loads the configuration again, it is called with the running configuration
+/// whenever the configuration is reloaded.
+pub type ConfigLoader = Box<dyn Fn(&Config) -> Result<Config, String> + Send + Sync>;
+
+impl Config {
+    /// Returns names of settings which differ in `new` but could not be
+    /// applied without restarting the daemon.
+    #[must_use]
+    pub fn restart_required_changes(&self, new: &Self) -> Vec<&'static str> {
+        let mut changes = Vec::new();
+        if self.grpc_listen_address != new.grpc_listen_address
+            || self.grpc_local_socket != new.grpc_local_socket
+            || self.grpc_access_token != new.grpc_access_token
+        {
+            changes.push("grpc");
+        }
+        if self.primary_threshold != new.primary_threshold {
+            changes.push("primary_threshold_ms");
+        }
+        if self.history_file_path != new.history_file_path {
+            changes.push("history_file_path");
+        }
+        if self.history_driver != new.history_driver {
+            changes.push("history_driver");
+        }
+        if self.history_encryption != new.history_encryption {
+            changes.push("history_encryption");
+        }
+        if self.log != new.log {
+            changes.push("log");
+        }
+        if self.watcher.enable_clipboard != new.watcher.enable_clipboard {
+            changes.push("watcher.enable_clipboard");
+        }
+        if self.watcher.enable_primary != new.watcher.enable_primary {
+            changes.push("watcher.enable_primary");
+        }
+        if self.watcher.enable_secondary != new.watcher.enable_secondary {
+            changes.push("watcher.enable_secondary");
+        }
+        if self.dbus != new.dbus {
+            changes.push("dbus");
+        }
+        if self.metrics != new.metrics {
+            changes.push("metrics");
+        }
+        if self.web != new.web {
+            changes.push("web");
+        }
+        if self.rest != new.rest {
+            changes.push("rest");
+        }
+        changes
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum HistoryDriver {
+    #[default]
+    FileSystem,
+    Sqlite,
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct HistoryEncryptionKey(Zeroizing<Vec<u8>>);
+
+impl HistoryEncryptionKey {
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] { &self.0 }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+}
+
+impl From<Vec<u8>> for HistoryEncryptionKey {
+    fn from(key: Vec<u8>) -> Self { Self(Zeroizing::new(key)) }
+}
+
+impl fmt::Debug for HistoryEncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HistoryEncryptionKey(<redacted>)")
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HistoryEncryptionKeySource {
+    File(PathBuf),
+    EnvironmentVariable(String),
+    Prompt,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogConfig {
+    pub file_path: Option<PathBuf>,
+
+    pub emit_journald: bool,
+
+    pub emit_stdout: bool,
+
+    pub emit_stderr: bool,
+
+    pub level: tracing::Level,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ExpiryConfig {
+    pub check_interval: Duration,
+
+    pub rules: Vec<ExpiryRule>,
+}
+
+impl ExpiryConfig {
+    /// Returns the maximum age of `clip`, the shortest one is chosen if
+    /// multiple rules apply to `clip`.
+    #[must_use]
+    pub fn max_age(&self, clip: &ClipEntry) -> Option<time::Duration> {
+        self.rules.iter().filter(|rule| rule.applies_to(clip)).map(|rule| rule.max_age).min()
+    }
 }
 
 #[derive(Clone, Debug)]
+pub struct ExpiryRule {
+    // the rule applies to all clipboard kinds if it is empty
+    pub kinds: HashSet<ClipboardKind>,
+
+    // either a MIME type like `image/png` or a top-level type like `image`, the
+    // rule applies to all clips if it is `None`
+    pub mime: Option<String>,
+
+    pub max_age: time::Duration,
+}
+
+impl ExpiryRule {
+    fn applies_to(&self, clip: &ClipEntry) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&clip.kind()) {
+            return false;
+        }
+        self.mime.as_deref().map_or(true, |mime| {
+            let clip_mime = clip.mime();
+            if mime.contains('/') {
+                clip_mime.essence_str().eq_ignore_ascii_case(mime)
+            } else {
+                clip_mime.type_().as_str().eq_ignore_ascii_case(mime)
+            }
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct DBusConfig {
     pub enable: bool,
 
@@ -47,16 +230,105 @@ pub struct DesktopNotificationConfig {
     pub long_plaintext_length: usize,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct MetricsConfig {
     pub enable: bool,
 
     pub listen_address: SocketAddr,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WebConfig {
+    pub enable: bool,
+
+    pub listen_address: SocketAddr,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RestConfig {
+    pub listen_address: Option<SocketAddr>,
+
+    pub local_socket: Option<PathBuf>,
+}
+
 #[derive(Clone, Debug)]
 pub enum SnippetConfig {
     Inline { name: String, content: String },
     File { name: String, path: PathBuf },
     Directory { name: String, path: PathBuf },
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+    use super::{
+        Config, DBusConfig, DesktopNotificationConfig, HistoryDriver, HistoryEncryptionKey,
+        HistoryEncryptionKeySource, LogConfig, MetricsConfig, RestConfig, WebConfig,
+    };
+    use crate::ClipboardWatcherOptions;
+
+    fn config() -> Config {
+        let listen_address = SocketAddr::from(([127, 0, 0, 1], 45044));
+        Config {
+            grpc_listen_address: None,
+            grpc_local_socket: Some(PathBuf::from("/tmp/clipcat/grpc.sock")),
+            grpc_access_token: None,
+            primary_threshold: time::Duration::milliseconds(5000),
+            max_history: 50,
+            max_history_bytes: 0,
+            max_history_disk_bytes: 0,
+            synchronize_selection_with_clipboard: true,
+            history_file_path: PathBuf::from("/tmp/clipcat/history"),
+            history_driver: HistoryDriver::FileSystem,
+            history_encryption_key: None,
+            history_encryption: None,
+            log: LogConfig {
+                file_path: None,
+                emit_journald: true,
+                emit_stdout: false,
+                emit_stderr: false,
+                level: tracing::Level::INFO,
+            },
+            expiry: super::ExpiryConfig::default(),
+            watcher: ClipboardWatcherOptions::default(),
+            dbus: DBusConfig { enable: true, identifier: None },
+            desktop_notification: DesktopNotificationConfig {
+                enable: true,
+                icon: PathBuf::from("accessories-clipboard"),
+                timeout: Duration::from_secs(2),
+                long_plaintext_length: 2000,
+            },
+            metrics: MetricsConfig { enable: false, listen_address },
+            web: WebConfig { enable: false, listen_address },
+            rest: RestConfig::default(),
+            snippets: Vec::new(),
+            snippet_directory: None,
+            snippet_environment_variables: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_restart_required_changes() {
+        let running = config();
+        assert!(running.restart_required_changes(&config()).is_empty());
+
+        let mut new = config();
+        new.max_history = 100;
+        new.history_encryption =
+            Some(HistoryEncryptionKeySource::File(PathBuf::from("/tmp/clipcat/key")));
+        new.log.level = tracing::Level::DEBUG;
+        assert_eq!(running.restart_required_changes(&new), ["history_encryption", "log"]);
+
+        // the running key is kept by the loader, only where it is loaded from is
+        // compared
+        let mut running = new.clone();
+        running.history_encryption_key = Some(HistoryEncryptionKey::from(b"key".to_vec()));
+        assert!(running.restart_required_changes(&new).is_empty());
+
+        new.history_encryption = Some(HistoryEncryptionKeySource::Prompt);
+        assert_eq!(running.restart_required_changes(&new), ["history_encryption"]);
+        new.history_encryption = None;
+        assert_eq!(running.restart_required_changes(&new), ["history_encryption"]);
+    }
+}
@@ -1,6 +1,6 @@
 #![allow(clippy::ignored_unit_patterns)]
 
-use std::{str::FromStr, sync::Arc};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use clipcat_dbus_variant as dbus_variant;
 use tokio::sync::Mutex;
@@ -32,7 +32,7 @@ where
         let id = manager.insert(
             clipcat_base::ClipEntry::new(data, &mime, kind.into(), None).unwrap_or_default(),
         );
-        let _unused = manager.mark(id, kind.into()).await;
+        let _unused = manager.store(id, kind.into()).await;
         drop(manager);
         id
     }
@@ -48,7 +48,7 @@ where
             clipcat_base::ClipEntry::new(data.as_bytes(), &mime::TEXT_PLAIN_UTF_8, kind, None)
                 .unwrap_or_default(),
         );
-        let _unused = manager.mark(id, kind).await;
+        let _unused = manager.store(id, kind).await;
         drop(manager);
     }
 
@@ -113,12 +113,12 @@ where
         metrics::dbus::REQUESTS_TOTAL.inc();
         let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
 
+        let query = clipcat_base::ListQuery {
+            preview_length: usize::try_from(preview_length).unwrap_or(30),
+            ..clipcat_base::ListQuery::default()
+        };
         let manager = self.manager.lock().await;
-        manager
-            .list(usize::try_from(preview_length).unwrap_or(30))
-            .into_iter()
-            .map(dbus_variant::ClipEntryMetadata::from)
-            .collect()
+        manager.list(&query).into_iter().map(dbus_variant::ClipEntryMetadata::from).collect()
     }
 
     async fn update(&self, id: u64, data: &[u8], mime: &str) -> (bool, u64) {
@@ -141,12 +141,127 @@ where
         manager.mark(id, kind.into()).await.is_ok()
     }
 
+    async fn mark_with_prompts(
+        &self,
+        id: u64,
+        kind: dbus_variant::ClipboardKind,
+        prompts: HashMap<String, String>,
+    ) -> bool {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let mut manager = self.manager.lock().await;
+        manager.mark_with_prompts(id, kind.into(), &prompts).await.is_ok()
+    }
+
+    async fn expand(
+        &self,
+        id: u64,
+        prompts: HashMap<String, String>,
+    ) -> zvariant::Optional<dbus_variant::ClipEntry> {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let manager = self.manager.lock().await;
+        zvariant::Optional::from(manager.expand(id, &prompts).ok().flatten().map(Into::into))
+    }
+
+    async fn pin(&self, id: u64) -> bool {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let mut manager = self.manager.lock().await;
+        manager.pin(id)
+    }
+
+    async fn unpin(&self, id: u64) -> bool {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let mut manager = self.manager.lock().await;
+        manager.unpin(id)
+    }
+
+    async fn tag(&self, id: u64, tag: &str) -> bool {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let mut manager = self.manager.lock().await;
+        manager.tag(id, tag)
+    }
+
+    async fn untag(&self, id: u64, tag: &str) -> bool {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let mut manager = self.manager.lock().await;
+        manager.untag(id, tag)
+    }
+
+    async fn list_tags(&self) -> Vec<(String, u64)> {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let manager = self.manager.lock().await;
+        manager
+            .tags()
+            .into_iter()
+            .map(|(tag, count)| (tag, u64::try_from(count).unwrap_or(u64::MAX)))
+            .collect()
+    }
+
+    async fn list_snippets(&self, preview_length: u64) -> Vec<dbus_variant::ClipEntryMetadata> {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let preview_length = usize::try_from(preview_length).unwrap_or(30);
+        let manager = self.manager.lock().await;
+        manager
+            .snippets(preview_length)
+            .into_iter()
+            .map(dbus_variant::ClipEntryMetadata::from)
+            .collect()
+    }
+
+    async fn add_snippet(&self, name: &str, content: &str) -> (bool, u64) {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let mut manager = self.manager.lock().await;
+        manager.add_snippet(name, content).await.map_or((false, 0), |id| (true, id))
+    }
+
+    async fn update_snippet(&self, name: &str, content: &str) -> (bool, u64) {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let mut manager = self.manager.lock().await;
+        manager.update_snippet(name, content).await.map_or((false, 0), |id| (true, id))
+    }
+
+    async fn remove_snippet(&self, name: &str) -> bool {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let mut manager = self.manager.lock().await;
+        manager.delete_snippet(name).await.is_ok()
+    }
+
     #[zbus(property)]
     async fn length(&self) -> u64 {
         metrics::dbus::REQUESTS_TOTAL.inc();
         let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
 
         let manager = self.manager.lock().await;
-        manager.len() as u64
+        u64::try_from(manager.len()).unwrap_or(u64::MAX)
+    }
+
+    #[zbus(property)]
+    async fn size_in_bytes(&self) -> u64 {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let manager = self.manager.lock().await;
+        u64::try_from(manager.size_in_bytes()).unwrap_or(u64::MAX)
     }
 }
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf};
 
 use snafu::{Backtrace, Snafu};
 
@@ -10,9 +10,17 @@ pub enum Error {
     #[snafu(display("Error occurs while starting tonic server, error: {source}"))]
     StartTonicServer { source: tonic::transport::Error, backtrace: Backtrace },
 
+    #[snafu(display(
+        "Error occurs while creating TCP listener on `{listen_address}`, error: {source}"
+    ))]
+    CreateTcpListener { listen_address: SocketAddr, source: std::io::Error, backtrace: Backtrace },
+
     #[snafu(display("Error occurs while creating Unix domain socket listener on `{}`, error: {source}", socket_path.display()))]
     CreateUnixListener { socket_path: PathBuf, source: std::io::Error, backtrace: Backtrace },
 
+    #[snafu(display("Could not use the socket passed by systemd, error: {source}"))]
+    UseActivatedSocket { source: std::io::Error, backtrace: Backtrace },
+
     #[snafu(display("Could not create clipboard backend, error: {source}"))]
     CreateClipboardBackend { source: crate::backend::Error },
 
@@ -28,6 +36,12 @@ pub enum Error {
     #[snafu(display("Could not clear HistoryManager, error: {source}"))]
     ClearHistoryManager { source: crate::history::Error },
 
+    #[snafu(display("Could not check history, error: {source}"))]
+    CheckHistory { source: crate::history::Error },
+
+    #[snafu(display("Could not repair history, error: {source}"))]
+    RepairHistory { source: crate::history::Error },
+
     #[snafu(display("Could not serve ClipboardWatcherWorker, error: {source}"))]
     ServeClipboardWatcherWorker { source: crate::watcher::Error },
 
@@ -37,6 +51,18 @@ pub enum Error {
     #[snafu(display("Could not generate clip filter, error: {source}"))]
     GenerateClipFilter { source: crate::watcher::ClipboardWatcherOptionsError },
 
+    #[snafu(display("Error occurs while binding web server, error: {source}"))]
+    BindWebServer { source: std::io::Error },
+
+    #[snafu(display("Error occurs while serving web server, error: {source}"))]
+    ServeWebServer { source: std::io::Error },
+
+    #[snafu(display("Error occurs while binding REST API server, error: {source}"))]
+    BindRestServer { source: std::io::Error },
+
+    #[snafu(display("Error occurs while serving REST API server, error: {source}"))]
+    ServeRestServer { source: std::io::Error },
+
     #[snafu(display("{source}"))]
     Metrics { source: clipcat_metrics::Error },
 }
@@ -0,0 +1,118 @@
+use std::{pin::Pin, sync::Arc};
+
+use clipcat_base::archive::ImportMode;
+use clipcat_proto as proto;
+use futures::Stream;
+use tokio::sync::Mutex;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::{
+    config::HistoryEncryptionKey,
+    history::{Error as HistoryError, HistoryManager},
+    notification, ClipboardManager,
+};
+
+pub struct HistoryService<Notification> {
+    manager: Arc<Mutex<ClipboardManager<Notification>>>,
+    history_manager: Arc<Mutex<HistoryManager>>,
+}
+
+impl<Notification> HistoryService<Notification> {
+    #[inline]
+    pub const fn new(
+        manager: Arc<Mutex<ClipboardManager<Notification>>>,
+        history_manager: Arc<Mutex<HistoryManager>>,
+    ) -> Self {
+        Self { manager, history_manager }
+    }
+}
+
+#[tonic::async_trait]
+impl<Notification> proto::History for HistoryService<Notification>
+where
+    Notification: notification::Notification + 'static,
+{
+    type ExportStream =
+        Pin<Box<dyn Stream<Item = Result<proto::ArchivedClip, Status>> + Send + 'static>>;
+
+    async fn rekey(
+        &self,
+        request: Request<proto::RekeyRequest>,
+    ) -> Result<Response<proto::RekeyResponse>, Status> {
+        let proto::RekeyRequest { key, current_key } = request.into_inner();
+        let key = (!key.is_empty()).then(|| HistoryEncryptionKey::from(key));
+        let current_key =
+            (!current_key.is_empty()).then(|| HistoryEncryptionKey::from(current_key));
+        let result =
+            self.history_manager.lock().await.rekey(current_key.as_ref(), key.as_ref()).await;
+        result.map_err(|err| match err {
+            HistoryError::CurrentKeyMismatch => {
+                tracing::warn!("Reject rekeying history, error: {err}");
+                Status::permission_denied(err.to_string())
+            }
+            err => {
+                tracing::error!("Could not rekey history, error: {err}");
+                Status::internal(err.to_string())
+            }
+        })?;
+        tracing::info!(
+            "History is {state}",
+            state = if key.is_some() { "encrypted with a new key" } else { "decrypted" }
+        );
+        Ok(Response::new(proto::RekeyResponse { encrypted: key.is_some() }))
+    }
+
+    async fn size(&self, _request: Request<()>) -> Result<Response<proto::SizeResponse>, Status> {
+        let result = self.history_manager.lock().await.size_in_bytes().await;
+        let size_in_bytes = result.map_err(|err| {
+            tracing::error!("Could not get size of history, error: {err}");
+            Status::internal(err.to_string())
+        })?;
+        Ok(Response::new(proto::SizeResponse { size_in_bytes }))
+    }
+
+    async fn export(&self, _request: Request<()>) -> Result<Response<Self::ExportStream>, Status> {
+        let mut clips = self.manager.lock().await.export(false);
+        clips.retain(|clip| !clip.is_sensitive());
+        // clips are sorted from the newest to the oldest
+        clips.sort();
+        tracing::info!("Export {count} clip(s)", count = clips.len());
+        let stream = futures::stream::iter(
+            clips.into_iter().map(|clip| Ok(proto::ArchivedClip::from(clip))),
+        );
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn import(
+        &self,
+        request: Request<Streaming<proto::ImportRequest>>,
+    ) -> Result<Response<proto::ImportResponse>, Status> {
+        let mut stream = request.into_inner();
+        let mut mode = None;
+        let mut clips = Vec::new();
+        while let Some(request) = stream.message().await? {
+            let _mode = mode.get_or_insert_with(|| ImportMode::from(request.mode()));
+            if let Some(clip) = request.clip {
+                clips.push(clipcat_base::ClipEntry::from(clip));
+            }
+        }
+        let mode = mode.unwrap_or_default();
+
+        let (inserted, merged, clips) = {
+            let mut manager = self.manager.lock().await;
+            let (inserted, merged) = manager.import_archive(clips, mode);
+            (inserted, merged, manager.export(false))
+        };
+        // history is rewritten as clips may be removed or merged
+        let result = self.history_manager.lock().await.save(&clips).await;
+        result.map_err(|err| {
+            tracing::error!("Could not save history after importing clips, error: {err}");
+            Status::internal(err.to_string())
+        })?;
+        tracing::info!("Import {inserted} clip(s), merge {merged} clip(s) ({mode:?})");
+        Ok(Response::new(proto::ImportResponse {
+            inserted: u64::try_from(inserted).unwrap_or(u64::MAX),
+            merged: u64::try_from(merged).unwrap_or(u64::MAX),
+        }))
+    }
+}
@@ -26,19 +26,25 @@ impl Interceptor {
 
         Self { authorization_metadata_value }
     }
+
+    /// Checks the value of the `authorization` header of a request, every
+    /// request is authorized if there is no access token.
+    pub fn is_authorized(&self, authorization: Option<&[u8]>) -> bool {
+        self.authorization_metadata_value
+            .as_ref()
+            .map_or(true, |expected| authorization.is_some_and(|token| expected == token))
+    }
 }
 
 impl tonic::service::Interceptor for Interceptor {
     fn call(&mut self, req: Request<()>) -> Result<Request<()>, Status> {
         metrics::grpc::REQUESTS_TOTAL.inc();
 
-        if let Some(ref expected) = self.authorization_metadata_value {
-            match req.metadata().get("authorization") {
-                Some(token) if expected == token => Ok(req),
-                _ => Err(Status::unauthenticated("No valid authorization token")),
-            }
-        } else {
+        let authorization = req.metadata().get("authorization").map(AsciiMetadataValue::as_bytes);
+        if self.is_authorized(authorization) {
             Ok(req)
+        } else {
+            Err(Status::unauthenticated("No valid authorization token"))
         }
     }
 }
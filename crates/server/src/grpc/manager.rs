@@ -1,18 +1,25 @@
-use std::{str::FromStr, sync::Arc};
+use std::{pin::Pin, str::FromStr, sync::Arc};
 
+use clipcat_base::ClipboardEvent;
 use clipcat_proto as proto;
+use futures::{future, Stream, StreamExt};
 use tokio::sync::Mutex;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
 use tonic::{Request, Response, Status};
 
-use crate::{notification, ClipboardManager};
+use crate::{notification, ClipboardManager, ClipboardWatcherToggle};
 
 pub struct ManagerService<Notification> {
     manager: Arc<Mutex<ClipboardManager<Notification>>>,
+    watcher_toggle: ClipboardWatcherToggle<Notification>,
 }
 
 impl<Notification> ManagerService<Notification> {
-    pub const fn new(manager: Arc<Mutex<ClipboardManager<Notification>>>) -> Self {
-        Self { manager }
+    pub const fn new(
+        manager: Arc<Mutex<ClipboardManager<Notification>>>,
+        watcher_toggle: ClipboardWatcherToggle<Notification>,
+    ) -> Self {
+        Self { manager, watcher_toggle }
     }
 }
 
@@ -21,6 +28,9 @@ impl<Notification> proto::Manager for ManagerService<Notification>
 where
     Notification: notification::Notification + 'static,
 {
+    type SubscribeStream =
+        Pin<Box<dyn Stream<Item = Result<proto::ClipboardEvent, Status>> + Send + 'static>>;
+
     async fn insert(
         &self,
         request: Request<proto::InsertRequest>,
@@ -32,7 +42,7 @@ where
             let id = manager.insert(
                 clipcat_base::ClipEntry::new(&data, &mime, kind.into(), None).unwrap_or_default(),
             );
-            let _unused = manager.mark(id, kind.into()).await;
+            let _unused = manager.store(id, kind.into()).await;
             drop(manager);
             id
         };
@@ -83,6 +93,21 @@ where
         Ok(Response::new(proto::GetResponse { data }))
     }
 
+    async fn get_by_index(
+        &self,
+        request: Request<proto::GetByIndexRequest>,
+    ) -> Result<Response<proto::GetByIndexResponse>, Status> {
+        let proto::GetByIndexRequest { index } = request.into_inner();
+        let data = {
+            let manager = self.manager.lock().await;
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| manager.get_by_index(index))
+                .map(Into::into)
+        };
+        Ok(Response::new(proto::GetByIndexResponse { data }))
+    }
+
     async fn get_current_clip(
         &self,
         request: Request<proto::GetCurrentClipRequest>,
@@ -99,18 +124,27 @@ where
         &self,
         request: Request<proto::ListRequest>,
     ) -> Result<Response<proto::ListResponse>, Status> {
-        let proto::ListRequest { preview_length } = request.into_inner();
+        let query = clipcat_base::ListQuery::try_from(request.into_inner())?;
         let metadata = {
             let manager = self.manager.lock().await;
-            manager
-                .list(usize::try_from(preview_length).unwrap_or(30))
-                .into_iter()
-                .map(proto::ClipEntryMetadata::from)
-                .collect()
+            manager.list(&query).into_iter().map(proto::ClipEntryMetadata::from).collect()
         };
         Ok(Response::new(proto::ListResponse { metadata }))
     }
 
+    async fn search(
+        &self,
+        request: Request<proto::SearchRequest>,
+    ) -> Result<Response<proto::SearchResponse>, Status> {
+        let query = clipcat_base::SearchQuery::try_from(request.into_inner())?;
+        let matches = {
+            let manager = self.manager.lock().await;
+            manager.search(&query).map_err(|err| Status::invalid_argument(err.to_string()))?
+        };
+        let results = matches.into_iter().map(proto::SearchResult::from).collect();
+        Ok(Response::new(proto::SearchResponse { results }))
+    }
+
     async fn update(
         &self,
         request: Request<proto::UpdateRequest>,
@@ -128,22 +162,245 @@ where
         &self,
         request: Request<proto::MarkRequest>,
     ) -> Result<Response<proto::MarkResponse>, Status> {
-        let proto::MarkRequest { id, kind } = request.into_inner();
+        let proto::MarkRequest { id, kind, prompts } = request.into_inner();
         let ok = {
             let mut manager = self.manager.lock().await;
-            manager.mark(id, kind.into()).await.is_ok()
+            match manager.mark_with_prompts(id, kind.into(), &prompts).await {
+                Ok(()) => true,
+                Err(err @ crate::manager::Error::ExpandTemplate { .. }) => {
+                    return Err(Status::invalid_argument(err.to_string()));
+                }
+                Err(_) => false,
+            }
         };
         Ok(Response::new(proto::MarkResponse { ok }))
     }
 
+    async fn mark_by_index(
+        &self,
+        request: Request<proto::MarkByIndexRequest>,
+    ) -> Result<Response<proto::MarkByIndexResponse>, Status> {
+        let proto::MarkByIndexRequest { index, kind, prompts } = request.into_inner();
+        let (ok, id) = {
+            let mut manager = self.manager.lock().await;
+            match usize::try_from(index).ok().and_then(|index| manager.id_by_index(index)) {
+                Some(id) => match manager.mark_with_prompts(id, kind.into(), &prompts).await {
+                    Ok(()) => (true, id),
+                    Err(err @ crate::manager::Error::ExpandTemplate { .. }) => {
+                        return Err(Status::invalid_argument(err.to_string()));
+                    }
+                    Err(_) => (false, id),
+                },
+                None => (false, 0),
+            }
+        };
+        Ok(Response::new(proto::MarkByIndexResponse { ok, id }))
+    }
+
+    async fn expand(
+        &self,
+        request: Request<proto::ExpandRequest>,
+    ) -> Result<Response<proto::ExpandResponse>, Status> {
+        let proto::ExpandRequest { id, prompts } = request.into_inner();
+        let data = {
+            let manager = self.manager.lock().await;
+            manager
+                .expand(id, &prompts)
+                .map_err(|err| Status::invalid_argument(err.to_string()))?
+                .map(Into::into)
+        };
+        Ok(Response::new(proto::ExpandResponse { data }))
+    }
+
+    async fn pin(
+        &self,
+        request: Request<proto::PinRequest>,
+    ) -> Result<Response<proto::PinResponse>, Status> {
+        let proto::PinRequest { id } = request.into_inner();
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            manager.pin(id)
+        };
+        Ok(Response::new(proto::PinResponse { ok }))
+    }
+
+    async fn unpin(
+        &self,
+        request: Request<proto::UnpinRequest>,
+    ) -> Result<Response<proto::UnpinResponse>, Status> {
+        let proto::UnpinRequest { id } = request.into_inner();
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            manager.unpin(id)
+        };
+        Ok(Response::new(proto::UnpinResponse { ok }))
+    }
+
+    async fn tag(
+        &self,
+        request: Request<proto::TagRequest>,
+    ) -> Result<Response<proto::TagResponse>, Status> {
+        let proto::TagRequest { id, tag } = request.into_inner();
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            manager.tag(id, &tag)
+        };
+        Ok(Response::new(proto::TagResponse { ok }))
+    }
+
+    async fn untag(
+        &self,
+        request: Request<proto::UntagRequest>,
+    ) -> Result<Response<proto::UntagResponse>, Status> {
+        let proto::UntagRequest { id, tag } = request.into_inner();
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            manager.untag(id, &tag)
+        };
+        Ok(Response::new(proto::UntagResponse { ok }))
+    }
+
+    async fn list_tags(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::ListTagsResponse>, Status> {
+        let tags = {
+            let manager = self.manager.lock().await;
+            manager
+                .tags()
+                .into_iter()
+                .map(|(name, count)| proto::TagInfo {
+                    name,
+                    count: u64::try_from(count).unwrap_or(u64::MAX),
+                })
+                .collect()
+        };
+        Ok(Response::new(proto::ListTagsResponse { tags }))
+    }
+
+    async fn list_snippets(
+        &self,
+        request: Request<proto::ListSnippetsRequest>,
+    ) -> Result<Response<proto::ListSnippetsResponse>, Status> {
+        let proto::ListSnippetsRequest { preview_length } = request.into_inner();
+        let snippets = {
+            let preview_length = usize::try_from(preview_length).unwrap_or(30);
+            let manager = self.manager.lock().await;
+            manager
+                .snippets(preview_length)
+                .into_iter()
+                .map(proto::ClipEntryMetadata::from)
+                .collect()
+        };
+        Ok(Response::new(proto::ListSnippetsResponse { snippets }))
+    }
+
+    async fn add_snippet(
+        &self,
+        request: Request<proto::AddSnippetRequest>,
+    ) -> Result<Response<proto::AddSnippetResponse>, Status> {
+        let proto::AddSnippetRequest { name, content } = request.into_inner();
+        let id = {
+            let mut manager = self.manager.lock().await;
+            manager
+                .add_snippet(&name, &content)
+                .await
+                .map_err(|err| snippet_error_to_status(&err))?
+        };
+        Ok(Response::new(proto::AddSnippetResponse { id }))
+    }
+
+    async fn update_snippet(
+        &self,
+        request: Request<proto::UpdateSnippetRequest>,
+    ) -> Result<Response<proto::UpdateSnippetResponse>, Status> {
+        let proto::UpdateSnippetRequest { name, content } = request.into_inner();
+        let id = {
+            let mut manager = self.manager.lock().await;
+            manager
+                .update_snippet(&name, &content)
+                .await
+                .map_err(|err| snippet_error_to_status(&err))?
+        };
+        Ok(Response::new(proto::UpdateSnippetResponse { id }))
+    }
+
+    async fn remove_snippet(
+        &self,
+        request: Request<proto::RemoveSnippetRequest>,
+    ) -> Result<Response<proto::RemoveSnippetResponse>, Status> {
+        let proto::RemoveSnippetRequest { name } = request.into_inner();
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            match manager.delete_snippet(&name).await {
+                Ok(()) => true,
+                Err(crate::manager::Error::SnippetNotFound { .. }) => false,
+                Err(err) => return Err(snippet_error_to_status(&err)),
+            }
+        };
+        Ok(Response::new(proto::RemoveSnippetResponse { ok }))
+    }
+
     async fn length(
         &self,
         _request: Request<()>,
     ) -> Result<Response<proto::LengthResponse>, Status> {
-        let length = {
+        let (length, size_in_bytes) = {
             let manager = self.manager.lock().await;
-            manager.len() as u64
+            (
+                u64::try_from(manager.len()).unwrap_or(u64::MAX),
+                u64::try_from(manager.size_in_bytes()).unwrap_or(u64::MAX),
+            )
         };
-        Ok(Response::new(proto::LengthResponse { length }))
+        Ok(Response::new(proto::LengthResponse { length, size_in_bytes }))
+    }
+
+    async fn subscribe(
+        &self,
+        request: Request<proto::SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let proto::SubscribeRequest { preview_length } = request.into_inner();
+        let preview_length = usize::try_from(preview_length).unwrap_or(30);
+        let clip_events = {
+            let manager = self.manager.lock().await;
+            BroadcastStream::new(manager.subscribe())
+        }
+        .filter_map(move |event| {
+            future::ready(skip_lagged(event).map(|event| event.to_clipboard_event(preview_length)))
+        });
+        let watcher_events =
+            BroadcastStream::new(self.watcher_toggle.subscribe()).filter_map(|state| {
+                future::ready(skip_lagged(state).map(ClipboardEvent::WatcherToggled))
+            });
+        let stream = futures::stream::select(clip_events, watcher_events)
+            .map(|event| Ok(proto::ClipboardEvent::from(event)));
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+fn skip_lagged<T>(event: Result<T, BroadcastStreamRecvError>) -> Option<T> {
+    match event {
+        Ok(event) => Some(event),
+        Err(BroadcastStreamRecvError::Lagged(n)) => {
+            tracing::warn!("Subscriber is lagging behind, {n} event(s) are skipped");
+            None
+        }
+    }
+}
+
+fn snippet_error_to_status(err: &crate::manager::Error) -> Status {
+    use crate::manager::Error;
+
+    let message = err.to_string();
+    match err {
+        Error::InvalidSnippetName { .. } | Error::EmptySnippet { .. } => {
+            Status::invalid_argument(message)
+        }
+        Error::SnippetExists { .. } => Status::already_exists(message),
+        Error::SnippetNotFound { .. } => Status::not_found(message),
+        Error::SnippetDirectoryUnavailable | Error::SnippetNotManaged { .. } => {
+            Status::failed_precondition(message)
+        }
+        _ => Status::internal(message),
     }
 }
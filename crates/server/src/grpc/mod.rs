@@ -1,9 +1,10 @@
+mod history;
 mod interceptor;
 mod manager;
 mod system;
 mod watcher;
 
 pub use self::{
-    interceptor::Interceptor, manager::ManagerService, system::SystemService,
-    watcher::WatcherService,
+    history::HistoryService, interceptor::Interceptor, manager::ManagerService,
+    system::SystemService, watcher::WatcherService,
 };
@@ -2,6 +2,8 @@ use clipcat_proto as proto;
 use once_cell::sync::Lazy;
 use tonic::{Request, Response, Status};
 
+use crate::reload;
+
 static GET_SYSTEM_VERSION_RESPONSE: Lazy<proto::GetSystemVersionResponse> =
     Lazy::new(|| proto::GetSystemVersionResponse {
         major: clipcat_base::PROJECT_SEMVER.major,
@@ -9,11 +11,13 @@ static GET_SYSTEM_VERSION_RESPONSE: Lazy<proto::GetSystemVersionResponse> =
         patch: clipcat_base::PROJECT_SEMVER.patch,
     });
 
-pub struct SystemService {}
+pub struct SystemService {
+    reload_handle: reload::Handle,
+}
 
 impl SystemService {
     #[inline]
-    pub const fn new() -> Self { Self {} }
+    pub const fn new(reload_handle: reload::Handle) -> Self { Self { reload_handle } }
 }
 
 #[tonic::async_trait]
@@ -24,4 +28,18 @@ impl proto::System for SystemService {
     ) -> Result<Response<proto::GetSystemVersionResponse>, Status> {
         Ok(Response::new(*GET_SYSTEM_VERSION_RESPONSE))
     }
+
+    async fn reload_config(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::ReloadConfigResponse>, Status> {
+        let restart_required = self
+            .reload_handle
+            .reload()
+            .await
+            .map_err(|err| Status::failed_precondition(err.to_string()))?;
+        Ok(Response::new(proto::ReloadConfigResponse {
+            restart_required: restart_required.into_iter().map(ToString::to_string).collect(),
+        }))
+    }
 }
@@ -0,0 +1,185 @@
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use zeroize::Zeroizing;
+
+use crate::{
+    config::HistoryEncryptionKey,
+    history::{error, Error},
+};
+
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+const KEY_LENGTH: usize = 32;
+
+// a known plaintext, used for telling a wrong key apart from corrupted data
+const KEY_CHECK_PLAINTEXT: &[u8] = b"clipcat-history";
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Header {
+    pub salt: String,
+
+    pub key_check: String,
+}
+
+#[derive(Clone)]
+pub struct Cipher {
+    aead: XChaCha20Poly1305,
+    salt: Vec<u8>,
+    key_check: Vec<u8>,
+}
+
+impl Cipher {
+    /// Creates a cipher with a random salt for a new history.
+    pub fn generate(key: &HistoryEncryptionKey) -> Result<Self, Error> {
+        let mut salt = vec![0; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        let aead = derive(key, &salt)?;
+        let mut cipher = Self { aead, salt, key_check: Vec::new() };
+        cipher.key_check = cipher.encrypt(KEY_CHECK_PLAINTEXT)?;
+        Ok(cipher)
+    }
+
+    /// Restores the cipher of an existing history, fails if `key` is not the
+    /// one the history was encrypted with.
+    pub fn from_header(
+        key: &HistoryEncryptionKey,
+        Header { salt, key_check }: &Header,
+    ) -> Result<Self, Error> {
+        let salt = hex::decode(salt).context(error::DecodeEncryptionHeaderSnafu)?;
+        let key_check = hex::decode(key_check).context(error::DecodeEncryptionHeaderSnafu)?;
+        let cipher = Self { aead: derive(key, &salt)?, salt, key_check };
+        match cipher.decrypt(&cipher.key_check) {
+            Ok(plaintext) if plaintext == KEY_CHECK_PLAINTEXT => Ok(cipher),
+            _ => Err(Error::WrongEncryptionKey),
+        }
+    }
+
+    pub fn header(&self) -> Header {
+        Header { salt: hex::encode(&self.salt), key_check: hex::encode(&self.key_check) }
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self.aead.encrypt(&nonce, plaintext).map_err(|_| Error::EncryptData)?;
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        if data.len() < NONCE_LENGTH {
+            return Err(Error::DecryptData);
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LENGTH);
+        self.aead.decrypt(XNonce::from_slice(nonce), ciphertext).map_err(|_| Error::DecryptData)
+    }
+}
+
+#[inline]
+pub fn seal(cipher: Option<&Cipher>, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+    match cipher {
+        Some(cipher) => cipher.encrypt(&data),
+        None => Ok(data),
+    }
+}
+
+#[inline]
+pub fn unseal(cipher: Option<&Cipher>, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+    match cipher {
+        Some(cipher) => cipher.decrypt(&data),
+        None => Ok(data),
+    }
+}
+
+fn derive(key: &HistoryEncryptionKey, salt: &[u8]) -> Result<XChaCha20Poly1305, Error> {
+    let mut derived_key = Zeroizing::new([0; KEY_LENGTH]);
+    Argon2::default()
+        .hash_password_into(key.as_bytes(), salt, derived_key.as_mut())
+        .context(error::DeriveEncryptionKeySnafu)?;
+    Ok(XChaCha20Poly1305::new(derived_key.as_ref().into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::HistoryEncryptionKey,
+        history::{
+            cipher::{self, Cipher, Header, NONCE_LENGTH},
+            Error,
+        },
+    };
+
+    fn key(key: &str) -> HistoryEncryptionKey {
+        HistoryEncryptionKey::from(key.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let cipher = Cipher::generate(&key("secret")).unwrap();
+        let ciphertext = cipher.encrypt(b"hello").unwrap();
+        assert_ne!(&ciphertext[NONCE_LENGTH..], b"hello");
+        assert_eq!(cipher.decrypt(&ciphertext).unwrap(), b"hello");
+
+        // a restored cipher reads data of the original one
+        let restored = Cipher::from_header(&key("secret"), &cipher.header()).unwrap();
+        assert_eq!(restored.decrypt(&ciphertext).unwrap(), b"hello");
+
+        let sealed = cipher::seal(Some(&cipher), b"hello".to_vec()).unwrap();
+        assert_eq!(cipher::unseal(Some(&restored), sealed).unwrap(), b"hello");
+        assert_eq!(cipher::seal(None, b"hello".to_vec()).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_wrong_key() {
+        let cipher = Cipher::generate(&key("secret")).unwrap();
+        assert!(matches!(
+            Cipher::from_header(&key("wrong"), &cipher.header()),
+            Err(Error::WrongEncryptionKey)
+        ));
+    }
+
+    #[test]
+    fn test_tampered_data() {
+        let cipher = Cipher::generate(&key("secret")).unwrap();
+        let mut ciphertext = cipher.encrypt(b"hello").unwrap();
+        let last = ciphertext.len() - 1;
+        ciphertext[last] ^= 1;
+        assert!(matches!(cipher.decrypt(&ciphertext), Err(Error::DecryptData)));
+
+        let mut ciphertext = cipher.encrypt(b"hello").unwrap();
+        ciphertext[0] ^= 1;
+        assert!(matches!(cipher.decrypt(&ciphertext), Err(Error::DecryptData)));
+    }
+
+    #[test]
+    fn test_truncated_data() {
+        let cipher = Cipher::generate(&key("secret")).unwrap();
+        let ciphertext = cipher.encrypt(b"hello").unwrap();
+        for len in [0, NONCE_LENGTH - 1, NONCE_LENGTH, ciphertext.len() - 1] {
+            assert!(
+                matches!(cipher.decrypt(&ciphertext[..len]), Err(Error::DecryptData)),
+                "length: {len}"
+            );
+        }
+
+        let Header { salt, key_check } = cipher.header();
+        let header = Header { salt: salt.clone(), key_check: key_check[..16].to_string() };
+        assert!(matches!(
+            Cipher::from_header(&key("secret"), &header),
+            Err(Error::WrongEncryptionKey)
+        ));
+        let header = Header { salt: salt[..7].to_string(), key_check: key_check.clone() };
+        assert!(matches!(
+            Cipher::from_header(&key("secret"), &header),
+            Err(Error::DecodeEncryptionHeader { .. })
+        ));
+        let header = Header { salt: salt[..4].to_string(), key_check };
+        assert!(matches!(
+            Cipher::from_header(&key("secret"), &header),
+            Err(Error::DeriveEncryptionKey { .. })
+        ));
+    }
+}
@@ -0,0 +1,430 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    mem::size_of,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use snafu::ResultExt;
+use time::OffsetDateTime;
+
+use super::{
+    clips_file_path, header_file_path, image_dir_path, image_file_path_from_digest,
+    model::{self, Upgrade},
+    open_cipher, read_record, write_file_atomically, FileSystemDriver, CURRENT_SCHEMA,
+};
+use crate::{
+    config::HistoryEncryptionKey,
+    history::{
+        cipher::{self, Cipher},
+        error, Error,
+    },
+};
+
+/// The result of checking a history directory.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CheckReport {
+    /// Number of readable records in the clips file.
+    pub records: usize,
+
+    /// Byte ranges of the clips file which could not be read as records.
+    pub corrupted_regions: Vec<Range<u64>>,
+
+    /// Image files referenced by records but missing or unreadable.
+    pub missing_images: Vec<PathBuf>,
+
+    /// Image files not referenced by any record.
+    pub orphaned_images: Vec<PathBuf>,
+
+    /// Backup of the original history, only present after a repair.
+    pub backup_path: Option<PathBuf>,
+}
+
+impl CheckReport {
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.corrupted_regions.is_empty()
+            && self.missing_images.is_empty()
+            && self.orphaned_images.is_empty()
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Readable records: {}", self.records)?;
+        writeln!(f, "Corrupted regions: {}", self.corrupted_regions.len())?;
+        for Range { start, end } in &self.corrupted_regions {
+            writeln!(f, "  bytes {start}..{end} ({} bytes)", end - start)?;
+        }
+        writeln!(f, "Missing images: {}", self.missing_images.len())?;
+        for file_path in &self.missing_images {
+            writeln!(f, "  {}", file_path.display())?;
+        }
+        writeln!(f, "Orphaned images: {}", self.orphaned_images.len())?;
+        for file_path in &self.orphaned_images {
+            writeln!(f, "  {}", file_path.display())?;
+        }
+        if let Some(backup_path) = &self.backup_path {
+            writeln!(f, "Backup of the original history: {}", backup_path.display())?;
+        }
+        Ok(())
+    }
+}
+
+struct Scan {
+    report: CheckReport,
+
+    // raw content of the clips file
+    content: Vec<u8>,
+
+    // byte ranges of records to keep while repairing
+    records: Vec<Range<usize>>,
+
+    // image files which exist but could not be read
+    unreadable_images: Vec<PathBuf>,
+}
+
+impl FileSystemDriver {
+    /// Checks the history at `file_path` without modifying it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the history header could not be read or is neither
+    /// in the current schema nor in the previous one. A history in the previous
+    /// schema is checked before it is migrated, the migration stops at the
+    /// first unreadable record.
+    pub async fn check(
+        file_path: &Path,
+        key: Option<&HistoryEncryptionKey>,
+    ) -> Result<CheckReport, Error> {
+        let file_path = file_path.to_path_buf();
+        let key = key.cloned();
+        tokio::task::spawn_blocking(move || scan(&file_path, key.as_ref()).map(|scan| scan.report))
+            .await
+            .context(error::JoinTaskSnafu)?
+    }
+
+    /// Checks the history at `file_path` and rewrites it without the corrupted
+    /// records, the records with missing images and the orphaned images. The
+    /// original history is copied to a sibling directory first, nothing is
+    /// modified if the history is healthy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the history could not be checked, backed up or
+    /// rewritten.
+    pub async fn repair(
+        file_path: &Path,
+        key: Option<&HistoryEncryptionKey>,
+    ) -> Result<CheckReport, Error> {
+        let Scan { mut report, content, records, unreadable_images } = {
+            let file_path = file_path.to_path_buf();
+            let key = key.cloned();
+            tokio::task::spawn_blocking(move || scan(&file_path, key.as_ref()))
+                .await
+                .context(error::JoinTaskSnafu)??
+        };
+        if report.is_healthy() {
+            return Ok(report);
+        }
+
+        let backup_path = backup_file_path(file_path);
+        tracing::info!("Back up history to `{}`", backup_path.display());
+        {
+            let file_path = file_path.to_path_buf();
+            let backup_path = backup_path.clone();
+            tokio::task::spawn_blocking(move || copy_directory(&file_path, &backup_path))
+                .await
+                .context(error::JoinTaskSnafu)??;
+        }
+        report.backup_path = Some(backup_path);
+
+        let mut repaired = Vec::with_capacity(content.len());
+        for range in records {
+            repaired.extend_from_slice(&content[range]);
+        }
+        write_file_atomically(&clips_file_path(file_path), &repaired).await?;
+
+        for file_path in report.orphaned_images.iter().chain(&unreadable_images) {
+            tracing::debug!("Remove image file `{}`", file_path.display());
+            tokio::fs::remove_file(file_path)
+                .await
+                .context(error::RemoveFileSnafu { file_path: file_path.clone() })?;
+        }
+
+        Ok(report)
+    }
+}
+
+fn scan(file_path: &Path, key: Option<&HistoryEncryptionKey>) -> Result<Scan, Error> {
+    let header_file_path = header_file_path(file_path);
+    let header = std::fs::read(&header_file_path)
+        .context(error::ReadFileSnafu { file_path: header_file_path })?;
+    let header = serde_json::from_slice::<model::FileHeader>(&header)
+        .context(error::DeseriailizeHistoryHeaderSnafu)?;
+    let read_value = match header.schema {
+        CURRENT_SCHEMA => read_value::<model::v10::ClipboardValue>,
+        model::v9::SCHEMA_VERSION => read_value::<model::v9::ClipboardValue>,
+        model::v8::SCHEMA_VERSION => read_v8_value,
+        schema if schema > CURRENT_SCHEMA => {
+            return Err(Error::NewerSchema { new: schema, current: CURRENT_SCHEMA })
+        }
+        schema => return Err(Error::OutdatedSchema { schema, current: CURRENT_SCHEMA }),
+    };
+    let cipher = open_cipher(header.encryption, key)?;
+
+    let clips_file_path = clips_file_path(file_path);
+    let content = match std::fs::read(&clips_file_path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(source) => return Err(Error::ReadFile { source, file_path: clips_file_path }),
+    };
+
+    let image_dir_path = image_dir_path(file_path);
+    let mut report = CheckReport::default();
+    let mut records = Vec::new();
+    let mut unreadable_images = Vec::new();
+    let mut image_files = HashSet::new();
+    let mut corrupted_start = None;
+    let mut offset = 0;
+    while offset < content.len() {
+        let Some((len, value)) = read_value(&content[offset..], cipher.as_ref()) else {
+            // resynchronise by looking for the next readable record byte by byte
+            let _unused = corrupted_start.get_or_insert(offset);
+            offset += 1;
+            continue;
+        };
+        if let Some(start) = corrupted_start.take() {
+            report.corrupted_regions.push(start as u64..offset as u64);
+        }
+        report.records += 1;
+
+        let mut keep = true;
+        if value.mime.type_() == mime::IMAGE {
+            let image_file_path =
+                image_file_path_from_digest(&image_dir_path, &value.data, &value.mime);
+            let readable = std::fs::read(&image_file_path)
+                .map(|data| cipher::unseal(cipher.as_ref(), data).is_ok());
+            match readable {
+                Ok(true) => {}
+                Ok(false) => {
+                    keep = false;
+                    unreadable_images.push(image_file_path.clone());
+                    report.missing_images.push(image_file_path.clone());
+                }
+                Err(_) => {
+                    keep = false;
+                    report.missing_images.push(image_file_path.clone());
+                }
+            }
+            let _unused = image_files.insert(image_file_path);
+        }
+        if keep {
+            records.push(offset..offset + len);
+        }
+        offset += len;
+    }
+    if let Some(start) = corrupted_start {
+        report.corrupted_regions.push(start as u64..content.len() as u64);
+    }
+
+    if let Ok(entries) = std::fs::read_dir(&image_dir_path) {
+        for entry in entries.flatten() {
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_file()) {
+                continue;
+            }
+            let file_path = entry.path();
+            if !image_files.contains(&file_path) {
+                report.orphaned_images.push(file_path);
+            }
+        }
+    }
+    report.orphaned_images.sort_unstable();
+
+    Ok(Scan { report, content, records, unreadable_images })
+}
+
+// reads the record at the beginning of `content`, a record is accepted only if
+// its checksum matches and it is decrypted and decoded into a `Value` which is
+// encoded back to the same number of bytes, records of v9 are framed the same
+// way as the current ones
+fn read_value<Value>(
+    content: &[u8],
+    cipher: Option<&Cipher>,
+) -> Option<(usize, model::v10::ClipboardValue)>
+where
+    Value: Upgrade + Serialize,
+{
+    let (payload, len) = read_record(content)?;
+    let data = cipher::unseal(cipher, payload.to_vec()).ok()?;
+    let value = bincode::deserialize::<Value>(&data).ok()?;
+    if bincode::serialized_size(&value).ok()? != data.len() as u64 {
+        return None;
+    }
+    let value = value.upgrade();
+    is_known_mime(&value.mime).then_some((len, value))
+}
+
+// same as `read_value` for a record of schema v8, which is a sealed value
+// prefixed with its length in little endian and has no checksum
+fn read_v8_value(
+    content: &[u8],
+    cipher: Option<&Cipher>,
+) -> Option<(usize, model::v10::ClipboardValue)> {
+    let len = content.get(..size_of::<u64>())?.try_into().ok()?;
+    let len = usize::try_from(u64::from_le_bytes(len)).ok().filter(|&len| len > 0)?;
+    let payload = content.get(size_of::<u64>()..)?.get(..len)?;
+    let data = cipher::unseal(cipher, payload.to_vec()).ok()?;
+    let value = bincode::deserialize::<model::v8::ClipboardValue>(&data).ok()?;
+    if bincode::serialized_size(&value).ok()? != data.len() as u64 {
+        return None;
+    }
+    is_known_mime(&value.mime).then(|| (size_of::<u64>() + len, value.upgrade()))
+}
+
+fn is_known_mime(mime: &mime::Mime) -> bool {
+    mime.type_() == mime::TEXT || mime.type_() == mime::IMAGE
+}
+
+fn backup_file_path(file_path: &Path) -> PathBuf {
+    let mut file_name = file_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".backup-{}", OffsetDateTime::now_utc().unix_timestamp()));
+    file_path.with_file_name(file_name)
+}
+
+fn copy_directory(from: &Path, to: &Path) -> Result<(), Error> {
+    std::fs::create_dir_all(to)
+        .context(error::CreateDirectorySnafu { file_path: to.to_path_buf() })?;
+    let entries = std::fs::read_dir(from)
+        .context(error::ReadDirectorySnafu { dir_path: from.to_path_buf() })?;
+    for entry in entries {
+        let entry = entry.context(error::ReadDirectorySnafu { dir_path: from.to_path_buf() })?;
+        let file_path = entry.path();
+        let target_path = to.join(entry.file_name());
+        if file_path.is_dir() {
+            copy_directory(&file_path, &target_path)?;
+        } else {
+            let _size = std::fs::copy(&file_path, &target_path)
+                .context(error::WriteFileSnafu { file_path: target_path })?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{ClipEntry, ClipboardKind};
+
+    use crate::history::driver::{Driver, FileSystemDriver};
+
+    #[tokio::test]
+    async fn test_check_and_repair() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-repair-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let clips = (0..3)
+            .map(|i| {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                ClipEntry::from_string(i, ClipboardKind::Clipboard)
+            })
+            .collect::<Vec<_>>();
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        driver.save(&clips[..1]).await.unwrap();
+        let first_record_len = tokio::fs::metadata(driver.clips_file_path()).await.unwrap().len();
+        driver.save(&clips).await.unwrap();
+        let clips_file_path = driver.clips_file_path();
+        let image_dir_path = driver.image_dir_path();
+        drop(driver);
+
+        assert!(FileSystemDriver::check(&file_path, None).await.unwrap().is_healthy());
+
+        // break the length prefix of the second record, the records after it are
+        // dropped by `load`
+        let mut content = tokio::fs::read(&clips_file_path).await.unwrap();
+        let offset = usize::try_from(first_record_len).unwrap();
+        content[offset..offset + 8].fill(0xff);
+        tokio::fs::write(&clips_file_path, &content).await.unwrap();
+        tokio::fs::create_dir_all(&image_dir_path).await.unwrap();
+        let orphaned_image = image_dir_path.join("orphaned.png");
+        tokio::fs::write(&orphaned_image, b"orphaned").await.unwrap();
+
+        let report = FileSystemDriver::check(&file_path, None).await.unwrap();
+        assert_eq!(report.records, 2);
+        assert_eq!(report.corrupted_regions.len(), 1);
+        assert_eq!(report.corrupted_regions[0].start, first_record_len);
+        assert_eq!(report.orphaned_images, vec![orphaned_image.clone()]);
+        assert!(report.missing_images.is_empty());
+
+        let report = FileSystemDriver::repair(&file_path, None).await.unwrap();
+        let backup_path = report.backup_path.unwrap();
+        assert_eq!(tokio::fs::read(backup_path.join("clips")).await.unwrap(), content);
+        assert!(!orphaned_image.exists());
+        assert!(FileSystemDriver::check(&file_path, None).await.unwrap().is_healthy());
+
+        let mut loaded =
+            FileSystemDriver::new(&file_path, None).await.unwrap().load().await.unwrap();
+        loaded.sort_unstable_by_key(ClipEntry::timestamp);
+        assert_eq!(loaded, vec![clips[0].clone(), clips[2].clone()]);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+        drop(tokio::fs::remove_dir_all(&backup_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_repair_previous_schema() {
+        use time::OffsetDateTime;
+
+        use crate::history::driver::fs::{clips_file_path, header_file_path, model};
+
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-repair-v8-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+        tokio::fs::create_dir_all(&file_path).await.unwrap();
+
+        let header = model::FileHeader {
+            schema: model::v8::SCHEMA_VERSION,
+            last_update: OffsetDateTime::now_utc(),
+            encryption: None,
+        };
+        tokio::fs::write(header_file_path(&file_path), serde_json::to_vec(&header).unwrap())
+            .await
+            .unwrap();
+        let timestamp = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap();
+        let records = ["first", "second"].map(|text| {
+            let value = model::v8::ClipboardValue {
+                timestamp,
+                mime: mime::TEXT_PLAIN_UTF_8,
+                data: text.as_bytes().to_vec(),
+                pinned: false,
+                representations: Vec::new(),
+                source_application: None,
+                use_count: 0,
+                last_used: None,
+                tags: Vec::new(),
+            };
+            bincode::serialize(&bincode::serialize(&value).unwrap()).unwrap()
+        });
+        // garbage between the records stops the migration at the first one
+        let content = [records[0].as_slice(), b"garbage", records[1].as_slice()].concat();
+        tokio::fs::write(clips_file_path(&file_path), &content).await.unwrap();
+
+        let report = FileSystemDriver::check(&file_path, None).await.unwrap();
+        assert_eq!(report.records, 2);
+        assert_eq!(report.corrupted_regions.len(), 1);
+
+        let report = FileSystemDriver::repair(&file_path, None).await.unwrap();
+        let backup_path = report.backup_path.unwrap();
+        assert!(FileSystemDriver::check(&file_path, None).await.unwrap().is_healthy());
+
+        let loaded = FileSystemDriver::new(&file_path, None).await.unwrap().load().await.unwrap();
+        let mut texts = loaded.iter().map(ClipEntry::as_utf8_string).collect::<Vec<_>>();
+        texts.sort_unstable();
+        assert_eq!(texts, ["first", "second"]);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+        drop(tokio::fs::remove_dir_all(&backup_path).await);
+    }
+}
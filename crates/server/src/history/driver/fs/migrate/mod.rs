@@ -1,2 +1,105 @@
-pub mod v1;
-pub mod v2;
+pub mod v10;
+
+use std::path::{Path, PathBuf};
+
+use clipcat_base::ClipEntry;
+use snafu::ResultExt;
+
+use crate::history::{
+    cipher::{self, Cipher},
+    driver::fs::{clip_from_value, model::Upgrade, read_clips_file, split_records},
+    error, Error,
+};
+
+// how the clips file of an out-of-date schema stores values
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Layout {
+    // values follow one another, images are stored in values as is (v1)
+    Inline,
+    // values follow one another, images are stored in image files (v2 and v3)
+    Plain,
+    // each value is sealed with the cipher of history (v4 to v8)
+    Sealed,
+    // each sealed value is framed with its length and checksum (v9)
+    Framed,
+}
+
+// loads clips from the clips file of an out-of-date schema whose values are
+// `Value`, a value which is not readable is skipped if the records are sealed
+// or framed, otherwise the rest of the file is not readable either
+pub async fn load<Value>(
+    clips_file_path: &Path,
+    image_dir_path: PathBuf,
+    cipher: Option<Cipher>,
+    layout: Layout,
+) -> Result<Vec<ClipEntry>, Error>
+where
+    Value: Upgrade,
+{
+    let content = read_clips_file(clips_file_path).await?;
+    let clips_file_path = clips_file_path.to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        let image_dir_path = (layout != Layout::Inline).then_some(image_dir_path.as_path());
+        if layout == Layout::Framed {
+            let (records, _) = split_records(&content);
+            return records
+                .into_iter()
+                .filter_map(|record| {
+                    match unseal_value::<Value>(cipher.as_ref(), record.to_vec()) {
+                        Ok(value) => {
+                            clip_from_value(value.upgrade(), image_dir_path, cipher.as_ref())
+                        }
+                        Err(err) => {
+                            tracing::error!("Skip unreadable clip, error: {err}");
+                            None
+                        }
+                    }
+                })
+                .collect();
+        }
+
+        let mut reader = content.as_slice();
+        let mut clips = Vec::new();
+        while !reader.is_empty() {
+            let value = if layout == Layout::Sealed {
+                let Ok(record) = bincode::deserialize_from::<_, Vec<u8>>(&mut reader) else {
+                    break;
+                };
+                match unseal_value::<Value>(cipher.as_ref(), record) {
+                    Ok(value) => value,
+                    Err(err) => {
+                        tracing::error!("Skip unreadable clip, error: {err}");
+                        continue;
+                    }
+                }
+            } else {
+                let Ok(value) = bincode::deserialize_from::<_, Value>(&mut reader) else {
+                    break;
+                };
+                value
+            };
+            if let Some(clip) = clip_from_value(value.upgrade(), image_dir_path, cipher.as_ref()) {
+                clips.push(clip);
+            }
+        }
+        if !reader.is_empty() {
+            tracing::error!(
+                "Skip {len} bytes of unreadable clips in `{path}`",
+                len = reader.len(),
+                path = clips_file_path.display()
+            );
+        }
+        clips
+    })
+    .await
+    .context(error::JoinTaskSnafu)
+}
+
+fn unseal_value<Value>(cipher: Option<&Cipher>, record: Vec<u8>) -> Result<Value, Error>
+where
+    Value: Upgrade,
+{
+    cipher::unseal(cipher, record)
+        .and_then(|data| bincode::deserialize::<Value>(&data).context(error::DeseriailizeClipSnafu))
+}
@@ -0,0 +1,21 @@
+use std::path::Path;
+
+use clipcat_base::ClipEntry;
+
+use crate::history::{
+    cipher::Cipher,
+    driver::fs::{commit_staged_history, stage_history},
+    Error,
+};
+
+pub async fn migrate_to(
+    file_path: &Path,
+    clips: Vec<ClipEntry>,
+    cipher: Option<&Cipher>,
+) -> Result<(), Error> {
+    tracing::info!("Migrate clips to v10 schema");
+
+    // the migrated history replaces the old one only once it is complete
+    stage_history(file_path, clips, cipher).await?;
+    commit_staged_history(file_path).await
+}
@@ -1,32 +1,52 @@
+mod check;
 mod migrate;
 mod model;
 
 use std::{
     collections::HashSet,
+    mem::size_of,
     path::{Path, PathBuf},
 };
 
 use async_trait::async_trait;
-use clipcat_base::{ClipEntry, ClipboardKind};
+use clipcat_base::{ClipEntry, ClipRepresentation, ClipboardKind};
 use snafu::ResultExt;
 use time::{format_description::well_known::Rfc3339, OffsetDateTime, UtcOffset};
 use tokio::{
     fs::{File, OpenOptions},
-    io::{AsyncSeekExt, AsyncWriteExt, SeekFrom},
+    io::AsyncWriteExt,
 };
 
-use crate::history::{driver::Driver, error, Error};
+pub use self::check::CheckReport;
+use self::migrate::Layout;
+use crate::{
+    config::HistoryEncryptionKey,
+    history::{
+        cipher::{self, Cipher},
+        driver::Driver,
+        error, Error,
+    },
+};
+
+const CURRENT_SCHEMA: u64 = model::v10::SCHEMA_VERSION;
 
-const CURRENT_SCHEMA: u64 = model::v2::FileHeader::SCHEMA_VERSION;
+// a record starts with the length of its payload and a CRC-32 checksum of the
+// payload, both in little endian
+const RECORD_HEADER_SIZE: usize = size_of::<u64>() + size_of::<u32>();
 
 pub struct FileSystemDriver {
     file_path: PathBuf,
+    // opened for appending, it is reopened after being replaced
     clips_file: File,
-    header_file: File,
+    cipher: Option<Cipher>,
+    // bytes of records and image files of clips which are not pinned, it is
+    // counted again if it is `None`, records which are replaced by putting a
+    // clip again are counted until the history is shrunk
+    unpinned_bytes: Option<u64>,
 }
 
 impl FileSystemDriver {
-    pub async fn new<P>(file_path: P) -> Result<Self, Error>
+    pub async fn new<P>(file_path: P, key: Option<&HistoryEncryptionKey>) -> Result<Self, Error>
     where
         P: AsRef<Path> + Send,
     {
@@ -38,89 +58,142 @@ impl FileSystemDriver {
             .await
             .context(error::CreateDirectorySnafu { file_path: file_path.clone() })?;
 
-        if let Ok(header_content) = tokio::fs::read(&header_file_path).await {
-            if let Ok(model::v2::FileHeader { schema, last_update }) =
-                serde_json::from_slice::<model::v2::FileHeader>(&header_content)
-            {
-                tracing::info!(
-                    "Open `{}`, schema: {schema}, last update: {last_update}",
-                    header_file_path.display(),
-                    last_update = last_update
-                        .to_offset(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC))
-                        .format(&Rfc3339)
-                        .unwrap_or_default()
-                );
-
-                let clips = match schema {
-                    schema if schema > CURRENT_SCHEMA => {
-                        return Err(Error::NewerSchema { new: schema, current: CURRENT_SCHEMA })
-                    }
-                    model::v1::FileHeader::SCHEMA_VERSION => {
-                        tracing::info!("Clip history schema `{schema}` is out-of-date");
-                        Some(migrate::v1::load(&clips_file_path).await?)
-                    }
-                    _ => None,
-                };
+        // a history which is staged completely replaces the current one, even if
+        // the daemon was interrupted while replacing
+        remove_dir_if_exists(&staging_dir_path(&file_path)).await?;
+        commit_staged_history(&file_path).await?;
 
-                if let Some(clips) = clips {
-                    migrate::v2::migrate_to(&file_path, &header_file_path, &clips_file_path, clips)
-                        .await?;
-                }
+        let header = tokio::fs::read(&header_file_path).await.ok().and_then(|header_content| {
+            serde_json::from_slice::<model::FileHeader>(&header_content).ok()
+        });
+
+        let (schema, encryption) = header.map_or((None, None), |header| {
+            tracing::info!(
+                "Open `{}`, schema: {schema}, last update: {last_update}",
+                header_file_path.display(),
+                schema = header.schema,
+                last_update = header
+                    .last_update
+                    .to_offset(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC))
+                    .format(&Rfc3339)
+                    .unwrap_or_default()
+            );
+            (Some(header.schema), header.encryption)
+        });
+
+        let cipher = open_cipher(encryption, key)?;
+
+        let clips =
+            load_outdated_clips(schema, &file_path, &clips_file_path, cipher.as_ref()).await?;
+
+        let (clips, cipher) = match (cipher, key) {
+            (Some(cipher), _) => (clips, Some(cipher)),
+            (None, Some(key)) => {
+                // a plaintext history in current schema, encrypt it with the new key
+                let clips = if clips.is_none() && schema == Some(CURRENT_SCHEMA) {
+                    tracing::info!("Encrypt clip history");
+                    Some(
+                        load_clips(clips_file_path.clone(), image_dir_path(&file_path), None)
+                            .await?,
+                    )
+                } else {
+                    clips
+                };
+                (clips, Some(Cipher::generate(key)?))
             }
+            (None, None) => (clips, None),
+        };
+
+        if let Some(clips) = clips {
+            migrate::v10::migrate_to(&file_path, clips, cipher.as_ref()).await?;
         }
 
-        let header_file = OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .read(true)
-            .append(false)
-            .open(&header_file_path)
-            .await
-            .context(error::OpenFileSnafu { file_path: header_file_path })?;
-
-        let clips_file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .read(true)
-            .append(true)
-            .open(&clips_file_path)
-            .await
-            .context(error::OpenFileSnafu { file_path: clips_file_path })?;
+        // the schema of a clips file without header is unknown, it is left untouched
+        if schema.is_some() {
+            discard_torn_tail(&clips_file_path).await?;
+        }
+        let clips_file = open_clips_file(&clips_file_path).await?;
 
-        Ok(Self { file_path, clips_file, header_file })
+        let driver = Self { file_path, clips_file, cipher, unpinned_bytes: None };
+        // a new history has no header yet, encryption parameters are kept in it, the
+        // header of a migrated history is written while staging
+        if schema.is_none() {
+            driver.update_header().await?;
+        }
+        Ok(driver)
     }
 
-    async fn update_header(&mut self) -> Result<(), Error> {
-        self.header_file
-            .set_len(0)
-            .await
-            .context(error::TruncateFileSnafu { file_path: self.header_file_path() })?;
-        drop(self.header_file.seek(SeekFrom::Start(0)).await);
+    /// Loads clips of the history at `file_path` without changing it, a history
+    /// in an out-of-date schema is read without being migrated.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the history could not be read.
+    pub async fn load_read_only<P>(
+        file_path: P,
+        key: Option<&HistoryEncryptionKey>,
+    ) -> Result<Vec<ClipEntry>, Error>
+    where
+        P: AsRef<Path> + Send,
+    {
+        // a staged history is complete, it replaces the current one once the history
+        // is opened
+        let staged_dir_path = staged_dir_path(&file_path);
+        let file_path =
+            if staged_dir_path.exists() { staged_dir_path.as_path() } else { file_path.as_ref() };
 
-        let content = serde_json::to_string_pretty(&model::v2::FileHeader {
-            schema: model::v2::FileHeader::SCHEMA_VERSION,
-            last_update: OffsetDateTime::now_utc(),
-        })
-        .context(error::SeriailizeHistoryHeaderSnafu)?;
+        let header =
+            tokio::fs::read(header_file_path(file_path)).await.ok().and_then(|header_content| {
+                serde_json::from_slice::<model::FileHeader>(&header_content).ok()
+            });
+        let (schema, encryption) =
+            header.map_or((None, None), |header| (Some(header.schema), header.encryption));
+        let cipher = open_cipher(encryption, key)?;
 
-        self.header_file
-            .write_all(content.as_bytes())
-            .await
-            .context(error::WriteFileSnafu { file_path: self.header_file_path() })
+        let clips_file_path = clips_file_path(file_path);
+        match load_outdated_clips(schema, file_path, &clips_file_path, cipher.as_ref()).await? {
+            Some(clips) => Ok(clips),
+            None => load_clips(clips_file_path, image_dir_path(file_path), cipher).await,
+        }
+    }
+
+    pub fn exists<P>(file_path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        header_file_path(&file_path).exists() || clips_file_path(&file_path).exists()
+    }
+
+    async fn update_header(&self) -> Result<(), Error> {
+        write_header(&self.header_file_path(), self.cipher.as_ref()).await
     }
 
-    async fn store_file_content(&mut self, clip: ClipEntry) -> Result<(), Error> {
+    // replaces the content of the clips file atomically, records are never
+    // rewritten in place
+    async fn replace_clips_file(&mut self, content: &[u8]) -> Result<(), Error> {
+        let clips_file_path = self.clips_file_path();
+        write_file_atomically(&clips_file_path, content).await?;
+        self.clips_file = open_clips_file(&clips_file_path).await?;
+        Ok(())
+    }
+
+    // stores the image of `clip` and returns its record and the size of the
+    // image file, `None` is returned if the clip could not be encoded
+    async fn store_file_content(&self, clip: ClipEntry) -> Result<Option<(Vec<u8>, u64)>, Error> {
         let image_dir_path = self.image_dir_path();
+        let mut image_file_size = 0;
         if clip.mime().type_() == mime::IMAGE {
             let content = match clip.encoded() {
                 Ok(content) => content,
                 Err(err) => {
                     tracing::error!("Error occurs while encoding clip, error: {err}");
-                    return Ok(());
+                    return Ok(None);
                 }
             };
-            let file_path = image_file_path_from_digest(image_dir_path, clip.sha256_digest());
+            let content = cipher::seal(self.cipher.as_ref(), content)?;
+            image_file_size = content.len() as u64;
+            let file_path =
+                image_file_path_from_digest(image_dir_path, clip.sha256_digest(), &clip.mime());
             if let Some(parent) = file_path.parent() {
                 tokio::fs::create_dir_all(parent)
                     .await
@@ -131,12 +204,20 @@ impl FileSystemDriver {
                 .context(error::WriteFileSnafu { file_path })?;
         }
 
-        let content = bincode::serialize(&model::v2::ClipboardValue::from(clip))
-            .context(error::SeriailizeClipSnafu)?;
-        self.clips_file
-            .write_all(content.as_ref())
-            .await
-            .with_context(|_| error::WriteFileSnafu { file_path: self.clips_file_path() })
+        let record = encode_record(self.cipher.as_ref(), &model::v10::ClipboardValue::from(clip))?;
+        Ok(Some((record, image_file_size)))
+    }
+
+    // counts the bytes in the same way as `shrink_to`
+    async fn count_unpinned_bytes(&self) -> Result<u64, Error> {
+        let image_dir_path = self.image_dir_path();
+        let mut bytes = 0;
+        for clip in load_values(self.clips_file_path(), self.cipher.clone()).await? {
+            if !clip.pinned {
+                bytes += stored_size(&image_dir_path, self.cipher.as_ref(), &clip).await?;
+            }
+        }
+        Ok(bytes)
     }
 
     pub fn header_file_path(&self) -> PathBuf { header_file_path(&self.file_path) }
@@ -149,145 +230,104 @@ impl FileSystemDriver {
 #[async_trait]
 impl Driver for FileSystemDriver {
     async fn save(&mut self, clips: &[ClipEntry]) -> Result<(), Error> {
-        self.clips_file
-            .set_len(0)
-            .await
-            .context(error::TruncateFileSnafu { file_path: self.clips_file_path() })?;
+        let mut content = Vec::new();
+        let mut unpinned_bytes = 0;
         for clip in clips {
-            self.store_file_content(clip.clone()).await?;
+            if let Some((record, image_file_size)) = self.store_file_content(clip.clone()).await? {
+                if !clip.is_pinned() {
+                    unpinned_bytes += record.len() as u64 + image_file_size;
+                }
+                content.extend(record);
+            }
         }
-
-        drop(self.clips_file.flush().await);
+        self.replace_clips_file(&content).await?;
+        self.unpinned_bytes = Some(unpinned_bytes);
 
         self.update_header().await
     }
 
     async fn load(&mut self) -> Result<Vec<ClipEntry>, Error> {
-        let clips_file_path = self.clips_file_path();
-        let clips_file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .read(true)
-            .append(true)
-            .open(&clips_file_path)
-            .await
-            .with_context(|_| error::OpenFileSnafu { file_path: clips_file_path })?
-            .into_std()
-            .await;
-        let image_dir_path = self.image_dir_path();
-
-        tokio::task::spawn_blocking(move || {
-            let mut clips = Vec::new();
-
-            while let Ok(clip) =
-                bincode::deserialize_from::<_, model::v2::ClipboardValue>(&clips_file)
-            {
-                let model::v2::ClipboardValue { timestamp, mime, data } = clip;
-                let data = if mime.type_() == mime::IMAGE {
-                    let file_path = image_file_path_from_digest(&image_dir_path, &data);
-                    let maybe_data = std::fs::read(&file_path)
-                        .context(error::ReadFileSnafu { file_path: file_path.clone() });
-                    match maybe_data {
-                        Ok(data) => data,
-                        Err(err) => {
-                            tracing::error!("{err}");
-                            continue;
-                        }
-                    }
-                } else {
-                    data
-                };
+        load_clips(self.clips_file_path(), self.image_dir_path(), self.cipher.clone()).await
+    }
 
-                if let Ok(clip) =
-                    ClipEntry::new(&data, &mime, ClipboardKind::Clipboard, Some(timestamp))
-                {
-                    clips.push(clip);
-                }
-            }
-            Ok(clips)
-        })
-        .await
-        .context(error::JoinTaskSnafu)?
+    async fn get(&mut self, id: u64) -> Result<Option<ClipEntry>, Error> {
+        // clips are stored sequentially, there is no index for looking up
+        Ok(self.load().await?.into_iter().find(|clip| clip.id() == id))
     }
 
     async fn clear(&mut self) -> Result<(), Error> {
         self.update_header().await?;
 
+        self.replace_clips_file(&[]).await?;
         drop(tokio::fs::remove_dir_all(image_dir_path(&self.file_path)).await);
-        self.clips_file
-            .set_len(0)
-            .await
-            .context(error::TruncateFileSnafu { file_path: self.clips_file_path() })
+        self.unpinned_bytes = Some(0);
+        Ok(())
     }
 
     async fn put(&mut self, clip: &ClipEntry) -> Result<(), Error> {
-        self.update_header().await?;
-
-        drop(self.clips_file.seek(SeekFrom::End(0)).await);
-        self.store_file_content(clip.clone()).await
+        // an interrupted append leaves a torn record, it is discarded while opening,
+        // the header is left as is since appending does not change it
+        if let Some((record, image_file_size)) = self.store_file_content(clip.clone()).await? {
+            self.clips_file
+                .write_all(&record)
+                .await
+                .with_context(|_| error::WriteFileSnafu { file_path: self.clips_file_path() })?;
+            self.clips_file
+                .flush()
+                .await
+                .with_context(|_| error::WriteFileSnafu { file_path: self.clips_file_path() })?;
+            self.clips_file
+                .sync_data()
+                .await
+                .with_context(|_| error::WriteFileSnafu { file_path: self.clips_file_path() })?;
+            if let Some(unpinned_bytes) = self.unpinned_bytes.as_mut().filter(|_| !clip.is_pinned())
+            {
+                *unpinned_bytes += record.len() as u64 + image_file_size;
+            }
+        }
+        Ok(())
     }
 
-    async fn shrink_to(&mut self, min_capacity: usize) -> Result<(), Error> {
+    async fn shrink_to(&mut self, min_capacity: usize, max_bytes: u64) -> Result<(), Error> {
         drop(self.clips_file.flush().await);
 
-        let clips_file_path = self.clips_file_path();
-        let clips_file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .write(true)
-            .read(true)
-            .open(&clips_file_path)
-            .await
-            .with_context(|_| error::OpenFileSnafu { file_path: clips_file_path })?
-            .into_std()
-            .await;
-
-        let mut buffer_length = 1024;
-        let mut clips = tokio::task::spawn_blocking(move || {
-            let mut clips = Vec::new();
-            while let Ok(clip) =
-                bincode::deserialize_from::<_, model::v2::ClipboardValue>(&clips_file)
-            {
-                let serialized_size =
-                    usize::try_from(bincode::serialized_size(&clip).unwrap_or_default())
-                        .unwrap_or_default();
-                buffer_length = buffer_length.max(serialized_size);
-                clips.push(clip);
-            }
-            clips
-        })
-        .await
-        .context(error::JoinTaskSnafu)?;
+        let mut clips = load_values(self.clips_file_path(), self.cipher.clone()).await?;
 
+        // pinned clips are exempt from the capacity and the size limit, the newest
+        // clips are retained
         clips.sort_unstable();
-        clips.truncate(min_capacity);
-
-        let mut image_files = HashSet::new();
         let image_dir_path = self.image_dir_path();
-
-        self.clips_file
-            .set_len(0)
-            .await
-            .with_context(|_| error::TruncateFileSnafu { file_path: self.clips_file_path() })?;
-
-        {
-            let mut buffer = Vec::with_capacity(buffer_length);
-            for clip in clips {
-                buffer.clear();
-                bincode::serialize_into(&mut buffer, &clip).context(error::SeriailizeClipSnafu)?;
-
-                self.clips_file.write_all(&buffer).await.with_context(|_| {
-                    error::WriteFileSnafu { file_path: self.clips_file_path() }
-                })?;
-
-                if clip.mime.type_() == mime::IMAGE {
-                    let _ = image_files
-                        .insert(image_file_path_from_digest(&image_dir_path, &clip.data));
+        let mut records = Vec::with_capacity(clips.len());
+        let mut unpinned_count = 0;
+        let mut unpinned_bytes = 0;
+        let mut kept_unpinned_bytes = 0;
+        for clip in clips {
+            let content = encode_record(self.cipher.as_ref(), &clip)?;
+            let image_file_path = (clip.mime.type_() == mime::IMAGE)
+                .then(|| image_file_path_from_digest(&image_dir_path, &clip.data, &clip.mime));
+            if !clip.pinned {
+                let size = content.len() as u64 + image_file_size(image_file_path.as_deref()).await;
+                unpinned_count += 1;
+                unpinned_bytes += size;
+                if unpinned_count > min_capacity || (max_bytes != 0 && unpinned_bytes > max_bytes) {
+                    continue;
                 }
+                kept_unpinned_bytes += size;
             }
+            records.push((content, image_file_path));
         }
 
-        drop(self.clips_file.flush().await);
+        let mut image_files = HashSet::new();
+        let mut content = Vec::new();
+        for (record, image_file_path) in records {
+            content.extend(record);
+
+            if let Some(image_file_path) = image_file_path {
+                let _ = image_files.insert(image_file_path);
+            }
+        }
+        self.replace_clips_file(&content).await?;
+        self.unpinned_bytes = Some(kept_unpinned_bytes);
 
         if let Ok(mut entries) = tokio::fs::read_dir(&image_dir_path).await {
             while let Ok(Some(entry)) = entries.next_entry().await {
@@ -301,6 +341,465 @@ impl Driver for FileSystemDriver {
 
         self.update_header().await
     }
+
+    async fn unpinned_bytes(&mut self) -> Result<u64, Error> {
+        if let Some(unpinned_bytes) = self.unpinned_bytes {
+            return Ok(unpinned_bytes);
+        }
+        let unpinned_bytes = self.count_unpinned_bytes().await?;
+        self.unpinned_bytes = Some(unpinned_bytes);
+        Ok(unpinned_bytes)
+    }
+
+    async fn rekey(&mut self, key: Option<&HistoryEncryptionKey>) -> Result<(), Error> {
+        drop(self.clips_file.flush().await);
+        let clips = self.load().await?;
+        let cipher = key.map(Cipher::generate).transpose()?;
+        // image files are named after their digest, they could not be rewritten in
+        // place without mixing keys, the whole history is staged instead
+        stage_history(&self.file_path, clips, cipher.as_ref()).await?;
+        self.cipher = cipher;
+        commit_staged_history(&self.file_path).await?;
+        self.clips_file = open_clips_file(&self.clips_file_path()).await?;
+        self.unpinned_bytes = None;
+        Ok(())
+    }
+}
+
+// loads clips stored in an out-of-date schema, `None` is returned if the
+// history is in current schema or does not exist
+async fn load_outdated_clips(
+    schema: Option<u64>,
+    file_path: &Path,
+    clips_file_path: &Path,
+    cipher: Option<&Cipher>,
+) -> Result<Option<Vec<ClipEntry>>, Error> {
+    let image_dir_path = image_dir_path(file_path);
+    let cipher = cipher.cloned();
+    let clips = match schema {
+        Some(schema) if schema > CURRENT_SCHEMA => {
+            return Err(Error::NewerSchema { new: schema, current: CURRENT_SCHEMA })
+        }
+        Some(schema) if schema < CURRENT_SCHEMA => {
+            tracing::info!("Clip history schema `{schema}` is out-of-date");
+            let (path, dir) = (clips_file_path, image_dir_path);
+            let clips = match schema {
+                model::v1::SCHEMA_VERSION => {
+                    migrate::load::<model::v1::ClipboardValue>(path, dir, cipher, Layout::Inline)
+                        .await?
+                }
+                model::v2::SCHEMA_VERSION => {
+                    migrate::load::<model::v2::ClipboardValue>(path, dir, cipher, Layout::Plain)
+                        .await?
+                }
+                model::v3::SCHEMA_VERSION => {
+                    migrate::load::<model::v3::ClipboardValue>(path, dir, cipher, Layout::Plain)
+                        .await?
+                }
+                model::v4::SCHEMA_VERSION => {
+                    migrate::load::<model::v4::ClipboardValue>(path, dir, cipher, Layout::Sealed)
+                        .await?
+                }
+                model::v5::SCHEMA_VERSION => {
+                    migrate::load::<model::v5::ClipboardValue>(path, dir, cipher, Layout::Sealed)
+                        .await?
+                }
+                model::v6::SCHEMA_VERSION => {
+                    migrate::load::<model::v6::ClipboardValue>(path, dir, cipher, Layout::Sealed)
+                        .await?
+                }
+                model::v7::SCHEMA_VERSION => {
+                    migrate::load::<model::v7::ClipboardValue>(path, dir, cipher, Layout::Sealed)
+                        .await?
+                }
+                model::v8::SCHEMA_VERSION => {
+                    migrate::load::<model::v8::ClipboardValue>(path, dir, cipher, Layout::Sealed)
+                        .await?
+                }
+                model::v9::SCHEMA_VERSION => {
+                    migrate::load::<model::v9::ClipboardValue>(path, dir, cipher, Layout::Framed)
+                        .await?
+                }
+                _ => return Ok(None),
+            };
+            Some(clips)
+        }
+        _ => None,
+    };
+    Ok(clips)
+}
+
+async fn load_values(
+    clips_file_path: PathBuf,
+    cipher: Option<Cipher>,
+) -> Result<Vec<model::v10::ClipboardValue>, Error> {
+    let content = read_clips_file(&clips_file_path).await?;
+
+    tokio::task::spawn_blocking(move || {
+        let (records, _) = split_records(&content);
+        let records_len =
+            records.iter().map(|record| RECORD_HEADER_SIZE + record.len()).sum::<usize>();
+        if records_len < content.len() {
+            tracing::error!(
+                "Skip {len} bytes of corrupted records in `{path}`, run `clipcatd history repair` \
+                 to repair it",
+                len = content.len() - records_len,
+                path = clips_file_path.display()
+            );
+        }
+
+        let mut values = Vec::new();
+        for record in records {
+            let value = cipher::unseal(cipher.as_ref(), record.to_vec()).and_then(|data| {
+                bincode::deserialize::<model::v10::ClipboardValue>(&data)
+                    .context(error::DeseriailizeClipSnafu)
+            });
+            match value {
+                Ok(value) => values.push(value),
+                Err(err) => tracing::error!("Skip unreadable clip, error: {err}"),
+            }
+        }
+
+        // a clip is appended again whenever it changes, only its last record is kept
+        let mut seen = HashSet::new();
+        values.reverse();
+        values.retain(|value| seen.insert((value.mime.clone(), value.data.clone())));
+        values.reverse();
+        values
+    })
+    .await
+    .context(error::JoinTaskSnafu)
+}
+
+async fn load_clips(
+    clips_file_path: PathBuf,
+    image_dir_path: PathBuf,
+    cipher: Option<Cipher>,
+) -> Result<Vec<ClipEntry>, Error> {
+    let values = load_values(clips_file_path, cipher.clone()).await?;
+
+    tokio::task::spawn_blocking(move || {
+        let clips = values
+            .into_iter()
+            .filter_map(|value| clip_from_value(value, Some(&image_dir_path), cipher.as_ref()))
+            .collect();
+        Ok(clips)
+    })
+    .await
+    .context(error::JoinTaskSnafu)?
+}
+
+// image data is kept in separate files, `image_dir_path` is `None` for schemas
+// storing it inline
+pub(super) fn clip_from_value(
+    value: model::v10::ClipboardValue,
+    image_dir_path: Option<&Path>,
+    cipher: Option<&Cipher>,
+) -> Option<ClipEntry> {
+    let model::v10::ClipboardValue {
+        timestamp,
+        mime,
+        data,
+        pinned,
+        representations,
+        source_application,
+        use_count,
+        last_used,
+        tags,
+        kind,
+    } = value;
+    let data = match image_dir_path {
+        Some(image_dir_path) if mime.type_() == mime::IMAGE => {
+            let file_path = image_file_path_from_digest(image_dir_path, &data, &mime);
+            let maybe_data = std::fs::read(&file_path)
+                .context(error::ReadFileSnafu { file_path })
+                .and_then(|data| cipher::unseal(cipher, data));
+            match maybe_data {
+                Ok(data) => data,
+                Err(err) => {
+                    tracing::error!("{err}");
+                    return None;
+                }
+            }
+        }
+        _ => data,
+    };
+
+    let mut clip = ClipEntry::new(&data, &mime, ClipboardKind::from(kind), Some(timestamp)).ok()?;
+    clip.set_pinned(pinned);
+    clip.set_representations(representations.into_iter().map(ClipRepresentation::from));
+    clip.set_source_application(source_application);
+    clip.set_usage(use_count, last_used);
+    clip.set_tags(tags);
+    Some(clip)
+}
+
+fn open_cipher(
+    encryption: Option<cipher::Header>,
+    key: Option<&HistoryEncryptionKey>,
+) -> Result<Option<Cipher>, Error> {
+    match (encryption, key) {
+        (Some(header), Some(key)) => Cipher::from_header(key, &header).map(Some),
+        (Some(_), None) => Err(Error::EncryptionKeyRequired),
+        (None, _) => Ok(None),
+    }
+}
+
+// returns the number of bytes of the record and the image file of `value`
+async fn stored_size(
+    image_dir_path: &Path,
+    cipher: Option<&Cipher>,
+    value: &model::v10::ClipboardValue,
+) -> Result<u64, Error> {
+    let record = encode_record(cipher, value)?;
+    let image_file_path = (value.mime.type_() == mime::IMAGE)
+        .then(|| image_file_path_from_digest(image_dir_path, &value.data, &value.mime));
+    Ok(record.len() as u64 + image_file_size(image_file_path.as_deref()).await)
+}
+
+async fn image_file_size(file_path: Option<&Path>) -> u64 {
+    match file_path {
+        Some(file_path) => {
+            tokio::fs::metadata(file_path).await.map_or(0, |metadata| metadata.len())
+        }
+        None => 0,
+    }
+}
+
+fn encode_record(
+    cipher: Option<&Cipher>,
+    value: &model::v10::ClipboardValue,
+) -> Result<Vec<u8>, Error> {
+    let data = bincode::serialize(value).context(error::SeriailizeClipSnafu)?;
+    let payload = cipher::seal(cipher, data)?;
+    let mut record = Vec::with_capacity(RECORD_HEADER_SIZE + payload.len());
+    record.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    record.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    record.extend_from_slice(&payload);
+    Ok(record)
+}
+
+// returns the payload of the record at the beginning of `content` and the
+// length of the whole record, `None` is returned if the record is incomplete or
+// its checksum does not match
+fn read_record(content: &[u8]) -> Option<(&[u8], usize)> {
+    let len = content.get(..size_of::<u64>())?.try_into().ok()?;
+    let checksum = content.get(size_of::<u64>()..RECORD_HEADER_SIZE)?.try_into().ok()?;
+    let len = usize::try_from(u64::from_le_bytes(len)).ok().filter(|&len| len > 0)?;
+    let payload = content.get(RECORD_HEADER_SIZE..)?.get(..len)?;
+    (crc32fast::hash(payload) == u32::from_le_bytes(checksum))
+        .then_some((payload, RECORD_HEADER_SIZE + len))
+}
+
+// returns the payloads of the well-formed records of `content` and the end of
+// the last one, corrupted regions are skipped by looking for the next
+// well-formed record byte by byte
+pub(super) fn split_records(content: &[u8]) -> (Vec<&[u8]>, usize) {
+    let mut records = Vec::new();
+    let mut offset = 0;
+    let mut end = 0;
+    while offset < content.len() {
+        if let Some((payload, len)) = read_record(&content[offset..]) {
+            records.push(payload);
+            offset += len;
+            end = offset;
+        } else {
+            offset += 1;
+        }
+    }
+    (records, end)
+}
+
+// an interrupted append leaves a torn record at the end of the clips file, it
+// is truncated so that the records appended later are readable
+async fn discard_torn_tail(clips_file_path: &Path) -> Result<(), Error> {
+    let content = read_clips_file(clips_file_path).await?;
+    let (_, end) = split_records(&content);
+    if end == content.len() {
+        return Ok(());
+    }
+
+    tracing::warn!(
+        "Discard {len} bytes of torn record at the end of `{path}`",
+        len = content.len() - end,
+        path = clips_file_path.display()
+    );
+    let clips_file = OpenOptions::new()
+        .write(true)
+        .open(clips_file_path)
+        .await
+        .context(error::OpenFileSnafu { file_path: clips_file_path.to_path_buf() })?;
+    clips_file
+        .set_len(end as u64)
+        .await
+        .context(error::TruncateFileSnafu { file_path: clips_file_path.to_path_buf() })?;
+    clips_file
+        .sync_all()
+        .await
+        .context(error::WriteFileSnafu { file_path: clips_file_path.to_path_buf() })
+}
+
+pub(super) async fn read_clips_file(clips_file_path: &Path) -> Result<Vec<u8>, Error> {
+    match tokio::fs::read(clips_file_path).await {
+        Ok(content) => Ok(content),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(source) => Err(Error::ReadFile { source, file_path: clips_file_path.to_path_buf() }),
+    }
+}
+
+async fn open_clips_file(clips_file_path: &Path) -> Result<File, Error> {
+    OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(clips_file_path)
+        .await
+        .context(error::OpenFileSnafu { file_path: clips_file_path.to_path_buf() })
+}
+
+async fn write_header(header_file_path: &Path, cipher: Option<&Cipher>) -> Result<(), Error> {
+    let content = serde_json::to_string_pretty(&model::FileHeader {
+        schema: CURRENT_SCHEMA,
+        last_update: OffsetDateTime::now_utc(),
+        encryption: cipher.map(Cipher::header),
+    })
+    .context(error::SeriailizeHistoryHeaderSnafu)?;
+
+    write_file_atomically(header_file_path, content.as_bytes()).await
+}
+
+// writes a complete history of `clips` into the staging directory of the
+// history at `file_path`, it is renamed once everything is synced so that only
+// a complete history is committed
+async fn stage_history(
+    file_path: &Path,
+    clips: Vec<ClipEntry>,
+    cipher: Option<&Cipher>,
+) -> Result<(), Error> {
+    let staging_dir_path = staging_dir_path(file_path);
+    remove_dir_if_exists(&staging_dir_path).await?;
+
+    let image_dir_path = image_dir_path(&staging_dir_path);
+    tokio::fs::create_dir_all(&image_dir_path)
+        .await
+        .context(error::CreateDirectorySnafu { file_path: image_dir_path.clone() })?;
+
+    let mut content = Vec::new();
+    for clip in clips {
+        if clip.mime().type_() == mime::IMAGE {
+            let file_path =
+                image_file_path_from_digest(&image_dir_path, clip.sha256_digest(), &clip.mime());
+            let image = match clip.encoded() {
+                Ok(image) => image,
+                Err(err) => {
+                    tracing::error!("Error occurs while encoding clip, error: {err}");
+                    continue;
+                }
+            };
+            write_file_atomically(&file_path, &cipher::seal(cipher, image)?).await?;
+        }
+        content.extend(encode_record(cipher, &model::v10::ClipboardValue::from(clip))?);
+    }
+    write_file_atomically(&clips_file_path(&staging_dir_path), &content).await?;
+    write_header(&header_file_path(&staging_dir_path), cipher).await?;
+
+    let staged_dir_path = staged_dir_path(file_path);
+    tokio::fs::rename(&staging_dir_path, &staged_dir_path)
+        .await
+        .context(error::RenameFileSnafu { from: staging_dir_path, to: staged_dir_path.clone() })?;
+    sync_directory(file_path).await;
+    Ok(())
+}
+
+// moves the files of a staged history over the current ones, the header is
+// moved last, each step is skipped once it is done so that an interrupted
+// commit is finished when the history is opened again
+async fn commit_staged_history(file_path: &Path) -> Result<(), Error> {
+    let staged_dir_path = staged_dir_path(file_path);
+    if !staged_dir_path.exists() {
+        return Ok(());
+    }
+
+    let staged_image_dir_path = image_dir_path(&staged_dir_path);
+    if staged_image_dir_path.exists() {
+        let image_dir_path = image_dir_path(file_path);
+        remove_dir_if_exists(&image_dir_path).await?;
+        tokio::fs::rename(&staged_image_dir_path, &image_dir_path).await.context(
+            error::RenameFileSnafu { from: staged_image_dir_path, to: image_dir_path.clone() },
+        )?;
+    }
+    for (from, to) in [
+        (clips_file_path(&staged_dir_path), clips_file_path(file_path)),
+        (header_file_path(&staged_dir_path), header_file_path(file_path)),
+    ] {
+        if from.exists() {
+            tokio::fs::rename(&from, &to)
+                .await
+                .context(error::RenameFileSnafu { from, to: to.clone() })?;
+        }
+    }
+    sync_directory(file_path).await;
+
+    remove_dir_if_exists(&staged_dir_path).await
+}
+
+async fn remove_dir_if_exists(dir_path: &Path) -> Result<(), Error> {
+    match tokio::fs::remove_dir_all(dir_path).await {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            Err(Error::RemoveFile { source: err, file_path: dir_path.to_path_buf() })
+        }
+        _ => Ok(()),
+    }
+}
+
+// renames in `dir_path` are durable once it is synced
+async fn sync_directory(dir_path: &Path) {
+    if let Ok(dir) = File::open(dir_path).await {
+        drop(dir.sync_all().await);
+    }
+}
+
+// writes `content` to a temporary file beside `file_path` and renames it over
+// `file_path` once it is synced, a crash leaves either the old or the new
+// content
+async fn write_file_atomically(file_path: &Path, content: &[u8]) -> Result<(), Error> {
+    let mut tmp_file_name = file_path.file_name().unwrap_or_default().to_os_string();
+    tmp_file_name.push(".tmp");
+    let tmp_file_path = file_path.with_file_name(tmp_file_name);
+
+    let mut file = File::create(&tmp_file_path)
+        .await
+        .context(error::OpenFileSnafu { file_path: tmp_file_path.clone() })?;
+    file.write_all(content)
+        .await
+        .context(error::WriteFileSnafu { file_path: tmp_file_path.clone() })?;
+    file.sync_all().await.context(error::WriteFileSnafu { file_path: tmp_file_path.clone() })?;
+    drop(file);
+
+    tokio::fs::rename(&tmp_file_path, file_path)
+        .await
+        .context(error::RenameFileSnafu { from: tmp_file_path, to: file_path.to_path_buf() })?;
+
+    if let Some(dir_path) = file_path.parent() {
+        sync_directory(dir_path).await;
+    }
+    Ok(())
+}
+
+// a history being staged, it is discarded if it is left incomplete
+fn staging_dir_path<P>(file_path: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    [file_path.as_ref(), Path::new("staging.tmp")].into_iter().collect()
+}
+
+// a complete history waiting to replace the current one
+fn staged_dir_path<P>(file_path: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    [file_path.as_ref(), Path::new("staging")].into_iter().collect()
 }
 
 fn header_file_path<P>(file_path: P) -> PathBuf
@@ -325,14 +824,450 @@ where
 }
 
 #[inline]
-fn image_file_path_from_digest<P>(image_dir_path: P, digest: &[u8]) -> PathBuf
+fn image_file_path_from_digest<P>(image_dir_path: P, digest: &[u8], mime: &mime::Mime) -> PathBuf
 where
     P: AsRef<Path>,
 {
-    [image_dir_path.as_ref(), Path::new(&image_file_name(digest))].iter().collect::<PathBuf>()
+    [image_dir_path.as_ref(), Path::new(&image_file_name(digest, mime))].iter().collect::<PathBuf>()
 }
 
+// images are stored in their original format, the extension follows the format
 #[inline]
-fn image_file_name(digest: &[u8]) -> String {
-    format!("{digest}.png", digest = hex::encode(digest))
+fn image_file_name(digest: &[u8], mime: &mime::Mime) -> String {
+    format!(
+        "{digest}.{extension}",
+        digest = hex::encode(digest),
+        extension = clipcat_base::utils::image::file_extension(mime)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{ClipEntry, ClipRepresentation, ClipboardKind};
+
+    use crate::{
+        config::HistoryEncryptionKey,
+        history::{
+            cipher::Cipher,
+            driver::{
+                fs::{stage_history, staged_dir_path, staging_dir_path},
+                Driver, FileSystemDriver,
+            },
+            Error,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_encryption() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-encryption-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let key = HistoryEncryptionKey::from(b"secret".to_vec());
+        let new_key = HistoryEncryptionKey::from(b"new secret".to_vec());
+        let clips = vec![ClipEntry::from_string("plaintext", ClipboardKind::Clipboard)];
+
+        FileSystemDriver::new(&file_path, None).await.unwrap().save(&clips).await.unwrap();
+
+        // a plaintext history is encrypted while opening with a key
+        let mut driver = FileSystemDriver::new(&file_path, Some(&key)).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), clips);
+        let content = tokio::fs::read(driver.clips_file_path()).await.unwrap();
+        assert!(!content.windows(b"plaintext".len()).any(|window| window == b"plaintext"));
+        drop(driver);
+
+        assert!(matches!(
+            FileSystemDriver::new(&file_path, None).await,
+            Err(Error::EncryptionKeyRequired)
+        ));
+        assert!(matches!(
+            FileSystemDriver::new(&file_path, Some(&new_key)).await,
+            Err(Error::WrongEncryptionKey)
+        ));
+
+        let mut driver = FileSystemDriver::new(&file_path, Some(&key)).await.unwrap();
+        driver.rekey(Some(&new_key)).await.unwrap();
+        drop(driver);
+
+        let mut driver = FileSystemDriver::new(&file_path, Some(&new_key)).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), clips);
+        driver.rekey(None).await.unwrap();
+        drop(driver);
+
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), clips);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_rekey() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-rekey-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let key = HistoryEncryptionKey::from(b"secret".to_vec());
+        let new_key = HistoryEncryptionKey::from(b"new secret".to_vec());
+        let clips = vec![ClipEntry::from_string("plaintext", ClipboardKind::Clipboard)];
+
+        let mut driver = FileSystemDriver::new(&file_path, Some(&key)).await.unwrap();
+        driver.save(&clips).await.unwrap();
+        drop(driver);
+
+        // an incomplete staging directory is discarded, the old key is still in use
+        let new_cipher = Cipher::generate(&new_key).unwrap();
+        stage_history(&file_path, clips.clone(), Some(&new_cipher)).await.unwrap();
+        tokio::fs::rename(staged_dir_path(&file_path), staging_dir_path(&file_path)).await.unwrap();
+        let mut driver = FileSystemDriver::new(&file_path, Some(&key)).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), clips);
+        assert!(!staging_dir_path(&file_path).exists());
+        drop(driver);
+
+        // a complete staged history is committed, even if its images were moved
+        // before the interruption
+        stage_history(&file_path, clips.clone(), Some(&new_cipher)).await.unwrap();
+        tokio::fs::rename(staged_dir_path(&file_path).join("clips"), file_path.join("clips"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            FileSystemDriver::new(&file_path, Some(&key)).await,
+            Err(Error::WrongEncryptionKey)
+        ));
+        let mut driver = FileSystemDriver::new(&file_path, Some(&new_key)).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), clips);
+        assert!(!staged_dir_path(&file_path).exists());
+
+        driver.rekey(Some(&key)).await.unwrap();
+        drop(driver);
+        let mut driver = FileSystemDriver::new(&file_path, Some(&key)).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), clips);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_torn_record() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-torn-record-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let clips = (0..4)
+            .map(|i| {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                ClipEntry::from_string(i, ClipboardKind::Clipboard)
+            })
+            .collect::<Vec<_>>();
+
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        driver.save(&clips[..1]).await.unwrap();
+        let record_len =
+            usize::try_from(tokio::fs::metadata(driver.clips_file_path()).await.unwrap().len())
+                .unwrap();
+        driver.save(&clips[..3]).await.unwrap();
+        let clips_file_path = driver.clips_file_path();
+        drop(driver);
+
+        // flip a byte of the second record and append half of a record, as if
+        // writing is interrupted
+        let mut content = tokio::fs::read(&clips_file_path).await.unwrap();
+        content[record_len + record_len / 2] ^= 0xff;
+        let torn_record = content[..record_len / 2].to_vec();
+        content.extend(torn_record);
+        tokio::fs::write(&clips_file_path, &content).await.unwrap();
+
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        let mut loaded = driver.load().await.unwrap();
+        loaded.sort_unstable_by_key(ClipEntry::timestamp);
+        assert_eq!(loaded, vec![clips[0].clone(), clips[2].clone()]);
+
+        driver.put(&clips[3]).await.unwrap();
+        drop(driver);
+
+        let mut loaded =
+            FileSystemDriver::new(&file_path, None).await.unwrap().load().await.unwrap();
+        loaded.sort_unstable_by_key(ClipEntry::timestamp);
+        assert_eq!(loaded, vec![clips[0].clone(), clips[2].clone(), clips[3].clone()]);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_shrink_to_max_bytes() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-shrink-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let clips = (0..5)
+            .map(|i| {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                let mut clip = ClipEntry::from_string(i, ClipboardKind::Clipboard);
+                clip.set_pinned(i == 0);
+                clip
+            })
+            .collect::<Vec<_>>();
+
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        driver.save(&clips[3..]).await.unwrap();
+        let max_bytes = tokio::fs::metadata(driver.clips_file_path()).await.unwrap().len();
+
+        driver.save_and_shrink_to(&clips, 10, max_bytes).await.unwrap();
+        let mut loaded = driver.load().await.unwrap();
+        loaded.sort_unstable_by_key(ClipEntry::timestamp);
+        assert_eq!(loaded, vec![clips[0].clone(), clips[3].clone(), clips[4].clone()]);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_representations() {
+        let file_path = std::env::temp_dir().join(format!(
+            "clipcat-fs-driver-representations-test-{pid}",
+            pid = std::process::id()
+        ));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let mut clip = ClipEntry::from_string("link", ClipboardKind::Clipboard);
+        clip.set_representations([ClipRepresentation::new(
+            "text/html".parse().unwrap(),
+            "<a>link</a>",
+        )]);
+        let clips = vec![clip];
+
+        FileSystemDriver::new(&file_path, None).await.unwrap().save(&clips).await.unwrap();
+
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        let loaded = driver.load().await.unwrap();
+        assert_eq!(loaded, clips);
+        assert_eq!(loaded[0].representations(), clips[0].representations());
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_usage() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-usage-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let mut clip = ClipEntry::from_string("reused", ClipboardKind::Clipboard);
+        clip.set_usage(3, Some(clip.timestamp()));
+        let clips = vec![clip];
+
+        FileSystemDriver::new(&file_path, None).await.unwrap().save(&clips).await.unwrap();
+
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        let loaded = driver.load().await.unwrap();
+        assert_eq!(loaded[0].use_count(), 3);
+        assert_eq!(loaded[0].last_used(), clips[0].last_used());
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_tags() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-tags-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let mut clip = ClipEntry::from_string("kubectl apply", ClipboardKind::Clipboard);
+        clip.set_tags(["deploy", "work"]);
+        let clips = vec![clip];
+
+        FileSystemDriver::new(&file_path, None).await.unwrap().save(&clips).await.unwrap();
+
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        let loaded = driver.load().await.unwrap();
+        assert_eq!(loaded[0].tags(), ["deploy", "work"]);
+
+        // a tagged clip is appended again, its last record is kept while shrinking
+        let mut clip = loaded[0].clone();
+        assert!(clip.add_tag("urgent"));
+        driver.put(&clip).await.unwrap();
+        driver.shrink_to(usize::MAX, 0).await.unwrap();
+        let loaded = driver.load().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].tags(), ["deploy", "urgent", "work"]);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_kind() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-kind-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let clips = vec![ClipEntry::from_string("selected", ClipboardKind::Primary)];
+        FileSystemDriver::new(&file_path, None).await.unwrap().save(&clips).await.unwrap();
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        driver.put(&ClipEntry::from_string("secondary", ClipboardKind::Secondary)).await.unwrap();
+        drop(driver);
+
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        let mut loaded = driver.load().await.unwrap();
+        loaded.sort_unstable_by_key(ClipEntry::as_utf8_string);
+        assert_eq!(
+            loaded.iter().map(ClipEntry::kind).collect::<Vec<_>>(),
+            [ClipboardKind::Secondary, ClipboardKind::Primary]
+        );
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_original_image_format() {
+        const GIF: &[u8] = &[
+            0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0xff, 0xff, 0xff, 0x2c, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00,
+            0x00, 0x02, 0x02, 0x44, 0x01, 0x00, 0x3b,
+        ];
+        const SVG: &[u8] = br#"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1"/>"#;
+
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-image-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let clips = [(GIF, mime::IMAGE_GIF), (SVG, mime::IMAGE_SVG)]
+            .into_iter()
+            .map(|(data, mime)| {
+                ClipEntry::new(data, &mime, ClipboardKind::Clipboard, None).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        driver.save(&clips).await.unwrap();
+
+        let mut extensions = std::fs::read_dir(driver.image_dir_path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path().extension().unwrap().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        extensions.sort_unstable();
+        assert_eq!(extensions, ["gif", "svg"]);
+
+        let loaded = driver.load().await.unwrap();
+        assert_eq!(loaded, clips);
+        assert_eq!(loaded[0].mime(), mime::IMAGE_GIF);
+        assert_eq!(loaded[0].encoded().unwrap(), GIF);
+        assert_eq!(loaded[0].as_ref().image_dimensions(), Some((1, 1)));
+        assert_eq!(loaded[1].encoded().unwrap(), SVG);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_migrate() {
+        use time::OffsetDateTime;
+
+        use crate::history::driver::fs::{clips_file_path, header_file_path, model};
+
+        async fn write_history(file_path: &std::path::Path, schema: u64, content: Vec<u8>) {
+            drop(tokio::fs::remove_dir_all(file_path).await);
+            tokio::fs::create_dir_all(file_path).await.unwrap();
+            let header = model::FileHeader {
+                schema,
+                last_update: OffsetDateTime::now_utc(),
+                encryption: None,
+            };
+            tokio::fs::write(header_file_path(file_path), serde_json::to_vec(&header).unwrap())
+                .await
+                .unwrap();
+            tokio::fs::write(clips_file_path(file_path), content).await.unwrap();
+        }
+
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-migrate-test-{pid}", pid = std::process::id()));
+        let timestamp = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap();
+
+        // values of v3 follow one another
+        let content = ["first", "second"]
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, text)| {
+                bincode::serialize(&model::v3::ClipboardValue {
+                    timestamp: timestamp - time::Duration::seconds(i64::try_from(i).unwrap()),
+                    mime: mime::TEXT_PLAIN_UTF_8,
+                    data: text.as_bytes().to_vec(),
+                    pinned: i == 0,
+                })
+                .unwrap()
+            })
+            .collect();
+        write_history(&file_path, model::v3::SCHEMA_VERSION, content).await;
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        let mut loaded = driver.load().await.unwrap();
+        loaded.sort_unstable_by_key(|clip| std::cmp::Reverse(clip.timestamp()));
+        assert_eq!(
+            loaded.iter().map(ClipEntry::as_utf8_string).collect::<Vec<_>>(),
+            ["first", "second"]
+        );
+        assert!(loaded[0].is_pinned());
+        assert!(!loaded[1].is_pinned());
+        drop(driver);
+
+        // values of v8 are sealed, an unreadable one is skipped
+        let value = model::v8::ClipboardValue {
+            timestamp,
+            mime: mime::TEXT_PLAIN_UTF_8,
+            data: b"tagged".to_vec(),
+            pinned: false,
+            representations: Vec::new(),
+            source_application: Some("editor".to_string()),
+            use_count: 2,
+            last_used: Some(timestamp),
+            tags: vec!["work".to_string()],
+        };
+        let mut content = bincode::serialize(&b"garbage".to_vec()).unwrap();
+        content.extend(bincode::serialize(&bincode::serialize(&value).unwrap()).unwrap());
+        write_history(&file_path, model::v8::SCHEMA_VERSION, content).await;
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        let loaded = driver.load().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].as_utf8_string(), "tagged");
+        assert_eq!(loaded[0].source_application(), Some("editor"));
+        assert_eq!(loaded[0].use_count(), 2);
+        assert_eq!(loaded[0].tags(), ["work"]);
+        drop(driver);
+
+        // the migrated history is in current schema
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), loaded);
+        drop(driver);
+
+        // values of v9 are the same as v8 and framed, the kind was not stored
+        let mut content = b"garbage".to_vec();
+        let data = bincode::serialize(&value).unwrap();
+        content.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        content.extend_from_slice(&crc32fast::hash(&data).to_le_bytes());
+        content.extend(data);
+        write_history(&file_path, model::v9::SCHEMA_VERSION, content).await;
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        let loaded = driver.load().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].as_utf8_string(), "tagged");
+        assert_eq!(loaded[0].kind(), ClipboardKind::Clipboard);
+        assert_eq!(loaded[0].tags(), ["work"]);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_put_keeps_header() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-fs-driver-header-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        let header = tokio::fs::read(driver.header_file_path()).await.unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        let clip = ClipEntry::from_string("appended", ClipboardKind::Clipboard);
+        driver.put(&clip).await.unwrap();
+        assert_eq!(tokio::fs::read(driver.header_file_path()).await.unwrap(), header);
+        drop(driver);
+
+        let mut driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        assert_eq!(tokio::fs::read(driver.header_file_path()).await.unwrap(), header);
+        assert_eq!(driver.load().await.unwrap(), [clip]);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
 }
@@ -1,2 +1,54 @@
 pub mod v1;
+pub mod v10;
 pub mod v2;
+pub mod v3;
+pub mod v4;
+pub mod v5;
+pub mod v6;
+pub mod v7;
+pub mod v8;
+pub mod v9;
+
+use clipcat_base::ClipRepresentation;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::history::cipher;
+
+// the header of every schema, fields added later are optional
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FileHeader {
+    pub schema: u64,
+
+    #[serde(with = "time::serde::iso8601")]
+    pub last_update: OffsetDateTime,
+
+    // clips and images are encrypted if it is present, since v4
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<cipher::Header>,
+}
+
+// an alternative representation of a clip, since v5
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Representation {
+    #[serde(with = "clipcat_base::serde::mime")]
+    pub mime: mime::Mime,
+
+    pub data: Vec<u8>,
+}
+
+impl From<&ClipRepresentation> for Representation {
+    fn from(ClipRepresentation { mime, data }: &ClipRepresentation) -> Self {
+        Self { mime: mime.clone(), data: data.to_vec() }
+    }
+}
+
+impl From<Representation> for ClipRepresentation {
+    fn from(Representation { mime, data }: Representation) -> Self { Self::new(mime, data) }
+}
+
+// a value of an out-of-date schema, it is upgraded to the value of the current
+// schema one schema at a time
+pub trait Upgrade: DeserializeOwned + Send + 'static {
+    fn upgrade(self) -> v10::ClipboardValue;
+}
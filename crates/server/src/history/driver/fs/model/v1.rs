@@ -1,19 +1,11 @@
-use clipcat_base::{ClipEntry, ClipboardKind};
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct FileHeader {
-    pub schema: u64,
+use crate::history::driver::fs::model::{v10, v2, Upgrade};
 
-    #[serde(with = "time::serde::iso8601")]
-    pub last_update: OffsetDateTime,
-}
-
-impl FileHeader {
-    pub const SCHEMA_VERSION: u64 = 1;
-}
+pub const SCHEMA_VERSION: u64 = 1;
 
+// images are stored in values as is
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ClipboardValue {
     pub timestamp: OffsetDateTime,
@@ -24,18 +16,12 @@ pub struct ClipboardValue {
     pub data: Vec<u8>,
 }
 
-impl From<ClipboardValue> for ClipEntry {
+impl From<ClipboardValue> for v2::ClipboardValue {
     fn from(ClipboardValue { timestamp, mime, data }: ClipboardValue) -> Self {
-        Self::new(&data, &mime, ClipboardKind::Clipboard, Some(timestamp)).unwrap_or_default()
+        Self { timestamp, mime, data }
     }
 }
 
-impl From<ClipEntry> for ClipboardValue {
-    fn from(entry: ClipEntry) -> Self {
-        Self {
-            data: entry.encoded().unwrap_or_default(),
-            mime: entry.mime(),
-            timestamp: entry.timestamp(),
-        }
-    }
+impl Upgrade for ClipboardValue {
+    fn upgrade(self) -> v10::ClipboardValue { v2::ClipboardValue::from(self).upgrade() }
 }
@@ -0,0 +1,76 @@
+use std::cmp::Ordering;
+
+use clipcat_base::ClipEntry;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::history::driver::fs::model::{Representation, Upgrade};
+
+pub const SCHEMA_VERSION: u64 = 10;
+
+// each record of the clips file is a `ClipboardValue` serialized with bincode,
+// then sealed with the cipher of history and framed with its length and a
+// CRC-32 checksum
+#[derive(Clone, Debug, Deserialize, Eq, Serialize)]
+pub struct ClipboardValue {
+    pub timestamp: OffsetDateTime,
+
+    #[serde(with = "clipcat_base::serde::mime")]
+    pub mime: mime::Mime,
+
+    pub data: Vec<u8>,
+
+    pub pinned: bool,
+
+    pub representations: Vec<Representation>,
+
+    pub source_application: Option<String>,
+
+    pub use_count: u64,
+
+    pub last_used: Option<OffsetDateTime>,
+
+    pub tags: Vec<String>,
+
+    // `ClipboardKind` as an integer, since v10
+    pub kind: i32,
+}
+
+impl From<ClipEntry> for ClipboardValue {
+    fn from(entry: ClipEntry) -> Self {
+        let data = if entry.mime().type_() == mime::IMAGE {
+            entry.sha256_digest().to_vec()
+        } else {
+            entry.encoded().unwrap_or_default()
+        };
+        let representations = entry.representations().iter().map(Representation::from).collect();
+        Self {
+            data,
+            mime: entry.mime(),
+            timestamp: entry.timestamp(),
+            pinned: entry.is_pinned(),
+            representations,
+            source_application: entry.source_application().map(ToString::to_string),
+            use_count: entry.use_count(),
+            last_used: entry.last_used(),
+            tags: entry.tags().to_vec(),
+            kind: i32::from(entry.kind()),
+        }
+    }
+}
+
+impl PartialOrd for ClipboardValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for ClipboardValue {
+    fn cmp(&self, other: &Self) -> Ordering { other.timestamp.cmp(&self.timestamp) }
+}
+
+impl PartialEq for ClipboardValue {
+    fn eq(&self, other: &Self) -> bool { self.data == other.data }
+}
+
+impl Upgrade for ClipboardValue {
+    fn upgrade(self) -> Self { self }
+}
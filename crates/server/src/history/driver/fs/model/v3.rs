@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::history::driver::fs::model::{v10, v4, Upgrade};
+
+pub const SCHEMA_VERSION: u64 = 3;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClipboardValue {
+    pub timestamp: OffsetDateTime,
+
+    #[serde(with = "clipcat_base::serde::mime")]
+    pub mime: mime::Mime,
+
+    pub data: Vec<u8>,
+
+    pub pinned: bool,
+}
+
+impl From<ClipboardValue> for v4::ClipboardValue {
+    fn from(ClipboardValue { timestamp, mime, data, pinned }: ClipboardValue) -> Self {
+        Self { timestamp, mime, data, pinned }
+    }
+}
+
+impl Upgrade for ClipboardValue {
+    fn upgrade(self) -> v10::ClipboardValue { v4::ClipboardValue::from(self).upgrade() }
+}
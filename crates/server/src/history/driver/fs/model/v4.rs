@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::history::driver::fs::model::{v10, v5, Upgrade};
+
+pub const SCHEMA_VERSION: u64 = 4;
+
+// the clips file is a sequence of records, each record is a `ClipboardValue`
+// serialized with bincode, then sealed with the cipher of history
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClipboardValue {
+    pub timestamp: OffsetDateTime,
+
+    #[serde(with = "clipcat_base::serde::mime")]
+    pub mime: mime::Mime,
+
+    pub data: Vec<u8>,
+
+    pub pinned: bool,
+}
+
+impl From<ClipboardValue> for v5::ClipboardValue {
+    fn from(ClipboardValue { timestamp, mime, data, pinned }: ClipboardValue) -> Self {
+        Self { timestamp, mime, data, pinned, representations: Vec::new() }
+    }
+}
+
+impl Upgrade for ClipboardValue {
+    fn upgrade(self) -> v10::ClipboardValue { v5::ClipboardValue::from(self).upgrade() }
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::history::driver::fs::model::{v10, v6, Representation, Upgrade};
+
+pub const SCHEMA_VERSION: u64 = 5;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClipboardValue {
+    pub timestamp: OffsetDateTime,
+
+    #[serde(with = "clipcat_base::serde::mime")]
+    pub mime: mime::Mime,
+
+    pub data: Vec<u8>,
+
+    pub pinned: bool,
+
+    pub representations: Vec<Representation>,
+}
+
+impl From<ClipboardValue> for v6::ClipboardValue {
+    fn from(
+        ClipboardValue { timestamp, mime, data, pinned, representations }: ClipboardValue,
+    ) -> Self {
+        Self { timestamp, mime, data, pinned, representations, source_application: None }
+    }
+}
+
+impl Upgrade for ClipboardValue {
+    fn upgrade(self) -> v10::ClipboardValue { v6::ClipboardValue::from(self).upgrade() }
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::history::driver::fs::model::{v10, v7, Representation, Upgrade};
+
+pub const SCHEMA_VERSION: u64 = 6;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClipboardValue {
+    pub timestamp: OffsetDateTime,
+
+    #[serde(with = "clipcat_base::serde::mime")]
+    pub mime: mime::Mime,
+
+    pub data: Vec<u8>,
+
+    pub pinned: bool,
+
+    pub representations: Vec<Representation>,
+
+    pub source_application: Option<String>,
+}
+
+impl From<ClipboardValue> for v7::ClipboardValue {
+    fn from(
+        ClipboardValue { timestamp, mime, data, pinned, representations, source_application }: ClipboardValue,
+    ) -> Self {
+        Self {
+            timestamp,
+            mime,
+            data,
+            pinned,
+            representations,
+            source_application,
+            use_count: 0,
+            last_used: None,
+        }
+    }
+}
+
+impl Upgrade for ClipboardValue {
+    fn upgrade(self) -> v10::ClipboardValue { v7::ClipboardValue::from(self).upgrade() }
+}
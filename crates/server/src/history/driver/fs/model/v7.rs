@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::history::driver::fs::model::{v10, v8, Representation, Upgrade};
+
+pub const SCHEMA_VERSION: u64 = 7;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClipboardValue {
+    pub timestamp: OffsetDateTime,
+
+    #[serde(with = "clipcat_base::serde::mime")]
+    pub mime: mime::Mime,
+
+    pub data: Vec<u8>,
+
+    pub pinned: bool,
+
+    pub representations: Vec<Representation>,
+
+    pub source_application: Option<String>,
+
+    pub use_count: u64,
+
+    pub last_used: Option<OffsetDateTime>,
+}
+
+impl From<ClipboardValue> for v8::ClipboardValue {
+    fn from(
+        ClipboardValue {
+            timestamp,
+            mime,
+            data,
+            pinned,
+            representations,
+            source_application,
+            use_count,
+            last_used,
+        }: ClipboardValue,
+    ) -> Self {
+        Self {
+            timestamp,
+            mime,
+            data,
+            pinned,
+            representations,
+            source_application,
+            use_count,
+            last_used,
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl Upgrade for ClipboardValue {
+    fn upgrade(self) -> v10::ClipboardValue { v8::ClipboardValue::from(self).upgrade() }
+}
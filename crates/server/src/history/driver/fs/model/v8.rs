@@ -0,0 +1,63 @@
+use clipcat_base::ClipboardKind;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::history::driver::fs::model::{v10, Representation, Upgrade};
+
+pub const SCHEMA_VERSION: u64 = 8;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClipboardValue {
+    pub timestamp: OffsetDateTime,
+
+    #[serde(with = "clipcat_base::serde::mime")]
+    pub mime: mime::Mime,
+
+    pub data: Vec<u8>,
+
+    pub pinned: bool,
+
+    pub representations: Vec<Representation>,
+
+    pub source_application: Option<String>,
+
+    pub use_count: u64,
+
+    pub last_used: Option<OffsetDateTime>,
+
+    pub tags: Vec<String>,
+}
+
+impl From<ClipboardValue> for v10::ClipboardValue {
+    fn from(
+        ClipboardValue {
+            timestamp,
+            mime,
+            data,
+            pinned,
+            representations,
+            source_application,
+            use_count,
+            last_used,
+            tags,
+        }: ClipboardValue,
+    ) -> Self {
+        Self {
+            timestamp,
+            mime,
+            data,
+            pinned,
+            representations,
+            source_application,
+            use_count,
+            last_used,
+            tags,
+            // the kind was not stored
+            kind: i32::from(ClipboardKind::Clipboard),
+        }
+    }
+}
+
+impl Upgrade for ClipboardValue {
+    fn upgrade(self) -> v10::ClipboardValue { self.into() }
+}
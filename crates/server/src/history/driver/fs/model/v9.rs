@@ -0,0 +1,6 @@
+pub use super::v8::ClipboardValue;
+
+// values are the same as v8, each record is framed with the length and a CRC-32
+// checksum of its payload instead, a torn or corrupted record does not hide the
+// records after it
+pub const SCHEMA_VERSION: u64 = 9;
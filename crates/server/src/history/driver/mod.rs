@@ -1,29 +1,56 @@
 mod fs;
+mod sqlite;
 
 use async_trait::async_trait;
 use clipcat_base::ClipEntry;
 
-pub use self::fs::FileSystemDriver;
-use crate::history::Error;
+pub use self::{
+    fs::{CheckReport, FileSystemDriver},
+    sqlite::SqliteDriver,
+};
+use crate::{config::HistoryEncryptionKey, history::Error};
 
 #[async_trait]
 pub trait Driver: Send + Sync {
     async fn load(&mut self) -> Result<Vec<ClipEntry>, Error>;
 
+    async fn get(&mut self, id: u64) -> Result<Option<ClipEntry>, Error>;
+
     async fn save(&mut self, clip_entry: &[ClipEntry]) -> Result<(), Error>;
 
     async fn clear(&mut self) -> Result<(), Error>;
 
     async fn put(&mut self, clip_entry: &ClipEntry) -> Result<(), Error>;
 
-    async fn shrink_to(&mut self, min_capacity: usize) -> Result<(), Error>;
+    /// Removes the oldest clips until at most `min_capacity` clips remain and
+    /// they take at most `max_bytes` bytes, the size is not limited if
+    /// `max_bytes` is 0. Pinned clips are exempt from both limits.
+    async fn shrink_to(&mut self, min_capacity: usize, max_bytes: u64) -> Result<(), Error>;
+
+    /// Returns the number of bytes of clips which are not pinned, they are
+    /// counted in the same way as the size limit of [`Self::shrink_to`].
+    async fn unpinned_bytes(&mut self) -> Result<u64, Error>;
+
+    /// Removes the clips with `ids`, the remaining clips are rewritten by
+    /// default.
+    async fn remove(&mut self, ids: &[u64]) -> Result<(), Error> {
+        let clips = self.load().await?;
+        let remaining =
+            clips.into_iter().filter(|clip| !ids.contains(&clip.id())).collect::<Vec<_>>();
+        self.save(&remaining).await
+    }
+
+    /// Re-encrypts the stored clips with `key`, or stores them in plaintext if
+    /// `key` is `None`.
+    async fn rekey(&mut self, key: Option<&HistoryEncryptionKey>) -> Result<(), Error>;
 
     async fn save_and_shrink_to(
         &mut self,
         data: &[ClipEntry],
         min_capacity: usize,
+        max_bytes: u64,
     ) -> Result<(), Error> {
         self.save(data).await?;
-        self.shrink_to(min_capacity).await
+        self.shrink_to(min_capacity, max_bytes).await
     }
 }
@@ -0,0 +1,877 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use clipcat_base::{ClipEntry, ClipRepresentation, ClipboardKind};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+use snafu::ResultExt;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    config::HistoryEncryptionKey,
+    history::{
+        cipher::{self, Cipher},
+        driver::{Driver, FileSystemDriver},
+        error, Error,
+    },
+};
+
+// schema 2: `data` of clips and images are sealed with the cipher stored in
+// metadata `encryption`
+// schema 3: alternative representations of clips are stored in
+// `representations`
+// schema 4: images are stored in their original format, IDs of images are
+// changed
+// schema 5: the application which clips are copied from is stored in
+// `clips.source_application`
+// schema 6: usage of clips is stored in `clips.use_count` and
+// `clips.last_used`
+// schema 7: tags of clips are stored in `tags`
+const SCHEMA_VERSION: u64 = 7;
+
+const CREATE_TABLES: &str = "
+    CREATE TABLE IF NOT EXISTS metadata (
+        key   TEXT PRIMARY KEY NOT NULL,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS images (
+        digest BLOB PRIMARY KEY NOT NULL,
+        data   BLOB NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS clips (
+        id           INTEGER PRIMARY KEY NOT NULL,
+        timestamp    INTEGER NOT NULL,
+        kind         INTEGER NOT NULL,
+        mime         TEXT NOT NULL,
+        pinned       INTEGER NOT NULL DEFAULT 0,
+        data         BLOB,
+        image_digest BLOB REFERENCES images (digest),
+        source_application TEXT,
+        use_count    INTEGER NOT NULL DEFAULT 0,
+        last_used    INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS clips_timestamp ON clips (timestamp);
+    CREATE TABLE IF NOT EXISTS representations (
+        clip_id INTEGER NOT NULL,
+        mime    TEXT NOT NULL,
+        data    BLOB NOT NULL,
+        PRIMARY KEY (clip_id, mime)
+    );
+    CREATE TABLE IF NOT EXISTS tags (
+        clip_id INTEGER NOT NULL,
+        tag     TEXT NOT NULL,
+        PRIMARY KEY (clip_id, tag)
+    );
+";
+
+// the number of bytes of a clip which is counted by the size limit
+const CLIP_SIZE: &str = "
+    LENGTH(COALESCE(clips.data, images.data)) + COALESCE((
+        SELECT SUM(LENGTH(data)) FROM representations WHERE clip_id = clips.id
+    ), 0)";
+
+const SELECT_CLIPS: &str = "
+    SELECT clips.timestamp, clips.kind, clips.mime, clips.pinned,
+           COALESCE(clips.data, images.data), clips.source_application, clips.use_count,
+           clips.last_used
+    FROM clips LEFT JOIN images ON clips.image_digest = images.digest
+";
+
+pub struct SqliteDriver {
+    connection: Arc<Mutex<Connection>>,
+    cipher: Option<Cipher>,
+}
+
+// a clip which is ready to be written into database
+struct Record {
+    id: i64,
+    timestamp: i64,
+    kind: i32,
+    mime: String,
+    pinned: bool,
+    data: Vec<u8>,
+    image_digest: Option<Vec<u8>>,
+    source_application: Option<String>,
+    use_count: i64,
+    last_used: Option<i64>,
+    // pairs of MIME and sealed data
+    representations: Vec<(String, Vec<u8>)>,
+    tags: Vec<String>,
+}
+
+impl SqliteDriver {
+    pub async fn new<P>(file_path: P, key: Option<&HistoryEncryptionKey>) -> Result<Self, Error>
+    where
+        P: AsRef<Path> + Send,
+    {
+        let file_path = file_path.as_ref().to_path_buf();
+        tokio::fs::create_dir_all(&file_path)
+            .await
+            .context(error::CreateDirectorySnafu { file_path: file_path.clone() })?;
+
+        let database_file_path = database_file_path(&file_path);
+        let (connection, schema, encryption) = tokio::task::spawn_blocking({
+            let database_file_path = database_file_path.clone();
+            move || {
+                let connection = Connection::open(&database_file_path)?;
+                connection.pragma_update(None, "journal_mode", "WAL")?;
+                connection.execute_batch(CREATE_TABLES)?;
+                add_missing_columns(&connection)?;
+                let schema = query_metadata(&connection, "schema")?;
+                let encryption = query_metadata(&connection, "encryption")?;
+                Ok((connection, schema, encryption))
+            }
+        })
+        .await
+        .context(error::JoinTaskSnafu)?
+        .context(error::OpenDatabaseSnafu { file_path: database_file_path.clone() })?;
+
+        let schema = schema.and_then(|schema| schema.parse::<u64>().ok());
+        let encryption = encryption
+            .map(|header| serde_json::from_str::<cipher::Header>(&header))
+            .transpose()
+            .context(error::DeseriailizeHistoryHeaderSnafu)?;
+
+        let cipher = match (&encryption, key) {
+            (Some(header), Some(key)) => Some(Cipher::from_header(key, header)?),
+            (Some(_), None) => return Err(Error::EncryptionKeyRequired),
+            (None, _) => None,
+        };
+
+        let mut driver = Self { connection: Arc::new(Mutex::new(connection)), cipher };
+        match schema {
+            Some(schema) if schema > SCHEMA_VERSION => {
+                return Err(Error::NewerSchema { new: schema, current: SCHEMA_VERSION });
+            }
+            Some(schema) => {
+                tracing::info!("Open `{}`, schema: {schema}", database_file_path.display());
+                if schema < 4 {
+                    // re-compute IDs of images
+                    let clips = driver.load().await?;
+                    driver.save(&clips).await?;
+                }
+                if encryption.is_none() && key.is_some() {
+                    tracing::info!("Encrypt clip history");
+                    driver.rekey(key).await?;
+                }
+            }
+            None => {
+                // a fresh database, import clips stored by `FileSystemDriver` once, the
+                // files are left as they are
+                let clips = if FileSystemDriver::exists(&file_path) {
+                    tracing::info!(
+                        "Migrate clips from `{}` into `{}`",
+                        file_path.display(),
+                        database_file_path.display()
+                    );
+                    FileSystemDriver::load_read_only(&file_path, key).await?
+                } else {
+                    Vec::new()
+                };
+                driver.cipher = key.map(Cipher::generate).transpose()?;
+                driver.save(&clips).await?;
+            }
+        }
+
+        Ok(driver)
+    }
+
+    fn encode_clips(&self, clips: &[ClipEntry]) -> Result<Vec<Record>, Error> {
+        let mut records = Vec::with_capacity(clips.len());
+        for clip in clips {
+            let data = match clip.encoded() {
+                Ok(data) => data,
+                Err(err) => {
+                    tracing::error!("Error occurs while encoding clip, error: {err}");
+                    continue;
+                }
+            };
+            let representations = clip
+                .representations()
+                .iter()
+                .map(|representation| {
+                    cipher::seal(self.cipher.as_ref(), representation.data.to_vec())
+                        .map(|data| (representation.mime.to_string(), data))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            records.push(Record {
+                id: to_sql_id(clip.id()),
+                timestamp: to_sql_timestamp(clip.timestamp()),
+                kind: i32::from(clip.kind()),
+                mime: clip.mime().to_string(),
+                pinned: clip.is_pinned(),
+                data: cipher::seal(self.cipher.as_ref(), data)?,
+                image_digest: (clip.mime().type_() == mime::IMAGE)
+                    .then(|| clip.sha256_digest().to_vec()),
+                source_application: clip.source_application().map(ToString::to_string),
+                use_count: i64::try_from(clip.use_count()).unwrap_or(i64::MAX),
+                last_used: clip.last_used().map(to_sql_timestamp),
+                representations,
+                tags: clip.tags().to_vec(),
+            });
+        }
+        Ok(records)
+    }
+
+    fn encryption_metadata(&self) -> Result<Option<String>, Error> {
+        self.cipher
+            .as_ref()
+            .map(|cipher| serde_json::to_string(&cipher.header()))
+            .transpose()
+            .context(error::SeriailizeHistoryHeaderSnafu)
+    }
+
+    // rewrites clips and images with the current cipher in one transaction
+    async fn rewrite(&self, clips: &[ClipEntry]) -> Result<(), Error> {
+        let records = self.encode_clips(clips)?;
+        let encryption = self.encryption_metadata()?;
+        self.execute(move |connection| {
+            let transaction = connection.transaction()?;
+            // images are shared by digest, drop them so that they are written with the
+            // current cipher
+            let _ = transaction.execute("DELETE FROM images", [])?;
+            replace_records(&transaction, &records, encryption.as_deref())?;
+            transaction.commit()
+        })
+        .await
+    }
+
+    async fn execute<F, T>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, rusqlite::Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || f(&mut connection.lock()))
+            .await
+            .context(error::JoinTaskSnafu)?
+            .context(error::QueryDatabaseSnafu)
+    }
+}
+
+#[async_trait]
+impl Driver for SqliteDriver {
+    async fn load(&mut self) -> Result<Vec<ClipEntry>, Error> {
+        let cipher = self.cipher.clone();
+        self.execute(move |connection| {
+            let mut statement =
+                connection.prepare(&format!("{SELECT_CLIPS} ORDER BY clips.timestamp"))?;
+            let clips = statement
+                .query_map([], |row| row_to_clip(row, cipher.as_ref()))?
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut representations = query_representations(connection, None, cipher.as_ref())?;
+            let mut tags = query_tags(connection, None)?;
+            Ok(clips
+                .into_iter()
+                .flatten()
+                .map(|mut clip| {
+                    if let Some(representations) = representations.remove(&to_sql_id(clip.id())) {
+                        clip.set_representations(representations);
+                    }
+                    if let Some(tags) = tags.remove(&to_sql_id(clip.id())) {
+                        clip.set_tags(tags);
+                    }
+                    clip
+                })
+                .collect())
+        })
+        .await
+    }
+
+    async fn get(&mut self, id: u64) -> Result<Option<ClipEntry>, Error> {
+        let cipher = self.cipher.clone();
+        self.execute(move |connection| {
+            let clip = connection
+                .query_row(&format!("{SELECT_CLIPS} WHERE clips.id = ?1"), [to_sql_id(id)], |row| {
+                    row_to_clip(row, cipher.as_ref())
+                })
+                .optional()?
+                .flatten();
+            let Some(mut clip) = clip else { return Ok(None) };
+            let mut representations =
+                query_representations(connection, Some(to_sql_id(id)), cipher.as_ref())?;
+            if let Some(representations) = representations.remove(&to_sql_id(id)) {
+                clip.set_representations(representations);
+            }
+            if let Some(tags) = query_tags(connection, Some(to_sql_id(id)))?.remove(&to_sql_id(id))
+            {
+                clip.set_tags(tags);
+            }
+            Ok(Some(clip))
+        })
+        .await
+    }
+
+    async fn save(&mut self, clips: &[ClipEntry]) -> Result<(), Error> {
+        let records = self.encode_clips(clips)?;
+        let encryption = self.encryption_metadata()?;
+        self.execute(move |connection| {
+            let transaction = connection.transaction()?;
+            replace_records(&transaction, &records, encryption.as_deref())?;
+            transaction.commit()
+        })
+        .await
+    }
+
+    async fn clear(&mut self) -> Result<(), Error> {
+        self.execute(|connection| {
+            let transaction = connection.transaction()?;
+            let _ = transaction.execute("DELETE FROM clips", [])?;
+            let _ = transaction.execute("DELETE FROM images", [])?;
+            let _ = transaction.execute("DELETE FROM representations", [])?;
+            let _ = transaction.execute("DELETE FROM tags", [])?;
+            update_metadata(&transaction)?;
+            transaction.commit()
+        })
+        .await
+    }
+
+    async fn put(&mut self, clip: &ClipEntry) -> Result<(), Error> {
+        let records = self.encode_clips(std::slice::from_ref(clip))?;
+        self.execute(move |connection| {
+            let transaction = connection.transaction()?;
+            for record in &records {
+                insert_record(&transaction, record)?;
+            }
+            update_metadata(&transaction)?;
+            transaction.commit()
+        })
+        .await
+    }
+
+    async fn shrink_to(&mut self, min_capacity: usize, max_bytes: u64) -> Result<(), Error> {
+        let min_capacity = i64::try_from(min_capacity).unwrap_or(i64::MAX);
+        let max_bytes = i64::try_from(max_bytes).unwrap_or(i64::MAX);
+        let removed = self
+            .execute(move |connection| {
+                let transaction = connection.transaction()?;
+                // pinned clips are exempt from the capacity and the size limit
+                let mut removed = transaction.execute(
+                    "DELETE FROM clips WHERE pinned = 0 AND id NOT IN (
+                        SELECT id FROM clips WHERE pinned = 0 ORDER BY timestamp DESC LIMIT ?1
+                    )",
+                    [min_capacity],
+                )?;
+                if max_bytes != 0 {
+                    removed += transaction.execute(
+                        &format!(
+                            "DELETE FROM clips WHERE id IN (
+                                SELECT id FROM (
+                                    SELECT clips.id,
+                                           SUM({CLIP_SIZE}) OVER (ORDER BY clips.timestamp DESC)
+                                           AS total
+                                    FROM clips
+                                    LEFT JOIN images ON clips.image_digest = images.digest
+                                    WHERE clips.pinned = 0
+                                ) WHERE total > ?1
+                            )"
+                        ),
+                        [max_bytes],
+                    )?;
+                }
+                remove_unused_images(&transaction)?;
+                let _ = transaction.execute(
+                    "DELETE FROM representations WHERE clip_id NOT IN (SELECT id FROM clips)",
+                    [],
+                )?;
+                let _ = transaction
+                    .execute("DELETE FROM tags WHERE clip_id NOT IN (SELECT id FROM clips)", [])?;
+                update_metadata(&transaction)?;
+                transaction.commit()?;
+                Ok(removed)
+            })
+            .await?;
+
+        if max_bytes != 0 && removed > 0 {
+            // free pages are not returned to the file system until the database is vacuumed
+            let _unused = self.execute(|connection| connection.execute("VACUUM", [])).await?;
+        }
+        Ok(())
+    }
+
+    async fn unpinned_bytes(&mut self) -> Result<u64, Error> {
+        let bytes = self
+            .execute(|connection| {
+                connection.query_row(
+                    &format!(
+                        "SELECT COALESCE(SUM({CLIP_SIZE}), 0) FROM clips
+                         LEFT JOIN images ON clips.image_digest = images.digest
+                         WHERE clips.pinned = 0"
+                    ),
+                    [],
+                    |row| row.get::<_, i64>(0),
+                )
+            })
+            .await?;
+        Ok(u64::try_from(bytes).unwrap_or_default())
+    }
+
+    async fn remove(&mut self, ids: &[u64]) -> Result<(), Error> {
+        let ids = ids.iter().copied().map(to_sql_id).collect::<Vec<_>>();
+        self.execute(move |connection| {
+            let transaction = connection.transaction()?;
+            for id in ids {
+                let _ = transaction.execute("DELETE FROM clips WHERE id = ?1", [id])?;
+                let _ =
+                    transaction.execute("DELETE FROM representations WHERE clip_id = ?1", [id])?;
+                let _ = transaction.execute("DELETE FROM tags WHERE clip_id = ?1", [id])?;
+            }
+            remove_unused_images(&transaction)?;
+            update_metadata(&transaction)?;
+            transaction.commit()
+        })
+        .await
+    }
+
+    async fn rekey(&mut self, key: Option<&HistoryEncryptionKey>) -> Result<(), Error> {
+        let clips = self.load().await?;
+        let previous_cipher =
+            std::mem::replace(&mut self.cipher, key.map(Cipher::generate).transpose()?);
+        let result = self.rewrite(&clips).await;
+        if result.is_err() {
+            // nothing is written, the history is still sealed with the previous cipher
+            self.cipher = previous_cipher;
+        }
+        result
+    }
+}
+
+// replaces all clips with `records`, images which are no longer referenced are
+// removed
+fn replace_records(
+    transaction: &Transaction<'_>,
+    records: &[Record],
+    encryption: Option<&str>,
+) -> Result<(), rusqlite::Error> {
+    let _ = transaction.execute("DELETE FROM clips", [])?;
+    let _ = transaction.execute("DELETE FROM representations", [])?;
+    let _ = transaction.execute("DELETE FROM tags", [])?;
+    for record in records {
+        insert_record(transaction, record)?;
+    }
+    remove_unused_images(transaction)?;
+    update_encryption(transaction, encryption)?;
+    update_metadata(transaction)
+}
+
+fn insert_record(transaction: &Transaction<'_>, record: &Record) -> Result<(), rusqlite::Error> {
+    let Record {
+        id,
+        timestamp,
+        kind,
+        mime,
+        pinned,
+        data,
+        image_digest,
+        source_application,
+        use_count,
+        last_used,
+        representations,
+        tags,
+    } = record;
+    let data = if let Some(digest) = image_digest {
+        let _ = transaction.execute(
+            "INSERT OR IGNORE INTO images (digest, data) VALUES (?1, ?2)",
+            params![digest, data],
+        )?;
+        None
+    } else {
+        Some(data)
+    };
+
+    let _ = transaction.execute(
+        "INSERT OR REPLACE INTO clips
+            (id, timestamp, kind, mime, pinned, data, image_digest, source_application,
+             use_count, last_used)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            id,
+            timestamp,
+            kind,
+            mime,
+            pinned,
+            data,
+            image_digest,
+            source_application,
+            use_count,
+            last_used
+        ],
+    )?;
+
+    let _ = transaction.execute("DELETE FROM representations WHERE clip_id = ?1", [id])?;
+    for (mime, data) in representations {
+        let _ = transaction.execute(
+            "INSERT OR REPLACE INTO representations (clip_id, mime, data) VALUES (?1, ?2, ?3)",
+            params![id, mime, data],
+        )?;
+    }
+
+    let _ = transaction.execute("DELETE FROM tags WHERE clip_id = ?1", [id])?;
+    for tag in tags {
+        let _ = transaction.execute(
+            "INSERT OR IGNORE INTO tags (clip_id, tag) VALUES (?1, ?2)",
+            params![id, tag],
+        )?;
+    }
+    Ok(())
+}
+
+// returns representations grouped by ID of clip, representations of all clips
+// are returned if `clip_id` is `None`
+fn query_representations(
+    connection: &Connection,
+    clip_id: Option<i64>,
+    cipher: Option<&Cipher>,
+) -> Result<HashMap<i64, Vec<ClipRepresentation>>, rusqlite::Error> {
+    let mut statement = connection.prepare(
+        "SELECT clip_id, mime, data FROM representations
+         WHERE ?1 IS NULL OR clip_id = ?1 ORDER BY rowid",
+    )?;
+    let mut rows = statement.query([clip_id])?;
+    let mut representations = HashMap::<_, Vec<_>>::new();
+    while let Some(row) = rows.next()? {
+        let Ok(mime) = mime::Mime::from_str(&row.get::<_, String>(1)?) else { continue };
+        match cipher::unseal(cipher, row.get(2)?) {
+            Ok(data) => representations
+                .entry(row.get::<_, i64>(0)?)
+                .or_default()
+                .push(ClipRepresentation::new(mime, data)),
+            Err(err) => {
+                tracing::error!(
+                    "Could not restore clip representation from database, error: {err}"
+                );
+            }
+        }
+    }
+    Ok(representations)
+}
+
+// returns tags grouped by ID of clip, tags of all clips are returned if
+// `clip_id` is `None`
+fn query_tags(
+    connection: &Connection,
+    clip_id: Option<i64>,
+) -> Result<HashMap<i64, Vec<String>>, rusqlite::Error> {
+    let mut statement =
+        connection.prepare("SELECT clip_id, tag FROM tags WHERE ?1 IS NULL OR clip_id = ?1")?;
+    let mut rows = statement.query([clip_id])?;
+    let mut tags = HashMap::<_, Vec<_>>::new();
+    while let Some(row) = rows.next()? {
+        tags.entry(row.get::<_, i64>(0)?).or_default().push(row.get(1)?);
+    }
+    Ok(tags)
+}
+
+// tables created by earlier schemas lack columns which are added later
+fn add_missing_columns(connection: &Connection) -> Result<(), rusqlite::Error> {
+    for (name, definition) in [
+        ("source_application", "TEXT"),
+        ("use_count", "INTEGER NOT NULL DEFAULT 0"),
+        ("last_used", "INTEGER"),
+    ] {
+        let exists = connection.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('clips') WHERE name = ?1",
+            [name],
+            |row| row.get::<_, i64>(0),
+        )? > 0;
+        if !exists {
+            connection
+                .execute_batch(&format!("ALTER TABLE clips ADD COLUMN {name} {definition}"))?;
+        }
+    }
+    Ok(())
+}
+
+fn query_metadata(connection: &Connection, key: &str) -> Result<Option<String>, rusqlite::Error> {
+    connection
+        .query_row("SELECT value FROM metadata WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+}
+
+fn update_encryption(
+    transaction: &Transaction<'_>,
+    encryption: Option<&str>,
+) -> Result<(), rusqlite::Error> {
+    let _ = if let Some(encryption) = encryption {
+        transaction.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES ('encryption', ?1)",
+            [encryption],
+        )?
+    } else {
+        transaction.execute("DELETE FROM metadata WHERE key = 'encryption'", [])?
+    };
+    Ok(())
+}
+
+fn remove_unused_images(transaction: &Transaction<'_>) -> Result<(), rusqlite::Error> {
+    let removed = transaction.execute(
+        "DELETE FROM images WHERE digest NOT IN (
+            SELECT image_digest FROM clips WHERE image_digest IS NOT NULL
+        )",
+        [],
+    )?;
+    if removed > 0 {
+        tracing::debug!("Remove {removed} unused image(s)");
+    }
+    Ok(())
+}
+
+fn update_metadata(transaction: &Transaction<'_>) -> Result<(), rusqlite::Error> {
+    let last_update = OffsetDateTime::now_utc().format(&Rfc3339).unwrap_or_default();
+    let _ = transaction.execute(
+        "INSERT OR REPLACE INTO metadata (key, value) VALUES ('schema', ?1), ('last_update', ?2)",
+        params![SCHEMA_VERSION.to_string(), last_update],
+    )?;
+    Ok(())
+}
+
+fn row_to_clip(
+    row: &Row<'_>,
+    cipher: Option<&Cipher>,
+) -> Result<Option<ClipEntry>, rusqlite::Error> {
+    let timestamp = from_sql_timestamp(row.get(0)?);
+    let kind = ClipboardKind::from(row.get::<_, i32>(1)?);
+    let mime =
+        mime::Mime::from_str(&row.get::<_, String>(2)?).unwrap_or(mime::APPLICATION_OCTET_STREAM);
+    let pinned = row.get::<_, bool>(3)?;
+    let source_application = row.get::<_, Option<String>>(5)?;
+    let use_count = u64::try_from(row.get::<_, i64>(6)?).unwrap_or_default();
+    let last_used = row.get::<_, Option<i64>>(7)?.map(from_sql_timestamp);
+    let data = match cipher::unseal(cipher, row.get::<_, Option<Vec<u8>>>(4)?.unwrap_or_default()) {
+        Ok(data) => data,
+        Err(err) => {
+            tracing::error!("Could not restore clip from database, error: {err}");
+            return Ok(None);
+        }
+    };
+
+    match ClipEntry::new(&data, &mime, kind, Some(timestamp)) {
+        Ok(mut clip) => {
+            clip.set_pinned(pinned);
+            clip.set_source_application(source_application);
+            clip.set_usage(use_count, last_used);
+            Ok(Some(clip))
+        }
+        Err(err) => {
+            tracing::error!("Could not restore clip from database, error: {err}");
+            Ok(None)
+        }
+    }
+}
+
+// SQLite stores integers as signed 64-bit values, keep the bits of ID as-is
+#[inline]
+const fn to_sql_id(id: u64) -> i64 { i64::from_ne_bytes(id.to_ne_bytes()) }
+
+#[inline]
+fn to_sql_timestamp(timestamp: OffsetDateTime) -> i64 {
+    i64::try_from(timestamp.unix_timestamp_nanos()).unwrap_or(i64::MAX)
+}
+
+#[inline]
+fn from_sql_timestamp(timestamp: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp_nanos(i128::from(timestamp))
+        .unwrap_or_else(|_| OffsetDateTime::now_utc())
+}
+
+fn database_file_path<P>(file_path: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    [file_path.as_ref(), Path::new("clips.db")].into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{ClipEntry, ClipRepresentation, ClipboardKind};
+
+    use crate::{
+        config::HistoryEncryptionKey,
+        history::{
+            driver::{Driver, FileSystemDriver, SqliteDriver},
+            Error,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_migrate_and_shrink() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-sqlite-driver-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let clips = (0..5)
+            .map(|i| {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                let mut clip = ClipEntry::from_string(i, ClipboardKind::Clipboard);
+                clip.set_pinned(i == 0);
+                clip
+            })
+            .collect::<Vec<_>>();
+        let mut fs_driver = FileSystemDriver::new(&file_path, None).await.unwrap();
+        fs_driver.save(&clips).await.unwrap();
+        let fs_files = [fs_driver.header_file_path(), fs_driver.clips_file_path()];
+        drop(fs_driver);
+        let read_fs_files =
+            || fs_files.iter().map(|path| std::fs::read(path).unwrap()).collect::<Vec<_>>();
+        let fs_contents = read_fs_files();
+
+        let mut driver = SqliteDriver::new(&file_path, None).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), clips);
+        // clips are imported without changing the files of `FileSystemDriver`
+        assert_eq!(read_fs_files(), fs_contents);
+
+        driver.shrink_to(2, 0).await.unwrap();
+        let loaded = driver.load().await.unwrap();
+        assert_eq!(loaded, vec![clips[0].clone(), clips[3].clone(), clips[4].clone()]);
+        assert!(loaded[0].is_pinned());
+        assert_eq!(driver.get(clips[4].id()).await.unwrap(), Some(clips[4].clone()));
+        assert_eq!(driver.get(clips[1].id()).await.unwrap(), None);
+
+        drop(driver);
+        let mut driver = SqliteDriver::new(&file_path, None).await.unwrap();
+        assert_eq!(driver.load().await.unwrap().len(), 3);
+
+        driver.remove(&[clips[3].id()]).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), vec![clips[0].clone(), clips[4].clone()]);
+
+        // each clip takes 1 byte
+        driver.save_and_shrink_to(&clips, 10, 2).await.unwrap();
+        assert_eq!(
+            driver.load().await.unwrap(),
+            vec![clips[0].clone(), clips[3].clone(), clips[4].clone()]
+        );
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_encryption() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-sqlite-driver-encryption-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let key = HistoryEncryptionKey::from(b"secret".to_vec());
+        let new_key = HistoryEncryptionKey::from(b"new secret".to_vec());
+        let clips = vec![ClipEntry::from_string("plaintext", ClipboardKind::Clipboard)];
+
+        let mut driver = SqliteDriver::new(&file_path, None).await.unwrap();
+        driver.save(&clips).await.unwrap();
+        drop(driver);
+
+        // a plaintext history is encrypted while opening with a key
+        let mut driver = SqliteDriver::new(&file_path, Some(&key)).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), clips);
+        drop(driver);
+
+        assert!(matches!(
+            SqliteDriver::new(&file_path, None).await,
+            Err(Error::EncryptionKeyRequired)
+        ));
+        assert!(matches!(
+            SqliteDriver::new(&file_path, Some(&new_key)).await,
+            Err(Error::WrongEncryptionKey)
+        ));
+
+        let mut driver = SqliteDriver::new(&file_path, Some(&key)).await.unwrap();
+        driver.rekey(Some(&new_key)).await.unwrap();
+        drop(driver);
+
+        assert!(matches!(
+            SqliteDriver::new(&file_path, Some(&key)).await,
+            Err(Error::WrongEncryptionKey)
+        ));
+        let mut driver = SqliteDriver::new(&file_path, Some(&new_key)).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), clips);
+        driver.rekey(None).await.unwrap();
+        drop(driver);
+
+        let mut driver = SqliteDriver::new(&file_path, None).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), clips);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_representations() {
+        let file_path = std::env::temp_dir().join(format!(
+            "clipcat-sqlite-driver-representations-test-{pid}",
+            pid = std::process::id()
+        ));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let key = HistoryEncryptionKey::from(b"secret".to_vec());
+        let mut clip = ClipEntry::from_string("link", ClipboardKind::Clipboard);
+        clip.set_representations([
+            ClipRepresentation::new("text/html".parse().unwrap(), "<a>link</a>"),
+            ClipRepresentation::new("text/uri-list".parse().unwrap(), "https://example.com"),
+        ]);
+        let clips = vec![clip];
+
+        let mut driver = SqliteDriver::new(&file_path, Some(&key)).await.unwrap();
+        driver.save(&clips).await.unwrap();
+        drop(driver);
+
+        let mut driver = SqliteDriver::new(&file_path, Some(&key)).await.unwrap();
+        let loaded = driver.load().await.unwrap();
+        assert_eq!(loaded[0].representations(), clips[0].representations());
+        let clip = driver.get(clips[0].id()).await.unwrap().unwrap();
+        assert_eq!(clip.representations(), clips[0].representations());
+
+        driver.put(&ClipEntry::from_string("link", ClipboardKind::Clipboard)).await.unwrap();
+        assert!(driver.load().await.unwrap()[0].representations().is_empty());
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_usage() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-sqlite-driver-usage-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let mut clip = ClipEntry::from_string("reused", ClipboardKind::Clipboard);
+        clip.set_usage(3, Some(clip.timestamp()));
+
+        let mut driver = SqliteDriver::new(&file_path, None).await.unwrap();
+        driver.save(&[clip.clone()]).await.unwrap();
+        drop(driver);
+
+        let mut driver = SqliteDriver::new(&file_path, None).await.unwrap();
+        let loaded = driver.get(clip.id()).await.unwrap().unwrap();
+        assert_eq!(loaded.use_count(), 3);
+        assert_eq!(loaded.last_used(), clip.last_used());
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_tags() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-sqlite-driver-tags-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let mut clip = ClipEntry::from_string("kubectl apply", ClipboardKind::Clipboard);
+        clip.set_tags(["deploy", "work"]);
+
+        let mut driver = SqliteDriver::new(&file_path, None).await.unwrap();
+        driver.save(&[clip.clone()]).await.unwrap();
+        drop(driver);
+
+        let mut driver = SqliteDriver::new(&file_path, None).await.unwrap();
+        assert_eq!(driver.load().await.unwrap()[0].tags(), ["deploy", "work"]);
+
+        clip.set_tags(["deploy"]);
+        driver.put(&clip).await.unwrap();
+        assert_eq!(driver.get(clip.id()).await.unwrap().unwrap().tags(), ["deploy"]);
+
+        driver.remove(&[clip.id()]).await.unwrap();
+        assert!(driver.get(clip.id()).await.unwrap().is_none());
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+}
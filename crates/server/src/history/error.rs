@@ -8,6 +8,12 @@ pub enum Error {
     #[snafu(display("A newer schema: `{new}` is in-use, current schema: `{current}`"))]
     NewerSchema { current: u64, new: u64 },
 
+    #[snafu(display(
+        "History is in an out-of-date schema: `{schema}`, current schema: `{current}`, please \
+         start clipcatd once to migrate it"
+    ))]
+    OutdatedSchema { schema: u64, current: u64 },
+
     #[snafu(display("Could not join spawned task, error: {source}"))]
     JoinTask { source: tokio::task::JoinError },
 
@@ -23,6 +29,12 @@ pub enum Error {
     #[snafu(display("Failed to write file {}, error: {source}", file_path.display()))]
     WriteFile { source: std::io::Error, file_path: PathBuf },
 
+    #[snafu(display("Failed to rename file {} to {}, error: {source}", from.display(), to.display()))]
+    RenameFile { source: std::io::Error, from: PathBuf, to: PathBuf },
+
+    #[snafu(display("Failed to remove file {}, error: {source}", file_path.display()))]
+    RemoveFile { source: std::io::Error, file_path: PathBuf },
+
     #[snafu(display("Failed to read file {}, error: {source}", file_path.display()))]
     ReadFile { source: std::io::Error, file_path: PathBuf },
 
@@ -40,4 +52,39 @@ pub enum Error {
 
     #[snafu(display("Failed to deserialize history header, error: {source}"))]
     DeseriailizeHistoryHeader { source: serde_json::Error },
+
+    #[snafu(display("Failed to open database {}, error: {source}", file_path.display()))]
+    OpenDatabase { source: rusqlite::Error, file_path: PathBuf },
+
+    #[snafu(display("Failed to query database, error: {source}"))]
+    QueryDatabase { source: rusqlite::Error },
+
+    #[snafu(display(
+        "History is encrypted, please provide the encryption key via `history_encryption` in the \
+         configuration"
+    ))]
+    EncryptionKeyRequired,
+
+    #[snafu(display("Could not decrypt history, the encryption key is wrong"))]
+    WrongEncryptionKey,
+
+    #[snafu(display("The current encryption key of history is wrong"))]
+    CurrentKeyMismatch,
+
+    #[snafu(display("Failed to derive encryption key, error: {source}"))]
+    DeriveEncryptionKey { source: argon2::Error },
+
+    #[snafu(display("Failed to decode encryption header of history, error: {source}"))]
+    DecodeEncryptionHeader { source: hex::FromHexError },
+
+    #[snafu(display(
+        "Checking and repairing history is only supported by the file system driver"
+    ))]
+    CheckNotSupported,
+
+    #[snafu(display("Failed to encrypt data"))]
+    EncryptData,
+
+    #[snafu(display("Failed to decrypt data, data might be corrupted"))]
+    DecryptData,
 }
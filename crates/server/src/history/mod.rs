@@ -1,35 +1,78 @@
+mod cipher;
 mod driver;
 mod error;
 
 use std::path::{Path, PathBuf};
 
 use clipcat_base::ClipEntry;
+use snafu::ResultExt;
 
-pub use self::error::Error;
+pub use self::{driver::CheckReport, error::Error};
+use crate::config::{HistoryDriver, HistoryEncryptionKey};
 
+// sensitive clips are never written to history, `HistoryManager` skips them
 pub struct HistoryManager {
     file_path: PathBuf,
     driver: Box<dyn driver::Driver>,
+    // the key which the history is encrypted with, it is changed by rekeying
+    key: Option<HistoryEncryptionKey>,
+    // maximum number of bytes of clips which are not pinned, 0 means unlimited
+    max_bytes: u64,
 }
 
 impl HistoryManager {
     /// # Errors
     #[inline]
-    pub async fn new<P>(file_path: P) -> Result<Self, Error>
+    pub async fn new<P>(
+        file_path: P,
+        driver: HistoryDriver,
+        key: Option<&HistoryEncryptionKey>,
+    ) -> Result<Self, Error>
     where
         P: AsRef<Path> + Send,
     {
         let file_path = file_path.as_ref().to_owned();
-        let driver = driver::FileSystemDriver::new(&file_path).await?;
-        Ok(Self { file_path, driver: Box::new(driver) })
+        let driver: Box<dyn driver::Driver> = match driver {
+            HistoryDriver::FileSystem => {
+                Box::new(driver::FileSystemDriver::new(&file_path, key).await?)
+            }
+            HistoryDriver::Sqlite => Box::new(driver::SqliteDriver::new(&file_path, key).await?),
+        };
+        Ok(Self { file_path, driver, key: key.cloned(), max_bytes: 0 })
     }
 
     #[inline]
     pub fn path(&self) -> &Path { &self.file_path }
 
+    /// Returns the key which the history is encrypted with currently.
     #[inline]
+    pub const fn encryption_key(&self) -> Option<&HistoryEncryptionKey> { self.key.as_ref() }
+
+    /// Sets the maximum number of bytes of stored clips, the oldest clips are
+    /// removed once it is exceeded and pinned clips are exempt. The limit is
+    /// disabled if `max_bytes` is 0.
+    #[inline]
+    pub fn set_max_bytes(&mut self, max_bytes: u64) { self.max_bytes = max_bytes; }
+
     pub async fn put(&mut self, data: &ClipEntry) -> Result<(), Error> {
-        self.driver.put(data).await
+        if data.is_sensitive() {
+            return Ok(());
+        }
+        self.driver.put(data).await?;
+        if self.max_bytes != 0 && self.driver.unpinned_bytes().await? > self.max_bytes {
+            // the capacity is enforced while shutting down, only the size is limited
+            // here
+            self.driver.shrink_to(usize::MAX, self.max_bytes).await?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub async fn remove(&mut self, ids: &[u64]) -> Result<(), Error> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        self.driver.remove(ids).await
     }
 
     #[allow(dead_code)]
@@ -39,16 +82,15 @@ impl HistoryManager {
     #[inline]
     pub async fn load(&mut self) -> Result<Vec<ClipEntry>, Error> { self.driver.load().await }
 
-    #[allow(dead_code)]
     #[inline]
     pub async fn save(&mut self, data: &[ClipEntry]) -> Result<(), Error> {
-        self.driver.save(data).await
+        self.driver.save(&without_sensitive(data)).await
     }
 
     #[allow(dead_code)]
     #[inline]
     pub async fn shrink_to(&mut self, min_capacity: usize) -> Result<(), Error> {
-        self.driver.shrink_to(min_capacity).await
+        self.driver.shrink_to(min_capacity, self.max_bytes).await
     }
 
     #[inline]
@@ -57,6 +99,200 @@ impl HistoryManager {
         data: &[ClipEntry],
         min_capacity: usize,
     ) -> Result<(), Error> {
-        self.driver.save_and_shrink_to(data, min_capacity).await
+        self.driver.save_and_shrink_to(&without_sensitive(data), min_capacity, self.max_bytes).await
+    }
+
+    /// Returns the number of bytes taken by the history directory.
+    pub async fn size_in_bytes(&self) -> Result<u64, Error> {
+        let dir_path = self.file_path.clone();
+        tokio::task::spawn_blocking(move || {
+            directory_size(&dir_path).context(error::ReadDirectorySnafu { dir_path })
+        })
+        .await
+        .context(error::JoinTaskSnafu)?
+    }
+
+    /// Re-encrypts the history with `new_key`, the history is decrypted if
+    /// `new_key` is `None`. `current_key` must be the key which the history is
+    /// encrypted with, `None` if it is not encrypted.
+    pub async fn rekey(
+        &mut self,
+        current_key: Option<&HistoryEncryptionKey>,
+        new_key: Option<&HistoryEncryptionKey>,
+    ) -> Result<(), Error> {
+        if !is_same_key(self.key.as_ref(), current_key) {
+            return Err(Error::CurrentKeyMismatch);
+        }
+        self.driver.rekey(new_key).await?;
+        self.key = new_key.cloned();
+        Ok(())
+    }
+}
+
+/// Checks the history at `file_path` without modifying it.
+pub async fn check(
+    file_path: &Path,
+    driver: HistoryDriver,
+    key: Option<&HistoryEncryptionKey>,
+) -> Result<CheckReport, Error> {
+    match driver {
+        HistoryDriver::FileSystem => driver::FileSystemDriver::check(file_path, key).await,
+        HistoryDriver::Sqlite => Err(Error::CheckNotSupported),
+    }
+}
+
+/// Repairs the history at `file_path`, the original history is backed up
+/// before it is modified.
+pub async fn repair(
+    file_path: &Path,
+    driver: HistoryDriver,
+    key: Option<&HistoryEncryptionKey>,
+) -> Result<CheckReport, Error> {
+    match driver {
+        HistoryDriver::FileSystem => driver::FileSystemDriver::repair(file_path, key).await,
+        HistoryDriver::Sqlite => Err(Error::CheckNotSupported),
+    }
+}
+
+fn directory_size(dir_path: &Path) -> std::io::Result<u64> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(dir_path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() { directory_size(&entry.path())? } else { metadata.len() };
+    }
+    Ok(size)
+}
+
+// keys of the same length are compared in constant time
+fn is_same_key(lhs: Option<&HistoryEncryptionKey>, rhs: Option<&HistoryEncryptionKey>) -> bool {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => {
+            let (lhs, rhs) = (lhs.as_bytes(), rhs.as_bytes());
+            lhs.len() == rhs.len() && lhs.iter().zip(rhs).fold(0, |acc, (l, r)| acc | (l ^ r)) == 0
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn without_sensitive(clips: &[ClipEntry]) -> Vec<ClipEntry> {
+    clips.iter().filter(|clip| !clip.is_sensitive()).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{ClipEntry, ClipboardKind};
+
+    use crate::{
+        config::{HistoryDriver, HistoryEncryptionKey},
+        history::{Error, HistoryManager},
+    };
+
+    #[tokio::test]
+    async fn test_rekey_requires_current_key() {
+        let file_path = std::env::temp_dir()
+            .join(format!("clipcat-history-rekey-test-{pid}", pid = std::process::id()));
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+
+        let key = HistoryEncryptionKey::from(b"secret".to_vec());
+        let new_key = HistoryEncryptionKey::from(b"new secret".to_vec());
+        let clips = vec![ClipEntry::from_string("plaintext", ClipboardKind::Clipboard)];
+
+        let mut history_manager =
+            HistoryManager::new(&file_path, HistoryDriver::FileSystem, Some(&key)).await.unwrap();
+        history_manager.save(&clips).await.unwrap();
+
+        for current_key in [None, Some(&new_key)] {
+            assert!(matches!(
+                history_manager.rekey(current_key, Some(&new_key)).await,
+                Err(Error::CurrentKeyMismatch)
+            ));
+        }
+        assert_eq!(history_manager.encryption_key(), Some(&key));
+
+        history_manager.rekey(Some(&key), Some(&new_key)).await.unwrap();
+        assert_eq!(history_manager.encryption_key(), Some(&new_key));
+        assert_eq!(history_manager.load().await.unwrap(), clips);
+        drop(history_manager);
+
+        let mut history_manager =
+            HistoryManager::new(&file_path, HistoryDriver::FileSystem, Some(&new_key))
+                .await
+                .unwrap();
+        assert_eq!(history_manager.load().await.unwrap(), clips);
+
+        drop(tokio::fs::remove_dir_all(&file_path).await);
+    }
+
+    #[tokio::test]
+    async fn test_put_pinned_clip() {
+        for (driver, name) in [(HistoryDriver::FileSystem, "fs"), (HistoryDriver::Sqlite, "sqlite")]
+        {
+            let file_path = std::env::temp_dir()
+                .join(format!("clipcat-history-pin-test-{name}-{pid}", pid = std::process::id()));
+            drop(tokio::fs::remove_dir_all(&file_path).await);
+            drop(tokio::fs::remove_file(&file_path).await);
+
+            let mut clip = ClipEntry::from_string("pinned", ClipboardKind::Clipboard);
+            let mut history_manager = HistoryManager::new(&file_path, driver, None).await.unwrap();
+            history_manager.put(&clip).await.unwrap();
+
+            // pinning and unpinning a clip are written as they happen
+            for pinned in [true, false] {
+                clip.set_pinned(pinned);
+                history_manager.put(&clip).await.unwrap();
+                let mut history_manager =
+                    HistoryManager::new(&file_path, driver, None).await.unwrap();
+                let loaded = history_manager.load().await.unwrap();
+                assert_eq!(loaded.len(), 1, "driver: {name}");
+                assert_eq!(loaded[0].is_pinned(), pinned, "driver: {name}");
+            }
+
+            drop(tokio::fs::remove_dir_all(&file_path).await);
+            drop(tokio::fs::remove_file(&file_path).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_limits_unpinned_bytes() {
+        for (driver, name) in [(HistoryDriver::FileSystem, "fs"), (HistoryDriver::Sqlite, "sqlite")]
+        {
+            let file_path = std::env::temp_dir()
+                .join(format!("clipcat-history-size-test-{name}-{pid}", pid = std::process::id()));
+            drop(tokio::fs::remove_dir_all(&file_path).await);
+            drop(tokio::fs::remove_file(&file_path).await);
+
+            let mut pinned = ClipEntry::from_string("p".repeat(4096), ClipboardKind::Clipboard);
+            pinned.set_pinned(true);
+            let clips = (0..3)
+                .map(|i| {
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                    ClipEntry::from_string(i.to_string().repeat(64), ClipboardKind::Clipboard)
+                })
+                .collect::<Vec<_>>();
+
+            let mut history_manager = HistoryManager::new(&file_path, driver, None).await.unwrap();
+            history_manager.put(&pinned).await.unwrap();
+            history_manager.put(&clips[0]).await.unwrap();
+            // the pinned clip is not counted, two clips fit into the limit
+            let max_bytes = history_manager.driver.unpinned_bytes().await.unwrap() * 5 / 2;
+            history_manager.set_max_bytes(max_bytes);
+            history_manager.put(&clips[1]).await.unwrap();
+            assert_eq!(history_manager.load().await.unwrap().len(), 3, "driver: {name}");
+
+            history_manager.put(&clips[2]).await.unwrap();
+            let mut loaded = history_manager.load().await.unwrap();
+            loaded.sort_unstable_by_key(ClipEntry::timestamp);
+            assert_eq!(
+                loaded,
+                [pinned.clone(), clips[1].clone(), clips[2].clone()],
+                "driver: {name}"
+            );
+            assert!(history_manager.driver.unpinned_bytes().await.unwrap() <= max_bytes);
+
+            drop(tokio::fs::remove_dir_all(&file_path).await);
+            drop(tokio::fs::remove_file(&file_path).await);
+        }
     }
 }
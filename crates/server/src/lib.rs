@@ -16,30 +16,47 @@ mod history;
 mod manager;
 mod metrics;
 mod notification;
+mod reload;
 mod snippets;
+mod systemd;
 mod watcher;
+mod web;
 
-use std::{future::Future, net::SocketAddr, path::PathBuf, pin::Pin, sync::Arc};
+use std::{
+    future::Future,
+    net::SocketAddr,
+    os::unix::fs::FileTypeExt,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
 
-use clipcat_base::ClipboardKind;
-use clipcat_proto::{ManagerServer, SystemServer, WatcherServer};
-use futures::FutureExt;
+use clipcat_base::{ClipboardKind, SharedClipFilter};
+use clipcat_proto::{HistoryServer, ManagerServer, SystemServer, WatcherServer};
+use futures::{FutureExt, StreamExt};
 use notification::Notification;
 use sigfinn::{ExitStatus, Handle, LifecycleManager, Shutdown};
 use snafu::ResultExt;
 use snippets::SnippetWatcherEvent;
+use time::OffsetDateTime;
 use tokio::{
-    net::UnixListener,
-    sync::{broadcast::error::RecvError, Mutex},
+    net::{TcpListener, UnixListener},
+    signal::unix::{signal, SignalKind},
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc, oneshot, Mutex,
+    },
 };
-use tokio_stream::wrappers::UnixListenerStream;
+use tokio_stream::wrappers::{TcpListenerStream, UnixListenerStream};
 
 pub use self::{
     config::Config,
     error::{Error, Result},
+    history::CheckReport as HistoryCheckReport,
     watcher::ClipboardWatcherOptions,
 };
 use self::{
+    config::{ConfigLoader, ExpiryConfig, HistoryDriver, HistoryEncryptionKey},
     history::HistoryManager,
     manager::ClipboardManager,
     metrics::Metrics,
@@ -47,35 +64,43 @@ use self::{
 };
 use crate::snippets::SnippetWatcherEventReceiver;
 
+/// Serves with `config`, `config_loader` is called to load the configuration
+/// again whenever `SIGHUP` or a reload request is received.
+///
 /// # Errors
 ///
 /// This function will return an error if the server fails to start.
 #[allow(clippy::too_many_lines)]
-pub async fn serve_with_shutdown(
-    Config {
+pub async fn serve_with_shutdown(config: Config, config_loader: ConfigLoader) -> Result<()> {
+    let Config {
         grpc_listen_address,
         grpc_local_socket,
         grpc_access_token,
         primary_threshold,
         max_history,
+        max_history_bytes,
+        max_history_disk_bytes,
         history_file_path,
-        synchronize_selection_with_clipboard,
+        history_driver,
+        history_encryption_key,
         watcher: watcher_opts,
         desktop_notification: desktop_notification_config,
         dbus,
         metrics: metrics_config,
-        snippets,
-    }: Config,
-) -> Result<()> {
-    let clip_filter =
-        Arc::new(watcher_opts.generate_clip_filter().context(error::GenerateClipFilterSnafu)?);
+        web: web_config,
+        rest: rest_config,
+        mut snippets,
+        snippet_directory,
+        snippet_environment_variables,
+        // applied by the clipboard worker, they could be changed by reloading
+        ..
+    } = config.clone();
+    let clip_filter = SharedClipFilter::new(
+        watcher_opts.generate_clip_filter().context(error::GenerateClipFilterSnafu)?,
+    );
 
     let (desktop_notification, desktop_notification_worker) =
-        notification::DesktopNotification::new(
-            desktop_notification_config.icon,
-            desktop_notification_config.timeout,
-            desktop_notification_config.long_plaintext_length,
-        );
+        notification::DesktopNotification::new(desktop_notification_config);
 
     let clipboard_backend = backend::new_shared(
         watcher_opts.clipboard_kinds(),
@@ -84,13 +109,21 @@ pub async fn serve_with_shutdown(
     )
     .context(error::CreateClipboardBackendSnafu)?;
 
+    let snippet_directory =
+        snippets::prepare_managed_directory(&mut snippets, snippet_directory).await;
+
     let (clipboard_manager, history_manager, snippets_watcher, snippet_event_receiver) = {
         let ((snippets_watcher, snippet_event_receiver), snippets) =
             snippets::load_and_create_watcher(&snippets).await?;
         tracing::info!("History file path: `{path}`", path = history_file_path.display());
-        let mut history_manager = HistoryManager::new(&history_file_path)
-            .await
-            .context(error::CreateHistoryManagerSnafu)?;
+        let mut history_manager = HistoryManager::new(
+            &history_file_path,
+            history_driver,
+            history_encryption_key.as_ref(),
+        )
+        .await
+        .context(error::CreateHistoryManagerSnafu)?;
+        history_manager.set_max_bytes(max_history_disk_bytes);
 
         tracing::info!("Load history from `{path}`", path = history_manager.path().display());
         let history_clips = history_manager
@@ -120,6 +153,9 @@ pub async fn serve_with_shutdown(
             primary_threshold,
             desktop_notification.clone(),
         );
+        clipboard_manager.set_max_bytes(max_history_bytes);
+        clipboard_manager.set_snippet_directory(snippet_directory);
+        clipboard_manager.set_snippet_environment_variables(snippet_environment_variables);
 
         tracing::info!("Import {clip_count} clip(s) into ClipboardManager");
         clipboard_manager.import(&history_clips);
@@ -129,7 +165,7 @@ pub async fn serve_with_shutdown(
 
         (
             Arc::new(Mutex::new(clipboard_manager)),
-            history_manager,
+            Arc::new(Mutex::new(history_manager)),
             snippets_watcher,
             snippet_event_receiver,
         )
@@ -138,18 +174,48 @@ pub async fn serve_with_shutdown(
     let (clipboard_watcher, clipboard_watcher_worker) = ClipboardWatcher::new(
         clipboard_backend,
         watcher_opts.clone(),
+        clip_filter.clone(),
+        desktop_notification.clone(),
+    );
+
+    let reloader = reload::Reloader::new(
+        config_loader,
+        config,
         clip_filter,
         desktop_notification.clone(),
+        snippets_watcher,
     );
+    let (reload_handle, reload_request_receiver) = reload::channel();
+
+    // the socket is bound before serving so that clients could connect once the
+    // daemon is reported to be ready
+    let grpc_local_socket = if let Some(listener) = systemd::take_activated_listener() {
+        tracing::info!("Listen Clipcat gRPC endpoint on the socket passed by systemd");
+        let listener = listener
+            .set_nonblocking(true)
+            .and_then(|()| UnixListener::from_std(listener))
+            .context(error::UseActivatedSocketSnafu)?;
+        Some((listener, None))
+    } else if let Some(path) = grpc_local_socket {
+        tracing::info!("Listen Clipcat gRPC endpoint on {}", path.display());
+        Some((bind_local_socket(&path).await?, Some(path)))
+    } else {
+        None
+    };
 
     let lifecycle_manager = LifecycleManager::<Error>::new();
 
-    if desktop_notification_config.enable {
-        let _handle = lifecycle_manager.spawn(
-            "Desktop notification worker",
-            create_desktop_notification_worker_future(desktop_notification_worker),
-        );
-    }
+    // the worker drops notifications while desktop notification is disabled, it
+    // could be enabled by reloading the configuration
+    let _handle = lifecycle_manager.spawn(
+        "Desktop notification worker",
+        create_desktop_notification_worker_future(desktop_notification_worker),
+    );
+
+    let _handle = lifecycle_manager.spawn(
+        "Configuration reload signal handler",
+        create_reload_signal_handler_future(reload_handle.clone()),
+    );
 
     #[cfg(all(
         unix,
@@ -161,39 +227,96 @@ pub async fn serve_with_shutdown(
         ))
     ))]
     if dbus.enable {
-        let _handle = lifecycle_manager.spawn(
-            "D-Bus",
-            create_dbus_service_future(
-                clipboard_watcher.get_toggle(),
-                clipboard_manager.clone(),
-                dbus.identifier,
-            ),
-        );
+        let connection = connect_dbus(
+            clipboard_watcher.get_toggle(),
+            clipboard_manager.clone(),
+            dbus.identifier,
+        )
+        .await?;
+        let _handle = lifecycle_manager.spawn("D-Bus", create_dbus_service_future(connection));
     }
 
     #[cfg(target_os = "macos")]
     drop(dbus);
 
     if let Some(grpc_listen_address) = grpc_listen_address {
+        tracing::info!("Listen Clipcat gRPC endpoint on {grpc_listen_address}");
+        let listener = TcpListener::bind(grpc_listen_address)
+            .await
+            .context(error::CreateTcpListenerSnafu { listen_address: grpc_listen_address })?;
         let _handle = lifecycle_manager.spawn(
             "gRPC HTTP server",
             create_grpc_http_server_future(
-                grpc_listen_address,
+                listener,
                 grpc_access_token.clone(),
+                reload_handle.clone(),
                 clipboard_watcher.get_toggle(),
                 clipboard_manager.clone(),
+                history_manager.clone(),
             ),
         );
     }
 
-    if let Some(grpc_local_socket) = grpc_local_socket {
+    // servers listen before they are spawned, systemd is notified that the daemon
+    // is ready once they are spawned
+    if web_config.enable {
+        tracing::info!("Listen web endpoint on http://{}", web_config.listen_address);
+        let listener = TcpListener::bind(web_config.listen_address)
+            .await
+            .context(error::BindWebServerSnafu)?;
+        let _handle = lifecycle_manager.spawn(
+            "Web server",
+            create_web_server_future(
+                listener,
+                grpc_access_token.clone(),
+                clipboard_watcher.get_toggle(),
+                clipboard_manager.clone(),
+            ),
+        );
+    }
+
+    if let Some(listen_address) = rest_config.listen_address {
+        tracing::info!("Listen REST endpoint on http://{listen_address}");
+        let listener =
+            TcpListener::bind(listen_address).await.context(error::BindRestServerSnafu)?;
+        let _handle = lifecycle_manager.spawn(
+            "REST HTTP server",
+            create_rest_http_server_future(
+                listener,
+                listen_address,
+                grpc_access_token.clone(),
+                clipboard_watcher.get_toggle(),
+                clipboard_manager.clone(),
+            ),
+        );
+    }
+
+    if let Some(local_socket) = rest_config.local_socket {
+        tracing::info!("Listen REST endpoint on {}", local_socket.display());
+        let listener = bind_local_socket(&local_socket).await?;
+        let _handle = lifecycle_manager.spawn(
+            "REST local socket server",
+            create_rest_local_socket_server_future(
+                listener,
+                local_socket,
+                grpc_access_token.clone(),
+                clipboard_watcher.get_toggle(),
+                clipboard_manager.clone(),
+            ),
+        );
+    }
+
+    if let Some((listener, socket_path)) = grpc_local_socket {
         let _handle = lifecycle_manager.spawn(
             "gRPC local socket server",
             create_grpc_local_socket_server_future(
-                grpc_local_socket,
+                listener,
+                socket_path,
                 grpc_access_token,
+                reload_handle,
                 clipboard_watcher.get_toggle(),
                 clipboard_manager.clone(),
+                history_manager.clone(),
             ),
         );
     }
@@ -207,9 +330,21 @@ pub async fn serve_with_shutdown(
         );
     }
 
+    let (watcher_ready, watcher_ready_receiver) = oneshot::channel();
+    if let Some(notifier) = systemd::Notifier::from_env() {
+        let _handle = lifecycle_manager.spawn(
+            "systemd notifier",
+            create_systemd_notifier_future(
+                notifier,
+                watcher_ready_receiver,
+                clipboard_manager.clone(),
+            ),
+        );
+    }
+
     let handle = lifecycle_manager.spawn(
         "Clipboard Watcher worker",
-        create_clipboard_watcher_worker_future(clipboard_watcher_worker),
+        create_clipboard_watcher_worker_future(clipboard_watcher_worker, watcher_ready),
     );
 
     let _handle = lifecycle_manager.spawn(
@@ -218,8 +353,9 @@ pub async fn serve_with_shutdown(
             clipboard_watcher,
             clipboard_manager,
             history_manager,
-            synchronize_selection_with_clipboard,
+            reloader,
             snippet_event_receiver,
+            reload_request_receiver,
             handle,
         ),
     );
@@ -230,48 +366,99 @@ pub async fn serve_with_shutdown(
         tracing::error!("{err}");
         Err(err)
     } else {
-        drop(snippets_watcher);
         Ok(())
     }
 }
 
+/// Checks the history at `file_path` for corrupted records, missing images
+/// and orphaned images without modifying it.
+///
+/// # Errors
+///
+/// This function will return an error if the history could not be read.
+pub async fn check_history(
+    file_path: &Path,
+    driver: HistoryDriver,
+    key: Option<&HistoryEncryptionKey>,
+) -> Result<HistoryCheckReport> {
+    history::check(file_path, driver, key).await.context(error::CheckHistorySnafu)
+}
+
+/// Rewrites the history at `file_path` without the problems found by
+/// [`check_history`], the original history is backed up first. The daemon
+/// must not be running while the history is repaired.
+///
+/// # Errors
+///
+/// This function will return an error if the history could not be repaired.
+pub async fn repair_history(
+    file_path: &Path,
+    driver: HistoryDriver,
+    key: Option<&HistoryEncryptionKey>,
+) -> Result<HistoryCheckReport> {
+    history::repair(file_path, driver, key).await.context(error::RepairHistorySnafu)
+}
+
+// binds a Unix domain socket on `socket_path`, its parent directory is created
+// if it does not exist
+async fn bind_local_socket(socket_path: &Path) -> Result<UnixListener> {
+    if let Some(parent) = socket_path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .context(error::CreateUnixListenerSnafu { socket_path: socket_path.to_path_buf() })?;
+    }
+    remove_stale_socket(socket_path).await;
+    UnixListener::bind(socket_path)
+        .context(error::CreateUnixListenerSnafu { socket_path: socket_path.to_path_buf() })
+}
+
+// a socket which no one listens on is left by a daemon which was not shut down
+// gracefully, a socket in use is kept and binding it fails
+async fn remove_stale_socket(socket_path: &Path) {
+    let is_socket = tokio::fs::symlink_metadata(socket_path)
+        .await
+        .is_ok_and(|metadata| metadata.file_type().is_socket());
+    if !is_socket {
+        return;
+    }
+    if let Err(err) = tokio::net::UnixStream::connect(socket_path).await {
+        if err.kind() == std::io::ErrorKind::ConnectionRefused {
+            tracing::info!("Remove stale Unix domain socket `{}`", socket_path.display());
+            drop(tokio::fs::remove_file(socket_path).await);
+        }
+    }
+}
+
+// `socket_path` is `None` if the listener is passed by systemd, the socket file
+// is owned by systemd and not removed on shutdown
 fn create_grpc_local_socket_server_future(
-    local_socket: PathBuf,
+    listener: UnixListener,
+    socket_path: Option<PathBuf>,
     grpc_access_token: Option<String>,
+    reload_handle: reload::Handle,
     clipboard_watcher_toggle: ClipboardWatcherToggle<notification::DesktopNotification>,
     clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
+    history_manager: Arc<Mutex<HistoryManager>>,
 ) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
     move |signal| {
         async move {
-            tracing::info!("Listen Clipcat gRPC endpoint on {}", local_socket.display());
-            if let Some(local_socket_parent) = local_socket.parent() {
-                if let Err(err) = tokio::fs::create_dir_all(&local_socket_parent)
-                    .await
-                    .context(error::CreateUnixListenerSnafu { socket_path: local_socket.clone() })
-                {
-                    return ExitStatus::FatalError(err);
-                }
-            }
-
-            let uds_stream = match UnixListener::bind(&local_socket)
-                .context(error::CreateUnixListenerSnafu { socket_path: local_socket.clone() })
-            {
-                Ok(uds) => UnixListenerStream::new(uds),
-                Err(err) => return ExitStatus::FatalError(err),
-            };
-
+            let uds_stream = UnixListenerStream::new(listener);
             let interceptor = grpc::Interceptor::new(grpc_access_token);
             let result = tonic::transport::Server::builder()
                 .add_service(SystemServer::with_interceptor(
-                    grpc::SystemService::new(),
+                    grpc::SystemService::new(reload_handle),
                     interceptor.clone(),
                 ))
                 .add_service(WatcherServer::with_interceptor(
-                    grpc::WatcherService::new(clipboard_watcher_toggle),
+                    grpc::WatcherService::new(clipboard_watcher_toggle.clone()),
                     interceptor.clone(),
                 ))
                 .add_service(ManagerServer::with_interceptor(
-                    grpc::ManagerService::new(clipboard_manager),
+                    grpc::ManagerService::new(clipboard_manager.clone(), clipboard_watcher_toggle),
+                    interceptor.clone(),
+                ))
+                .add_service(HistoryServer::with_interceptor(
+                    grpc::HistoryService::new(clipboard_manager, history_manager),
                     interceptor,
                 ))
                 .serve_with_incoming_shutdown(uds_stream, signal)
@@ -280,11 +467,13 @@ fn create_grpc_local_socket_server_future(
 
             match result {
                 Ok(()) => {
-                    tracing::info!(
-                        "Remove Unix domain socket `{path}`",
-                        path = local_socket.display()
-                    );
-                    drop(tokio::fs::remove_file(local_socket).await);
+                    if let Some(socket_path) = socket_path {
+                        tracing::info!(
+                            "Remove Unix domain socket `{path}`",
+                            path = socket_path.display()
+                        );
+                        drop(tokio::fs::remove_file(socket_path).await);
+                    }
                     tracing::info!("gRPC local socket server is shut down gracefully");
                     ExitStatus::Success
                 }
@@ -305,20 +494,15 @@ fn create_grpc_local_socket_server_future(
     ))
 ))]
 fn create_dbus_service_future(
-    clipboard_watcher_toggle: ClipboardWatcherToggle<notification::DesktopNotification>,
-    clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
-    identifier: Option<String>,
+    connection: zbus::Connection,
 ) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
     move |signal| {
         async move {
-            match serve_dbus(clipboard_watcher_toggle, clipboard_manager, identifier, signal).await
-            {
-                Ok(()) => {
-                    tracing::info!("D-Bus service is shut down gracefully");
-                    ExitStatus::Success
-                }
-                Err(err) => ExitStatus::FatalError(err),
-            }
+            // the service is provided until the connection is dropped
+            signal.await;
+            drop(connection);
+            tracing::info!("D-Bus service is shut down gracefully");
+            ExitStatus::Success
         }
         .boxed()
     }
@@ -340,12 +524,13 @@ fn create_desktop_notification_worker_future(
 
 fn create_clipboard_watcher_worker_future(
     worker: ClipboardWatcherWorker,
+    ready: oneshot::Sender<()>,
 ) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
     move |signal| {
         async move {
             tracing::info!("Clipboard Watcher worker is started");
             let result =
-                worker.serve(signal).await.context(error::ServeClipboardWatcherWorkerSnafu);
+                worker.serve(signal, ready).await.context(error::ServeClipboardWatcherWorkerSnafu);
             match result {
                 Ok(()) => {
                     tracing::info!("Clipboard Watcher worker is shut down gracefully");
@@ -359,30 +544,34 @@ fn create_clipboard_watcher_worker_future(
 }
 
 fn create_grpc_http_server_future(
-    listen_address: SocketAddr,
+    listener: TcpListener,
     grpc_access_token: Option<String>,
+    reload_handle: reload::Handle,
     clipboard_watcher_toggle: ClipboardWatcherToggle<notification::DesktopNotification>,
     clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
+    history_manager: Arc<Mutex<HistoryManager>>,
 ) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
     move |signal| {
         async move {
-            tracing::info!("Listen Clipcat gRPC endpoint on {listen_address}");
-
             let interceptor = grpc::Interceptor::new(grpc_access_token);
             let result = tonic::transport::Server::builder()
                 .add_service(SystemServer::with_interceptor(
-                    grpc::SystemService::new(),
+                    grpc::SystemService::new(reload_handle),
                     interceptor.clone(),
                 ))
                 .add_service(WatcherServer::with_interceptor(
-                    grpc::WatcherService::new(clipboard_watcher_toggle),
+                    grpc::WatcherService::new(clipboard_watcher_toggle.clone()),
                     interceptor.clone(),
                 ))
                 .add_service(ManagerServer::with_interceptor(
-                    grpc::ManagerService::new(clipboard_manager),
+                    grpc::ManagerService::new(clipboard_manager.clone(), clipboard_watcher_toggle),
+                    interceptor.clone(),
+                ))
+                .add_service(HistoryServer::with_interceptor(
+                    grpc::HistoryService::new(clipboard_manager, history_manager),
                     interceptor,
                 ))
-                .serve_with_shutdown(listen_address, signal)
+                .serve_with_incoming_shutdown(TcpListenerStream::new(listener), signal)
                 .await
                 .context(error::StartTonicServerSnafu);
 
@@ -401,9 +590,10 @@ fn create_grpc_http_server_future(
 fn create_clipboard_worker_future(
     clipboard_watcher: ClipboardWatcher<notification::DesktopNotification>,
     clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
-    history_manager: HistoryManager,
-    synchronize_selection_with_clipboard: bool,
+    history_manager: Arc<Mutex<HistoryManager>>,
+    reloader: reload::Reloader,
     snippet_event_receiver: SnippetWatcherEventReceiver,
+    reload_request_receiver: mpsc::UnboundedReceiver<reload::Responder>,
     handle: Handle<Error>,
 ) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
     move |shutdown_signal| {
@@ -412,8 +602,9 @@ fn create_clipboard_worker_future(
                 clipboard_watcher,
                 clipboard_manager,
                 history_manager,
-                synchronize_selection_with_clipboard,
+                reloader,
                 snippet_event_receiver,
+                reload_request_receiver,
                 handle,
                 shutdown_signal,
             )
@@ -430,6 +621,159 @@ fn create_clipboard_worker_future(
     }
 }
 
+fn create_systemd_notifier_future(
+    notifier: systemd::Notifier,
+    watcher_ready: oneshot::Receiver<()>,
+    clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
+) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
+    move |shutdown_signal| {
+        async move {
+            // the notifier is spawned after the clipboard manager is loaded, the servers
+            // listen and the D-Bus name is acquired, the watcher is the last to be ready,
+            // the daemon is shutting down if the watcher fails before that
+            let mut shutdown_signal = shutdown_signal.into_stream();
+            let ready = tokio::select! {
+                result = watcher_ready => result.is_ok(),
+                _ = shutdown_signal.next() => false,
+            };
+            if ready {
+                notifier.notify("READY=1");
+            }
+            if let (true, Some(watchdog_interval)) = (ready, systemd::Notifier::watchdog_interval())
+            {
+                let mut interval = tokio::time::interval(watchdog_interval);
+                loop {
+                    tokio::select! {
+                        _ = interval.tick() => {
+                            // the watchdog is not pinged while the clipboard manager is stuck
+                            drop(clipboard_manager.lock().await);
+                            notifier.notify("WATCHDOG=1");
+                        }
+                        _ = shutdown_signal.next() => break,
+                    }
+                }
+            } else if ready {
+                let _unused = shutdown_signal.next().await;
+            }
+            notifier.notify("STOPPING=1");
+            tracing::info!("systemd notifier is shut down gracefully");
+            ExitStatus::Success
+        }
+        .boxed()
+    }
+}
+
+fn create_reload_signal_handler_future(
+    reload_handle: reload::Handle,
+) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
+    move |shutdown_signal| {
+        async move {
+            let mut hangup = match signal(SignalKind::hangup()) {
+                Ok(hangup) => hangup,
+                Err(err) => {
+                    tracing::warn!("Could not listen to `SIGHUP`, error: {err}");
+                    return ExitStatus::Success;
+                }
+            };
+            let mut shutdown_signal = shutdown_signal.into_stream();
+            loop {
+                tokio::select! {
+                    _ = hangup.recv() => {},
+                    _ = shutdown_signal.next() => break,
+                }
+                tracing::info!("`SIGHUP` is received, reload configuration");
+                match reload_handle.reload().await {
+                    Ok(restart_required) if restart_required.is_empty() => {}
+                    Ok(restart_required) => tracing::warn!(
+                        "Changes of {} take effect after restarting",
+                        restart_required.join(", ")
+                    ),
+                    Err(err) => tracing::warn!("{err}"),
+                }
+            }
+            tracing::info!("Configuration reload signal handler is shut down gracefully");
+            ExitStatus::Success
+        }
+        .boxed()
+    }
+}
+
+fn create_web_server_future(
+    listener: TcpListener,
+    access_token: Option<String>,
+    clipboard_watcher_toggle: ClipboardWatcherToggle<notification::DesktopNotification>,
+    clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
+) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
+    move |signal| {
+        async move {
+            let result = web::serve(
+                listener,
+                access_token,
+                clipboard_manager,
+                clipboard_watcher_toggle,
+                signal,
+            )
+            .await;
+            match result {
+                Ok(()) => {
+                    tracing::info!("Web server is shut down gracefully");
+                    ExitStatus::Success
+                }
+                Err(err) => ExitStatus::FatalError(err),
+            }
+        }
+        .boxed()
+    }
+}
+
+fn create_rest_http_server_future(
+    listener: TcpListener,
+    listen_address: SocketAddr,
+    access_token: Option<String>,
+    clipboard_watcher_toggle: ClipboardWatcherToggle<notification::DesktopNotification>,
+    clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
+) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
+    move |signal| {
+        async move {
+            let router = web::router(
+                access_token,
+                Some(listen_address),
+                clipboard_manager,
+                clipboard_watcher_toggle,
+            );
+            match web::serve_http(listener, router, signal).await {
+                Ok(()) => {
+                    tracing::info!("REST HTTP server is shut down gracefully");
+                    ExitStatus::Success
+                }
+                Err(err) => ExitStatus::FatalError(err),
+            }
+        }
+        .boxed()
+    }
+}
+
+fn create_rest_local_socket_server_future(
+    listener: UnixListener,
+    local_socket: PathBuf,
+    access_token: Option<String>,
+    clipboard_watcher_toggle: ClipboardWatcherToggle<notification::DesktopNotification>,
+    clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
+) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
+    move |signal| {
+        async move {
+            let router =
+                web::router(access_token, None, clipboard_manager, clipboard_watcher_toggle);
+            web::serve_local_socket(listener, router, signal).await;
+            tracing::info!("Remove Unix domain socket `{path}`", path = local_socket.display());
+            drop(tokio::fs::remove_file(local_socket).await);
+            tracing::info!("REST local socket server is shut down gracefully");
+            ExitStatus::Success
+        }
+        .boxed()
+    }
+}
+
 fn create_metrics_server_future<Metrics>(
     listen_address: SocketAddr,
     metrics: Metrics,
@@ -454,36 +798,92 @@ where
     }
 }
 
-#[allow(clippy::redundant_pub_crate)]
+#[allow(clippy::redundant_pub_crate, clippy::too_many_arguments, clippy::too_many_lines)]
 async fn serve_worker(
     clipboard_watcher: ClipboardWatcher<notification::DesktopNotification>,
     clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
-    mut history_manager: HistoryManager,
-    synchronize_selection_with_clipboard: bool,
-    mut snippet_event_receiver: SnippetWatcherEventReceiver,
+    history_manager: Arc<Mutex<HistoryManager>>,
+    mut reloader: reload::Reloader,
+    snippet_event_receiver: SnippetWatcherEventReceiver,
+    mut reload_request_receiver: mpsc::UnboundedReceiver<reload::Responder>,
     handle: Handle<Error>,
     shutdown_signal: Shutdown,
 ) -> Result<()> {
     enum Event {
         NewClip(clipcat_base::ClipEntry),
+        UpdateClip { old_id: u64, clip: clipcat_base::ClipEntry },
         NewSnippet(clipcat_base::ClipEntry),
         RemoveSnippet(u64),
+        RemoveExpiredClips,
+        ReloadConfig(reload::Responder),
         Shutdown,
     }
 
-    let (send, mut recv) = tokio::sync::mpsc::unbounded_channel();
-    let snippets_event_handle = tokio::spawn({
-        let send = send.clone();
-        async move {
+    fn spawn_snippet_event_forwarder(
+        mut snippet_event_receiver: SnippetWatcherEventReceiver,
+        send: mpsc::UnboundedSender<Event>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
             while let Some(event) = snippet_event_receiver.recv().await {
                 let event = match event {
-                    SnippetWatcherEvent::Add(clip) => Event::NewSnippet(clip),
+                    SnippetWatcherEvent::Add(clip) => Event::NewSnippet(*clip),
                     SnippetWatcherEvent::Remove(id) => Event::RemoveSnippet(id),
                 };
                 drop(send.send(event));
             }
-        }
-    });
+        })
+    }
+
+    // changes made through the manager, such as tagging a clip, are written to
+    // history as they happen, snippets are not stored in history
+    fn spawn_manager_event_forwarder(
+        mut manager_event_receiver: broadcast::Receiver<manager::Event>,
+        send: mpsc::UnboundedSender<Event>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match manager_event_receiver.recv().await {
+                    Ok(manager::Event::Updated { old_id, clip })
+                        if clip.snippet_name().is_none() =>
+                    {
+                        drop(send.send(Event::UpdateClip { old_id, clip }));
+                    }
+                    // marking a clip updates its usage
+                    Ok(manager::Event::Marked(clip)) if clip.snippet_name().is_none() => {
+                        drop(send.send(Event::UpdateClip { old_id: clip.id(), clip }));
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!("{n} change(s) of clips are written on shutdown");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    fn spawn_expiry_timer(
+        expiry: &ExpiryConfig,
+        send: mpsc::UnboundedSender<Event>,
+    ) -> tokio::task::JoinHandle<()> {
+        let check_interval = expiry.check_interval;
+        let enable = !expiry.rules.is_empty() && !check_interval.is_zero();
+        tokio::spawn(async move {
+            if enable {
+                let mut interval = tokio::time::interval(check_interval);
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                while send.send(Event::RemoveExpiredClips).is_ok() {
+                    let _instant = interval.tick().await;
+                }
+            }
+        })
+    }
+
+    let (send, mut recv) = mpsc::unbounded_channel();
+    let mut snippets_event_handle =
+        spawn_snippet_event_forwarder(snippet_event_receiver, send.clone());
+    let manager_event_handle =
+        spawn_manager_event_forwarder(clipboard_manager.lock().await.subscribe(), send.clone());
     let clip_reciever_handle = tokio::spawn({
         let send = send.clone();
         async move {
@@ -507,9 +907,21 @@ async fn serve_worker(
             }
         }
     });
-    let shutdown_handle = tokio::spawn(async move {
-        shutdown_signal.await;
-        drop(send.send(Event::Shutdown));
+    let mut expiry_handle = spawn_expiry_timer(&reloader.config().expiry, send.clone());
+    let reload_request_handle = tokio::spawn({
+        let send = send.clone();
+        async move {
+            while let Some(responder) = reload_request_receiver.recv().await {
+                drop(send.send(Event::ReloadConfig(responder)));
+            }
+        }
+    });
+    let shutdown_handle = tokio::spawn({
+        let send = send.clone();
+        async move {
+            shutdown_signal.await;
+            drop(send.send(Event::Shutdown));
+        }
     });
 
     while let Some(event) = recv.recv().await {
@@ -519,29 +931,67 @@ async fn serve_worker(
                 let mut clipboard_manager = clipboard_manager.lock().await;
                 let _ = clipboard_manager.remove_snippet(clip_id);
             }
+            Event::UpdateClip { old_id, clip } => {
+                let mut history_manager = history_manager.lock().await;
+                // the ID of a clip changes with its content
+                if old_id != clip.id() {
+                    if let Err(err) = history_manager.remove(&[old_id]).await {
+                        tracing::error!("{err}");
+                    }
+                }
+                if let Err(err) = history_manager.put(&clip).await {
+                    tracing::error!("{err}");
+                }
+            }
             Event::NewSnippet(snippet) => {
                 let mut clipboard_manager = clipboard_manager.lock().await;
                 clipboard_manager.insert_snippets(&[snippet]);
             }
+            Event::RemoveExpiredClips => {
+                remove_expired_clips(
+                    &clipboard_manager,
+                    &history_manager,
+                    &reloader.config().expiry,
+                )
+                .await;
+            }
+            Event::ReloadConfig(responder) => {
+                let result = reloader.reload(&clipboard_manager, &history_manager).await.map(
+                    |(restart_required, snippet_event_receiver)| {
+                        snippets_event_handle.abort();
+                        snippets_event_handle =
+                            spawn_snippet_event_forwarder(snippet_event_receiver, send.clone());
+                        expiry_handle.abort();
+                        expiry_handle = spawn_expiry_timer(&reloader.config().expiry, send.clone());
+                        restart_required
+                    },
+                );
+                drop(responder.send(result));
+            }
             Event::NewClip(clip) => {
                 tracing::debug!(
                     "New clip: {kind} [{basic_info}]",
                     kind = clip.kind(),
                     basic_info = clip.basic_information()
                 );
-                {
+                let clip = {
                     let mut clipboard_manager = clipboard_manager.lock().await;
                     let id = clipboard_manager.insert(clip.clone());
-                    if synchronize_selection_with_clipboard
+                    if reloader.config().synchronize_selection_with_clipboard
                         && clip.kind() == ClipboardKind::Clipboard
                     {
-                        if let Err(err) = clipboard_manager.mark(id, ClipboardKind::Primary).await {
+                        if let Err(err) = clipboard_manager.store(id, ClipboardKind::Primary).await
+                        {
                             tracing::warn!("{err}");
                         }
                     }
-                }
+                    // the stored clip carries the state of the clip with the same content, such
+                    // as its usage
+                    clipboard_manager.get(id).unwrap_or(clip)
+                };
 
-                if let Err(err) = history_manager.put(&clip).await {
+                let result = history_manager.lock().await.put(&clip).await;
+                if let Err(err) = result {
                     tracing::error!("{err}");
                 }
             }
@@ -555,19 +1005,48 @@ async fn serve_worker(
 
     {
         tracing::info!("Save history and shrink to capacity {history_capacity}");
+        let mut history_manager = history_manager.lock().await;
         if let Err(err) = history_manager.save_and_shrink_to(&clips, history_capacity).await {
             tracing::warn!("Failed to save history, error: {err}");
         }
-        tracing::info!("Clips are stored in `{path}`", path = history_manager.path().display());
+        let path = history_manager.path().to_path_buf();
+        drop(history_manager);
+        tracing::info!("Clips are stored in `{path}`", path = path.display());
     }
 
     snippets_event_handle.abort();
+    manager_event_handle.abort();
     clip_reciever_handle.abort();
+    expiry_handle.abort();
+    reload_request_handle.abort();
     shutdown_handle.abort();
 
     Ok(())
 }
 
+// removes expired clips from both memory and history
+async fn remove_expired_clips(
+    clipboard_manager: &Mutex<ClipboardManager<notification::DesktopNotification>>,
+    history_manager: &Mutex<HistoryManager>,
+    expiry: &ExpiryConfig,
+) {
+    let expired_ids = clipboard_manager
+        .lock()
+        .await
+        .remove_expired(|clip| expiry.max_age(clip), OffsetDateTime::now_utc());
+    if expired_ids.is_empty() {
+        return;
+    }
+    tracing::info!("Remove {count} expired clip(s)", count = expired_ids.len());
+    metrics::manager::EXPIRED_CLIPS_TOTAL
+        .inc_by(u64::try_from(expired_ids.len()).unwrap_or_default());
+
+    let result = history_manager.lock().await.remove(&expired_ids).await;
+    if let Err(err) = result {
+        tracing::error!("{err}");
+    }
+}
+
 #[cfg(all(
     unix,
     not(any(
@@ -577,12 +1056,11 @@ async fn serve_worker(
         target_os = "emscripten"
     ))
 ))]
-async fn serve_dbus(
+async fn connect_dbus(
     clipboard_watcher_toggle: ClipboardWatcherToggle<notification::DesktopNotification>,
     clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
     identifier: Option<String>,
-    signal: Shutdown,
-) -> Result<()> {
+) -> Result<zbus::Connection> {
     let dbus_service_name = identifier.map_or_else(
         || clipcat_base::DBUS_SERVICE_NAME.to_string(),
         |identifier| format!("{}.{identifier}", clipcat_base::DBUS_SERVICE_NAME),
@@ -593,7 +1071,7 @@ async fn serve_dbus(
     let system = dbus::SystemService::new();
     let watcher = dbus::WatcherService::new(clipboard_watcher_toggle);
     let manager = dbus::ManagerService::new(clipboard_manager);
-    let _conn = zbus::connection::Builder::session()?
+    let connection = zbus::connection::Builder::session()?
         .name(dbus_service_name)?
         .serve_at(clipcat_base::DBUS_SYSTEM_OBJECT_PATH, system)?
         .serve_at(clipcat_base::DBUS_WATCHER_OBJECT_PATH, watcher)?
@@ -602,7 +1080,5 @@ async fn serve_dbus(
         .await?;
 
     tracing::info!("D-Bus service is created");
-    signal.await;
-
-    Ok(())
+    Ok(connection)
 }
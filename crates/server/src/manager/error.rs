@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use snafu::Snafu;
 
 use crate::backend;
@@ -7,4 +9,36 @@ use crate::backend;
 pub enum Error {
     #[snafu(display("Error occurs while storing clipboard content, error: {source}"))]
     StoreClipboardContent { source: backend::Error },
+
+    #[snafu(display("Error occurs while searching clips, error: {source}"))]
+    Search { source: clipcat_base::SearchError },
+
+    #[snafu(display("Error occurs while expanding snippet template, error: {source}"))]
+    ExpandTemplate { source: clipcat_base::TemplateError },
+
+    #[snafu(display("Snippet directory is not configured"))]
+    SnippetDirectoryUnavailable,
+
+    #[snafu(display("Invalid snippet name `{name}`"))]
+    InvalidSnippetName { name: String },
+
+    #[snafu(display("Content of snippet `{name}` is empty"))]
+    EmptySnippet { name: String },
+
+    #[snafu(display("Snippet `{name}` already exists"))]
+    SnippetExists { name: String },
+
+    #[snafu(display("Snippet `{name}` is not found"))]
+    SnippetNotFound { name: String },
+
+    #[snafu(display(
+        "Snippet `{name}` is not in the snippet directory, change it in the configuration instead"
+    ))]
+    SnippetNotManaged { name: String },
+
+    #[snafu(display("Could not write snippet file `{}`, error: {source}", path.display()))]
+    WriteSnippet { path: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Could not remove snippet file `{}`, error: {source}", path.display()))]
+    RemoveSnippetFile { path: PathBuf, source: std::io::Error },
 }
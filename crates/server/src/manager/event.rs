@@ -0,0 +1,30 @@
+use clipcat_base::{ClipEntry, ClipboardEvent};
+
+#[derive(Clone, Debug)]
+pub enum Event {
+    Inserted(ClipEntry),
+
+    Removed(u64),
+
+    Updated { old_id: u64, clip: ClipEntry },
+
+    Marked(ClipEntry),
+
+    Cleared,
+}
+
+impl Event {
+    #[must_use]
+    pub fn to_clipboard_event(&self, preview_length: usize) -> ClipboardEvent {
+        match self {
+            Self::Inserted(clip) => ClipboardEvent::Inserted(clip.metadata(Some(preview_length))),
+            Self::Removed(id) => ClipboardEvent::Removed(*id),
+            Self::Updated { old_id, clip } => ClipboardEvent::Updated {
+                old_id: *old_id,
+                metadata: clip.metadata(Some(preview_length)),
+            },
+            Self::Marked(clip) => ClipboardEvent::Marked(clip.metadata(Some(preview_length))),
+            Self::Cleared => ClipboardEvent::Cleared,
+        }
+    }
+}
@@ -1,19 +1,28 @@
 mod error;
+mod event;
 
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::{Component, Path, PathBuf},
     sync::Arc,
 };
 
-use clipcat_base::{ClipEntry, ClipEntryMetadata, ClipboardContent, ClipboardKind};
+use clipcat_base::{
+    archive::ImportMode, ClipEntry, ClipEntryMetadata, ClipboardContent, ClipboardKind, ListOrder,
+    ListQuery, SearchMatch, SearchQuery, SnippetTemplate, TemplateContext,
+};
 use snafu::ResultExt;
-use time::OffsetDateTime;
+use time::{OffsetDateTime, UtcOffset};
+use tokio::sync::broadcast;
 
-pub use self::error::Error;
-use crate::{backend::ClipboardBackend, notification};
+pub use self::{error::Error, event::Event};
+use crate::{backend::ClipboardBackend, notification, snippets};
 
 const DEFAULT_CAPACITY: usize = 40;
 
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
 pub struct ClipboardManager<Notification> {
     backend: Arc<dyn ClipboardBackend>,
 
@@ -21,18 +30,33 @@ pub struct ClipboardManager<Notification> {
 
     capacity: usize,
 
+    // maximum number of bytes of clips which are neither pinned nor snippets, 0
+    // means unlimited
+    max_bytes: usize,
+
     // use id of ClipEntry as the key
     clips: HashMap<u64, ClipEntry>,
 
     // store current clip for each clipboard kind
     current_clips: [Option<u64>; ClipboardKind::MAX_LENGTH],
 
-    // use BTreeMap to store timestamps for remove the oldest clip
-    timestamp_to_id: BTreeMap<OffsetDateTime, u64>,
+    // clips ordered by timestamp and then ID, the last one is the most recent
+    // clip, it is used for removing the oldest clips and addressing clips by
+    // index
+    recency_index: BTreeSet<(OffsetDateTime, u64)>,
 
     snippet_ids: HashSet<u64>,
 
+    // the directory where snippets added at runtime are stored, they are named
+    // by their paths relative to it
+    snippet_directory: Option<PathBuf>,
+
+    // environment variables which snippet templates are allowed to read
+    snippet_environment_variables: Vec<String>,
+
     notification: Notification,
+
+    event_sender: broadcast::Sender<Event>,
 }
 
 impl<Notification> ClipboardManager<Notification>
@@ -46,15 +70,20 @@ where
         notification: Notification,
     ) -> Self {
         let capacity = if capacity == 0 { DEFAULT_CAPACITY } else { capacity };
+        let (event_sender, _event_receiver) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
         Self {
             backend,
             primary_threshold,
             capacity,
+            max_bytes: 0,
             clips: HashMap::new(),
             current_clips: [None; ClipboardKind::MAX_LENGTH],
-            timestamp_to_id: BTreeMap::new(),
+            recency_index: BTreeSet::new(),
             snippet_ids: HashSet::new(),
+            snippet_directory: None,
+            snippet_environment_variables: Vec::new(),
             notification,
+            event_sender,
         }
     }
 
@@ -72,17 +101,54 @@ where
     #[inline]
     pub const fn capacity(&self) -> usize { self.capacity }
 
+    /// Sets the maximum number of clips, the oldest clips are removed if the
+    /// history exceeds the new capacity.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = if capacity == 0 { DEFAULT_CAPACITY } else { capacity };
+        self.remove_oldest();
+    }
+
+    /// Sets the maximum number of bytes of the history, pinned clips and
+    /// snippets are exempt. The limit is disabled if `max_bytes` is 0.
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        self.remove_oldest();
+    }
+
+    #[inline]
+    pub fn set_snippet_directory(&mut self, snippet_directory: Option<PathBuf>) {
+        self.snippet_directory = snippet_directory;
+    }
+
+    /// Sets names of environment variables which `{{env:<name>}}` in snippet
+    /// templates may read, other variables expand to an empty string.
+    #[inline]
+    pub fn set_snippet_environment_variables(&mut self, names: Vec<String>) {
+        self.snippet_environment_variables = names;
+    }
+
+    /// Returns the number of bytes of all clips.
+    #[inline]
+    pub fn size_in_bytes(&self) -> usize { self.clips.values().map(ClipEntry::size_in_bytes).sum() }
+
+    #[inline]
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> { self.event_sender.subscribe() }
+
+    #[inline]
+    fn emit(&self, event: Event) {
+        // sending fails only if there is no subscriber, it is fine to drop the event
+        drop(self.event_sender.send(event));
+    }
+
     #[inline]
     pub fn import(&mut self, clips: &[ClipEntry]) { self.import_iter(clips.iter()); }
 
     #[inline]
     pub fn import_iter<'a>(&'a mut self, clips_iter: impl Iterator<Item = &'a ClipEntry>) {
         self.clips.clear();
-        self.timestamp_to_id.clear();
+        self.recency_index.clear();
         for clip in clips_iter {
-            let (id, timestamp) = (clip.id(), clip.timestamp());
-            let _ = self.timestamp_to_id.insert(timestamp, id);
-            drop(self.clips.insert(id, clip.clone()));
+            self.insert_indexed(clip.clone());
         }
 
         self.remove_oldest();
@@ -90,15 +156,27 @@ where
 
     pub fn insert_snippets(&mut self, snippets: &[ClipEntry]) {
         for clip in snippets {
-            let (id, timestamp) = (clip.id(), clip.timestamp());
-            let _ = self.timestamp_to_id.insert(timestamp, id);
-            drop(self.clips.insert(id, clip.clone()));
+            let id = clip.id();
+            self.insert_indexed(clip.clone());
             let _unused = self.snippet_ids.insert(id);
+            self.emit(Event::Inserted(clip.clone()));
         }
 
         self.remove_oldest();
     }
 
+    /// Replaces all snippets with `snippets`, snippets which are not in
+    /// `snippets` are removed.
+    pub fn replace_snippets(&mut self, snippets: &[ClipEntry]) {
+        let new_ids = snippets.iter().map(ClipEntry::id).collect::<HashSet<_>>();
+        let removed_ids =
+            self.snippet_ids.iter().copied().filter(|id| !new_ids.contains(id)).collect::<Vec<_>>();
+        for id in removed_ids {
+            let _removed = self.remove_snippet(id);
+        }
+        self.insert_snippets(snippets);
+    }
+
     #[inline]
     pub fn export(&self, with_snippets: bool) -> Vec<ClipEntry> {
         self.iter()
@@ -110,9 +188,91 @@ where
             .collect()
     }
 
-    #[inline]
-    pub fn list(&self, preview_length: usize) -> Vec<ClipEntryMetadata> {
-        self.iter().map(|entry| entry.metadata(Some(preview_length))).collect()
+    /// Imports `clips` from an archive, returns the numbers of inserted clips
+    /// and clips merged into clips with the same content. Inserted clips which
+    /// are evicted right away as the history is full are not counted. Clips are
+    /// deduplicated by the digest of their content and duplicates are merged
+    /// with [`ClipEntry::merge`]. Sensitive clips and snippets are skipped.
+    /// With [`ImportMode::Replace`], all clips except snippets are removed
+    /// first.
+    pub fn import_archive(&mut self, clips: Vec<ClipEntry>, mode: ImportMode) -> (usize, usize) {
+        if mode == ImportMode::Replace {
+            let ids =
+                self.clips.keys().copied().filter(|&id| !self.is_snippet(id)).collect::<Vec<_>>();
+            for id in ids {
+                if self.remove_inner(id).is_some() {
+                    self.emit(Event::Removed(id));
+                }
+            }
+        }
+
+        let mut ids_by_digest = self
+            .clips
+            .iter()
+            .map(|(&id, clip)| (clip.sha256_digest().to_vec(), id))
+            .collect::<HashMap<_, _>>();
+        let mut inserted_ids = Vec::new();
+        let mut merged = 0;
+        for clip in clips {
+            if clip.is_empty() || clip.is_sensitive() || clip.snippet_name().is_some() {
+                continue;
+            }
+            if let Some(&id) = ids_by_digest.get(clip.sha256_digest()) {
+                if self.is_snippet(id) {
+                    continue;
+                }
+                if let Some(mut existing) = self.clips.get(&id).cloned() {
+                    existing.merge(&clip);
+                    self.insert_indexed(existing.clone());
+                    self.emit(Event::Updated { old_id: id, clip: existing });
+                    merged += 1;
+                }
+            } else {
+                let _unused = ids_by_digest.insert(clip.sha256_digest().to_vec(), clip.id());
+                inserted_ids.push(clip.id());
+                self.insert_indexed(clip.clone());
+                self.emit(Event::Inserted(clip));
+            }
+        }
+
+        self.remove_oldest();
+        let inserted = inserted_ids.iter().filter(|id| self.clips.contains_key(id)).count();
+        (inserted, merged)
+    }
+
+    /// Returns metadata of clips which pass the filters of `query`. Clips are
+    /// sorted by `query.order`, ties are broken by timestamp and then ID so
+    /// that paging through the result is stable. The newest order agrees with
+    /// [`Self::get_by_index`].
+    pub fn list(&self, query: &ListQuery) -> Vec<ClipEntryMetadata> {
+        let mut clips: Vec<_> = self
+            .iter_newest()
+            .filter(|clip| query.matches(clip, self.is_snippet(clip.id())))
+            .collect();
+        match query.order {
+            ListOrder::Newest => {}
+            ListOrder::Oldest => clips.reverse(),
+            ListOrder::MostUsed => clips.sort_by_key(|clip| Reverse(clip.use_count())),
+            ListOrder::Frecency => {
+                let now = OffsetDateTime::now_utc();
+                clips.sort_by_cached_key(|clip| Reverse(clip.frecency(now)));
+            }
+        }
+        let limit = if query.limit == 0 { usize::MAX } else { query.limit };
+        clips
+            .into_iter()
+            .skip(query.offset)
+            .take(limit)
+            .map(|clip| clip.metadata(Some(query.preview_length)))
+            .collect()
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if the search pattern is invalid.
+    pub fn search(&self, query: &SearchQuery) -> Result<Vec<SearchMatch>, Error> {
+        let matcher = query.build_matcher().context(error::SearchSnafu)?;
+        Ok(matcher.search(self.iter(), |clip| self.is_snippet(clip.id())))
     }
 
     #[inline]
@@ -121,19 +281,49 @@ where
     #[inline]
     pub fn get(&self, id: u64) -> Option<ClipEntry> { self.clips.get(&id).cloned() }
 
+    // iterates clips from the most recent one to the oldest one
+    fn iter_newest(&self) -> impl Iterator<Item = &ClipEntry> {
+        self.recency_index.iter().rev().filter_map(|(_, id)| self.clips.get(id))
+    }
+
+    /// Returns the ID of the clip at `index` of the history, the most recent
+    /// clip is at index 0.
+    #[inline]
+    pub fn id_by_index(&self, index: usize) -> Option<u64> {
+        self.recency_index.iter().rev().nth(index).map(|&(_, id)| id)
+    }
+
+    /// Returns the clip at `index` of the history, the most recent clip is at
+    /// index 0.
+    #[inline]
+    pub fn get_by_index(&self, index: usize) -> Option<ClipEntry> {
+        self.id_by_index(index).and_then(|id| self.get(id))
+    }
+
     #[inline]
     pub fn get_current_clip(&self, kind: ClipboardKind) -> Option<&ClipEntry> {
         self.current_clips[usize::from(kind)].and_then(|id| self.clips.get(&id))
     }
 
     #[inline]
-    pub fn insert(&mut self, data: ClipEntry) -> u64 { self.insert_inner(data) }
+    pub fn insert(&mut self, data: ClipEntry) -> u64 {
+        let id = self.insert_inner(data);
+        if let Some(clip) = self.clips.get(&id) {
+            self.emit(Event::Inserted(clip.clone()));
+        }
+        id
+    }
 
     fn insert_inner(&mut self, entry: ClipEntry) -> u64 {
         // emit notification
         match entry.as_ref() {
-            ClipboardContent::Image { width, height, bytes } => {
-                self.notification.on_image_fetched(bytes.len(), *width, *height);
+            ClipboardContent::Image { bytes, .. } => {
+                let (width, height) = entry.as_ref().image_dimensions().unwrap_or_default();
+                self.notification.on_image_fetched(
+                    bytes.len(),
+                    usize::try_from(width).unwrap_or(usize::MAX),
+                    usize::try_from(height).unwrap_or(usize::MAX),
+                );
             }
             ClipboardContent::Plaintext(text) => {
                 self.notification.on_plaintext_fetched(text.chars().count());
@@ -146,9 +336,11 @@ where
                                 let text = text.as_bytes();
                                 let current_text = current_text.as_bytes();
                                 let len = text.len().min(current_text.len());
-                                if text[..len] == current_text[..len] {
+                                if text[..len] == current_text[..len] && !current_clip.is_pinned() {
                                     if let Some(clip) = self.clips.remove(&id) {
-                                        let _id = self.timestamp_to_id.remove(&clip.timestamp());
+                                        let _removed =
+                                            self.recency_index.remove(&(clip.timestamp(), id));
+                                        self.emit(Event::Removed(id));
                                     }
                                 }
                             }
@@ -158,14 +350,37 @@ where
             }
         }
 
-        let (id, timestamp) = (entry.id(), entry.timestamp());
+        let mut entry = entry;
+        let id = entry.id();
+        if let Some(clip) = self.clips.get(&id) {
+            // the same content is copied again, keep the state of the existing clip
+            if clip.is_pinned() {
+                entry.set_pinned(true);
+            }
+            entry.set_usage(clip.use_count(), clip.last_used());
+            entry.set_tags(clip.tags());
+            entry.set_template(clip.is_template());
+            entry.set_snippet(
+                clip.snippet_name().map(ToString::to_string),
+                clip.snippet_path().map(Path::to_path_buf),
+            );
+        }
         self.current_clips[usize::from(entry.kind())] = Some(id);
-        drop(self.clips.insert(id, entry));
-        let _unused = self.timestamp_to_id.insert(timestamp, id);
+        self.insert_indexed(entry);
         self.remove_oldest();
         id
     }
 
+    // inserts `entry` and keeps the recency index in sync, the index entry of
+    // the replaced clip with the same ID is removed
+    fn insert_indexed(&mut self, entry: ClipEntry) {
+        let (id, timestamp) = (entry.id(), entry.timestamp());
+        if let Some(old_entry) = self.clips.insert(id, entry) {
+            let _removed = self.recency_index.remove(&(old_entry.timestamp(), id));
+        }
+        let _inserted = self.recency_index.insert((timestamp, id));
+    }
+
     #[inline]
     pub fn len(&self) -> usize { self.clips.len() }
 
@@ -178,32 +393,271 @@ where
         }
 
         let snippet_count = self.snippet_ids.len();
+        let pinned_count = self.pinned_count();
+        let mut evictable_bytes = if self.max_bytes == 0 {
+            0
+        } else {
+            self.clips
+                .iter()
+                .filter(|(id, clip)| !clip.is_pinned() && !self.snippet_ids.contains(id))
+                .map(|(_, clip)| clip.size_in_bytes())
+                .sum()
+        };
         let now = OffsetDateTime::now_utc();
-
-        while self.clips.len() > self.capacity + snippet_count {
-            if let Some((timestamp, id)) = self.timestamp_to_id.pop_first() {
-                if self.snippet_ids.contains(&id) {
-                    tracing::trace!("Retain snippet clip and update its timestamp (id: {id})");
-                    let _ = self.timestamp_to_id.insert(now, id);
-                    let _ = self.clips.get_mut(&id).map(|entry| entry.set_timestamp(now));
-                } else {
-                    tracing::trace!("Remove old clip (id: {id}, timestamp: {timestamp})");
-                    drop(self.clips.remove(&id));
+        let mut retained_clips = Vec::new();
+
+        while self.clips.len() > self.capacity + snippet_count + pinned_count
+            || evictable_bytes > self.max_bytes
+        {
+            let Some((timestamp, id)) = self.recency_index.pop_first() else {
+                break;
+            };
+            if self.snippet_ids.contains(&id) {
+                tracing::trace!("Retain snippet clip and update its timestamp (id: {id})");
+                retained_clips.push((now, id));
+                let _ = self.clips.get_mut(&id).map(|entry| entry.set_timestamp(now));
+            } else if self.clips.get(&id).is_some_and(ClipEntry::is_pinned) {
+                retained_clips.push((timestamp, id));
+            } else {
+                tracing::trace!("Remove old clip (id: {id}, timestamp: {timestamp})");
+                if let Some(clip) = self.clips.remove(&id) {
+                    evictable_bytes = evictable_bytes.saturating_sub(clip.size_in_bytes());
+                    self.emit(Event::Removed(id));
                 }
             }
         }
+
+        self.recency_index.extend(retained_clips);
+    }
+
+    #[inline]
+    fn pinned_count(&self) -> usize {
+        self.clips
+            .iter()
+            .filter(|(id, clip)| clip.is_pinned() && !self.snippet_ids.contains(id))
+            .count()
+    }
+
+    #[inline]
+    pub fn pin(&mut self, id: u64) -> bool { self.set_pinned(id, true) }
+
+    #[inline]
+    pub fn unpin(&mut self, id: u64) -> bool {
+        let ok = self.set_pinned(id, false);
+        self.remove_oldest();
+        ok
+    }
+
+    fn set_pinned(&mut self, id: u64, pinned: bool) -> bool {
+        let Some(clip) = self.clips.get_mut(&id) else {
+            return false;
+        };
+        clip.set_pinned(pinned);
+        let clip = clip.clone();
+        self.emit(Event::Updated { old_id: id, clip });
+        true
+    }
+
+    /// Tags clip `id` with `tag`, returns `false` if the clip is not found or
+    /// `tag` is empty. Tagging a clip with a tag it already has is a no-op.
+    pub fn tag(&mut self, id: u64, tag: &str) -> bool {
+        let Some(clip) = self.clips.get_mut(&id) else {
+            return false;
+        };
+        if tag.trim().is_empty() {
+            return false;
+        }
+        if clip.add_tag(tag) {
+            let clip = clip.clone();
+            self.emit(Event::Updated { old_id: id, clip });
+        }
+        true
+    }
+
+    /// Removes `tag` from clip `id`, returns `false` if the clip is not found
+    /// or is not tagged with `tag`.
+    pub fn untag(&mut self, id: u64, tag: &str) -> bool {
+        let Some(clip) = self.clips.get_mut(&id) else {
+            return false;
+        };
+        if !clip.remove_tag(tag) {
+            return false;
+        }
+        let clip = clip.clone();
+        self.emit(Event::Updated { old_id: id, clip });
+        true
+    }
+
+    /// Returns all tags in use with the number of clips tagged with each of
+    /// them, in lexicographic order.
+    pub fn tags(&self) -> Vec<(String, usize)> {
+        let mut tags = BTreeMap::<&str, usize>::new();
+        for tag in self.clips.values().flat_map(ClipEntry::tags) {
+            *tags.entry(tag.as_str()).or_default() += 1;
+        }
+        tags.into_iter().map(|(tag, count)| (tag.to_string(), count)).collect()
+    }
+
+    /// Returns metadata of snippets sorted by name, unnamed snippets come
+    /// last.
+    pub fn snippets(&self, preview_length: usize) -> Vec<ClipEntryMetadata> {
+        let mut snippets = self
+            .snippet_ids
+            .iter()
+            .filter_map(|id| self.clips.get(id))
+            .map(|clip| clip.metadata(Some(preview_length)))
+            .collect::<Vec<_>>();
+        snippets.sort_by(|a, b| {
+            match (&a.snippet_name, &b.snippet_name) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+            .then(a.id.cmp(&b.id))
+        });
+        snippets
+    }
+
+    /// Saves a snippet into the snippet directory and inserts it, returns the
+    /// ID of the new snippet.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snippet directory is not
+    /// configured, `name` is invalid or in use, or the file could not be
+    /// written.
+    pub async fn add_snippet(&mut self, name: &str, content: &str) -> Result<u64, Error> {
+        let path = self.snippet_file_path(name)?;
+        if self.snippet_id_by_name(name).is_some() {
+            return Err(Error::SnippetExists { name: name.to_string() });
+        }
+        let clip = Self::write_snippet(name, content, path).await?;
+        let id = clip.id();
+        self.insert_snippets(&[clip]);
+        Ok(id)
+    }
+
+    /// Replaces the content of snippet `name` in the snippet directory,
+    /// returns the ID of the updated snippet.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snippet is not found, it is
+    /// not in the snippet directory, or the file could not be written.
+    pub async fn update_snippet(&mut self, name: &str, content: &str) -> Result<u64, Error> {
+        let (old_id, path) = self.managed_snippet(name)?;
+        let clip = Self::write_snippet(name, content, path).await?;
+        let id = clip.id();
+        let _removed = self.remove_snippet(old_id);
+        self.insert_snippets(&[clip]);
+        Ok(id)
+    }
+
+    /// Removes snippet `name` and its file from the snippet directory.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snippet is not found, it is
+    /// not in the snippet directory, or the file could not be removed.
+    pub async fn delete_snippet(&mut self, name: &str) -> Result<(), Error> {
+        let (id, path) = self.managed_snippet(name)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(source) => return Err(Error::RemoveSnippetFile { path, source }),
+        }
+        let _removed = self.remove_snippet(id);
+        Ok(())
+    }
+
+    fn snippet_id_by_name(&self, name: &str) -> Option<u64> {
+        self.snippet_ids
+            .iter()
+            .copied()
+            .find(|id| self.clips.get(id).is_some_and(|clip| clip.snippet_name() == Some(name)))
+    }
+
+    // returns the file path of snippet `name` in the snippet directory, `name`
+    // must be a relative path without `..`
+    fn snippet_file_path(&self, name: &str) -> Result<PathBuf, Error> {
+        let directory =
+            self.snippet_directory.as_ref().ok_or(Error::SnippetDirectoryUnavailable)?;
+        let relative_path = Path::new(name);
+        let is_valid = !name.is_empty()
+            && relative_path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+        if is_valid {
+            Ok(directory.join(relative_path))
+        } else {
+            Err(Error::InvalidSnippetName { name: name.to_string() })
+        }
+    }
+
+    // returns the ID and file path of snippet `name` which is stored in the
+    // snippet directory
+    fn managed_snippet(&self, name: &str) -> Result<(u64, PathBuf), Error> {
+        let path = self.snippet_file_path(name)?;
+        let id = self
+            .snippet_id_by_name(name)
+            .ok_or_else(|| Error::SnippetNotFound { name: name.to_string() })?;
+        if self.clips.get(&id).is_some_and(|clip| clip.snippet_path() == Some(path.as_path())) {
+            Ok((id, path))
+        } else {
+            Err(Error::SnippetNotManaged { name: name.to_string() })
+        }
+    }
+
+    async fn write_snippet(name: &str, content: &str, path: PathBuf) -> Result<ClipEntry, Error> {
+        if content.is_empty() {
+            return Err(Error::EmptySnippet { name: name.to_string() });
+        }
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .context(error::WriteSnippetSnafu { path: path.clone() })?;
+        }
+        // the snippet watcher never reads a partially written snippet
+        let temporary_path = snippets::temporary_file_path(&path);
+        tokio::fs::write(&temporary_path, content)
+            .await
+            .context(error::WriteSnippetSnafu { path: temporary_path.clone() })?;
+        if let Err(source) = tokio::fs::rename(&temporary_path, &path).await {
+            drop(tokio::fs::remove_file(&temporary_path).await);
+            return Err(Error::WriteSnippet { path, source });
+        }
+
+        let mut clip = ClipEntry::from_string(content, ClipboardKind::Clipboard);
+        clip.set_template(SnippetTemplate::has_placeholders(content));
+        clip.set_snippet(Some(name.to_string()), Some(path));
+        Ok(clip)
     }
 
     pub fn remove_snippet(&mut self, id: u64) -> bool {
-        if self.snippet_ids.remove(&id) {
-            self.clips.remove(&id).is_some()
+        if !self.snippet_ids.remove(&id) {
+            return false;
+        }
+        if let Some(clip) = self.clips.remove(&id) {
+            let _removed = self.recency_index.remove(&(clip.timestamp(), id));
+            self.emit(Event::Removed(id));
+            true
         } else {
             false
         }
     }
 
     #[inline]
-    pub fn remove(&mut self, id: u64) -> bool { self.remove_inner(id).is_some() }
+    pub fn remove(&mut self, id: u64) -> bool {
+        if self.is_snippet(id) {
+            return self.remove_snippet(id);
+        }
+        let removed = self.remove_inner(id).is_some();
+        if removed {
+            self.emit(Event::Removed(id));
+        }
+        removed
+    }
 
     #[inline]
     fn remove_inner(&mut self, id: u64) -> Option<ClipEntry> {
@@ -218,37 +672,161 @@ where
         }
 
         if let Some(clip) = self.clips.remove(&id) {
-            let _id = self.timestamp_to_id.remove(&clip.timestamp());
+            let _removed = self.recency_index.remove(&(clip.timestamp(), id));
             Some(clip)
         } else {
             None
         }
     }
 
+    /// Removes clips which are older than their maximum age, returns IDs of the
+    /// removed clips. Snippets and pinned clips never expire.
+    pub fn remove_expired<F>(&mut self, max_age: F, now: OffsetDateTime) -> Vec<u64>
+    where
+        F: Fn(&ClipEntry) -> Option<time::Duration>,
+    {
+        let expired_ids = self
+            .clips
+            .iter()
+            .filter(|(id, clip)| !self.snippet_ids.contains(id) && !clip.is_pinned())
+            .filter(|(_, clip)| {
+                max_age(clip).is_some_and(|max_age| now - clip.timestamp() > max_age)
+            })
+            .map(|(&id, _)| id)
+            .collect::<Vec<_>>();
+        for &id in &expired_ids {
+            if self.remove_inner(id).is_some() {
+                tracing::trace!("Remove expired clip (id: {id})");
+                self.emit(Event::Removed(id));
+            }
+        }
+        expired_ids
+    }
+
     #[inline]
     pub fn clear(&mut self) {
-        self.timestamp_to_id.retain(|_, id| self.snippet_ids.contains(id));
+        self.clips.retain(|id, clip| self.snippet_ids.contains(id) || clip.is_pinned());
+        self.recency_index.retain(|(_, id)| self.clips.contains_key(id));
         self.current_clips = [None; ClipboardKind::MAX_LENGTH];
-        self.clips.retain(|id, _| self.snippet_ids.contains(id));
         self.notification.on_history_cleared();
+        self.emit(Event::Cleared);
     }
 
     pub fn replace(&mut self, old_id: u64, data: &[u8], mime: &mime::Mime) -> (bool, u64) {
-        let kind = self.remove_inner(old_id).map_or(ClipboardKind::Primary, |clip| clip.kind());
-        ClipEntry::new(data, mime, kind, None).map_or((false, old_id), |entry| {
+        let (kind, pinned) = self
+            .remove_inner(old_id)
+            .map_or((ClipboardKind::Primary, false), |clip| (clip.kind(), clip.is_pinned()));
+        ClipEntry::new(data, mime, kind, None).map_or((false, old_id), |mut entry| {
+            entry.set_pinned(pinned);
             let new_id = entry.id();
-            let _ = self.insert_inner(entry);
+            let _ = self.insert_inner(entry.clone());
+            self.emit(Event::Updated { old_id, clip: entry });
             (true, new_id)
         })
     }
 
+    /// Returns clip `id` with placeholders expanded if it is a snippet
+    /// template, other clips are returned as-is. The expanded clip is not
+    /// stored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the template could not be
+    /// expanded, such as a value of `prompts` is missing.
+    pub fn expand(
+        &self,
+        id: u64,
+        prompts: &HashMap<String, String>,
+    ) -> Result<Option<ClipEntry>, Error> {
+        self.clips.get(&id).map(|clip| self.expand_clip(clip, prompts)).transpose()
+    }
+
+    fn expand_clip(
+        &self,
+        clip: &ClipEntry,
+        prompts: &HashMap<String, String>,
+    ) -> Result<ClipEntry, Error> {
+        if !clip.is_template() {
+            return Ok(clip.clone());
+        }
+        let context = TemplateContext {
+            now: OffsetDateTime::now_utc()
+                .to_offset(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC)),
+            clipboard: self
+                .get_current_clip(ClipboardKind::Clipboard)
+                .filter(|current| current.id() != clip.id() && current.is_utf8_string())
+                .map(ClipEntry::as_utf8_string),
+            prompts: prompts.clone(),
+            environment: self
+                .snippet_environment_variables
+                .iter()
+                .filter_map(|name| Some((name.clone(), std::env::var(name).ok()?)))
+                .collect(),
+        };
+        let text = SnippetTemplate::parse(&clip.as_utf8_string())
+            .expand(&context)
+            .context(error::ExpandTemplateSnafu)?;
+        Ok(ClipEntry::from_string(text, clip.kind()))
+    }
+
+    /// Stores the clip into clipboard `clipboard_kind` and counts it as a use
+    /// of the clip.
+    #[inline]
     pub async fn mark(&mut self, id: u64, clipboard_kind: ClipboardKind) -> Result<(), Error> {
+        self.mark_inner(id, clipboard_kind, true, &HashMap::new()).await
+    }
+
+    /// Same as [`Self::mark`], placeholders of a snippet template are expanded
+    /// with `prompts` and the expanded content is stored into clipboard.
+    #[inline]
+    pub async fn mark_with_prompts(
+        &mut self,
+        id: u64,
+        clipboard_kind: ClipboardKind,
+        prompts: &HashMap<String, String>,
+    ) -> Result<(), Error> {
+        self.mark_inner(id, clipboard_kind, true, prompts).await
+    }
+
+    /// Stores the clip into clipboard `clipboard_kind` without counting it as a
+    /// use, such as storing a newly inserted clip.
+    #[inline]
+    pub async fn store(&mut self, id: u64, clipboard_kind: ClipboardKind) -> Result<(), Error> {
+        self.mark_inner(id, clipboard_kind, false, &HashMap::new()).await
+    }
+
+    async fn mark_inner(
+        &mut self,
+        id: u64,
+        clipboard_kind: ClipboardKind,
+        record_use: bool,
+        prompts: &HashMap<String, String>,
+    ) -> Result<(), Error> {
+        // expand a template before touching the clip, it is left unchanged if
+        // expanding fails
+        let expanded = match self.clips.get(&id) {
+            Some(clip) if clip.is_template() => Some(self.expand_clip(clip, prompts)?),
+            _ => None,
+        };
         if let Some(clip) = self.clips.get_mut(&id) {
+            // marking refreshes the timestamp, move the clip to the front
+            let _removed = self.recency_index.remove(&(clip.timestamp(), id));
             clip.mark(clipboard_kind);
+            if record_use {
+                clip.record_use(clip.timestamp());
+            }
+            let _inserted = self.recency_index.insert((clip.timestamp(), id));
+            let clip = clip.clone();
+            let stored = expanded.as_ref().unwrap_or(&clip);
             self.backend
-                .store(clipboard_kind, clip.as_ref().clone())
+                .store_with_representations(
+                    clipboard_kind,
+                    stored.as_ref().clone(),
+                    stored.representations().to_vec(),
+                )
                 .await
                 .context(error::StoreClipboardContentSnafu)?;
+            self.emit(Event::Marked(clip));
         }
 
         Ok(())
@@ -260,13 +838,21 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashSet, sync::Arc, time::Duration};
+    use std::{
+        collections::{HashMap, HashSet},
+        path::PathBuf,
+        sync::Arc,
+        time::Duration,
+    };
 
-    use clipcat_base::{ClipEntry, ClipboardKind};
+    use clipcat_base::{
+        archive::ImportMode, ClipEntry, ClipboardContent, ClipboardKind, ListOrder, ListQuery,
+        SearchMode, SearchQuery,
+    };
 
     use crate::{
-        backend::LocalClipboardBackend,
-        manager::{ClipboardManager, DEFAULT_CAPACITY},
+        backend::{ClipboardBackend as _, LocalClipboardBackend},
+        manager::{ClipboardManager, Error, Event, DEFAULT_CAPACITY},
         notification::DummyNotification,
     };
 
@@ -442,6 +1028,433 @@ mod tests {
         assert!(!ok);
     }
 
+    #[test]
+    fn test_pin() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let cap = 5;
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            cap,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+        assert!(!mgr.pin(43));
+
+        let clips = create_clips(cap);
+        let pinned_id = clips[0].id();
+        for clip in clips {
+            let _ = mgr.insert(clip);
+        }
+        assert!(mgr.pin(pinned_id));
+        assert!(mgr.get(pinned_id).unwrap().is_pinned());
+
+        // the oldest clip is pinned, it should survive eviction
+        for clip in create_clips(cap * 3).into_iter().skip(cap) {
+            let _ = mgr.insert(clip);
+        }
+        assert_eq!(mgr.len(), cap + 1);
+        assert!(mgr.get(pinned_id).is_some());
+
+        // pinned clip should survive `clear`
+        mgr.clear();
+        assert_eq!(mgr.len(), 1);
+        assert!(mgr.get(pinned_id).is_some());
+
+        assert!(mgr.unpin(pinned_id));
+        assert!(!mgr.get(pinned_id).unwrap().is_pinned());
+        mgr.clear();
+        assert!(mgr.is_empty());
+    }
+
+    #[test]
+    fn test_search() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let long_text = format!("{}needle", "x".repeat(100));
+        let _ = mgr.insert(ClipEntry::from_string(&long_text, ClipboardKind::Clipboard));
+        let _ = mgr.insert(ClipEntry::from_string("haystack", ClipboardKind::Primary));
+
+        // match text beyond the preview
+        let query = SearchQuery {
+            pattern: "needle".to_string(),
+            preview_length: 10,
+            ..SearchQuery::default()
+        };
+        let matches = mgr.search(&query).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].ranges, vec![100..106]);
+
+        let query = SearchQuery {
+            pattern: "[".to_string(),
+            mode: SearchMode::Regex,
+            ..SearchQuery::default()
+        };
+        assert!(mgr.search(&query).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let clips = create_clips(5);
+        let ids = clips.iter().map(|clip| mgr.insert(clip.clone())).collect::<Vec<_>>();
+        assert!(mgr.pin(ids[1]));
+
+        let list_ids = |mgr: &ClipboardManager<DummyNotification>, query: &ListQuery| {
+            mgr.list(query).into_iter().map(|metadata| metadata.id).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            list_ids(&mgr, &ListQuery::default()),
+            vec![ids[4], ids[3], ids[2], ids[1], ids[0]]
+        );
+        assert_eq!(
+            list_ids(&mgr, &ListQuery { order: ListOrder::Oldest, ..ListQuery::default() }),
+            ids
+        );
+        assert_eq!(
+            list_ids(&mgr, &ListQuery { offset: 1, limit: 2, ..ListQuery::default() }),
+            vec![ids[3], ids[2]]
+        );
+        assert!(list_ids(&mgr, &ListQuery { offset: 5, ..ListQuery::default() }).is_empty());
+        assert_eq!(
+            list_ids(&mgr, &ListQuery { pinned: Some(true), ..ListQuery::default() }),
+            vec![ids[1]]
+        );
+        assert_eq!(
+            list_ids(
+                &mgr,
+                &ListQuery {
+                    since: Some(clips[1].timestamp()),
+                    until: Some(clips[3].timestamp()),
+                    ..ListQuery::default()
+                }
+            ),
+            vec![ids[2], ids[1]]
+        );
+
+        // marking a clip counts as a use and moves it to the clipboard
+        mgr.mark(ids[2], ClipboardKind::Clipboard).await.unwrap();
+        mgr.mark(ids[2], ClipboardKind::Clipboard).await.unwrap();
+        mgr.mark(ids[3], ClipboardKind::Clipboard).await.unwrap();
+        assert_eq!(
+            list_ids(
+                &mgr,
+                &ListQuery { order: ListOrder::MostUsed, limit: 3, ..ListQuery::default() }
+            ),
+            vec![ids[2], ids[3], ids[4]]
+        );
+        assert_eq!(
+            list_ids(
+                &mgr,
+                &ListQuery {
+                    kinds: vec![ClipboardKind::Clipboard],
+                    order: ListOrder::Oldest,
+                    ..ListQuery::default()
+                }
+            ),
+            vec![ids[2], ids[3]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_usage() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let clips = create_clips(3);
+        let ids = clips.iter().map(|clip| mgr.insert(clip.clone())).collect::<Vec<_>>();
+
+        // storing an inserted clip is not a use
+        mgr.store(ids[2], ClipboardKind::Clipboard).await.unwrap();
+        assert_eq!(mgr.get(ids[2]).unwrap().use_count(), 0);
+
+        mgr.mark(ids[0], ClipboardKind::Clipboard).await.unwrap();
+        mgr.mark(ids[0], ClipboardKind::Clipboard).await.unwrap();
+        let clip = mgr.get(ids[0]).unwrap();
+        assert_eq!(clip.use_count(), 2);
+        assert_eq!(clip.last_used(), Some(clip.timestamp()));
+
+        // copying the same content again keeps its usage
+        let _ = mgr.insert(clips[0].clone());
+        assert_eq!(mgr.get(ids[0]).unwrap().use_count(), 2);
+
+        let list_ids = |query: &ListQuery| {
+            mgr.list(query).into_iter().map(|metadata| metadata.id).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            list_ids(&ListQuery { order: ListOrder::Frecency, ..ListQuery::default() }),
+            vec![ids[0], ids[2], ids[1]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_templates() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        let mut snippet =
+            ClipEntry::from_string("git checkout -b {{prompt:Ticket}}", ClipboardKind::Clipboard);
+        snippet.set_template(true);
+        mgr.insert_snippets(&[snippet.clone()]);
+
+        let prompts = HashMap::from([("Ticket".to_string(), "CC-42".to_string())]);
+        let expanded = mgr.expand(snippet.id(), &prompts).unwrap().unwrap();
+        assert_eq!(expanded.as_utf8_string(), "git checkout -b CC-42");
+        assert!(!expanded.is_template());
+
+        // the template is kept unchanged if a prompt is missing
+        assert!(mgr.mark(snippet.id(), ClipboardKind::Clipboard).await.is_err());
+        assert_eq!(mgr.get(snippet.id()).unwrap().use_count(), 0);
+
+        mgr.mark_with_prompts(snippet.id(), ClipboardKind::Clipboard, &prompts).await.unwrap();
+        assert_eq!(
+            backend.load(ClipboardKind::Clipboard, None).await.unwrap(),
+            ClipboardContent::Plaintext("git checkout -b CC-42".to_string())
+        );
+        assert!(mgr.get(snippet.id()).unwrap().is_template());
+    }
+
+    #[test]
+    fn test_snippets() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let mut signature = ClipEntry::from_string("Best regards", ClipboardKind::Clipboard);
+        signature.set_snippet(
+            Some("mail/signature".to_string()),
+            Some(PathBuf::from("/snippets/mail/signature")),
+        );
+        let mut address = ClipEntry::from_string("Main Street", ClipboardKind::Clipboard);
+        address.set_snippet(Some("address".to_string()), None);
+        let unnamed = ClipEntry::from_string("unnamed", ClipboardKind::Clipboard);
+        mgr.insert_snippets(&[signature.clone(), unnamed.clone(), address.clone()]);
+        let _id = mgr.insert(ClipEntry::from_string("not a snippet", ClipboardKind::Clipboard));
+
+        let snippets = mgr.snippets(20);
+        assert_eq!(
+            snippets.iter().map(|metadata| metadata.id).collect::<Vec<_>>(),
+            [address.id(), signature.id(), unnamed.id()]
+        );
+        assert_eq!(snippets[1].snippet_name.as_deref(), Some("mail/signature"));
+        assert_eq!(
+            snippets[1].snippet_path.as_deref(),
+            Some(PathBuf::from("/snippets/mail/signature").as_path())
+        );
+        assert!(snippets[0].snippet_path.is_none());
+
+        // copying the content of a snippet keeps its name
+        let _id = mgr.insert(ClipEntry::from_string("Main Street", ClipboardKind::Clipboard));
+        assert_eq!(mgr.get(address.id()).unwrap().snippet_name(), Some("address"));
+    }
+
+    #[test]
+    fn test_replace_snippets() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let snippets = ["kept", "removed"]
+            .map(|content| ClipEntry::from_string(content, ClipboardKind::Clipboard));
+        mgr.insert_snippets(&snippets);
+        let clip_id = mgr.insert(ClipEntry::from_string("not a snippet", ClipboardKind::Clipboard));
+
+        let added = ClipEntry::from_string("added", ClipboardKind::Clipboard);
+        mgr.replace_snippets(&[snippets[0].clone(), added.clone()]);
+        assert!(mgr.is_snippet(snippets[0].id()));
+        assert!(mgr.is_snippet(added.id()));
+        assert!(!mgr.is_snippet(snippets[1].id()));
+        assert!(mgr.get(snippets[1].id()).is_none());
+        assert!(mgr.get(clip_id).is_some());
+    }
+
+    #[test]
+    fn test_set_capacity() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let clips = create_clips(5);
+        for clip in &clips {
+            let _id = mgr.insert(clip.clone());
+        }
+
+        mgr.set_capacity(3);
+        assert_eq!(mgr.capacity(), 3);
+        assert_eq!(mgr.len(), 3);
+        assert!(mgr.get(clips[0].id()).is_none());
+        assert!(mgr.get(clips[4].id()).is_some());
+
+        mgr.set_capacity(0);
+        assert_eq!(mgr.capacity(), DEFAULT_CAPACITY);
+    }
+
+    #[test]
+    fn test_import_archive() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let clips = create_clips(3);
+        for clip in &clips {
+            let _id = mgr.insert(clip.clone());
+        }
+        mgr.insert_snippets(&[ClipEntry::from_string("snippet", ClipboardKind::Clipboard)]);
+
+        let mut duplicate = clips[0].clone();
+        duplicate.set_pinned(true);
+        duplicate.set_tags(["imported"]);
+        let mut sensitive = ClipEntry::from_string("secret", ClipboardKind::Clipboard);
+        sensitive.set_sensitive(true);
+        let new_clip = ClipEntry::from_string("new", ClipboardKind::Clipboard);
+        let (inserted, merged) = mgr.import_archive(
+            vec![duplicate, new_clip.clone(), new_clip.clone(), sensitive.clone()],
+            ImportMode::Merge,
+        );
+        assert_eq!((inserted, merged), (1, 2));
+        assert_eq!(mgr.len(), 5);
+        let merged_clip = mgr.get(clips[0].id()).unwrap();
+        assert!(merged_clip.is_pinned());
+        assert!(merged_clip.has_tag("imported"));
+        assert!(mgr.get(sensitive.id()).is_none());
+
+        let (inserted, merged) = mgr.import_archive(vec![new_clip.clone()], ImportMode::Replace);
+        assert_eq!((inserted, merged), (1, 0));
+        assert_eq!(mgr.len(), 2);
+        assert!(mgr.get(new_clip.id()).is_some());
+        assert_eq!(mgr.snippets(10).len(), 1);
+
+        // a clip which is older than the rest is evicted at once
+        mgr.set_capacity(1);
+        let timestamp = time::OffsetDateTime::now_utc() - time::Duration::days(1);
+        let old_clip = ClipEntry::new(
+            b"old",
+            &mime::TEXT_PLAIN_UTF_8,
+            ClipboardKind::Clipboard,
+            Some(timestamp),
+        )
+        .unwrap();
+        let (inserted, merged) = mgr.import_archive(vec![old_clip.clone()], ImportMode::Merge);
+        assert_eq!((inserted, merged), (0, 0));
+        assert!(mgr.get(old_clip.id()).is_none());
+        assert!(mgr.get(new_clip.id()).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_manage_snippets() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        assert!(matches!(
+            mgr.add_snippet("greeting", "hello").await,
+            Err(Error::SnippetDirectoryUnavailable)
+        ));
+
+        let directory =
+            std::env::temp_dir().join(format!("clipcat-test-snippets-{}", std::process::id()));
+        drop(std::fs::remove_dir_all(&directory));
+        let _guard = RemoveDirGuard(directory.clone());
+        mgr.set_snippet_directory(Some(directory.clone()));
+        assert!(matches!(
+            mgr.add_snippet("../greeting", "hello").await,
+            Err(Error::InvalidSnippetName { .. })
+        ));
+        assert!(matches!(mgr.add_snippet("greeting", "").await, Err(Error::EmptySnippet { .. })));
+
+        let id = mgr.add_snippet("mail/greeting", "hello").await.unwrap();
+        assert_eq!(std::fs::read_to_string(directory.join("mail/greeting")).unwrap(), "hello");
+        assert!(!directory.join("mail/.greeting.tmp").exists());
+        assert!(mgr.is_snippet(id));
+        assert!(matches!(
+            mgr.add_snippet("mail/greeting", "hi").await,
+            Err(Error::SnippetExists { .. })
+        ));
+
+        let new_id = mgr.update_snippet("mail/greeting", "hi").await.unwrap();
+        assert_ne!(id, new_id);
+        assert!(mgr.get(id).is_none());
+        assert_eq!(mgr.get(new_id).unwrap().snippet_name(), Some("mail/greeting"));
+        assert_eq!(std::fs::read_to_string(directory.join("mail/greeting")).unwrap(), "hi");
+
+        let mut external = ClipEntry::from_string("external", ClipboardKind::Clipboard);
+        external.set_snippet(Some("external".to_string()), Some(PathBuf::from("/external")));
+        mgr.insert_snippets(&[external]);
+        assert!(matches!(
+            mgr.delete_snippet("external").await,
+            Err(Error::SnippetNotManaged { .. })
+        ));
+
+        mgr.delete_snippet("mail/greeting").await.unwrap();
+        assert!(mgr.get(new_id).is_none());
+        assert!(!directory.join("mail/greeting").exists());
+        assert!(matches!(
+            mgr.delete_snippet("mail/greeting").await,
+            Err(Error::SnippetNotFound { .. })
+        ));
+    }
+
+    // removes the directory even if the test fails
+    struct RemoveDirGuard(PathBuf);
+
+    impl Drop for RemoveDirGuard {
+        fn drop(&mut self) { drop(std::fs::remove_dir_all(&self.0)); }
+    }
+
+    #[test]
+    fn test_tags() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let clips = create_clips(3);
+        let ids = clips.iter().map(|clip| mgr.insert(clip.clone())).collect::<Vec<_>>();
+
+        assert!(mgr.tag(ids[0], "work"));
+        assert!(mgr.tag(ids[0], "work"));
+        assert!(mgr.tag(ids[1], " work "));
+        assert!(mgr.tag(ids[1], "code"));
+        assert!(!mgr.tag(ids[2], "  "));
+        assert!(!mgr.tag(0, "work"));
+        assert_eq!(mgr.get(ids[1]).unwrap().tags(), ["code", "work"]);
+        assert_eq!(mgr.tags(), vec![("code".to_string(), 1), ("work".to_string(), 2)]);
+
+        let query = ListQuery { tags: vec!["work".to_string()], ..ListQuery::default() };
+        let listed = mgr.list(&query).into_iter().map(|metadata| metadata.id).collect::<Vec<_>>();
+        assert_eq!(listed, vec![ids[1], ids[0]]);
+
+        // copying the same content again keeps its tags
+        let _ = mgr.insert(clips[1].clone());
+        assert_eq!(mgr.get(ids[1]).unwrap().tags(), ["code", "work"]);
+
+        assert!(mgr.untag(ids[1], "work"));
+        assert!(!mgr.untag(ids[1], "work"));
+        assert_eq!(mgr.tags(), vec![("code".to_string(), 1), ("work".to_string(), 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_get_by_index() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        assert!(mgr.get_by_index(0).is_none());
+
+        let ids = create_clips(4).into_iter().map(|clip| mgr.insert(clip)).collect::<Vec<_>>();
+        assert_eq!(mgr.id_by_index(0), Some(ids[3]));
+        assert_eq!(mgr.get_by_index(2).map(|clip| clip.id()), Some(ids[1]));
+        assert!(mgr.get_by_index(4).is_none());
+
+        // marking a clip moves it to the front
+        mgr.mark(ids[1], ClipboardKind::Clipboard).await.unwrap();
+        assert_eq!(mgr.id_by_index(0), Some(ids[1]));
+        assert_eq!(mgr.id_by_index(1), Some(ids[3]));
+
+        // index agrees with the newest order of `list`
+        let listed = mgr.list(&ListQuery::default()).into_iter().map(|metadata| metadata.id);
+        assert!(listed.enumerate().all(|(index, id)| mgr.id_by_index(index) == Some(id)));
+
+        assert!(mgr.remove(ids[1]));
+        assert_eq!(mgr.id_by_index(0), Some(ids[3]));
+        assert_eq!(mgr.id_by_index(3), None);
+    }
+
     #[test]
     fn test_clear() {
         let backend = Arc::new(LocalClipboardBackend::new());
@@ -458,4 +1471,118 @@ mod tests {
         assert!(mgr.is_empty());
         assert_eq!(mgr.len(), 0);
     }
+
+    #[test]
+    fn test_remove_expired() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let now = time::OffsetDateTime::now_utc();
+        let hour_ago = Some(now - time::Duration::hours(1));
+        let old_primary =
+            ClipEntry::new(b"primary", &mime::TEXT_PLAIN_UTF_8, ClipboardKind::Primary, hour_ago)
+                .unwrap();
+        let old_clipboard = ClipEntry::new(
+            b"clipboard",
+            &mime::TEXT_PLAIN_UTF_8,
+            ClipboardKind::Clipboard,
+            hour_ago,
+        )
+        .unwrap();
+        let pinned =
+            ClipEntry::new(b"pinned", &mime::TEXT_PLAIN_UTF_8, ClipboardKind::Primary, hour_ago)
+                .unwrap();
+        let new_primary = ClipEntry::from_string("new", ClipboardKind::Primary);
+        for clip in [&old_primary, &old_clipboard, &pinned, &new_primary] {
+            let _ = mgr.insert(clip.clone());
+        }
+        assert!(mgr.pin(pinned.id()));
+        let mut events = mgr.subscribe();
+
+        let max_age = |clip: &ClipEntry| {
+            (clip.kind() == ClipboardKind::Primary).then(|| time::Duration::minutes(10))
+        };
+        assert_eq!(mgr.remove_expired(max_age, now), vec![old_primary.id()]);
+        assert!(matches!(events.try_recv(), Ok(Event::Removed(id)) if id == old_primary.id()));
+        assert_eq!(mgr.len(), 3);
+        assert!(mgr.remove_expired(max_age, now).is_empty());
+    }
+
+    #[test]
+    fn test_max_bytes() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+
+        // each clip takes 4 bytes
+        let clips = (1000..1010)
+            .map(|i| {
+                std::thread::sleep(Duration::from_millis(1));
+                ClipEntry::from_string(i, ClipboardKind::Primary)
+            })
+            .collect::<Vec<_>>();
+        for clip in &clips[..5] {
+            let _ = mgr.insert(clip.clone());
+        }
+        assert_eq!(mgr.size_in_bytes(), 20);
+        assert!(mgr.pin(clips[0].id()));
+
+        mgr.set_max_bytes(12);
+        assert_eq!(mgr.len(), 4);
+        assert!(mgr.get(clips[0].id()).is_some());
+        assert!(mgr.get(clips[1].id()).is_none());
+        assert_eq!(mgr.size_in_bytes(), 16);
+
+        for clip in &clips[5..] {
+            let _ = mgr.insert(clip.clone());
+        }
+        assert_eq!(mgr.len(), 4);
+        assert_eq!(mgr.size_in_bytes(), 16);
+        for clip in &clips[7..] {
+            assert!(mgr.get(clip.id()).is_some());
+        }
+
+        mgr.set_max_bytes(0);
+        mgr.import(&clips);
+        assert_eq!(mgr.len(), clips.len());
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            1,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+        let mut events = mgr.subscribe();
+
+        let clips = create_clips(2);
+        let first_id = mgr.insert(clips[0].clone());
+        let second_id = mgr.insert(clips[1].clone());
+        assert!(mgr.pin(second_id));
+        assert!(mgr.remove(second_id));
+        mgr.clear();
+        let snippet = ClipEntry::from_string("snippet", ClipboardKind::Clipboard);
+        mgr.insert_snippets(std::slice::from_ref(&snippet));
+        assert!(mgr.remove(snippet.id()));
+        assert!(!mgr.remove(snippet.id()));
+
+        assert!(matches!(events.try_recv(), Ok(Event::Inserted(clip)) if clip.id() == first_id));
+        assert!(matches!(events.try_recv(), Ok(Event::Removed(id)) if id == first_id));
+        assert!(matches!(events.try_recv(), Ok(Event::Inserted(clip)) if clip.id() == second_id));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(Event::Updated { old_id, clip }) if old_id == second_id && clip.is_pinned()
+        ));
+        assert!(matches!(events.try_recv(), Ok(Event::Removed(id)) if id == second_id));
+        assert!(matches!(events.try_recv(), Ok(Event::Cleared)));
+        assert!(
+            matches!(events.try_recv(), Ok(Event::Inserted(clip)) if clip.id() == snippet.id())
+        );
+        assert!(matches!(events.try_recv(), Ok(Event::Removed(id)) if id == snippet.id()));
+        assert!(events.try_recv().is_err());
+    }
 }
@@ -0,0 +1,7 @@
+use once_cell::sync::Lazy;
+use prometheus::IntCounter;
+
+pub static EXPIRED_CLIPS_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    IntCounter::new("expired_clips_total", "Total number of clips removed after they expired")
+        .expect("setup metrics")
+});
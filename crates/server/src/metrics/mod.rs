@@ -1,5 +1,6 @@
 pub mod dbus;
 pub mod grpc;
+pub mod manager;
 
 use clipcat_metrics::error;
 use snafu::ResultExt;
@@ -26,6 +27,11 @@ impl Metrics {
             .register(Box::new(dbus::REQUEST_DURATION_SECONDS.clone()))
             .context(error::SetupMetricsSnafu)?;
 
+        // clipboard manager
+        registry
+            .register(Box::new(manager::EXPIRED_CLIPS_TOTAL.clone()))
+            .context(error::SetupMetricsSnafu)?;
+
         Ok(Self { registry })
     }
 }
@@ -1,15 +1,11 @@
-use std::{
-    fmt,
-    path::{Path, PathBuf},
-    time::Duration,
-};
+use std::fmt;
 
 use clipcat_base::{ClipboardKind, PROJECT_VERSION};
 use futures::{FutureExt, StreamExt};
 use notify_rust::Notification as DesktopNotification;
 use tokio::sync::mpsc;
 
-use crate::notification::traits;
+use crate::{config::DesktopNotificationConfig, notification::traits};
 
 enum Event {
     DaemonStarted,
@@ -20,6 +16,7 @@ enum Event {
     WaylandConnected { clipboard_kind: ClipboardKind, connection_info: String },
     ImageFetched { size: usize, width: usize, height: usize },
     PlaintextFetched { character_count: usize },
+    Reconfigure(DesktopNotificationConfig),
     Shutdown,
 }
 
@@ -29,24 +26,15 @@ pub struct Notification {
 }
 
 impl Notification {
-    pub fn new<IconPath>(
-        icon: IconPath,
-        timeout: Duration,
-        long_plaintext_length: usize,
-    ) -> (Self, Worker)
-    where
-        IconPath: AsRef<Path>,
-    {
+    pub fn new(config: DesktopNotificationConfig) -> (Self, Worker) {
         let (event_sender, event_receiver) = mpsc::unbounded_channel();
-        (
-            Self { event_sender },
-            Worker {
-                event_receiver,
-                icon: icon.as_ref().to_path_buf(),
-                timeout,
-                long_plaintext_length,
-            },
-        )
+        (Self { event_sender }, Worker { event_receiver, config })
+    }
+
+    /// Replaces the configuration of the worker, notifications are dropped
+    /// while `config.enable` is `false`.
+    pub fn reconfigure(&self, config: DesktopNotificationConfig) {
+        drop(self.event_sender.send(Event::Reconfigure(config)));
     }
 }
 
@@ -91,18 +79,14 @@ impl traits::Notification for Notification {
 pub struct Worker {
     event_receiver: mpsc::UnboundedReceiver<Event>,
 
-    icon: PathBuf,
-
-    timeout: Duration,
-
-    long_plaintext_length: usize,
+    config: DesktopNotificationConfig,
 }
 
 impl Worker {
     #[allow(clippy::redundant_pub_crate)]
     pub async fn serve(self, shutdown_signal: sigfinn::Shutdown) {
         let mut shutdown_signal = shutdown_signal.into_stream();
-        let Self { mut event_receiver, ref icon, timeout, long_plaintext_length } = self;
+        let Self { mut event_receiver, mut config } = self;
         let pid = std::process::id();
 
         loop {
@@ -144,40 +128,24 @@ impl Worker {
                     )
                 }
                 Some(Event::PlaintextFetched { character_count }) => {
+                    let long_plaintext_length = config.long_plaintext_length;
                     if character_count >= long_plaintext_length && long_plaintext_length > 0 {
                         format!("Fetched a long plaintext.\n(size: {character_count})")
                     } else {
                         continue;
                     }
                 }
+                Some(Event::Reconfigure(new_config)) => {
+                    config = new_config;
+                    continue;
+                }
                 Some(Event::Shutdown) | None => {
                     prepare_to_shutdown = true;
                     format!("Daemon is shutting down.\n(version: {PROJECT_VERSION}, PID: {pid})")
                 }
             };
-            let notification = DesktopNotification::new()
-                .summary(clipcat_base::NOTIFICATION_SUMMARY)
-                .body(&body)
-                .icon(&icon.display().to_string())
-                .timeout(timeout)
-                .finalize();
-
-            #[cfg(all(
-                unix,
-                not(any(
-                    target_os = "macos",
-                    target_os = "ios",
-                    target_os = "android",
-                    target_os = "emscripten"
-                ))
-            ))]
-            if let Err(err) = notification.show_async().await {
-                tracing::warn!("Could not send desktop notification, error: {err}");
-            }
-
-            #[cfg(target_os = "macos")]
-            if let Err(err) = notification.show() {
-                tracing::warn!("Could not send desktop notification, error: {err}");
+            if config.enable {
+                show_notification(&body, &config).await;
             }
 
             if prepare_to_shutdown {
@@ -187,6 +155,33 @@ impl Worker {
     }
 }
 
+async fn show_notification(body: &str, config: &DesktopNotificationConfig) {
+    let notification = DesktopNotification::new()
+        .summary(clipcat_base::NOTIFICATION_SUMMARY)
+        .body(body)
+        .icon(&config.icon.display().to_string())
+        .timeout(config.timeout)
+        .finalize();
+
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "android",
+            target_os = "emscripten"
+        ))
+    ))]
+    if let Err(err) = notification.show_async().await {
+        tracing::warn!("Could not send desktop notification, error: {err}");
+    }
+
+    #[cfg(target_os = "macos")]
+    if let Err(err) = notification.show() {
+        tracing::warn!("Could not send desktop notification, error: {err}");
+    }
+}
+
 impl clipcat_clipboard::EventObserver for Notification {
     fn on_connected(
         &self,
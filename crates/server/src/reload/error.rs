@@ -0,0 +1,17 @@
+use snafu::Snafu;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    #[snafu(display("Could not load configuration, error: {message}"))]
+    LoadConfig { message: String },
+
+    #[snafu(display("Could not generate clip filter, error: {source}"))]
+    GenerateClipFilter { source: crate::watcher::ClipboardWatcherOptionsError },
+
+    #[snafu(display("Could not load snippets, error: {source}"))]
+    LoadSnippets { source: crate::Error },
+
+    #[snafu(display("Clipboard worker is not running"))]
+    WorkerClosed,
+}
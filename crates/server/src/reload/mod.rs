@@ -0,0 +1,142 @@
+mod error;
+
+use clipcat_base::SharedClipFilter;
+use snafu::ResultExt;
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+pub use self::error::Error;
+use crate::{
+    config::{Config, ConfigLoader},
+    history::HistoryManager,
+    manager::ClipboardManager,
+    notification,
+    snippets::{self, SnippetWatcherEventReceiver},
+};
+
+/// Sends the result of a reload request back to its requester.
+pub type Responder = oneshot::Sender<Result<Vec<&'static str>, Error>>;
+
+/// Creates a handle for requesting reloads and the receiver of the requests,
+/// requests are served by the clipboard worker.
+pub fn channel() -> (Handle, mpsc::UnboundedReceiver<Responder>) {
+    let (request_sender, request_receiver) = mpsc::unbounded_channel();
+    (Handle { request_sender }, request_receiver)
+}
+
+#[derive(Clone, Debug)]
+pub struct Handle {
+    request_sender: mpsc::UnboundedSender<Responder>,
+}
+
+impl Handle {
+    /// Reloads the configuration, returns names of changed settings which take
+    /// effect only after the daemon is restarted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the configuration could not be
+    /// loaded or applied, the running configuration is kept in that case.
+    pub async fn reload(&self) -> Result<Vec<&'static str>, Error> {
+        let (responder, response) = oneshot::channel();
+        self.request_sender.send(responder).map_err(|_err| Error::WorkerClosed)?;
+        response.await.map_err(|_err| Error::WorkerClosed)?
+    }
+}
+
+pub struct Reloader {
+    loader: ConfigLoader,
+
+    // settings which require restart are compared with the configuration which
+    // the daemon was started with
+    startup_config: Config,
+
+    config: Config,
+
+    clip_filter: SharedClipFilter,
+
+    desktop_notification: notification::DesktopNotification,
+
+    snippet_watcher: notify::RecommendedWatcher,
+}
+
+impl Reloader {
+    pub fn new(
+        loader: ConfigLoader,
+        config: Config,
+        clip_filter: SharedClipFilter,
+        desktop_notification: notification::DesktopNotification,
+        snippet_watcher: notify::RecommendedWatcher,
+    ) -> Self {
+        Self {
+            loader,
+            startup_config: config.clone(),
+            config,
+            clip_filter,
+            desktop_notification,
+            snippet_watcher,
+        }
+    }
+
+    /// Returns the configuration which is applied currently.
+    #[inline]
+    pub const fn config(&self) -> &Config { &self.config }
+
+    /// Loads the configuration and applies settings which could be changed
+    /// without restarting the daemon, snippets are reloaded and watched by a
+    /// new watcher whose events are sent to the returned receiver.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the configuration could not be
+    /// loaded, nothing is changed in that case.
+    pub async fn reload(
+        &mut self,
+        clipboard_manager: &Mutex<ClipboardManager<notification::DesktopNotification>>,
+        history_manager: &Mutex<HistoryManager>,
+    ) -> Result<(Vec<&'static str>, SnippetWatcherEventReceiver), Error> {
+        // the history might be rekeyed since the daemon was started, the key in use is
+        // the running one
+        let history_encryption_key = history_manager.lock().await.encryption_key().cloned();
+        self.startup_config.history_encryption_key.clone_from(&history_encryption_key);
+        self.config.history_encryption_key = history_encryption_key;
+
+        let mut config =
+            (self.loader)(&self.config).map_err(|message| Error::LoadConfig { message })?;
+        let clip_filter =
+            config.watcher.generate_clip_filter().context(error::GenerateClipFilterSnafu)?;
+        let snippet_directory = snippets::prepare_managed_directory(
+            &mut config.snippets,
+            config.snippet_directory.take(),
+        )
+        .await;
+        config.snippet_directory.clone_from(&snippet_directory);
+        let ((snippet_watcher, snippet_event_receiver), snippets) =
+            snippets::load_and_create_watcher(&config.snippets)
+                .await
+                .context(error::LoadSnippetsSnafu)?;
+
+        self.clip_filter.replace(clip_filter);
+        self.desktop_notification.reconfigure(config.desktop_notification.clone());
+        {
+            let mut clipboard_manager = clipboard_manager.lock().await;
+            clipboard_manager.set_capacity(config.max_history);
+            clipboard_manager.set_max_bytes(config.max_history_bytes);
+            clipboard_manager.set_snippet_directory(snippet_directory);
+            clipboard_manager
+                .set_snippet_environment_variables(config.snippet_environment_variables.clone());
+            clipboard_manager.replace_snippets(&snippets);
+        }
+        history_manager.lock().await.set_max_bytes(config.max_history_disk_bytes);
+        // the previous watcher stops watching snippet files once it is dropped
+        drop(std::mem::replace(&mut self.snippet_watcher, snippet_watcher));
+
+        let restart_required = self.startup_config.restart_required_changes(&config);
+        tracing::info!(
+            "Configuration is reloaded, {count} snippet(s) loaded, clipboard capacity {capacity}",
+            count = snippets.len(),
+            capacity = config.max_history
+        );
+        self.config = config;
+        Ok((restart_required, snippet_event_receiver))
+    }
+}
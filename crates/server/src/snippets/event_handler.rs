@@ -3,12 +3,12 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use clipcat_base::ClipEntry;
+use clipcat_base::{ClipEntry, SnippetTemplate};
 use notify::{event, Event, EventKind};
 use tokio::sync::mpsc;
 
 pub enum SnippetWatcherEvent {
-    Add(ClipEntry),
+    Add(Box<ClipEntry>),
     Remove(u64),
 }
 
@@ -23,31 +23,56 @@ impl SnippetWatcherEventReceiver {
 pub struct EventHandler {
     file_path_to_id: HashMap<PathBuf, u64>,
 
+    // file and directory paths of snippets, with their names
+    snippet_paths: HashMap<PathBuf, String>,
+
     event_sender: mpsc::UnboundedSender<SnippetWatcherEvent>,
 }
 
 impl EventHandler {
-    pub fn new(file_path_to_id: HashMap<PathBuf, u64>) -> (Self, SnippetWatcherEventReceiver) {
+    pub fn new(
+        file_path_to_id: HashMap<PathBuf, u64>,
+        snippet_paths: HashMap<PathBuf, String>,
+    ) -> (Self, SnippetWatcherEventReceiver) {
         let (event_sender, event_receiver) = mpsc::unbounded_channel();
-        (Self { file_path_to_id, event_sender }, SnippetWatcherEventReceiver { event_receiver })
+        (
+            Self { file_path_to_id, snippet_paths, event_sender },
+            SnippetWatcherEventReceiver { event_receiver },
+        )
     }
 
     pub fn on_snippet_modified(&mut self, paths: Vec<PathBuf>) {
         for file_path in paths {
             tracing::info!("Snippet `{}` is modified", file_path.display());
             // insert new snippet to clipboard manager
-            if let Some(clip) = load(&file_path) {
+            if super::is_hidden_file(&file_path) && !self.snippet_paths.contains_key(&file_path) {
+                continue;
+            }
+            let name = self.snippet_name(&file_path);
+            if let Some(clip) = load(&file_path, name) {
                 let id = clip.id();
                 if let Some(id) = self.file_path_to_id.insert(file_path.clone(), id) {
                     // remove old snippet from clipboard manager
                     tracing::info!("Remove clip {id}");
                     drop(self.event_sender.send(SnippetWatcherEvent::Remove(id)));
                 }
-                drop(self.event_sender.send(SnippetWatcherEvent::Add(clip)));
+                drop(self.event_sender.send(SnippetWatcherEvent::Add(Box::new(clip))));
             }
         }
     }
 
+    fn snippet_name(&self, file_path: &Path) -> Option<String> {
+        if let Some(name) = self.snippet_paths.get(file_path) {
+            return Some(name.clone());
+        }
+        // snippet directories might be nested, the innermost one names the snippet
+        self.snippet_paths
+            .iter()
+            .filter(|(path, _)| file_path.starts_with(path))
+            .max_by_key(|(path, _)| path.components().count())
+            .map(|(directory, name)| super::directory_snippet_name(name, directory, file_path))
+    }
+
     pub fn on_snippet_removed(&mut self, paths: Vec<PathBuf>) {
         for file_path in paths {
             tracing::info!("Snippet `{}` is removed", file_path.display());
@@ -64,10 +89,22 @@ impl notify::EventHandler for EventHandler {
     fn handle_event(&mut self, event: notify::Result<Event>) {
         match event {
             Ok(event) => match event.kind {
-                EventKind::Modify(event::ModifyKind::Data(_)) => {
+                EventKind::Modify(
+                    event::ModifyKind::Data(_) | event::ModifyKind::Name(event::RenameMode::To),
+                ) => {
                     self.on_snippet_modified(event.paths);
                 }
-                EventKind::Remove(event::RemoveKind::File) => self.on_snippet_removed(event.paths),
+                EventKind::Modify(event::ModifyKind::Name(event::RenameMode::Both)) => {
+                    // the paths are the source followed by the destination
+                    let mut paths = event.paths;
+                    let destination = paths.split_off(paths.len().min(1));
+                    self.on_snippet_removed(paths);
+                    self.on_snippet_modified(destination);
+                }
+                EventKind::Remove(event::RemoveKind::File)
+                | EventKind::Modify(event::ModifyKind::Name(event::RenameMode::From)) => {
+                    self.on_snippet_removed(event.paths);
+                }
                 _ => {}
             },
             Err(err) => tracing::warn!("Error occurs while watching file system, error: {err:?}"),
@@ -75,7 +112,7 @@ impl notify::EventHandler for EventHandler {
     }
 }
 
-fn load<P>(path: P) -> Option<ClipEntry>
+fn load<P>(path: P, name: Option<String>) -> Option<ClipEntry>
 where
     P: AsRef<Path>,
 {
@@ -93,11 +130,22 @@ where
         return None;
     }
 
-    if let Err(err) = simdutf8::basic::from_utf8(&data) {
-        tracing::warn!("Contents of `{}` is not valid UTF-8, error: {err}", path.display());
-        return None;
-    }
+    let is_template = match simdutf8::basic::from_utf8(&data) {
+        Ok(text) => SnippetTemplate::has_placeholders(text),
+        Err(err) => {
+            tracing::warn!("Contents of `{}` is not valid UTF-8, error: {err}", path.display());
+            return None;
+        }
+    };
 
-    ClipEntry::new(&data, &mime::TEXT_PLAIN_UTF_8, clipcat_base::ClipboardKind::Clipboard, None)
-        .ok()
+    let mut clip = ClipEntry::new(
+        &data,
+        &mime::TEXT_PLAIN_UTF_8,
+        clipcat_base::ClipboardKind::Clipboard,
+        None,
+    )
+    .ok()?;
+    clip.set_template(is_template);
+    clip.set_snippet(name, Some(path));
+    Some(clip)
 }
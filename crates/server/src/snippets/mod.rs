@@ -1,8 +1,13 @@
 mod event_handler;
 
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
 
-use clipcat_base::ClipEntry;
+use clipcat_base::{ClipEntry, SnippetTemplate};
 use notify::{RecursiveMode, Watcher};
 use snafu::ResultExt;
 use time::OffsetDateTime;
@@ -11,11 +16,13 @@ use self::event_handler::EventHandler;
 pub use self::event_handler::{SnippetWatcherEvent, SnippetWatcherEventReceiver};
 use crate::{config, error, error::Error};
 
+// loads clips of the snippet, a clip is named after the snippet, or
+// `<snippet name>/<relative file path>` if it is loaded from a directory
 async fn load(config: &config::SnippetConfig) -> HashMap<ClipEntry, Option<PathBuf>> {
     let (name, clip_contents) = match config {
         config::SnippetConfig::Inline { name, content } => {
             tracing::trace!("Load snippet `{name}`");
-            (name, vec![(content.as_bytes().to_vec(), None)])
+            (name, vec![(content.as_bytes().to_vec(), name.clone(), None)])
         }
         config::SnippetConfig::File { name, path } => {
             tracing::trace!("Load snippet `{name}` from file `{}`", path.display());
@@ -27,7 +34,7 @@ async fn load(config: &config::SnippetConfig) -> HashMap<ClipEntry, Option<PathB
                     );
                     Vec::new()
                 },
-                |content| vec![(content, Some(path.clone()))],
+                |content| vec![(content, name.clone(), Some(path.clone()))],
             );
             (name, contents)
         }
@@ -37,33 +44,43 @@ async fn load(config: &config::SnippetConfig) -> HashMap<ClipEntry, Option<PathB
                 clipcat_base::utils::fs::read_dir_recursively_async(&path)
                     .await
                     .into_iter()
+                    .filter(|file| !is_hidden_file(file))
                     .map(|file| (async move { (tokio::fs::read(&file).await.ok(), file) })),
             )
             .await
             .into_iter()
-            .filter_map(|(c, _file_path)| c.map(|c| (c, Some(path.clone()))))
+            .filter_map(|(content, file_path)| {
+                let clip_name = directory_snippet_name(name, path, &file_path);
+                content.map(|content| (content, clip_name, Some(file_path)))
+            })
             .collect();
             (name, contents)
         }
     };
 
     if clip_contents.is_empty() {
-        tracing::warn!("Snippet `{name}` is empty, ignored it");
+        // the managed snippet directory is empty until a snippet is added
+        if !name.is_empty() {
+            tracing::warn!("Snippet `{name}` is empty, ignored it");
+        }
         return HashMap::new();
     }
 
     clip_contents
         .into_iter()
-        .filter_map(|(data, path)| {
+        .filter_map(|(data, name, path)| {
             if data.is_empty() {
                 tracing::warn!("Snippet `{name}` is empty, ignored it");
                 return None;
             }
 
-            if let Err(err) = simdutf8::basic::from_utf8(&data) {
-                tracing::warn!("Snippet `{name}` is not valid UTF-8 string, error: {err}");
-                return None;
-            }
+            let is_template = match simdutf8::basic::from_utf8(&data) {
+                Ok(text) => SnippetTemplate::has_placeholders(text),
+                Err(err) => {
+                    tracing::warn!("Snippet `{name}` is not valid UTF-8 string, error: {err}");
+                    return None;
+                }
+            };
 
             ClipEntry::new(
                 &data,
@@ -72,28 +89,93 @@ async fn load(config: &config::SnippetConfig) -> HashMap<ClipEntry, Option<PathB
                 Some(OffsetDateTime::UNIX_EPOCH),
             )
             .ok()
-            .map(|clip| (clip, path))
+            .map(|mut clip| {
+                // placeholders are expanded when the snippet is marked
+                clip.set_template(is_template);
+                clip.set_snippet(Some(name), path.clone());
+                (clip, path)
+            })
         })
         .collect()
 }
 
+// names a clip loaded from `file_path` in directory `directory` of snippet
+// `name`, clips in the snippet directory managed by the daemon are named by
+// their relative paths only
+fn directory_snippet_name(name: &str, directory: &Path, file_path: &Path) -> String {
+    let relative_path = file_path.strip_prefix(directory).unwrap_or(file_path);
+    if name.is_empty() {
+        relative_path.display().to_string()
+    } else {
+        format!("{name}/{}", relative_path.display())
+    }
+}
+
+// hidden files in snippet directories are not snippets, e.g. files being
+// written or swap files of editors
+fn is_hidden_file(file_path: &Path) -> bool {
+    file_path.file_name().is_some_and(|name| name.as_bytes().starts_with(b"."))
+}
+
+/// Returns the hidden file which the content of `file_path` is written to
+/// before it is renamed to `file_path`.
+pub fn temporary_file_path(file_path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(file_path.file_name().unwrap_or_default());
+    name.push(".tmp");
+    file_path.with_file_name(name)
+}
+
+/// Creates the snippet directory managed by the daemon and appends it to
+/// `snippets`, snippets added at runtime are stored in it. `None` is returned
+/// if the directory could not be created.
+pub async fn prepare_managed_directory(
+    snippets: &mut Vec<config::SnippetConfig>,
+    path: Option<PathBuf>,
+) -> Option<PathBuf> {
+    let path = path?;
+    match tokio::fs::create_dir_all(&path).await {
+        Ok(()) => {
+            tracing::info!("Snippet directory: `{}`", path.display());
+            snippets
+                .push(config::SnippetConfig::Directory { name: String::new(), path: path.clone() });
+            Some(path)
+        }
+        Err(err) => {
+            tracing::warn!(
+                "Could not create snippet directory `{}`, snippets can not be added, error: {err}",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
 pub async fn load_and_create_watcher(
     snippets: &[config::SnippetConfig],
 ) -> Result<((notify::RecommendedWatcher, SnippetWatcherEventReceiver), Vec<ClipEntry>), Error> {
     let mut file_path_to_id = HashMap::new();
-    let mut file_paths = Vec::new();
+    // file and directory paths of snippets, with their names
+    let mut snippet_paths = HashMap::new();
     let mut new_clips = Vec::new();
     for snippet in snippets {
+        match snippet {
+            config::SnippetConfig::Inline { .. } => {}
+            config::SnippetConfig::File { name, path }
+            | config::SnippetConfig::Directory { name, path } => {
+                let _unused = snippet_paths.insert(path.clone(), name.clone());
+            }
+        }
         for (clip, file_path) in load(snippet).await {
             if let Some(file_path) = file_path {
-                file_paths.push(file_path.clone());
-                let _ = file_path_to_id.insert(file_path.clone(), clip.id());
+                let _ = file_path_to_id.insert(file_path, clip.id());
             }
             new_clips.push(clip);
         }
     }
 
-    let (event_handler, event_receiver) = EventHandler::new(file_path_to_id);
+    let file_paths = snippet_paths.keys().cloned().collect::<Vec<_>>();
+    let (event_handler, event_receiver) = EventHandler::new(file_path_to_id, snippet_paths);
     let mut watcher =
         notify::recommended_watcher(event_handler).context(error::CreateFileWatcherSnafu)?;
     for file_path in file_paths {
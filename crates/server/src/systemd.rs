@@ -0,0 +1,184 @@
+use std::{
+    env,
+    ffi::OsStr,
+    io,
+    os::unix::{
+        ffi::OsStrExt,
+        io::FromRawFd,
+        net::{UnixDatagram, UnixListener},
+    },
+    path::PathBuf,
+    time::Duration,
+};
+
+// the first file descriptor passed by socket activation
+const LISTEN_FDS_START: i32 = 3;
+
+/// Returns the Unix domain socket listener passed by systemd socket
+/// activation, `None` is returned if the daemon is not socket activated. See
+/// `sd_listen_fds(3)`.
+pub fn take_activated_listener() -> Option<UnixListener> {
+    let listen_fds = activated_fd_count(
+        env::var("LISTEN_PID").ok().as_deref(),
+        env::var("LISTEN_FDS").ok().as_deref(),
+        std::process::id(),
+    )?;
+    if listen_fds > 1 {
+        tracing::warn!(
+            "{listen_fds} sockets are passed by systemd, only the first one is used for gRPC"
+        );
+    }
+
+    // SAFETY: systemd passes the sockets as file descriptors starting from
+    // `LISTEN_FDS_START` and nothing else in this process owns them
+    #[allow(unsafe_code)]
+    let listener = unsafe { UnixListener::from_raw_fd(LISTEN_FDS_START) };
+    match listener.local_addr() {
+        Ok(_) => Some(listener),
+        Err(err) => {
+            tracing::warn!(
+                "Socket passed by systemd is not a Unix domain socket listener, error: {err}"
+            );
+            None
+        }
+    }
+}
+
+// the variables are not removed, they are ignored by child processes whose PIDs
+// differ from `LISTEN_PID`
+fn activated_fd_count(listen_pid: Option<&str>, listen_fds: Option<&str>, pid: u32) -> Option<i32> {
+    if listen_pid?.parse::<u32>().ok()? != pid {
+        return None;
+    }
+    let listen_fds = listen_fds?.parse::<i32>().ok()?;
+    (listen_fds > 0).then_some(listen_fds)
+}
+
+/// Sends service state notifications to systemd, see `sd_notify(3)`.
+#[derive(Debug)]
+pub struct Notifier {
+    socket: UnixDatagram,
+
+    address: NotifySocketAddress,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum NotifySocketAddress {
+    Path(PathBuf),
+    #[cfg(target_os = "linux")]
+    Abstract(Vec<u8>),
+}
+
+impl NotifySocketAddress {
+    fn parse(path: &OsStr) -> Option<Self> {
+        match path.as_bytes() {
+            [] => None,
+            #[cfg(target_os = "linux")]
+            [b'@', name @ ..] => Some(Self::Abstract(name.to_vec())),
+            _ => Some(Self::Path(PathBuf::from(path))),
+        }
+    }
+}
+
+impl Notifier {
+    /// Creates a notifier from `NOTIFY_SOCKET`, `None` is returned if the
+    /// daemon is not started by systemd with a notification socket.
+    pub fn from_env() -> Option<Self> {
+        let address = NotifySocketAddress::parse(&env::var_os("NOTIFY_SOCKET")?)?;
+        match UnixDatagram::unbound() {
+            Ok(socket) => Some(Self { socket, address }),
+            Err(err) => {
+                tracing::warn!("Could not create socket for notifying systemd, error: {err}");
+                None
+            }
+        }
+    }
+
+    /// Sends `state`, e.g. `READY=1`, to systemd.
+    pub fn notify(&self, state: &str) {
+        if let Err(err) = self.send(state.as_bytes()) {
+            tracing::warn!("Could not notify systemd with `{state}`, error: {err}");
+        }
+    }
+
+    fn send(&self, data: &[u8]) -> io::Result<usize> {
+        match &self.address {
+            NotifySocketAddress::Path(path) => self.socket.send_to(data, path),
+            #[cfg(target_os = "linux")]
+            NotifySocketAddress::Abstract(name) => {
+                use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+
+                self.socket.send_to_addr(data, &SocketAddr::from_abstract_name(name)?)
+            }
+        }
+    }
+
+    /// Returns the interval of sending `WATCHDOG=1`, it is half of
+    /// `WATCHDOG_USEC`. `None` is returned if the watchdog is disabled.
+    pub fn watchdog_interval() -> Option<Duration> {
+        watchdog_interval(
+            env::var("WATCHDOG_PID").ok().as_deref(),
+            env::var("WATCHDOG_USEC").ok().as_deref(),
+            std::process::id(),
+        )
+    }
+}
+
+fn watchdog_interval(
+    watchdog_pid: Option<&str>,
+    watchdog_usec: Option<&str>,
+    pid: u32,
+) -> Option<Duration> {
+    if let Some(watchdog_pid) = watchdog_pid {
+        if watchdog_pid.parse::<u32>().ok()? != pid {
+            return None;
+        }
+    }
+    let watchdog_usec = watchdog_usec?.parse::<u64>().ok()?;
+    (watchdog_usec > 0).then(|| Duration::from_micros(watchdog_usec / 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::OsStr, path::PathBuf, time::Duration};
+
+    use super::{activated_fd_count, watchdog_interval, NotifySocketAddress};
+
+    #[test]
+    fn test_activated_fd_count() {
+        assert_eq!(activated_fd_count(Some("42"), Some("1"), 42), Some(1));
+        assert_eq!(activated_fd_count(Some("42"), Some("2"), 42), Some(2));
+        // sockets passed to another process are not taken
+        assert_eq!(activated_fd_count(Some("41"), Some("1"), 42), None);
+        assert_eq!(activated_fd_count(None, Some("1"), 42), None);
+        assert_eq!(activated_fd_count(Some("pid"), Some("1"), 42), None);
+        assert_eq!(activated_fd_count(Some("42"), Some("0"), 42), None);
+        assert_eq!(activated_fd_count(Some("42"), None, 42), None);
+    }
+
+    #[test]
+    fn test_watchdog_interval() {
+        assert_eq!(watchdog_interval(None, Some("30000000"), 42), Some(Duration::from_secs(15)));
+        assert_eq!(
+            watchdog_interval(Some("42"), Some("30000000"), 42),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(watchdog_interval(Some("41"), Some("30000000"), 42), None);
+        assert_eq!(watchdog_interval(None, Some("0"), 42), None);
+        assert_eq!(watchdog_interval(None, None, 42), None);
+    }
+
+    #[test]
+    fn test_parse_notify_socket_address() {
+        assert_eq!(NotifySocketAddress::parse(OsStr::new("")), None);
+        assert_eq!(
+            NotifySocketAddress::parse(OsStr::new("/run/user/1000/systemd/notify")),
+            Some(NotifySocketAddress::Path(PathBuf::from("/run/user/1000/systemd/notify")))
+        );
+        #[cfg(target_os = "linux")]
+        assert_eq!(
+            NotifySocketAddress::parse(OsStr::new("@/org/freedesktop/systemd1/notify")),
+            Some(NotifySocketAddress::Abstract(b"/org/freedesktop/systemd1/notify".to_vec()))
+        );
+    }
+}
@@ -7,10 +7,13 @@ use std::sync::{
     Arc,
 };
 
-use clipcat_base::{ClipEntry, ClipFilter, ClipboardContent, ClipboardKind};
+use clipcat_base::{
+    ClipEntry, ClipFilter, ClipboardContent, ClipboardKind, ClipboardWatcherState, SecretAction,
+    SecretDetection, SharedClipFilter,
+};
 use futures::{FutureExt, StreamExt};
 use snafu::OptionExt;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, oneshot};
 
 pub use self::{
     error::Error,
@@ -19,13 +22,14 @@ pub use self::{
     Worker as ClipboardWatcherWorker,
 };
 use crate::{
-    backend::{ClipboardBackend, Error as BackendError},
+    backend::{ClipboardBackend, ClipboardChange, Error as BackendError},
     notification,
 };
 
 pub struct ClipboardWatcher<Notification> {
     is_watching: Arc<AtomicBool>,
     clip_sender: broadcast::Sender<ClipEntry>,
+    state_sender: broadcast::Sender<ClipboardWatcherState>,
     notification: Notification,
 }
 
@@ -36,14 +40,16 @@ where
     pub fn new(
         backend: Arc<dyn ClipboardBackend>,
         opts: ClipboardWatcherOptions,
-        clip_filter: Arc<ClipFilter>,
+        clip_filter: SharedClipFilter,
         notification: Notification,
     ) -> (Self, ClipboardWatcherWorker) {
         let (clip_sender, _event_receiver) = broadcast::channel(16);
+        let (state_sender, _state_receiver) = broadcast::channel(16);
         let is_watching = Arc::new(AtomicBool::new(true));
         let watcher = Self {
             is_watching: is_watching.clone(),
             clip_sender: clip_sender.clone(),
+            state_sender,
             notification,
         };
         let worker =
@@ -56,25 +62,37 @@ where
 
     #[inline]
     pub fn get_toggle(&self) -> ClipboardWatcherToggle<Notification> {
-        ClipboardWatcherToggle::new(self.is_watching.clone(), self.notification.clone())
+        ClipboardWatcherToggle::new(
+            self.is_watching.clone(),
+            self.state_sender.clone(),
+            self.notification.clone(),
+        )
     }
 }
 
 pub struct Worker {
     backend: Arc<dyn ClipboardBackend>,
     clip_sender: broadcast::Sender<ClipEntry>,
-    clip_filter: Arc<ClipFilter>,
+    clip_filter: SharedClipFilter,
     is_watching: Arc<AtomicBool>,
     opts: ClipboardWatcherOptions,
 }
 
 impl Worker {
+    /// Watches clipboard until `shutdown_signal` is resolved, `ready` is sent
+    /// once changes of clipboard are subscribed.
+    ///
     /// # Errors
     #[allow(clippy::redundant_pub_crate)]
-    pub async fn serve(self, shutdown_signal: sigfinn::Shutdown) -> Result<(), Error> {
+    pub async fn serve(
+        self,
+        shutdown_signal: sigfinn::Shutdown,
+        ready: oneshot::Sender<()>,
+    ) -> Result<(), Error> {
         let enabled_kinds = self.opts.get_enable_kinds();
-        let Self { backend, is_watching, clip_sender, clip_filter, .. } = self;
+        let Self { backend, is_watching, clip_sender, clip_filter: shared_clip_filter, .. } = self;
         let mut subscriber = backend.subscribe()?;
+        let _unused = ready.send(());
         let mut shutdown_signal = shutdown_signal.into_stream();
         let mut current_contents: [ClipboardContent; ClipboardKind::MAX_LENGTH] =
             [ClipboardContent::default(), ClipboardContent::default(), ClipboardContent::default()];
@@ -85,13 +103,16 @@ impl Worker {
             .map(|(kind, &enable)| (ClipboardKind::from(kind), enable))
         {
             if enable {
+                let clip_filter = shared_clip_filter.load();
                 match backend.load(kind, None).await {
                     Ok(data) => {
                         if !clip_filter.filter_clipboard_content(data.as_ref()) {
                             current_contents[usize::from(kind)] = data.clone();
-                            if let Err(_err) = clip_sender
-                                .send(ClipEntry::from_clipboard_content(data, kind, None))
-                            {
+                            let Some(clip) = new_clip(&clip_filter, data, kind) else {
+                                continue;
+                            };
+                            let clip = with_representations(backend.as_ref(), clip).await;
+                            if let Err(_err) = clip_sender.send(clip) {
                                 tracing::info!("ClipEntry receiver is closed.");
                                 return Err(Error::SendClipEntry);
                             }
@@ -115,15 +136,30 @@ impl Worker {
                 event = subscriber.next() => event,
                 _ = shutdown_signal.next() => return Ok(()),
             };
-            let (kind, mime) = maybe_event.context(error::SubscriberClosedSnafu)?;
+            let ClipboardChange { kind, mime, source_application } =
+                maybe_event.context(error::SubscriberClosedSnafu)?;
             if is_watching.load(Ordering::Relaxed) && enabled_kinds[usize::from(kind)] {
+                // the filter is loaded for each change, it may be replaced when the
+                // configuration is reloaded
+                let clip_filter = shared_clip_filter.load();
+                if clip_filter.filter_source_application(kind, source_application.as_deref()) {
+                    tracing::info!(
+                        "Ignore clipboard content from `{}`",
+                        source_application.as_deref().unwrap_or("unknown application")
+                    );
+                    continue;
+                }
                 match backend.load(kind, Some(mime)).await {
                     Ok(new_content)
                         if !clip_filter.filter_clipboard_content(new_content.as_ref())
                             && current_contents[usize::from(kind)] != new_content =>
                     {
                         current_contents[usize::from(kind)] = new_content.clone();
-                        let clip = ClipEntry::from_clipboard_content(new_content, kind, None);
+                        let Some(mut clip) = new_clip(&clip_filter, new_content, kind) else {
+                            continue;
+                        };
+                        clip.set_source_application(source_application);
+                        let clip = with_representations(backend.as_ref(), clip).await;
                         if let Err(_err) = clip_sender.send(clip) {
                             tracing::info!("ClipEntry receiver is closed.");
                             return Err(Error::SendClipEntry);
@@ -143,3 +179,196 @@ impl Worker {
         }
     }
 }
+
+// creates a clip from `content`, `None` is returned if a secret is found in
+// `content` and the clip should be dropped
+fn new_clip(
+    clip_filter: &ClipFilter,
+    content: ClipboardContent,
+    kind: ClipboardKind,
+) -> Option<ClipEntry> {
+    let detection = clip_filter.detect_secret(&content);
+    let mut clip = ClipEntry::from_clipboard_content(content, kind, None);
+    match detection {
+        Some(SecretDetection { kind: secret_kind, action: SecretAction::Drop }) => {
+            tracing::info!("Secret ({secret_kind}) detected, ignore it");
+            return None;
+        }
+        Some(SecretDetection { kind: secret_kind, action: SecretAction::KeepInMemory }) => {
+            tracing::info!("Secret ({secret_kind}) detected, keep it in memory only");
+            clip.set_sensitive(true);
+        }
+        None => {}
+    }
+    Some(clip)
+}
+
+// attaches the alternative representations currently offered by the clipboard
+// to `clip`
+async fn with_representations(backend: &dyn ClipboardBackend, mut clip: ClipEntry) -> ClipEntry {
+    if clip.is_utf8_string() {
+        match backend.load_representations(clip.kind()).await {
+            Ok(representations) => clip.set_representations(representations),
+            Err(error) => {
+                tracing::warn!("Failed to load clipboard representations, error: {error}");
+            }
+        }
+    }
+    clip
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::{Arc, Mutex},
+    };
+
+    use async_trait::async_trait;
+    use clipcat_base::{
+        ClipEntry, ClipRepresentation, ClipboardContent, ClipboardKind, SharedClipFilter,
+        SourceApplicationRule,
+    };
+    use tokio::sync::mpsc;
+
+    use crate::{
+        backend::{ClipboardBackend, ClipboardChange, Error as BackendError, Subscriber},
+        notification::DummyNotification,
+        watcher::{ClipboardWatcher, ClipboardWatcherOptions},
+    };
+
+    // a backend whose clipboard changes are fed by the test, the clipboard is
+    // empty when the watcher starts and holds `content` afterwards
+    struct Backend {
+        content: ClipboardContent,
+        changes: Mutex<Option<mpsc::UnboundedReceiver<ClipboardChange>>>,
+    }
+
+    #[async_trait]
+    impl ClipboardBackend for Backend {
+        async fn load(
+            &self,
+            _kind: ClipboardKind,
+            mime: Option<mime::Mime>,
+        ) -> Result<ClipboardContent, BackendError> {
+            if mime.is_some() {
+                Ok(self.content.clone())
+            } else {
+                Err(BackendError::EmptyClipboard)
+            }
+        }
+
+        async fn load_representations(
+            &self,
+            _kind: ClipboardKind,
+        ) -> Result<Vec<ClipRepresentation>, BackendError> {
+            Ok(Vec::new())
+        }
+
+        async fn store(
+            &self,
+            _kind: ClipboardKind,
+            _data: ClipboardContent,
+        ) -> Result<(), BackendError> {
+            Ok(())
+        }
+
+        async fn store_with_representations(
+            &self,
+            _kind: ClipboardKind,
+            _data: ClipboardContent,
+            _representations: Vec<ClipRepresentation>,
+        ) -> Result<(), BackendError> {
+            Ok(())
+        }
+
+        async fn clear(&self, _kind: ClipboardKind) -> Result<(), BackendError> { Ok(()) }
+
+        fn subscribe(&self) -> Result<Subscriber, BackendError> {
+            let changes = self.changes.lock().expect("lock is not poisoned").take();
+            Ok(Subscriber::from_receiver(changes.expect("subscribe is called once")))
+        }
+
+        fn supported_clipboard_kinds(&self) -> Vec<ClipboardKind> { vec![ClipboardKind::Clipboard] }
+    }
+
+    fn watcher_options(
+        source_application_rules: Vec<SourceApplicationRule>,
+    ) -> ClipboardWatcherOptions {
+        ClipboardWatcherOptions {
+            enable_clipboard: true,
+            enable_primary: false,
+            enable_secondary: false,
+            capture_image: true,
+            filter_text_min_length: 1,
+            filter_text_max_length: 1024,
+            filter_image_max_size: 1024,
+            denied_text_regex_patterns: HashSet::new(),
+            sensitive_x11_atoms: HashSet::new(),
+            source_application_rules,
+            secret_detection_rules: HashMap::new(),
+        }
+    }
+
+    // feeds a change copied from `source_application` through the watcher and
+    // returns the clips it captures
+    async fn watch_change(
+        source_application_rules: Vec<SourceApplicationRule>,
+        source_application: &str,
+    ) -> Vec<ClipEntry> {
+        let (change_sender, change_receiver) = mpsc::unbounded_channel();
+        let backend = Arc::new(Backend {
+            content: ClipboardContent::Plaintext("hello".to_string()),
+            changes: Mutex::new(Some(change_receiver)),
+        });
+        let opts = watcher_options(source_application_rules);
+        let clip_filter: SharedClipFilter =
+            opts.generate_clip_filter().expect("options are valid").into();
+        let (watcher, worker) =
+            ClipboardWatcher::new(backend, opts, clip_filter, DummyNotification::default());
+        let mut clip_receiver = watcher.subscribe();
+
+        change_sender
+            .send(ClipboardChange {
+                kind: ClipboardKind::Clipboard,
+                mime: mime::TEXT_PLAIN_UTF_8,
+                source_application: Some(source_application.to_string()),
+            })
+            .expect("receiver is alive");
+        // the worker stops once all changes are handled and the subscriber is closed
+        drop(change_sender);
+
+        let lifecycle_manager = sigfinn::LifecycleManager::<super::Error>::new();
+        let _handle = lifecycle_manager.spawn("Clipboard watcher", |shutdown| async move {
+            let (ready, _ready) = tokio::sync::oneshot::channel();
+            match worker.serve(shutdown, ready).await {
+                Ok(()) => sigfinn::ExitStatus::Success,
+                Err(err) => sigfinn::ExitStatus::Error(err),
+            }
+        });
+        let _result = lifecycle_manager.serve().await.expect("watcher is joined");
+
+        let mut clips = Vec::new();
+        while let Ok(clip) = clip_receiver.try_recv() {
+            clips.push(clip);
+        }
+        clips
+    }
+
+    #[tokio::test]
+    async fn test_capture_clips_by_source_application() {
+        let clips = watch_change(Vec::new(), "firefox").await;
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].source_application(), Some("firefox"));
+
+        let rules = vec![SourceApplicationRule {
+            deny: HashSet::from(["KeePassXC".to_string()]),
+            ..SourceApplicationRule::default()
+        }];
+        let clips = watch_change(rules.clone(), "firefox").await;
+        assert_eq!(clips.len(), 1);
+
+        let clips = watch_change(rules, "keepassxc").await;
+        assert!(clips.is_empty());
+    }
+}
@@ -1,6 +1,8 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use clipcat_base::{ClipFilter, ClipboardKind};
+use clipcat_base::{
+    ClipFilter, ClipboardKind, SecretAction, SecretDetector, SecretKind, SourceApplicationRule,
+};
 use snafu::Snafu;
 
 // SAFETY: user may use bool to enable/disable the functions
@@ -24,6 +26,11 @@ pub struct Options {
     pub denied_text_regex_patterns: HashSet<String>,
 
     pub sensitive_x11_atoms: HashSet<String>,
+
+    pub source_application_rules: Vec<SourceApplicationRule>,
+
+    // enabled rules of secret detection
+    pub secret_detection_rules: HashMap<SecretKind, SecretAction>,
 }
 
 impl Options {
@@ -36,6 +43,13 @@ impl Options {
         filter.deny_image(!self.capture_image);
         filter.set_regex_patterns(regex::RegexSet::new(&self.denied_text_regex_patterns)?);
         filter.add_sensitive_atoms(self.sensitive_x11_atoms.clone());
+        filter.add_source_application_rules(self.source_application_rules.clone());
+        filter.set_secret_detector(
+            self.secret_detection_rules
+                .iter()
+                .map(|(&kind, &action)| (kind, action))
+                .collect::<SecretDetector>(),
+        );
         Ok(filter)
     }
 
@@ -68,6 +82,8 @@ impl Default for Options {
             filter_image_max_size: 5 * (1 << 20),
             denied_text_regex_patterns: HashSet::new(),
             sensitive_x11_atoms: HashSet::new(),
+            source_application_rules: Vec::new(),
+            secret_detection_rules: HashMap::new(),
         }
     }
 }
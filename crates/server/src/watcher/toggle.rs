@@ -4,11 +4,14 @@ use std::sync::{
 };
 
 use clipcat_base::ClipboardWatcherState;
+use tokio::sync::broadcast;
 
 use crate::notification;
 
+#[derive(Clone)]
 pub struct Toggle<Notification> {
     is_watching: Arc<AtomicBool>,
+    state_sender: broadcast::Sender<ClipboardWatcherState>,
     notification: Notification,
 }
 
@@ -16,14 +19,19 @@ impl<Notification> Toggle<Notification>
 where
     Notification: notification::Notification,
 {
-    pub const fn new(is_watching: Arc<AtomicBool>, notification: Notification) -> Self {
-        Self { is_watching, notification }
+    pub const fn new(
+        is_watching: Arc<AtomicBool>,
+        state_sender: broadcast::Sender<ClipboardWatcherState>,
+        notification: Notification,
+    ) -> Self {
+        Self { is_watching, state_sender, notification }
     }
 
     #[inline]
     pub fn enable(&self) {
         self.is_watching.store(true, Ordering::Release);
         self.notification.on_watcher_enabled();
+        drop(self.state_sender.send(ClipboardWatcherState::Enabled));
         tracing::info!("ClipboardWatcher is watching for clipboard event");
     }
 
@@ -31,6 +39,7 @@ where
     pub fn disable(&self) {
         self.is_watching.store(false, Ordering::Release);
         self.notification.on_watcher_disabled();
+        drop(self.state_sender.send(ClipboardWatcherState::Disabled));
         tracing::info!("ClipboardWatcher is not watching for clipboard event");
     }
 
@@ -43,6 +52,11 @@ where
         }
     }
 
+    #[inline]
+    pub fn subscribe(&self) -> broadcast::Receiver<ClipboardWatcherState> {
+        self.state_sender.subscribe()
+    }
+
     #[inline]
     #[must_use]
     pub fn is_watching(&self) -> bool { self.is_watching.load(Ordering::Acquire) }
@@ -0,0 +1,124 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use snafu::Snafu;
+
+use crate::web::model::ErrorResponse;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    #[snafu(display("Clip `{id}` is not found"))]
+    ClipNotFound { id: u64 },
+
+    #[snafu(display("There is no clip at index {index}"))]
+    ClipIndexNotFound { index: usize },
+
+    #[snafu(display("No clip is in clipboard `{kind}`"))]
+    CurrentClipNotFound { kind: clipcat_base::ClipboardKind },
+
+    #[snafu(display("Invalid clip ID `{value}`"))]
+    ParseClipId { value: String },
+
+    #[snafu(display("Invalid clipboard kind `{value}`"))]
+    ParseClipboardKind { value: String },
+
+    #[snafu(display("Invalid search mode `{value}`"))]
+    ParseSearchMode { value: String },
+
+    #[snafu(display("Invalid list order `{value}`"))]
+    ParseListOrder { value: String },
+
+    #[snafu(display("Invalid timestamp `{value}`, RFC 3339 is expected"))]
+    ParseTimestamp { value: String },
+
+    #[snafu(display("Clip `{id}` has no tag `{tag}`"))]
+    TagNotFound { id: u64, tag: String },
+
+    #[snafu(display("Invalid tag `{tag}`"))]
+    InvalidTag { tag: String },
+
+    #[snafu(display("Invalid MIME type `{value}`"))]
+    ParseMime { value: String },
+
+    #[snafu(display("Could not decode Base64 data, error: {source}"))]
+    DecodeBase64 { source: base64::DecodeError },
+
+    #[snafu(display("Could not create clip, error: {source}"))]
+    CreateClip { source: clipcat_base::ClipEntryError },
+
+    #[snafu(display("Could not encode clip, error: {source}"))]
+    EncodeClip { source: clipcat_base::ClipEntryError },
+
+    #[snafu(display("Clip `{id}` is not an image"))]
+    NotImage { id: u64 },
+
+    #[snafu(display("Could not create thumbnail, error: {source}"))]
+    CreateThumbnail { source: clipcat_base::ClipEntryError },
+
+    #[snafu(display("Could not join task, error: {source}"))]
+    JoinTask { source: tokio::task::JoinError },
+
+    #[snafu(display("{source}"))]
+    Search { source: crate::manager::Error },
+
+    #[snafu(display("{source}"))]
+    MarkClip { source: crate::manager::Error },
+
+    #[snafu(display("{source}"))]
+    ExpandClip { source: crate::manager::Error },
+
+    #[snafu(display("{source}"))]
+    ManageSnippet { source: crate::manager::Error },
+}
+
+impl Error {
+    const fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ClipNotFound { .. }
+            | Self::ClipIndexNotFound { .. }
+            | Self::CurrentClipNotFound { .. }
+            | Self::NotImage { .. }
+            | Self::TagNotFound { .. }
+            | Self::ManageSnippet { source: crate::manager::Error::SnippetNotFound { .. } } => {
+                StatusCode::NOT_FOUND
+            }
+            Self::ParseClipId { .. }
+            | Self::ParseClipboardKind { .. }
+            | Self::ParseSearchMode { .. }
+            | Self::ParseListOrder { .. }
+            | Self::ParseTimestamp { .. }
+            | Self::ParseMime { .. }
+            | Self::InvalidTag { .. }
+            | Self::DecodeBase64 { .. }
+            | Self::CreateClip { .. }
+            | Self::Search { .. }
+            | Self::ExpandClip { .. }
+            | Self::MarkClip { source: crate::manager::Error::ExpandTemplate { .. } }
+            | Self::ManageSnippet {
+                source:
+                    crate::manager::Error::InvalidSnippetName { .. }
+                    | crate::manager::Error::EmptySnippet { .. },
+            } => StatusCode::BAD_REQUEST,
+            Self::ManageSnippet {
+                source:
+                    crate::manager::Error::SnippetExists { .. }
+                    | crate::manager::Error::SnippetNotManaged { .. }
+                    | crate::manager::Error::SnippetDirectoryUnavailable,
+            } => StatusCode::CONFLICT,
+            Self::EncodeClip { .. }
+            | Self::CreateThumbnail { .. }
+            | Self::JoinTask { .. }
+            | Self::MarkClip { .. }
+            | Self::ManageSnippet { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        (self.status_code(), Json(ErrorResponse { error: self.to_string() })).into_response()
+    }
+}
@@ -0,0 +1,446 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use clipcat_base::{ClipEntry, ListQuery, SearchMode, SearchQuery};
+
+use crate::{
+    notification,
+    web::{
+        error::Error,
+        model::{self, Clip, ClipMetadata, SearchResult},
+        Context,
+    },
+};
+
+pub async fn list<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Query(params): Query<model::ListParams>,
+) -> Result<Json<Vec<ClipMetadata>>, Error>
+where
+    Notification: notification::Notification,
+{
+    let query = ListQuery::try_from(params)?;
+    let list = context.manager.lock().await.list(&query);
+    Ok(Json(list.into_iter().map(ClipMetadata::from).collect()))
+}
+
+pub async fn search<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Query(params): Query<model::SearchParams>,
+) -> Result<Json<Vec<SearchResult>>, Error>
+where
+    Notification: notification::Notification,
+{
+    let model::SearchParams { pattern, mode, kinds, mimes, tags, preview_length, limit } = params;
+    let mode = mode
+        .map(|mode| SearchMode::from_str(&mode).map_err(|_| Error::ParseSearchMode { value: mode }))
+        .transpose()?
+        .unwrap_or_default();
+    let kinds = model::parse_clipboard_kinds(kinds.as_deref())?;
+    let mimes = model::parse_mimes(mimes.as_deref())?;
+    let tags = model::parse_tags(tags.as_deref());
+    let query = SearchQuery {
+        pattern,
+        mode,
+        kinds,
+        mimes,
+        tags,
+        preview_length,
+        limit,
+        ..SearchQuery::default()
+    };
+    let matches =
+        context.manager.lock().await.search(&query).map_err(|source| Error::Search { source })?;
+    Ok(Json(matches.into_iter().map(SearchResult::from).collect()))
+}
+
+pub async fn insert<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Json(model::InsertRequest { data, mime, kind }): Json<model::InsertRequest>,
+) -> Result<(StatusCode, Json<model::InsertResponse>), Error>
+where
+    Notification: notification::Notification,
+{
+    let data = model::decode_base64(&data)?;
+    let mime = model::parse_mime(mime.as_deref())?;
+    let kind = model::KindParams { kind }.kind()?;
+    let clip =
+        ClipEntry::new(&data, &mime, kind, None).map_err(|source| Error::CreateClip { source })?;
+    let id = {
+        let mut manager = context.manager.lock().await;
+        let id = manager.insert(clip);
+        let _unused = manager.store(id, kind).await;
+        drop(manager);
+        id
+    };
+    Ok((StatusCode::CREATED, Json(model::InsertResponse { id: id.to_string() })))
+}
+
+pub async fn get<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(id): Path<String>,
+) -> Result<Json<Clip>, Error>
+where
+    Notification: notification::Notification,
+{
+    let id = model::parse_clip_id(&id)?;
+    let clip = context.manager.lock().await.get(id).ok_or(Error::ClipNotFound { id })?;
+    Clip::try_from(&clip).map(Json)
+}
+
+/// Returns the clip at `index` of the history, the most recent clip is at
+/// index 0.
+pub async fn get_by_index<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(index): Path<usize>,
+) -> Result<Json<Clip>, Error>
+where
+    Notification: notification::Notification,
+{
+    let clip = context
+        .manager
+        .lock()
+        .await
+        .get_by_index(index)
+        .ok_or(Error::ClipIndexNotFound { index })?;
+    Clip::try_from(&clip).map(Json)
+}
+
+/// Returns the data of a clip as is, images can be fetched without decoding.
+pub async fn get_data<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(id): Path<String>,
+) -> Result<Response, Error>
+where
+    Notification: notification::Notification,
+{
+    let id = model::parse_clip_id(&id)?;
+    let clip = context.manager.lock().await.get(id).ok_or(Error::ClipNotFound { id })?;
+    let data = clip.encoded().map_err(|source| Error::EncodeClip { source })?;
+    Ok(([(header::CONTENT_TYPE, clip.mime().to_string())], data).into_response())
+}
+
+/// Returns a thumbnail of an image clip in PNG, vector images are returned as
+/// is since they can be scaled by the client.
+pub async fn get_thumbnail<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(id): Path<String>,
+    Query(params): Query<model::ThumbnailParams>,
+) -> Result<Response, Error>
+where
+    Notification: notification::Notification,
+{
+    const MAX_SIZE: u32 = 1024;
+
+    let id = model::parse_clip_id(&id)?;
+    let clip = context.manager.lock().await.get(id).ok_or(Error::ClipNotFound { id })?;
+    let mime = clip.mime();
+    if mime.type_() != mime::IMAGE {
+        return Err(Error::NotImage { id });
+    }
+    if clipcat_base::utils::image::is_vector(&mime) {
+        let data = clip.encoded().map_err(|source| Error::EncodeClip { source })?;
+        return Ok(([(header::CONTENT_TYPE, mime.to_string())], data).into_response());
+    }
+
+    // decoding an image takes a while, keep it off the runtime
+    let size = params.size.clamp(1, MAX_SIZE);
+    let data = tokio::task::spawn_blocking(move || clip.as_ref().thumbnail(size))
+        .await
+        .map_err(|source| Error::JoinTask { source })?
+        .map_err(|source| Error::CreateThumbnail { source })?;
+    Ok(([(header::CONTENT_TYPE, mime::IMAGE_PNG.to_string())], data).into_response())
+}
+
+pub async fn get_current_clip<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Query(params): Query<model::KindParams>,
+) -> Result<Json<Clip>, Error>
+where
+    Notification: notification::Notification,
+{
+    let kind = params.kind()?;
+    let clip = context
+        .manager
+        .lock()
+        .await
+        .get_current_clip(kind)
+        .cloned()
+        .ok_or(Error::CurrentClipNotFound { kind })?;
+    Clip::try_from(&clip).map(Json)
+}
+
+pub async fn update<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(id): Path<String>,
+    Json(model::UpdateRequest { data, mime }): Json<model::UpdateRequest>,
+) -> Result<Json<model::UpdateResponse>, Error>
+where
+    Notification: notification::Notification,
+{
+    let id = model::parse_clip_id(&id)?;
+    let data = model::decode_base64(&data)?;
+    let mime = model::parse_mime(mime.as_deref())?;
+    let (ok, new_id) = context.manager.lock().await.replace(id, &data, &mime);
+    Ok(Json(model::UpdateResponse { ok, new_id: new_id.to_string() }))
+}
+
+pub async fn remove<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, Error>
+where
+    Notification: notification::Notification,
+{
+    let id = model::parse_clip_id(&id)?;
+    if context.manager.lock().await.remove(id) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::ClipNotFound { id })
+    }
+}
+
+pub async fn batch_remove<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Json(model::BatchRemoveRequest { ids }): Json<model::BatchRemoveRequest>,
+) -> Result<Json<model::BatchRemoveResponse>, Error>
+where
+    Notification: notification::Notification,
+{
+    let ids = ids.iter().map(|id| model::parse_clip_id(id)).collect::<Result<Vec<_>, _>>()?;
+    let ids = {
+        let mut manager = context.manager.lock().await;
+        ids.into_iter().filter(|id| manager.remove(*id)).map(|id| id.to_string()).collect()
+    };
+    Ok(Json(model::BatchRemoveResponse { ids }))
+}
+
+pub async fn clear<Notification>(State(context): State<Arc<Context<Notification>>>) -> StatusCode
+where
+    Notification: notification::Notification,
+{
+    context.manager.lock().await.clear();
+    StatusCode::NO_CONTENT
+}
+
+pub async fn mark<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(id): Path<String>,
+    Query(params): Query<model::KindParams>,
+    body: Option<Json<model::PromptsRequest>>,
+) -> Result<StatusCode, Error>
+where
+    Notification: notification::Notification,
+{
+    let id = model::parse_clip_id(&id)?;
+    let kind = params.kind()?;
+    let Json(model::PromptsRequest { prompts }) = body.unwrap_or_default();
+    let mut manager = context.manager.lock().await;
+    if manager.get(id).is_none() {
+        return Err(Error::ClipNotFound { id });
+    }
+    let result = manager.mark_with_prompts(id, kind, &prompts).await;
+    drop(manager);
+    result.map_err(|source| Error::MarkClip { source })?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Returns the clip with placeholders of a snippet template expanded, other
+/// clips are returned as-is.
+pub async fn expand<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(id): Path<String>,
+    body: Option<Json<model::PromptsRequest>>,
+) -> Result<Json<Clip>, Error>
+where
+    Notification: notification::Notification,
+{
+    let id = model::parse_clip_id(&id)?;
+    let Json(model::PromptsRequest { prompts }) = body.unwrap_or_default();
+    let clip = context
+        .manager
+        .lock()
+        .await
+        .expand(id, &prompts)
+        .map_err(|source| Error::ExpandClip { source })?
+        .ok_or(Error::ClipNotFound { id })?;
+    Clip::try_from(&clip).map(Json)
+}
+
+pub async fn mark_by_index<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(index): Path<usize>,
+    Query(params): Query<model::KindParams>,
+    body: Option<Json<model::PromptsRequest>>,
+) -> Result<Json<model::MarkByIndexResponse>, Error>
+where
+    Notification: notification::Notification,
+{
+    let kind = params.kind()?;
+    let Json(model::PromptsRequest { prompts }) = body.unwrap_or_default();
+    let mut manager = context.manager.lock().await;
+    let Some(id) = manager.id_by_index(index) else {
+        return Err(Error::ClipIndexNotFound { index });
+    };
+    let result = manager.mark_with_prompts(id, kind, &prompts).await;
+    drop(manager);
+    result.map_err(|source| Error::MarkClip { source })?;
+    Ok(Json(model::MarkByIndexResponse { id: id.to_string() }))
+}
+
+pub async fn pin<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, Error>
+where
+    Notification: notification::Notification,
+{
+    let id = model::parse_clip_id(&id)?;
+    if context.manager.lock().await.pin(id) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::ClipNotFound { id })
+    }
+}
+
+pub async fn unpin<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, Error>
+where
+    Notification: notification::Notification,
+{
+    let id = model::parse_clip_id(&id)?;
+    if context.manager.lock().await.unpin(id) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::ClipNotFound { id })
+    }
+}
+
+pub async fn tag<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path((id, tag)): Path<(String, String)>,
+) -> Result<StatusCode, Error>
+where
+    Notification: notification::Notification,
+{
+    let id = model::parse_clip_id(&id)?;
+    let mut manager = context.manager.lock().await;
+    if manager.get(id).is_none() {
+        return Err(Error::ClipNotFound { id });
+    }
+    let ok = manager.tag(id, &tag);
+    drop(manager);
+    if ok {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::InvalidTag { tag })
+    }
+}
+
+pub async fn untag<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path((id, tag)): Path<(String, String)>,
+) -> Result<StatusCode, Error>
+where
+    Notification: notification::Notification,
+{
+    let id = model::parse_clip_id(&id)?;
+    if context.manager.lock().await.untag(id, &tag) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::TagNotFound { id, tag })
+    }
+}
+
+pub async fn list_tags<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+) -> Json<Vec<model::TagInfo>>
+where
+    Notification: notification::Notification,
+{
+    let tags = context.manager.lock().await.tags();
+    Json(tags.into_iter().map(|(name, count)| model::TagInfo { name, count }).collect())
+}
+
+/// Returns snippets sorted by name.
+pub async fn list_snippets<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Query(model::SnippetsParams { preview_length }): Query<model::SnippetsParams>,
+) -> Json<Vec<ClipMetadata>>
+where
+    Notification: notification::Notification,
+{
+    let snippets = context.manager.lock().await.snippets(preview_length);
+    Json(snippets.into_iter().map(ClipMetadata::from).collect())
+}
+
+pub async fn add_snippet<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Json(model::AddSnippetRequest { name, content }): Json<model::AddSnippetRequest>,
+) -> Result<(StatusCode, Json<model::SnippetResponse>), Error>
+where
+    Notification: notification::Notification,
+{
+    let id = context
+        .manager
+        .lock()
+        .await
+        .add_snippet(&name, &content)
+        .await
+        .map_err(|source| Error::ManageSnippet { source })?;
+    Ok((StatusCode::CREATED, Json(model::SnippetResponse { id: id.to_string() })))
+}
+
+/// Replaces the content of a snippet in the snippet directory, `name` may
+/// contain slashes.
+pub async fn update_snippet<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(name): Path<String>,
+    Json(model::UpdateSnippetRequest { content }): Json<model::UpdateSnippetRequest>,
+) -> Result<Json<model::SnippetResponse>, Error>
+where
+    Notification: notification::Notification,
+{
+    let id = context
+        .manager
+        .lock()
+        .await
+        .update_snippet(&name, &content)
+        .await
+        .map_err(|source| Error::ManageSnippet { source })?;
+    Ok(Json(model::SnippetResponse { id: id.to_string() }))
+}
+
+pub async fn remove_snippet<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, Error>
+where
+    Notification: notification::Notification,
+{
+    context
+        .manager
+        .lock()
+        .await
+        .delete_snippet(&name)
+        .await
+        .map_err(|source| Error::ManageSnippet { source })?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn length<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+) -> Json<model::LengthResponse>
+where
+    Notification: notification::Notification,
+{
+    let manager = context.manager.lock().await;
+    Json(model::LengthResponse { length: manager.len(), size_in_bytes: manager.size_in_bytes() })
+}
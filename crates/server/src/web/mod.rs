@@ -0,0 +1,478 @@
+mod error;
+mod manager;
+mod model;
+mod system;
+mod watcher;
+
+use std::{
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    pin::pin,
+    sync::Arc,
+};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, uri::Authority, StatusCode, Uri},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Response},
+    routing, Router,
+};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use snafu::ResultExt;
+use tokio::{
+    net::{TcpListener, UnixListener},
+    sync::Mutex,
+};
+
+use crate::{grpc::Interceptor, metrics, notification, ClipboardManager, ClipboardWatcherToggle};
+
+const INDEX_PAGE: &str = include_str!("index.html");
+
+struct Context<Notification> {
+    manager: Arc<Mutex<ClipboardManager<Notification>>>,
+    watcher_toggle: ClipboardWatcherToggle<Notification>,
+}
+
+#[derive(Clone)]
+struct Guard {
+    interceptor: Interceptor,
+    listen_address: Option<SocketAddr>,
+}
+
+/// Creates the JSON API of the Manager, Watcher and System services. Every
+/// request requires `access_token` if it is given.
+///
+/// `listen_address` is the address of the TCP listener serving the API, a
+/// request through it must name the listen address as its host, and as its
+/// origin if it is sent by a web page. Neither a page on another site nor a
+/// host name rebound to the listen address reaches the API this way. It is
+/// `None` for a Unix domain socket, which web pages cannot connect to.
+pub fn router<Notification>(
+    access_token: Option<String>,
+    listen_address: Option<SocketAddr>,
+    manager: Arc<Mutex<ClipboardManager<Notification>>>,
+    watcher_toggle: ClipboardWatcherToggle<Notification>,
+) -> Router
+where
+    Notification: notification::Notification + 'static,
+{
+    Router::new()
+        .route(
+            "/clips",
+            routing::get(manager::list::<Notification>)
+                .post(manager::insert::<Notification>)
+                .delete(manager::clear::<Notification>),
+        )
+        .route("/clips/search", routing::get(manager::search::<Notification>))
+        .route("/clips/current", routing::get(manager::get_current_clip::<Notification>))
+        .route("/clips/batch-remove", routing::post(manager::batch_remove::<Notification>))
+        .route("/clips/index/:index", routing::get(manager::get_by_index::<Notification>))
+        .route("/clips/index/:index/mark", routing::post(manager::mark_by_index::<Notification>))
+        .route(
+            "/clips/:id",
+            routing::get(manager::get::<Notification>)
+                .put(manager::update::<Notification>)
+                .delete(manager::remove::<Notification>),
+        )
+        .route("/clips/:id/data", routing::get(manager::get_data::<Notification>))
+        .route("/clips/:id/thumbnail", routing::get(manager::get_thumbnail::<Notification>))
+        .route("/clips/:id/mark", routing::post(manager::mark::<Notification>))
+        .route("/clips/:id/expand", routing::post(manager::expand::<Notification>))
+        .route("/clips/:id/pin", routing::post(manager::pin::<Notification>))
+        .route("/clips/:id/unpin", routing::post(manager::unpin::<Notification>))
+        .route(
+            "/clips/:id/tags/:tag",
+            routing::put(manager::tag::<Notification>).delete(manager::untag::<Notification>),
+        )
+        .route("/tags", routing::get(manager::list_tags::<Notification>))
+        .route(
+            "/snippets",
+            routing::get(manager::list_snippets::<Notification>)
+                .post(manager::add_snippet::<Notification>),
+        )
+        .route(
+            "/snippets/*name",
+            routing::put(manager::update_snippet::<Notification>)
+                .delete(manager::remove_snippet::<Notification>),
+        )
+        .route("/length", routing::get(manager::length::<Notification>))
+        .route("/watcher", routing::get(watcher::get_state::<Notification>))
+        .route("/watcher/enable", routing::post(watcher::enable::<Notification>))
+        .route("/watcher/disable", routing::post(watcher::disable::<Notification>))
+        .route("/watcher/toggle", routing::post(watcher::toggle::<Notification>))
+        .route("/system/version", routing::get(system::get_version))
+        .route_layer(middleware::from_fn_with_state(
+            Guard { interceptor: Interceptor::new(access_token), listen_address },
+            authorize,
+        ))
+        .with_state(Arc::new(Context { manager, watcher_toggle }))
+}
+
+/// Serves the history page and the API behind it on `listener` until
+/// `shutdown_signal` is resolved. The API requires `access_token` if it is
+/// given, the page itself contains no clip.
+///
+/// # Errors
+///
+/// This function will return an error if the server fails while serving.
+pub async fn serve<Notification, ShutdownSignal>(
+    listener: TcpListener,
+    access_token: Option<String>,
+    manager: Arc<Mutex<ClipboardManager<Notification>>>,
+    watcher_toggle: ClipboardWatcherToggle<Notification>,
+    shutdown_signal: ShutdownSignal,
+) -> Result<(), crate::Error>
+where
+    Notification: notification::Notification + 'static,
+    ShutdownSignal: Future<Output = ()> + Send + 'static,
+{
+    let listen_address = listener.local_addr().context(crate::error::BindWebServerSnafu)?;
+    let api = router(access_token, Some(listen_address), manager, watcher_toggle);
+    let router = Router::new().route("/", routing::get(index)).nest("/api", api);
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown_signal)
+        .await
+        .context(crate::error::ServeWebServerSnafu)
+}
+
+/// Serves `router` on `listener` until `shutdown_signal` is resolved.
+///
+/// # Errors
+///
+/// This function will return an error if the server fails while serving.
+pub async fn serve_http<ShutdownSignal>(
+    listener: TcpListener,
+    router: Router,
+    shutdown_signal: ShutdownSignal,
+) -> Result<(), crate::Error>
+where
+    ShutdownSignal: Future<Output = ()> + Send + 'static,
+{
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown_signal)
+        .await
+        .context(crate::error::ServeRestServerSnafu)
+}
+
+/// Serves `router` on the Unix domain socket `listener` until
+/// `shutdown_signal` is resolved.
+pub async fn serve_local_socket<ShutdownSignal>(
+    listener: UnixListener,
+    router: Router,
+    shutdown_signal: ShutdownSignal,
+) where
+    ShutdownSignal: Future<Output = ()> + Send + 'static,
+{
+    let mut shutdown_signal = pin!(shutdown_signal);
+    loop {
+        let stream = tokio::select! {
+            result = listener.accept() => match result {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    tracing::warn!("Could not accept connection, error: {err}");
+                    continue;
+                }
+            },
+            () = &mut shutdown_signal => return,
+        };
+        let service = TowerToHyperService::new(router.clone());
+        let _handle = tokio::spawn(async move {
+            if let Err(err) = auto::Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!("Error occurs while serving connection, error: {err}");
+            }
+        });
+    }
+}
+
+async fn authorize(
+    State(Guard { interceptor, listen_address }): State<Guard>,
+    request: Request,
+    next: Next,
+) -> Response {
+    metrics::grpc::REQUESTS_TOTAL.inc();
+
+    if let Some(listen_address) = listen_address {
+        if !is_same_origin(listen_address, &request) {
+            return StatusCode::FORBIDDEN.into_response();
+        }
+    }
+
+    let authorization =
+        request.headers().get(header::AUTHORIZATION).map(header::HeaderValue::as_bytes);
+    if interceptor.is_authorized(authorization) {
+        next.run(request).await
+    } else {
+        StatusCode::UNAUTHORIZED.into_response()
+    }
+}
+
+// checks that both the host and the origin of `request` are `listen_address`,
+// requests without an origin are not sent by web pages
+fn is_same_origin(listen_address: SocketAddr, request: &Request) -> bool {
+    // HTTP/2 requests carry the host in their URI instead of a header
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .and_then(|host| host.parse::<Authority>().ok())
+        .or_else(|| request.uri().authority().cloned());
+    if !host.is_some_and(|host| is_listen_address(listen_address, &host)) {
+        return false;
+    }
+
+    request.headers().get(header::ORIGIN).map_or(true, |origin| {
+        origin.to_str().ok().and_then(|origin| origin.parse::<Uri>().ok()).is_some_and(|origin| {
+            origin.scheme_str() == Some("http")
+                && origin
+                    .authority()
+                    .is_some_and(|authority| is_listen_address(listen_address, authority))
+        })
+    })
+}
+
+// `localhost` is accepted for a loopback address, any IP address is accepted
+// for an unspecified one, host names other than `localhost` are never
+// accepted because they could be rebound to the listen address
+fn is_listen_address(listen_address: SocketAddr, authority: &Authority) -> bool {
+    if authority.port_u16().unwrap_or(80) != listen_address.port() {
+        return false;
+    }
+    let ip = listen_address.ip();
+    let host = authority.host();
+    if host.eq_ignore_ascii_case("localhost") {
+        return ip.is_loopback() || ip.is_unspecified();
+    }
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .is_ok_and(|host| ip.is_unspecified() || host == ip)
+}
+
+async fn index() -> Html<&'static str> { Html(INDEX_PAGE) }
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, sync::Arc};
+
+    use axum::{
+        body::{self, Body},
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use clipcat_base::{ClipEntry, ClipboardContent, ClipboardKind, SharedClipFilter};
+    use tokio::sync::Mutex;
+    use tower::ServiceExt;
+
+    use crate::{
+        backend::{ClipboardBackend, LocalClipboardBackend},
+        notification::DummyNotification,
+        web, ClipboardManager, ClipboardWatcher, ClipboardWatcherOptions,
+    };
+
+    const LISTEN_ADDRESS: &str = "127.0.0.1:45931";
+
+    fn create_router(
+        access_token: Option<&str>,
+        listen_address: Option<SocketAddr>,
+        clips: Vec<ClipEntry>,
+    ) -> (Router, Vec<u64>, Arc<LocalClipboardBackend>) {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut manager = ClipboardManager::new(backend.clone(), DummyNotification::default());
+        let ids = clips.into_iter().map(|clip| manager.insert(clip)).collect();
+        let (watcher, _worker) = ClipboardWatcher::new(
+            backend.clone(),
+            ClipboardWatcherOptions::default(),
+            SharedClipFilter::default(),
+            DummyNotification::default(),
+        );
+        let router = web::router(
+            access_token.map(ToString::to_string),
+            listen_address,
+            Arc::new(Mutex::new(manager)),
+            watcher.get_toggle(),
+        );
+        (router, ids, backend)
+    }
+
+    fn request(method: Method, uri: &str, headers: &[(header::HeaderName, &str)]) -> Request<Body> {
+        let mut builder = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            builder = builder.header(name, *value);
+        }
+        builder.body(Body::empty()).expect("request is valid")
+    }
+
+    async fn send(router: &Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = router.clone().oneshot(request).await.expect("router is infallible");
+        let status = response.status();
+        let body = body::to_bytes(response.into_body(), usize::MAX).await.expect("body is read");
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_authorize() {
+        let listen_address = LISTEN_ADDRESS.parse().unwrap();
+        let (router, ..) = create_router(Some("secret"), Some(listen_address), Vec::new());
+
+        for (authorization, expected) in [
+            (None, StatusCode::UNAUTHORIZED),
+            (Some("Bearer wrong"), StatusCode::UNAUTHORIZED),
+            (Some("secret"), StatusCode::UNAUTHORIZED),
+            (Some("Bearer secret"), StatusCode::OK),
+        ] {
+            let mut headers = vec![(header::HOST, LISTEN_ADDRESS)];
+            headers.extend(authorization.map(|value| (header::AUTHORIZATION, value)));
+            let (status, _) = send(&router, request(Method::GET, "/clips", &headers)).await;
+            assert_eq!(status, expected, "authorization: {authorization:?}");
+        }
+
+        // the host is checked before the access token
+        let headers =
+            [(header::HOST, "evil.example:45931"), (header::AUTHORIZATION, "Bearer secret")];
+        let (status, _) = send(&router, request(Method::GET, "/clips", &headers)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_reject_other_origins() {
+        let listen_address = LISTEN_ADDRESS.parse().unwrap();
+        let (router, ..) = create_router(None, Some(listen_address), Vec::new());
+
+        for (host, origin, expected) in [
+            (Some(LISTEN_ADDRESS), None, StatusCode::OK),
+            (Some("localhost:45931"), None, StatusCode::OK),
+            (Some(LISTEN_ADDRESS), Some("http://127.0.0.1:45931"), StatusCode::OK),
+            (Some(LISTEN_ADDRESS), Some("http://localhost:45931"), StatusCode::OK),
+            (None, None, StatusCode::FORBIDDEN),
+            (Some("evil.example:45931"), None, StatusCode::FORBIDDEN),
+            (Some("127.0.0.1:80"), None, StatusCode::FORBIDDEN),
+            (Some("10.0.0.1:45931"), None, StatusCode::FORBIDDEN),
+            (Some(LISTEN_ADDRESS), Some("http://evil.example"), StatusCode::FORBIDDEN),
+            (Some(LISTEN_ADDRESS), Some("null"), StatusCode::FORBIDDEN),
+            (Some(LISTEN_ADDRESS), Some("https://127.0.0.1:45931"), StatusCode::FORBIDDEN),
+        ] {
+            let mut headers = Vec::new();
+            headers.extend(host.map(|value| (header::HOST, value)));
+            headers.extend(origin.map(|value| (header::ORIGIN, value)));
+            let (status, _) =
+                send(&router, request(Method::POST, "/watcher/toggle", &headers)).await;
+            assert_eq!(status, expected, "host: {host:?}, origin: {origin:?}");
+        }
+
+        // any IP address reaches a server listening on an unspecified address
+        let (router, ..) = create_router(None, Some("0.0.0.0:45931".parse().unwrap()), Vec::new());
+        let headers = [(header::HOST, "10.0.0.1:45931")];
+        let (status, _) = send(&router, request(Method::GET, "/clips", &headers)).await;
+        assert_eq!(status, StatusCode::OK);
+        let headers = [(header::HOST, "evil.example:45931")];
+        let (status, _) = send(&router, request(Method::GET, "/clips", &headers)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // web pages cannot connect to a Unix domain socket
+        let (router, ..) = create_router(None, None, Vec::new());
+        let headers = [(header::HOST, "evil.example")];
+        let (status, _) = send(&router, request(Method::GET, "/clips", &headers)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_list_get_and_mark() {
+        let clips = vec![
+            ClipEntry::from_string("first", ClipboardKind::Clipboard),
+            ClipEntry::from_string("second", ClipboardKind::Clipboard),
+        ];
+        let (router, ids, backend) = create_router(None, None, clips);
+
+        let (status, list) = send(&router, request(Method::GET, "/clips", &[])).await;
+        assert_eq!(status, StatusCode::OK);
+        let mut listed = list
+            .as_array()
+            .expect("clips are listed")
+            .iter()
+            .map(|clip| clip["id"].as_str().expect("ID is a string").parse::<u64>().unwrap())
+            .collect::<Vec<_>>();
+        listed.sort_unstable();
+        let mut expected = ids.clone();
+        expected.sort_unstable();
+        assert_eq!(listed, expected);
+
+        let (status, clip) =
+            send(&router, request(Method::GET, &format!("/clips/{}", ids[0]), &[])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(clip["id"], ids[0].to_string());
+        assert_eq!(clip["mime"], mime::TEXT_PLAIN_UTF_8.to_string());
+
+        let (status, _) = send(&router, request(Method::GET, "/clips/0", &[])).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&router, request(Method::GET, "/clips/not-an-id", &[])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let uri = format!("/clips/{}/mark?kind=primary", ids[0]);
+        let (status, _) = send(&router, request(Method::POST, &uri, &[])).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let content = backend.load(ClipboardKind::Primary, None).await.unwrap();
+        assert_eq!(content, ClipboardContent::Plaintext("first".to_string()));
+
+        let (status, _) = send(&router, request(Method::POST, "/clips/0/mark", &[])).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let uri = format!("/clips/{}/mark?kind=unknown", ids[0]);
+        let (status, _) = send(&router, request(Method::POST, &uri, &[])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_mark_by_index_with_prompts() {
+        let mut template = ClipEntry::from_string("ticket {{prompt:Id}}", ClipboardKind::Clipboard);
+        template.set_template(true);
+        let (router, ids, backend) = create_router(None, None, vec![template]);
+
+        let (status, _) = send(&router, request(Method::POST, "/clips/index/0/mark", &[])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/clips/index/0/mark")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"prompts":{"Id":"CC-42"}}"#))
+            .expect("request is valid");
+        let (status, marked) = send(&router, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(marked["id"], ids[0].to_string());
+        let content = backend.load(ClipboardKind::Clipboard, None).await.unwrap();
+        assert_eq!(content, ClipboardContent::Plaintext("ticket CC-42".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_thumbnail() {
+        let pixels = vec![255; 4 * 400 * 200];
+        let png = clipcat_base::utils::image::encode_rgba_as_png(400, 200, &pixels).unwrap();
+        let clips = vec![
+            ClipEntry::new(&png, &mime::IMAGE_PNG, ClipboardKind::Clipboard, None).unwrap(),
+            ClipEntry::from_string("text", ClipboardKind::Clipboard),
+        ];
+        let (router, ids, _backend) = create_router(None, None, clips);
+
+        let uri = format!("/clips/{}/thumbnail?size=100", ids[0]);
+        let response = router.clone().oneshot(request(Method::GET, &uri, &[])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], mime::IMAGE_PNG.as_ref());
+        let thumbnail = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            clipcat_base::utils::image::dimensions(&mime::IMAGE_PNG, &thumbnail),
+            Some((100, 50))
+        );
+
+        let uri = format!("/clips/{}/thumbnail", ids[1]);
+        let (status, _) = send(&router, request(Method::GET, &uri, &[])).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,409 @@
+use std::{collections::HashMap, str::FromStr};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use clipcat_base::{
+    ClipEntry, ClipEntryMetadata, ClipboardKind, ClipboardWatcherState, ListOrder, ListQuery,
+    SearchMatch,
+};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::web::error::Error;
+
+// IDs are serialized as strings, JavaScript numbers cannot hold all 64-bit
+// integers
+
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClipMetadata {
+    pub id: String,
+    pub kind: String,
+    pub mime: String,
+    pub preview: String,
+    pub timestamp: String,
+    pub pinned: bool,
+    pub source_application: Option<String>,
+    pub sensitive: bool,
+    pub tags: Vec<String>,
+    pub template: bool,
+    pub snippet_name: Option<String>,
+    pub snippet_path: Option<String>,
+}
+
+impl From<ClipEntryMetadata> for ClipMetadata {
+    fn from(
+        ClipEntryMetadata {
+            id,
+            kind,
+            timestamp,
+            mime,
+            preview,
+            pinned,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path,
+        }: ClipEntryMetadata,
+    ) -> Self {
+        Self {
+            id: id.to_string(),
+            kind: kind.to_string(),
+            mime: mime.to_string(),
+            preview,
+            timestamp: timestamp.format(&Rfc3339).unwrap_or_default(),
+            pinned,
+            source_application,
+            sensitive,
+            tags,
+            template,
+            snippet_name,
+            snippet_path: snippet_path.map(|path| path.display().to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Representation {
+    pub mime: String,
+    // encoded in Base64
+    pub data: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Clip {
+    pub id: String,
+    pub kind: String,
+    pub mime: String,
+    // encoded in Base64
+    pub data: String,
+    pub timestamp: String,
+    pub pinned: bool,
+    pub representations: Vec<Representation>,
+    pub source_application: Option<String>,
+    pub sensitive: bool,
+    pub tags: Vec<String>,
+    pub template: bool,
+    pub snippet_name: Option<String>,
+    pub snippet_path: Option<String>,
+}
+
+impl TryFrom<&ClipEntry> for Clip {
+    type Error = Error;
+
+    fn try_from(clip: &ClipEntry) -> Result<Self, Self::Error> {
+        let data = clip.encoded().map_err(|source| Error::EncodeClip { source })?;
+        Ok(Self {
+            id: clip.id().to_string(),
+            kind: clip.kind().to_string(),
+            mime: clip.mime().to_string(),
+            data: BASE64.encode(data),
+            timestamp: clip.timestamp().format(&Rfc3339).unwrap_or_default(),
+            pinned: clip.is_pinned(),
+            representations: clip
+                .representations()
+                .iter()
+                .map(|representation| Representation {
+                    mime: representation.mime.to_string(),
+                    data: BASE64.encode(&representation.data),
+                })
+                .collect(),
+            source_application: clip.source_application().map(ToString::to_string),
+            sensitive: clip.is_sensitive(),
+            tags: clip.tags().to_vec(),
+            template: clip.is_template(),
+            snippet_name: clip.snippet_name().map(ToString::to_string),
+            snippet_path: clip.snippet_path().map(|path| path.display().to_string()),
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub metadata: ClipMetadata,
+    pub score: i64,
+    // pairs of start and end byte offsets of matched text
+    pub ranges: Vec<[usize; 2]>,
+}
+
+impl From<SearchMatch> for SearchResult {
+    fn from(SearchMatch { metadata, score, ranges }: SearchMatch) -> Self {
+        Self {
+            metadata: metadata.into(),
+            score,
+            ranges: ranges.into_iter().map(|range| [range.start, range.end]).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    #[serde(default = "default_preview_length")]
+    pub preview_length: usize,
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default)]
+    pub limit: usize,
+    #[serde(default)]
+    pub order: Option<String>,
+    // comma separated clipboard kinds, all kinds are listed if it is absent
+    #[serde(default)]
+    pub kinds: Option<String>,
+    // comma separated MIME types, all MIME types are listed if it is absent
+    #[serde(default)]
+    pub mimes: Option<String>,
+    // comma separated tags, clips with any of the tags are listed
+    #[serde(default)]
+    pub tags: Option<String>,
+    #[serde(default)]
+    pub pinned: Option<bool>,
+    #[serde(default)]
+    pub snippet: Option<bool>,
+    // RFC 3339 timestamps
+    #[serde(default)]
+    pub since: Option<String>,
+    #[serde(default)]
+    pub until: Option<String>,
+}
+
+impl TryFrom<ListParams> for ListQuery {
+    type Error = Error;
+
+    fn try_from(
+        ListParams {
+            preview_length,
+            offset,
+            limit,
+            order,
+            kinds,
+            mimes,
+            tags,
+            pinned,
+            snippet,
+            since,
+            until,
+        }: ListParams,
+    ) -> Result<Self, Self::Error> {
+        let order = order
+            .map(|order| {
+                ListOrder::from_str(&order).map_err(|_| Error::ParseListOrder { value: order })
+            })
+            .transpose()?
+            .unwrap_or_default();
+        Ok(Self {
+            preview_length,
+            offset,
+            limit,
+            order,
+            kinds: parse_clipboard_kinds(kinds.as_deref())?,
+            mimes: parse_mimes(mimes.as_deref())?,
+            tags: parse_tags(tags.as_deref()),
+            pinned,
+            snippet,
+            since: since.as_deref().map(parse_timestamp).transpose()?,
+            until: until.as_deref().map(parse_timestamp).transpose()?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub pattern: String,
+    #[serde(default)]
+    pub mode: Option<String>,
+    // comma separated clipboard kinds, all kinds are searched if it is absent
+    #[serde(default)]
+    pub kinds: Option<String>,
+    // comma separated MIME types, all MIME types are searched if it is absent
+    #[serde(default)]
+    pub mimes: Option<String>,
+    // comma separated tags, clips with any of the tags are searched
+    #[serde(default)]
+    pub tags: Option<String>,
+    #[serde(default = "default_preview_length")]
+    pub preview_length: usize,
+    #[serde(default)]
+    pub limit: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThumbnailParams {
+    // images are scaled down to fit in a square of `size` pixels
+    #[serde(default = "default_thumbnail_size")]
+    pub size: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KindParams {
+    #[serde(default)]
+    pub kind: Option<String>,
+}
+
+impl KindParams {
+    pub fn kind(&self) -> Result<ClipboardKind, Error> {
+        self.kind.as_deref().map_or(Ok(ClipboardKind::Clipboard), parse_clipboard_kind)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InsertRequest {
+    // encoded in Base64
+    pub data: String,
+    #[serde(default)]
+    pub mime: Option<String>,
+    #[serde(default)]
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InsertResponse {
+    pub id: String,
+}
+
+// values of `{{prompt:<label>}}` placeholders of a snippet template keyed by
+// label
+#[derive(Debug, Default, Deserialize)]
+pub struct PromptsRequest {
+    #[serde(default)]
+    pub prompts: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MarkByIndexResponse {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRequest {
+    // encoded in Base64
+    pub data: String,
+    #[serde(default)]
+    pub mime: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdateResponse {
+    pub ok: bool,
+    pub new_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchRemoveRequest {
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchRemoveResponse {
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LengthResponse {
+    pub length: usize,
+    pub size_in_bytes: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnippetsParams {
+    #[serde(default = "default_preview_length")]
+    pub preview_length: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddSnippetRequest {
+    // relative path in the snippet directory of the server
+    pub name: String,
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSnippetRequest {
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnippetResponse {
+    pub id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagInfo {
+    pub name: String,
+    // number of clips with the tag
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WatcherStateResponse {
+    pub state: String,
+}
+
+impl From<ClipboardWatcherState> for WatcherStateResponse {
+    fn from(state: ClipboardWatcherState) -> Self {
+        let state = match state {
+            ClipboardWatcherState::Enabled => "enabled",
+            ClipboardWatcherState::Disabled => "disabled",
+        };
+        Self { state: state.to_string() }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionResponse {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+pub fn decode_base64(data: &str) -> Result<Vec<u8>, Error> {
+    BASE64.decode(data).map_err(|source| Error::DecodeBase64 { source })
+}
+
+pub fn parse_clip_id(value: &str) -> Result<u64, Error> {
+    value.parse().map_err(|_| Error::ParseClipId { value: value.to_string() })
+}
+
+pub fn parse_clipboard_kind(value: &str) -> Result<ClipboardKind, Error> {
+    ClipboardKind::from_str(value)
+        .map_err(|_| Error::ParseClipboardKind { value: value.to_string() })
+}
+
+pub fn parse_clipboard_kinds(value: Option<&str>) -> Result<Vec<ClipboardKind>, Error> {
+    value.iter().flat_map(|kinds| kinds.split(',')).map(parse_clipboard_kind).collect()
+}
+
+pub fn parse_mimes(value: Option<&str>) -> Result<Vec<mime::Mime>, Error> {
+    value.iter().flat_map(|mimes| mimes.split(',')).map(|mime| parse_mime(Some(mime))).collect()
+}
+
+pub fn parse_tags(value: Option<&str>) -> Vec<String> {
+    value
+        .iter()
+        .flat_map(|tags| tags.split(','))
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+pub fn parse_timestamp(value: &str) -> Result<OffsetDateTime, Error> {
+    OffsetDateTime::parse(value, &Rfc3339)
+        .map_err(|_| Error::ParseTimestamp { value: value.to_string() })
+}
+
+// text is assumed if no MIME type is given
+pub fn parse_mime(value: Option<&str>) -> Result<mime::Mime, Error> {
+    value.map_or(Ok(mime::TEXT_PLAIN_UTF_8), |value| {
+        mime::Mime::from_str(value).map_err(|_| Error::ParseMime { value: value.to_string() })
+    })
+}
+
+const fn default_preview_length() -> usize { 30 }
+
+const fn default_thumbnail_size() -> u32 { 160 }
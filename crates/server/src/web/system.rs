@@ -0,0 +1,11 @@
+use axum::Json;
+
+use crate::web::model::VersionResponse;
+
+pub async fn get_version() -> Json<VersionResponse> {
+    Json(VersionResponse {
+        major: clipcat_base::PROJECT_SEMVER.major,
+        minor: clipcat_base::PROJECT_SEMVER.minor,
+        patch: clipcat_base::PROJECT_SEMVER.patch,
+    })
+}
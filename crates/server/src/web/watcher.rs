@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+
+use crate::{
+    notification,
+    web::{model::WatcherStateResponse, Context},
+};
+
+pub async fn enable<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+) -> Json<WatcherStateResponse>
+where
+    Notification: notification::Notification,
+{
+    context.watcher_toggle.enable();
+    Json(context.watcher_toggle.state().into())
+}
+
+pub async fn disable<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+) -> Json<WatcherStateResponse>
+where
+    Notification: notification::Notification,
+{
+    context.watcher_toggle.disable();
+    Json(context.watcher_toggle.state().into())
+}
+
+pub async fn toggle<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+) -> Json<WatcherStateResponse>
+where
+    Notification: notification::Notification,
+{
+    context.watcher_toggle.toggle();
+    Json(context.watcher_toggle.state().into())
+}
+
+pub async fn get_state<Notification>(
+    State(context): State<Arc<Context<Notification>>>,
+) -> Json<WatcherStateResponse>
+where
+    Notification: notification::Notification,
+{
+    Json(context.watcher_toggle.state().into())
+}